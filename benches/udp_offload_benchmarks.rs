@@ -0,0 +1,123 @@
+//! UDP Offload Benchmarks
+//!
+//! Loopback throughput of the QUIC UDP I/O path:
+//! - Plain `send_to`/`recv_from`, one datagram per syscall
+//! - `QuicUdpSocket` with GSO/GRO and ECN disabled
+//! - `QuicUdpSocket` with GSO/GRO and ECN enabled
+
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use quyc_client::protocols::quiche::{QuicUdpSocket, UdpIoConfig};
+
+const DATAGRAM_SIZE: usize = 1200;
+const DATAGRAMS: usize = 200_000;
+const BURST: usize = 32;
+
+fn main() {
+    println!("🏁 UDP Offload Benchmarks\n");
+
+    bench_plain_socket();
+    bench_quic_socket("2. QuicUdpSocket (offload off)", UdpIoConfig {
+        offload: false,
+        ecn: false,
+        ..UdpIoConfig::default()
+    });
+    bench_quic_socket("3. QuicUdpSocket (GSO/GRO + ECN)", UdpIoConfig::default());
+}
+
+fn loopback() -> SocketAddr {
+    "127.0.0.1:0".parse().expect("valid loopback address")
+}
+
+/// Benchmark plain std socket I/O as the baseline
+fn bench_plain_socket() {
+    println!("📊 1. Plain UdpSocket send_to/recv_from");
+
+    let sender = UdpSocket::bind(loopback()).expect("bind sender");
+    let receiver = UdpSocket::bind(loopback()).expect("bind receiver");
+    receiver.set_nonblocking(true).expect("non-blocking receiver");
+    let destination = receiver.local_addr().expect("receiver address");
+
+    let payload = vec![0xA5u8; DATAGRAM_SIZE];
+    let mut buf = vec![0u8; 65535];
+    let mut sent = 0;
+    let mut received = 0;
+    let mut syscalls = 0u64;
+
+    let start = Instant::now();
+    while sent < DATAGRAMS {
+        let burst = BURST.min(DATAGRAMS - sent);
+        for _ in 0..burst {
+            if sender.send_to(&payload, destination).is_ok() {
+                sent += 1;
+            }
+            syscalls += 1;
+        }
+        while let Ok((_len, _from)) = receiver.recv_from(&mut buf) {
+            received += 1;
+            syscalls += 1;
+        }
+    }
+    let duration = start.elapsed();
+
+    report(received, duration, syscalls);
+    println!();
+}
+
+/// Benchmark `QuicUdpSocket` batched I/O with the given configuration
+fn bench_quic_socket(label: &str, config: UdpIoConfig) {
+    println!("📊 {label}");
+
+    let mut sender = QuicUdpSocket::bind(loopback(), config).expect("bind sender");
+    let mut receiver = QuicUdpSocket::bind(loopback(), config).expect("bind receiver");
+    let destination = receiver.local_addr();
+
+    let payload = vec![0xA5u8; DATAGRAM_SIZE * BURST];
+    let mut sent = 0;
+    let mut received = 0;
+
+    let start = Instant::now();
+    while sent < DATAGRAMS {
+        let burst = BURST.min(DATAGRAMS - sent);
+        if sender
+            .send_segments(destination, &payload[..burst * DATAGRAM_SIZE], DATAGRAM_SIZE)
+            .is_ok()
+        {
+            sent += burst;
+        }
+        while let Ok(count) = receiver.recv_batch(|_datagram, _info| {}) {
+            received += count;
+        }
+    }
+    let duration = start.elapsed();
+
+    let send_stats = sender.stats();
+    let recv_stats = receiver.stats();
+    report(received, duration, send_stats.send_calls + recv_stats.recv_calls);
+    println!(
+        "   GSO segments: {}, GRO segments: {}",
+        sender.max_gso_segments(),
+        receiver.gro_segments()
+    );
+    println!(
+        "   Datagrams per send: {:.1}, per receive: {:.1}",
+        send_stats.datagrams_per_send(),
+        recv_stats.datagrams_per_recv()
+    );
+    println!(
+        "   ECN marks received: {} ECT, {} CE",
+        recv_stats.ecn_ect_received, recv_stats.ecn_ce_received
+    );
+    println!();
+}
+
+fn report(received: usize, duration: Duration, syscalls: u64) {
+    let secs = duration.as_secs_f64().max(f64::EPSILON);
+    let datagrams_per_sec = received as f64 / secs;
+    let megabytes_per_sec = (received * DATAGRAM_SIZE) as f64 / secs / (1024.0 * 1024.0);
+
+    println!("   {} datagrams received in {:?}", received, duration);
+    println!("   Throughput: {:.0} datagrams/sec, {:.1} MB/s", datagrams_per_sec, megabytes_per_sec);
+    println!("   Syscalls: {} ({:.2} datagrams/syscall)", syscalls, received as f64 / syscalls.max(1) as f64);
+}
@@ -17,6 +17,10 @@ pub use quyc_client::{
 };
 pub use quyc_client::builder::fluent::DownloadBuilder;
 
+/// Local mock server for integration tests
+#[cfg(feature = "testing")]
+pub use quyc_client::testing;
+
 /// Error chunk for MessageChunk pattern compatibility
 #[derive(Debug, Clone)]
 pub struct BadChunk {
@@ -10,6 +10,15 @@ pub trait AuthProvider {
     /// Apply authentication to headers
     fn apply_auth(&self, headers: &mut HeaderMap) -> Result<(), HttpError>;
 
+    /// Apply authentication to a whole request
+    ///
+    /// Applies [`apply_auth`](Self::apply_auth) to the request headers by
+    /// default; providers whose credentials cover the method, URL or body
+    /// override it.
+    fn sign_request(&self, request: &mut crate::http::request::HttpRequest) -> Result<(), HttpError> {
+        self.apply_auth(request.headers_mut())
+    }
+
     /// Get authentication method name
     fn auth_type(&self) -> &'static str;
 }
@@ -2,8 +2,12 @@ pub mod auth;
 pub mod auth_method;
 pub mod basic_auth;
 pub mod builder;
+pub mod oauth2;
+pub mod sigv4;
 
 // Re-export specific types to avoid conflicts
 pub use auth::{AuthProvider, BearerToken, ApiKey, ApiKeyPlacement, AuthError};
 pub use basic_auth::{basic_auth, encode_basic_auth, decode_basic_auth, BasicAuth};
 pub use builder::*;
+pub use oauth2::{ClientAuthentication, DeviceAuthorization, OAuth2, OAuth2Grant, OAuth2Token};
+pub use sigv4::{AwsCredentials, ChunkSigner, CredentialsChain, CredentialsProvider, EnvCredentials, ProfileCredentials, SigV4};
@@ -0,0 +1,651 @@
+//! OAuth 2.0 access tokens
+//!
+//! [`OAuth2`] obtains access tokens from a token endpoint with the client
+//! credentials grant, the refresh token grant or the device authorization
+//! grant (RFC 8628), and sends them as bearer tokens. Tokens are cached and
+//! refreshed shortly before they expire; a `401` answer refreshes the token
+//! and sends the request once more. Concurrent requests share one in-flight
+//! refresh, and requests holding a token that still works do not wait for it.
+
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use futures::executor::block_on;
+use http::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+use http::{HeaderMap, HeaderValue, StatusCode};
+use serde::Deserialize;
+use url::Url;
+use url::form_urlencoded::byte_serialize;
+use ystream::spawn_task;
+
+use super::AuthProvider;
+use super::basic_auth::basic_auth;
+use crate::HttpClient;
+use crate::error::HttpError;
+use crate::http::request::{HttpRequest, RequestBody};
+use crate::http::response::HttpResponse;
+use crate::middleware::error_response;
+use crate::retry::executor::{Answer, first_answer};
+
+/// Refresh tokens this long before they expire, unless configured otherwise
+pub const DEFAULT_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// `grant_type` of the device authorization grant
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Device flow poll interval when the server names none (RFC 8628 section 3.2)
+const DEFAULT_DEVICE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Added to the device flow poll interval on `slow_down` (RFC 8628 section 3.5)
+const SLOW_DOWN_STEP: Duration = Duration::from_secs(5);
+
+/// How new tokens are obtained
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OAuth2Grant {
+    /// Client credentials grant (RFC 6749 section 4.4)
+    ClientCredentials,
+    /// Refresh token grant (RFC 6749 section 6), from a refresh token
+    /// obtained elsewhere
+    RefreshToken,
+    /// Device authorization grant (RFC 8628) with this device authorization
+    /// endpoint
+    DeviceCode(Url),
+}
+
+/// How the client authenticates to the token endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClientAuthentication {
+    /// HTTP Basic with the client ID and secret (`client_secret_basic`)
+    #[default]
+    Basic,
+    /// Client ID and secret as form parameters (`client_secret_post`)
+    RequestBody,
+}
+
+/// Access token from a token endpoint
+#[derive(Clone, PartialEq, Eq)]
+pub struct OAuth2Token {
+    /// Token sent as `Authorization: Bearer`
+    pub access_token: String,
+    /// Token type named by the server, usually `Bearer`
+    pub token_type: String,
+    /// Token for the refresh token grant, if the server issued one
+    pub refresh_token: Option<String>,
+    /// Granted scope, if it differs from the requested one
+    pub scope: Option<String>,
+    /// When the token expires, if the server said
+    pub expires_at: Option<Instant>,
+}
+
+impl fmt::Debug for OAuth2Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuth2Token")
+            .field("access_token", &"<redacted>")
+            .field("token_type", &self.token_type)
+            .field("refresh_token", &self.refresh_token.as_ref().map(|_| "<redacted>"))
+            .field("scope", &self.scope)
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+impl OAuth2Token {
+    /// Whether the token has expired
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        self.expires_within(Duration::ZERO)
+    }
+
+    /// Whether the token expires within `margin`
+    #[inline]
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        self.expires_at.is_some_and(|expires_at| Instant::now() + margin >= expires_at)
+    }
+}
+
+/// Codes of a device authorization request (RFC 8628 section 3.2)
+#[derive(Debug, Clone)]
+pub struct DeviceAuthorization {
+    /// Code the client polls the token endpoint with
+    pub device_code: String,
+    /// Code the user enters at `verification_uri`
+    pub user_code: String,
+    /// Where the user approves the request
+    pub verification_uri: String,
+    /// `verification_uri` with the user code filled in, if offered
+    pub verification_uri_complete: Option<String>,
+    /// When the codes expire
+    pub expires_at: Instant,
+    /// Time to wait between polls
+    pub interval: Duration,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    token_type: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DeviceAuthorizationResponse {
+    device_code: String,
+    user_code: String,
+    #[serde(alias = "verification_url")]
+    verification_uri: String,
+    #[serde(default)]
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    #[serde(default)]
+    interval: Option<u64>,
+}
+
+/// Error answer of a token endpoint (RFC 6749 section 5.2)
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+/// What a token request produced
+enum TokenAnswer {
+    Token(OAuth2Token),
+    Refused(StatusCode, ErrorResponse),
+}
+
+/// OAuth 2.0 auth provider with token caching and refresh
+///
+/// Attach it to a client with
+/// [`HttpClient::with_oauth2`](crate::HttpClient::with_oauth2) so every
+/// request carries a token, or use it as an [`AuthProvider`]. Clones share
+/// the token cache.
+#[derive(Clone)]
+pub struct OAuth2 {
+    token_url: Url,
+    client_id: String,
+    client_secret: Option<String>,
+    scopes: Vec<String>,
+    grant: OAuth2Grant,
+    client_authentication: ClientAuthentication,
+    refresh_margin: Duration,
+    /// Client sending token requests; a default client if unset
+    http: Option<HttpClient>,
+    tokens: Arc<TokenCache>,
+}
+
+#[derive(Debug, Default)]
+struct TokenCache {
+    state: Mutex<TokenState>,
+    /// Signalled when a refresh finishes
+    refreshed: Condvar,
+}
+
+#[derive(Default)]
+struct TokenState {
+    token: Option<OAuth2Token>,
+    refresh_token: Option<String>,
+    refreshing: bool,
+}
+
+impl fmt::Debug for OAuth2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuth2")
+            .field("token_url", &self.token_url.as_str())
+            .field("client_id", &self.client_id)
+            .field("client_secret", &self.client_secret.as_ref().map(|_| "<redacted>"))
+            .field("scopes", &self.scopes)
+            .field("grant", &self.grant)
+            .field("client_authentication", &self.client_authentication)
+            .field("refresh_margin", &self.refresh_margin)
+            .field("http", &self.http)
+            .field("tokens", &self.tokens)
+            .finish()
+    }
+}
+
+impl fmt::Debug for TokenState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenState")
+            .field("token", &self.token)
+            .field("refresh_token", &self.refresh_token.as_ref().map(|_| "<redacted>"))
+            .field("refreshing", &self.refreshing)
+            .finish()
+    }
+}
+
+impl OAuth2 {
+    fn new(token_url: &str, client_id: String, grant: OAuth2Grant) -> Result<Self, HttpError> {
+        Ok(Self {
+            token_url: parse_url(token_url)?,
+            client_id,
+            client_secret: None,
+            scopes: Vec::new(),
+            grant,
+            client_authentication: ClientAuthentication::default(),
+            refresh_margin: DEFAULT_REFRESH_MARGIN,
+            http: None,
+            tokens: Arc::default(),
+        })
+    }
+
+    /// Provider using the client credentials grant
+    pub fn client_credentials(
+        token_url: &str,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Result<Self, HttpError> {
+        Ok(Self::new(token_url, client_id.into(), OAuth2Grant::ClientCredentials)?.client_secret(client_secret))
+    }
+
+    /// Provider using the refresh token grant, starting from `refresh_token`
+    pub fn refresh_token(
+        token_url: &str,
+        client_id: impl Into<String>,
+        refresh_token: impl Into<String>,
+    ) -> Result<Self, HttpError> {
+        let oauth = Self::new(token_url, client_id.into(), OAuth2Grant::RefreshToken)?;
+        oauth.lock().refresh_token = Some(refresh_token.into());
+        Ok(oauth)
+    }
+
+    /// Provider using the device authorization grant
+    ///
+    /// Requests fail until [`device_authorization`](Self::device_authorization)
+    /// and [`wait_for_device_token`](Self::wait_for_device_token) have
+    /// obtained a first token; it is refreshed with its refresh token after.
+    pub fn device_code(
+        device_authorization_url: &str,
+        token_url: &str,
+        client_id: impl Into<String>,
+    ) -> Result<Self, HttpError> {
+        let grant = OAuth2Grant::DeviceCode(parse_url(device_authorization_url)?);
+        Self::new(token_url, client_id.into(), grant)
+    }
+
+    /// Authenticate to the token endpoint with `secret`
+    pub fn client_secret(mut self, secret: impl Into<String>) -> Self {
+        self.client_secret = Some(secret.into());
+        self
+    }
+
+    /// Request `scope` for new tokens
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.scopes.push(scope.into());
+        self
+    }
+
+    /// Send the client secret as `client_authentication` says
+    pub fn client_authentication(mut self, client_authentication: ClientAuthentication) -> Self {
+        self.client_authentication = client_authentication;
+        self
+    }
+
+    /// Refresh tokens `margin` before they expire
+    pub fn refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin = margin;
+        self
+    }
+
+    /// Send token requests with `client`
+    pub fn with_http_client(mut self, client: HttpClient) -> Self {
+        self.http = Some(client);
+        self
+    }
+
+    /// Whether token requests have a client of their own
+    #[inline]
+    pub(crate) fn has_http_client(&self) -> bool {
+        self.http.is_some()
+    }
+
+    /// Token endpoint
+    #[inline]
+    pub fn token_url(&self) -> &Url {
+        &self.token_url
+    }
+
+    /// Grant used for new tokens
+    #[inline]
+    pub fn grant(&self) -> &OAuth2Grant {
+        &self.grant
+    }
+
+    /// Cached token, without refreshing it
+    pub fn cached_token(&self) -> Option<OAuth2Token> {
+        self.lock().token.clone()
+    }
+
+    /// Cache `token`, e.g. one obtained in an earlier session
+    pub fn set_token(&self, token: OAuth2Token) {
+        let mut state = self.lock();
+        if token.refresh_token.is_some() {
+            state.refresh_token.clone_from(&token.refresh_token);
+        }
+        state.token = Some(token);
+        drop(state);
+        self.tokens.refreshed.notify_all();
+    }
+
+    /// Access token to send, from the cache or the token endpoint
+    ///
+    /// Blocks while a token is fetched. A token within the refresh margin
+    /// of its expiry is refreshed first; it is still returned if the
+    /// refresh fails while it has not expired.
+    pub fn token(&self) -> Result<OAuth2Token, HttpError> {
+        self.obtain(None)
+    }
+
+    /// Token to use instead of `rejected`, or any token if `None`
+    fn obtain(&self, rejected: Option<&str>) -> Result<OAuth2Token, HttpError> {
+        let usable = |token: &Option<OAuth2Token>| {
+            token
+                .as_ref()
+                .filter(|token| !token.is_expired() && rejected != Some(token.access_token.as_str()))
+                .cloned()
+        };
+
+        let mut state = self.lock();
+        loop {
+            let current = usable(&state.token);
+            if let Some(token) = &current {
+                if !token.expires_within(self.refresh_margin) {
+                    return Ok(token.clone());
+                }
+            }
+            if !state.refreshing {
+                break;
+            }
+            // Another request is refreshing; a token that still works need not wait
+            if let Some(token) = current {
+                return Ok(token);
+            }
+            state = self.tokens.refreshed.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+        state.refreshing = true;
+        let refresh_token = state.refresh_token.clone();
+        drop(state);
+
+        let result = self.fetch(refresh_token);
+
+        let mut state = self.lock();
+        state.refreshing = false;
+        let result = match result {
+            Ok(token) => {
+                if token.refresh_token.is_some() {
+                    state.refresh_token.clone_from(&token.refresh_token);
+                }
+                state.token = Some(token.clone());
+                Ok(token)
+            }
+            Err(error) => match usable(&state.token) {
+                Some(token) => {
+                    tracing::warn!(target: "quyc::oauth2", error = %error, "Token refresh failed; using the current token");
+                    Ok(token)
+                }
+                None => Err(error),
+            },
+        };
+        drop(state);
+        self.tokens.refreshed.notify_all();
+        result
+    }
+
+    /// New token from the token endpoint
+    fn fetch(&self, refresh_token: Option<String>) -> Result<OAuth2Token, HttpError> {
+        let scope = self.scope_param();
+        let with_scope = |mut params: Vec<(&'static str, String)>| {
+            if let Some(scope) = &scope {
+                params.push(("scope", scope.clone()));
+            }
+            params
+        };
+
+        if let Some(refresh_token) = refresh_token {
+            let params = with_scope(vec![("grant_type", "refresh_token".to_string()), ("refresh_token", refresh_token)]);
+            match block_on(self.token_request(&params)).and_then(|answer| self.accept(answer)) {
+                Ok(token) => return Ok(token),
+                // Client credentials start over when the refresh token is refused
+                Err(error) if self.grant == OAuth2Grant::ClientCredentials => {
+                    tracing::debug!(target: "quyc::oauth2", error = %error, "Refresh token refused");
+                    self.lock().refresh_token = None;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        match &self.grant {
+            OAuth2Grant::ClientCredentials => {
+                let params = with_scope(vec![("grant_type", "client_credentials".to_string())]);
+                block_on(self.token_request(&params)).and_then(|answer| self.accept(answer))
+            }
+            OAuth2Grant::RefreshToken | OAuth2Grant::DeviceCode(_) => Err(crate::error::request(format!(
+                "no OAuth 2.0 token for {}: the grant needs a refresh token",
+                self.token_url
+            ))),
+        }
+    }
+
+    /// Start the device authorization grant (RFC 8628)
+    ///
+    /// Show the returned user code and verification URI to the user, then
+    /// call [`wait_for_device_token`](Self::wait_for_device_token).
+    pub async fn device_authorization(&self) -> Result<DeviceAuthorization, HttpError> {
+        let OAuth2Grant::DeviceCode(url) = &self.grant else {
+            return Err(crate::error::configuration("not configured for the device authorization grant"));
+        };
+        let params: Vec<(&str, String)> = self.scope_param().map(|scope| ("scope", scope)).into_iter().collect();
+        let (status, body) = self.post_form(url, &params).await?;
+        if !status.is_success() {
+            return Err(refused(url, status, &body));
+        }
+        let response: DeviceAuthorizationResponse =
+            serde_json::from_slice(&body).map_err(crate::error::deserialization_error)?;
+        Ok(DeviceAuthorization {
+            device_code: response.device_code,
+            user_code: response.user_code,
+            verification_uri: response.verification_uri,
+            verification_uri_complete: response.verification_uri_complete,
+            expires_at: Instant::now() + Duration::from_secs(response.expires_in),
+            interval: response.interval.map_or(DEFAULT_DEVICE_INTERVAL, Duration::from_secs),
+        })
+    }
+
+    /// Poll the token endpoint until the user approves `authorization`
+    ///
+    /// The token is cached for later requests. Fails once the user denies
+    /// the request or the codes expire.
+    pub async fn wait_for_device_token(&self, authorization: &DeviceAuthorization) -> Result<OAuth2Token, HttpError> {
+        let params = [
+            ("grant_type", DEVICE_CODE_GRANT.to_string()),
+            ("device_code", authorization.device_code.clone()),
+        ];
+        let mut interval = authorization.interval;
+        loop {
+            tokio::time::sleep(interval).await;
+            if Instant::now() >= authorization.expires_at {
+                return Err(crate::error::request("device code expired before the user approved it"));
+            }
+            match self.token_request(&params).await? {
+                TokenAnswer::Token(token) => {
+                    self.set_token(token.clone());
+                    return Ok(token);
+                }
+                TokenAnswer::Refused(_, error) if error.error == "authorization_pending" => {}
+                TokenAnswer::Refused(_, error) if error.error == "slow_down" => interval += SLOW_DOWN_STEP,
+                refused @ TokenAnswer::Refused(..) => return self.accept(refused),
+            }
+        }
+    }
+
+    /// Token of `answer`, or the error the endpoint answered with
+    fn accept(&self, answer: TokenAnswer) -> Result<OAuth2Token, HttpError> {
+        match answer {
+            TokenAnswer::Token(token) => Ok(token),
+            TokenAnswer::Refused(status, error) => {
+                let description = error.error_description.map(|d| format!(": {d}")).unwrap_or_default();
+                Err(crate::error::http_status(format!(
+                    "token endpoint {} refused the request with {status}: {}{description}",
+                    self.token_url, error.error
+                )))
+            }
+        }
+    }
+
+    async fn token_request(&self, params: &[(&str, String)]) -> Result<TokenAnswer, HttpError> {
+        let (status, body) = self.post_form(&self.token_url, params).await?;
+        if !status.is_success() {
+            return match serde_json::from_slice::<ErrorResponse>(&body) {
+                Ok(error) => Ok(TokenAnswer::Refused(status, error)),
+                Err(_) => Err(refused(&self.token_url, status, &body)),
+            };
+        }
+        let response: TokenResponse = serde_json::from_slice(&body).map_err(crate::error::deserialization_error)?;
+        tracing::debug!(target: "quyc::oauth2", token_url = %self.token_url, expires_in = ?response.expires_in, "Obtained access token");
+        Ok(TokenAnswer::Token(OAuth2Token {
+            access_token: response.access_token,
+            token_type: response.token_type.unwrap_or_else(|| "Bearer".to_string()),
+            refresh_token: response.refresh_token,
+            scope: response.scope,
+            expires_at: response.expires_in.map(|secs| Instant::now() + Duration::from_secs(secs)),
+        }))
+    }
+
+    /// POST `params` as a form to `url`, authenticated as the client
+    async fn post_form(&self, url: &Url, params: &[(&str, String)]) -> Result<(StatusCode, Bytes), HttpError> {
+        let mut params = params.to_vec();
+        let mut request = HttpRequest::post(url.clone())
+            .header(ACCEPT, HeaderValue::from_static("application/json"))
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/x-www-form-urlencoded"));
+        match (&self.client_secret, self.client_authentication) {
+            (Some(secret), ClientAuthentication::Basic) => {
+                // RFC 6749 section 2.3.1: both parts are form-encoded first
+                let header = basic_auth(form_encode(&self.client_id), Some(form_encode(secret)))?;
+                request.headers_mut().insert(AUTHORIZATION, header);
+            }
+            (Some(secret), ClientAuthentication::RequestBody) => {
+                params.push(("client_id", self.client_id.clone()));
+                params.push(("client_secret", secret.clone()));
+            }
+            // Public clients identify themselves in the body
+            (None, _) => params.push(("client_id", self.client_id.clone())),
+        }
+        let body = serde_urlencoded::to_string(&params).map_err(crate::error::serialization_error)?;
+
+        let http = self.http.clone().unwrap_or_default();
+        let mut response = http.execute(request.body_text(body));
+        let body = response.collect_body().await;
+        let status = response
+            .status_code()
+            .ok_or_else(|| crate::error::network_error(format!("no answer from {url}")))?;
+        Ok((status, body))
+    }
+
+    /// Send `request` with a token, through `send`
+    ///
+    /// Returns at once; the response fills in from the background. A `401`
+    /// refreshes the token and sends a replayable request once more.
+    pub(crate) fn execute<F>(&self, request: HttpRequest, header_timeout: Duration, send: F) -> HttpResponse
+    where
+        F: Fn(HttpRequest) -> Result<HttpResponse, HttpError> + Send + 'static,
+    {
+        let (response, slot) = HttpResponse::pending(request.version(), 0);
+        let oauth = self.clone();
+        spawn_task(move || {
+            let send_with = |request: HttpRequest, token: &OAuth2Token| {
+                authorize(request, token)
+                    .and_then(&send)
+                    .unwrap_or_else(error_response)
+            };
+            let token = match oauth.token() {
+                Ok(token) => token,
+                Err(error) => {
+                    slot.fill(Vec::new(), None, error_response(error));
+                    return;
+                }
+            };
+            // Everything but a streaming body survives the clone
+            let replay = (!matches!(request.body(), Some(RequestBody::Stream(_)))).then(|| request.clone());
+            let answer = first_answer(send_with(request, &token), header_timeout);
+            let rejected = matches!(
+                &answer,
+                Answer::Headers { response, .. } if response.status_code() == Some(StatusCode::UNAUTHORIZED)
+            );
+            let Some(replay) = replay.filter(|_| rejected) else {
+                answer.deliver(slot);
+                return;
+            };
+            match oauth.obtain(Some(&token.access_token)) {
+                Ok(token) => {
+                    tracing::debug!(target: "quyc::oauth2", url = %replay.url(), "Sending request again with a new token");
+                    // Dropping the answer closes the rejected response's streams
+                    drop(answer);
+                    first_answer(send_with(replay, &token), header_timeout).deliver(slot);
+                }
+                Err(error) => {
+                    tracing::debug!(target: "quyc::oauth2", error = %error, "No new token after 401");
+                    answer.deliver(slot);
+                }
+            }
+        });
+        response
+    }
+
+    fn scope_param(&self) -> Option<String> {
+        (!self.scopes.is_empty()).then(|| self.scopes.join(" "))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TokenState> {
+        self.tokens.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl AuthProvider for OAuth2 {
+    /// Blocks while a token is fetched
+    fn apply_auth(&self, headers: &mut HeaderMap) -> Result<(), HttpError> {
+        headers.insert(AUTHORIZATION, bearer(&self.token()?)?);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn auth_type(&self) -> &'static str {
+        "OAuth2"
+    }
+}
+
+/// `request` carrying `token`
+fn authorize(mut request: HttpRequest, token: &OAuth2Token) -> Result<HttpRequest, HttpError> {
+    request.headers_mut().insert(AUTHORIZATION, bearer(token)?);
+    Ok(request)
+}
+
+fn bearer(token: &OAuth2Token) -> Result<HeaderValue, HttpError> {
+    let mut value = HeaderValue::from_str(&format!("Bearer {}", token.access_token))
+        .map_err(|e| crate::error::invalid_header(format!("Invalid access token: {e}")))?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+/// Error for an answer that is not a token endpoint's JSON error
+fn refused(url: &Url, status: StatusCode, body: &[u8]) -> HttpError {
+    crate::error::http_status(format!(
+        "{url} answered {status}: {}",
+        String::from_utf8_lossy(body)
+    ))
+}
+
+fn parse_url(url: &str) -> Result<Url, HttpError> {
+    Url::parse(url).map_err(|e| crate::error::url_parse_error(format!("Invalid OAuth 2.0 endpoint {url}: {e}")))
+}
+
+fn form_encode(value: &str) -> String {
+    byte_serialize(value.as_bytes()).collect()
+}
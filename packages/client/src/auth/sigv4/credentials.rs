@@ -0,0 +1,252 @@
+//! AWS credentials and where they come from
+//!
+//! Credentials are given directly, read from the `AWS_ACCESS_KEY_ID` family
+//! of environment variables, or read from a profile in the shared
+//! credentials file (`~/.aws/credentials`). [`CredentialsChain`] tries
+//! providers in order, as the AWS CLI does.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::error::HttpError;
+
+/// Profile used when `AWS_PROFILE` is not set
+const DEFAULT_PROFILE: &str = "default";
+
+/// Access key, secret key and optional session token
+#[derive(Clone, PartialEq, Eq)]
+pub struct AwsCredentials {
+    /// Access key ID, e.g. `AKIDEXAMPLE`
+    pub access_key_id: String,
+    /// Secret access key
+    pub secret_access_key: String,
+    /// Session token of temporary credentials, sent as `x-amz-security-token`
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    /// Long-term credentials
+    pub fn new(access_key_id: impl Into<String>, secret_access_key: impl Into<String>) -> Self {
+        Self {
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            session_token: None,
+        }
+    }
+
+    /// Temporary credentials with `token`
+    #[must_use]
+    pub fn session_token(mut self, token: impl Into<String>) -> Self {
+        self.session_token = Some(token.into());
+        self
+    }
+}
+
+impl fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"<redacted>")
+            .field("session_token", &self.session_token.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+/// Source of the credentials requests are signed with
+///
+/// Called for every request, so providers that read files or call out
+/// should cache what they find.
+pub trait CredentialsProvider: fmt::Debug + Send + Sync {
+    /// Credentials to sign the next request with
+    fn credentials(&self) -> Result<AwsCredentials, HttpError>;
+}
+
+/// Static credentials
+impl CredentialsProvider for AwsCredentials {
+    #[inline]
+    fn credentials(&self) -> Result<AwsCredentials, HttpError> {
+        Ok(self.clone())
+    }
+}
+
+/// Credentials from `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and
+/// `AWS_SESSION_TOKEN`
+///
+/// The variables are read on every call, so rotated credentials are
+/// picked up.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvCredentials;
+
+impl CredentialsProvider for EnvCredentials {
+    fn credentials(&self) -> Result<AwsCredentials, HttpError> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let (Some(access_key_id), Some(secret_access_key)) = (var("AWS_ACCESS_KEY_ID"), var("AWS_SECRET_ACCESS_KEY"))
+        else {
+            return Err(crate::error::configuration(
+                "AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY are not set",
+            ));
+        };
+        Ok(AwsCredentials {
+            access_key_id,
+            secret_access_key,
+            // AWS_SECURITY_TOKEN is the name older tools export
+            session_token: var("AWS_SESSION_TOKEN").or_else(|| var("AWS_SECURITY_TOKEN")),
+        })
+    }
+}
+
+/// Credentials from a profile in the shared credentials file
+///
+/// The file is `AWS_SHARED_CREDENTIALS_FILE` or `~/.aws/credentials`, and
+/// the profile is `AWS_PROFILE` or `default`, unless set explicitly. The
+/// file is read once, on first use.
+#[derive(Debug, Default)]
+pub struct ProfileCredentials {
+    path: Option<PathBuf>,
+    profile: Option<String>,
+    cached: Mutex<Option<AwsCredentials>>,
+}
+
+impl ProfileCredentials {
+    /// Default profile from the default credentials file
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read `path` instead of the default credentials file
+    #[must_use]
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Use `profile` instead of `AWS_PROFILE` or `default`
+    #[must_use]
+    pub fn with_profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    fn load(&self) -> Result<AwsCredentials, HttpError> {
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None => default_credentials_file()
+                .ok_or_else(|| crate::error::configuration("no home directory for ~/.aws/credentials"))?,
+        };
+        let profile = self
+            .profile
+            .clone()
+            .or_else(|| std::env::var("AWS_PROFILE").ok().filter(|profile| !profile.is_empty()))
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+        let text = std::fs::read_to_string(&path).map_err(|e| {
+            crate::error::configuration(format!("Failed to read AWS credentials file {}: {e}", path.display()))
+        })?;
+        parse_profile(&text, &profile).ok_or_else(|| {
+            crate::error::configuration(format!(
+                "no credentials for profile {profile} in {}",
+                path.display()
+            ))
+        })
+    }
+}
+
+impl CredentialsProvider for ProfileCredentials {
+    fn credentials(&self) -> Result<AwsCredentials, HttpError> {
+        let mut cached = self.cached.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(credentials) = &*cached {
+            return Ok(credentials.clone());
+        }
+        let credentials = self.load()?;
+        *cached = Some(credentials.clone());
+        Ok(credentials)
+    }
+}
+
+/// Providers tried in order; the first with credentials wins
+#[derive(Debug, Clone)]
+pub struct CredentialsChain {
+    providers: Vec<Arc<dyn CredentialsProvider>>,
+}
+
+impl CredentialsChain {
+    /// Try `providers` in order
+    pub fn new(providers: Vec<Arc<dyn CredentialsProvider>>) -> Self {
+        Self { providers }
+    }
+
+    /// Also try `provider`, after the others
+    #[must_use]
+    pub fn or(mut self, provider: impl CredentialsProvider + 'static) -> Self {
+        self.providers.push(Arc::new(provider));
+        self
+    }
+}
+
+impl Default for CredentialsChain {
+    /// Environment variables, then the shared credentials file
+    fn default() -> Self {
+        Self::new(vec![Arc::new(EnvCredentials), Arc::new(ProfileCredentials::new())])
+    }
+}
+
+impl CredentialsProvider for CredentialsChain {
+    fn credentials(&self) -> Result<AwsCredentials, HttpError> {
+        let mut reasons = Vec::with_capacity(self.providers.len());
+        for provider in &self.providers {
+            match provider.credentials() {
+                Ok(credentials) => return Ok(credentials),
+                Err(error) => reasons.push(error.to_string()),
+            }
+        }
+        Err(crate::error::configuration(format!(
+            "no AWS credentials found: {}",
+            reasons.join("; ")
+        )))
+    }
+}
+
+fn default_credentials_file() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("AWS_SHARED_CREDENTIALS_FILE").filter(|path| !path.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(Path::new(&home).join(".aws").join("credentials"))
+}
+
+/// Credentials of `profile` in an INI-style credentials file
+///
+/// Sections may also be written `[profile name]`, as in `~/.aws/config`.
+fn parse_profile(text: &str, profile: &str) -> Option<AwsCredentials> {
+    let mut in_profile = false;
+    let (mut access_key_id, mut secret_access_key, mut session_token) = (None, None, None);
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            let section = section.trim();
+            in_profile = section == profile || section.strip_prefix("profile ").map(str::trim) == Some(profile);
+            continue;
+        }
+        if !in_profile {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = Some(value.trim().to_string());
+        match key.trim().to_ascii_lowercase().as_str() {
+            "aws_access_key_id" => access_key_id = value,
+            "aws_secret_access_key" => secret_access_key = value,
+            "aws_session_token" | "aws_security_token" => session_token = value,
+            _ => {}
+        }
+    }
+    Some(AwsCredentials {
+        access_key_id: access_key_id?,
+        secret_access_key: secret_access_key?,
+        session_token,
+    })
+}
@@ -0,0 +1,143 @@
+//! SigV4 signing of streaming bodies
+//!
+//! A streaming upload is signed chunk by chunk. Each chunk's signature
+//! covers the chunk and the signature before it, starting from the signature
+//! of the request itself, so chunks cannot be dropped, reordered or
+//! replaced. Event streams (`STREAMING-AWS4-HMAC-SHA256-EVENTS`, used by
+//! Bedrock and Transcribe streaming) send every message inside a frame
+//! carrying a `:date` and a `:chunk-signature` header, and end with an
+//! empty signed frame.
+
+use std::time::SystemTime;
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use super::credentials::AwsCredentials;
+use super::signer::{amz_date, hex_sha256, signing_key};
+
+/// Algorithm named in the string to sign of each chunk
+const CHUNK_ALGORITHM: &str = "AWS4-HMAC-SHA256-PAYLOAD";
+
+/// Header type tags of the event-stream encoding
+const BYTES_HEADER: u8 = 6;
+const STRING_HEADER: u8 = 7;
+const TIMESTAMP_HEADER: u8 = 8;
+
+/// Signs the chunks of a streaming body, each chained to the one before
+///
+/// Obtained from [`SigV4::sign_event_stream`](super::SigV4::sign_event_stream)
+/// or [`SignatureDetails::chunk_signer`](super::SignatureDetails::chunk_signer).
+#[derive(Debug, Clone)]
+pub struct ChunkSigner {
+    credentials: AwsCredentials,
+    region: String,
+    service: String,
+    previous: String,
+}
+
+impl ChunkSigner {
+    pub(crate) fn new(credentials: AwsCredentials, region: String, service: String, seed: String) -> Self {
+        Self {
+            credentials,
+            region,
+            service,
+            previous: seed,
+        }
+    }
+
+    /// Signature of the last chunk, or of the request before the first
+    #[inline]
+    pub fn previous_signature(&self) -> &str {
+        &self.previous
+    }
+
+    /// Signature of the next chunk, made of the encoded `headers` and
+    /// `payload`, at `time`
+    ///
+    /// `aws-chunked` uploads sign each chunk with no headers.
+    pub fn sign_chunk(&mut self, headers: &[u8], payload: &[u8], time: SystemTime) -> String {
+        let (timestamp, date) = amz_date(time);
+        let scope = format!("{date}/{}/{}/aws4_request", self.region, self.service);
+        let string_to_sign = format!(
+            "{CHUNK_ALGORITHM}\n{timestamp}\n{scope}\n{}\n{}\n{}",
+            self.previous,
+            hex_sha256(headers),
+            hex_sha256(payload),
+        );
+        let key = signing_key(&self.credentials.secret_access_key, &date, &self.region, &self.service);
+        self.previous = hex::encode(ring::hmac::sign(&key, string_to_sign.as_bytes()));
+        self.previous.clone()
+    }
+
+    /// Signed frame carrying `message`, an encoded event-stream message
+    #[inline]
+    pub fn sign_event(&mut self, message: &[u8]) -> Bytes {
+        self.sign_event_at(message, SystemTime::now())
+    }
+
+    /// Signed frame carrying `message`, dated `time`
+    pub fn sign_event_at(&mut self, message: &[u8], time: SystemTime) -> Bytes {
+        let millis = time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as i64);
+        let mut date = BytesMut::new();
+        put_header(&mut date, ":date", TIMESTAMP_HEADER, &millis.to_be_bytes());
+        let signature = self.sign_chunk(&date, message, time);
+
+        let mut headers = date;
+        // The signature travels as its raw 32 bytes
+        let signature = hex::decode(signature).unwrap_or_default();
+        put_header(&mut headers, ":chunk-signature", BYTES_HEADER, &bytes_value(&signature));
+        encode_frame(&headers, message)
+    }
+
+    /// Empty signed frame that ends the stream
+    #[inline]
+    pub fn finish(&mut self) -> Bytes {
+        self.sign_event(&[])
+    }
+}
+
+/// Event-stream message with string `headers` around `payload`, ready for
+/// [`ChunkSigner::sign_event`]
+///
+/// Headers such as `:message-type`, `:event-type` and `:content-type` name
+/// the event for the service.
+pub fn encode_message(headers: &[(&str, &str)], payload: &[u8]) -> Bytes {
+    let mut encoded = BytesMut::new();
+    for (name, value) in headers {
+        put_header(&mut encoded, name, STRING_HEADER, &bytes_value(value.as_bytes()));
+    }
+    encode_frame(&encoded, payload)
+}
+
+/// Prelude (total and header lengths, CRC), headers, payload and a CRC of
+/// everything before it
+fn encode_frame(headers: &[u8], payload: &[u8]) -> Bytes {
+    let total = 12 + headers.len() + payload.len() + 4;
+    let mut frame = BytesMut::with_capacity(total);
+    frame.put_u32(total as u32);
+    frame.put_u32(headers.len() as u32);
+    let prelude_crc = crc32fast::hash(&frame);
+    frame.put_u32(prelude_crc);
+    frame.put_slice(headers);
+    frame.put_slice(payload);
+    let message_crc = crc32fast::hash(&frame);
+    frame.put_u32(message_crc);
+    frame.freeze()
+}
+
+fn put_header(buf: &mut BytesMut, name: &str, kind: u8, value: &[u8]) {
+    buf.put_u8(name.len() as u8);
+    buf.put_slice(name.as_bytes());
+    buf.put_u8(kind);
+    buf.put_slice(value);
+}
+
+/// Length-prefixed value of a string or byte array header
+fn bytes_value(value: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(2 + value.len());
+    encoded.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    encoded.extend_from_slice(value);
+    encoded
+}
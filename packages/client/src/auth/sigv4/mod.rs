@@ -0,0 +1,14 @@
+//! AWS Signature Version 4
+//!
+//! Signs requests for AWS services such as Bedrock and S3, with credentials
+//! from the environment, the shared credentials file or the caller.
+//! Streaming uploads are signed chunk by chunk, including event streams.
+
+mod canonical;
+pub mod credentials;
+pub mod event_stream;
+pub mod signer;
+
+pub use credentials::{AwsCredentials, CredentialsChain, CredentialsProvider, EnvCredentials, ProfileCredentials};
+pub use event_stream::{ChunkSigner, encode_message};
+pub use signer::{STREAMING_EVENTS_PAYLOAD, SigV4, SignatureDetails, UNSIGNED_PAYLOAD};
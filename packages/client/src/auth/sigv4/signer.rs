@@ -0,0 +1,291 @@
+//! SigV4 request signer
+
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use http::header::AUTHORIZATION;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use ring::{digest, hmac};
+
+use super::canonical::CanonicalRequest;
+use super::credentials::{AwsCredentials, CredentialsChain, CredentialsProvider};
+use super::event_stream::ChunkSigner;
+use crate::auth::AuthProvider;
+use crate::error::HttpError;
+use crate::http::request::{HttpRequest, RequestBody};
+
+/// Payload hash of bodies that are not signed, e.g. streams
+pub const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// Payload hash of event-stream bodies signed message by message
+pub const STREAMING_EVENTS_PAYLOAD: &str = "STREAMING-AWS4-HMAC-SHA256-EVENTS";
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+const X_AMZ_DATE: HeaderName = HeaderName::from_static("x-amz-date");
+const X_AMZ_SECURITY_TOKEN: HeaderName = HeaderName::from_static("x-amz-security-token");
+const X_AMZ_CONTENT_SHA256: HeaderName = HeaderName::from_static("x-amz-content-sha256");
+
+/// Signs requests with AWS Signature Version 4
+///
+/// Each signature covers the method, path, query, headers and a hash of
+/// the body; streaming bodies are sent as `UNSIGNED-PAYLOAD`. Credentials
+/// are fetched from the provider for every request, so rotated credentials
+/// are used as soon as the provider returns them. Set on a client with
+/// [`HttpClient::with_sigv4`](crate::HttpClient::with_sigv4), every attempt
+/// and redirect hop is signed afresh.
+///
+/// ```no_run
+/// use quyc_client::auth::{AwsCredentials, SigV4};
+/// use quyc_client::HttpRequest;
+///
+/// let signer = SigV4::new("us-east-1", "bedrock", AwsCredentials::new("AKIDEXAMPLE", "secret"));
+/// let mut request = HttpRequest::post("https://bedrock-runtime.us-east-1.amazonaws.com/model/m/invoke")
+///     .body_text("{}");
+/// signer.sign(&mut request).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SigV4 {
+    region: String,
+    service: String,
+    credentials: Arc<dyn CredentialsProvider>,
+    content_sha256: bool,
+    unsigned_payload: bool,
+}
+
+/// What a signature was computed from, for debugging signature mismatches
+#[derive(Debug, Clone)]
+pub struct SignatureDetails {
+    /// Canonical request
+    pub canonical_request: String,
+    /// String to sign, ending in the hash of the canonical request
+    pub string_to_sign: String,
+    /// Hex-encoded signature
+    pub signature: String,
+    credentials: AwsCredentials,
+    region: String,
+    service: String,
+}
+
+impl SignatureDetails {
+    /// Signer for the chunks of a streaming body, seeded with this signature
+    pub fn chunk_signer(&self) -> ChunkSigner {
+        ChunkSigner::new(
+            self.credentials.clone(),
+            self.region.clone(),
+            self.service.clone(),
+            self.signature.clone(),
+        )
+    }
+}
+
+impl SigV4 {
+    /// Sign for `service` in `region` with credentials from `credentials`
+    ///
+    /// S3 requests also carry an `x-amz-content-sha256` header, as S3
+    /// requires.
+    pub fn new(
+        region: impl Into<String>,
+        service: impl Into<String>,
+        credentials: impl CredentialsProvider + 'static,
+    ) -> Self {
+        let service = service.into();
+        Self {
+            region: region.into(),
+            content_sha256: service == "s3",
+            service,
+            credentials: Arc::new(credentials),
+            unsigned_payload: false,
+        }
+    }
+
+    /// Sign with credentials from the environment or the shared
+    /// credentials file
+    pub fn from_default_credentials(region: impl Into<String>, service: impl Into<String>) -> Self {
+        Self::new(region, service, CredentialsChain::default())
+    }
+
+    /// Send the payload hash as `x-amz-content-sha256`
+    #[must_use]
+    pub fn content_sha256_header(mut self, enabled: bool) -> Self {
+        self.content_sha256 = enabled;
+        self
+    }
+
+    /// Sign `UNSIGNED-PAYLOAD` instead of hashing bodies, where the service
+    /// allows it
+    #[must_use]
+    pub fn unsigned_payload(mut self, enabled: bool) -> Self {
+        self.unsigned_payload = enabled;
+        self
+    }
+
+    /// Region requests are signed for
+    #[inline]
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    /// Service requests are signed for
+    #[inline]
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    /// Sign `request` now
+    ///
+    /// Replaces the signature of an earlier signing.
+    #[inline]
+    pub fn sign(&self, request: &mut HttpRequest) -> Result<(), HttpError> {
+        self.sign_at(request, SystemTime::now()).map(drop)
+    }
+
+    /// Sign `request` as of `time`
+    ///
+    /// A payload hash set as `x-amz-content-sha256` beforehand is signed as
+    /// given, e.g. `STREAMING-AWS4-HMAC-SHA256-PAYLOAD` for `aws-chunked`
+    /// uploads.
+    pub fn sign_at(&self, request: &mut HttpRequest, time: SystemTime) -> Result<SignatureDetails, HttpError> {
+        let credentials = self.credentials.credentials()?;
+        let (timestamp, date) = amz_date(time);
+
+        let preset_hash = request
+            .headers()
+            .get(X_AMZ_CONTENT_SHA256)
+            .map(|value| {
+                value
+                    .to_str()
+                    .map(str::to_string)
+                    .map_err(|e| crate::error::invalid_header(format!("Invalid x-amz-content-sha256: {e}")))
+            })
+            .transpose()?;
+        let payload_hash = match preset_hash {
+            Some(hash) => hash,
+            None => self.payload_hash(request.body()),
+        };
+
+        let headers = request.headers_mut();
+        headers.remove(AUTHORIZATION);
+        headers.remove(X_AMZ_SECURITY_TOKEN);
+        headers.insert(X_AMZ_DATE, header_value(&timestamp, false)?);
+        if let Some(token) = &credentials.session_token {
+            headers.insert(X_AMZ_SECURITY_TOKEN, header_value(token, true)?);
+        }
+        if self.content_sha256 {
+            headers.insert(X_AMZ_CONTENT_SHA256, header_value(&payload_hash, false)?);
+        }
+
+        let canonical = CanonicalRequest::new(
+            request.method(),
+            request.url(),
+            request.headers(),
+            &payload_hash,
+            self.service != "s3",
+        );
+        let scope = format!("{date}/{}/{}/aws4_request", self.region, self.service);
+        let string_to_sign = format!(
+            "{ALGORITHM}\n{timestamp}\n{scope}\n{}",
+            hex_sha256(canonical.text.as_bytes())
+        );
+        let key = signing_key(&credentials.secret_access_key, &date, &self.region, &self.service);
+        let signature = hex::encode(hmac::sign(&key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "{ALGORITHM} Credential={}/{scope}, SignedHeaders={}, Signature={signature}",
+            credentials.access_key_id, canonical.signed_headers
+        );
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, header_value(&authorization, true)?);
+        tracing::debug!(
+            target: "quyc::sigv4",
+            url = %request.url(),
+            signed_headers = %canonical.signed_headers,
+            "Signed request"
+        );
+
+        Ok(SignatureDetails {
+            canonical_request: canonical.text,
+            string_to_sign,
+            signature,
+            credentials,
+            region: self.region.clone(),
+            service: self.service.clone(),
+        })
+    }
+
+    /// Sign `request` for an event-stream body and return the signer for
+    /// its messages
+    ///
+    /// Send each message as [`ChunkSigner::sign_event`] frames it, then the
+    /// frame of [`ChunkSigner::finish`]. The request itself is not signed
+    /// again on retries or redirects, since its messages chain to this
+    /// signature.
+    pub fn sign_event_stream(&self, request: &mut HttpRequest) -> Result<ChunkSigner, HttpError> {
+        request
+            .headers_mut()
+            .insert(X_AMZ_CONTENT_SHA256, HeaderValue::from_static(STREAMING_EVENTS_PAYLOAD));
+        Ok(self.sign_at(request, SystemTime::now())?.chunk_signer())
+    }
+
+    fn payload_hash(&self, body: Option<&RequestBody>) -> String {
+        if self.unsigned_payload {
+            return UNSIGNED_PAYLOAD.to_string();
+        }
+        match body {
+            None => hex_sha256(b""),
+            Some(body) => body
+                .to_bytes()
+                .map_or_else(|| UNSIGNED_PAYLOAD.to_string(), |bytes| hex_sha256(&bytes)),
+        }
+    }
+}
+
+impl AuthProvider for SigV4 {
+    /// SigV4 signs more than headers; this fails, use
+    /// [`sign_request`](AuthProvider::sign_request)
+    fn apply_auth(&self, _headers: &mut HeaderMap) -> Result<(), HttpError> {
+        Err(crate::error::configuration(
+            "SigV4 signs the method, URL and body too; use sign_request",
+        ))
+    }
+
+    #[inline]
+    fn sign_request(&self, request: &mut HttpRequest) -> Result<(), HttpError> {
+        self.sign(request)
+    }
+
+    #[inline(always)]
+    fn auth_type(&self) -> &'static str {
+        "SigV4"
+    }
+}
+
+/// `X-Amz-Date` timestamp and date of the credential scope
+pub(crate) fn amz_date(time: SystemTime) -> (String, String) {
+    let time: DateTime<Utc> = time.into();
+    (
+        time.format("%Y%m%dT%H%M%SZ").to_string(),
+        time.format("%Y%m%d").to_string(),
+    )
+}
+
+/// Key derived from the secret for one day, region and service
+pub(crate) fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> hmac::Key {
+    let mut key = hmac::Key::new(hmac::HMAC_SHA256, format!("AWS4{secret}").as_bytes());
+    for part in [date, region, service, "aws4_request"] {
+        key = hmac::Key::new(hmac::HMAC_SHA256, hmac::sign(&key, part.as_bytes()).as_ref());
+    }
+    key
+}
+
+pub(crate) fn hex_sha256(data: &[u8]) -> String {
+    hex::encode(digest::digest(&digest::SHA256, data))
+}
+
+fn header_value(value: &str, sensitive: bool) -> Result<HeaderValue, HttpError> {
+    let mut value = HeaderValue::from_str(value)
+        .map_err(|e| crate::error::invalid_header(format!("Invalid SigV4 header value: {e}")))?;
+    value.set_sensitive(sensitive);
+    Ok(value)
+}
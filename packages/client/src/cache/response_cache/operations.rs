@@ -76,6 +76,7 @@ impl ResponseCache {
                     offset: 0,
                     is_final: true,
                     timestamp: std::time::Instant::now(),
+                    failure: None,
                 }
             );
         });
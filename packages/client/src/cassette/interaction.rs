@@ -0,0 +1,170 @@
+//! Serialized form of recorded interactions
+//!
+//! Cassette files are JSON documents holding a list of request/response
+//! pairs. Bodies are stored as UTF-8 text when possible and base64 otherwise,
+//! so recorded JSON APIs stay readable and diffable.
+
+use base64::Engine;
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode, Version};
+use serde::{Deserialize, Serialize};
+
+/// Current cassette file format version
+pub const CASSETTE_FORMAT_VERSION: u32 = 1;
+
+/// Contents of a cassette file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CassetteFile {
+    /// File format version
+    pub version: u32,
+    /// Recorded interactions in recording order
+    pub interactions: Vec<Interaction>,
+}
+
+/// One recorded request and the response it received
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The request as sent, after redaction
+    pub request: RecordedRequest,
+    /// The response as received, after redaction
+    pub response: RecordedResponse,
+    /// When the interaction was recorded
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Recorded request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// Request method
+    pub method: String,
+    /// Full request URL
+    pub url: String,
+    /// Request headers as name/value pairs
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// Request body; absent for empty, multipart and streaming bodies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<CassetteBody>,
+}
+
+/// Recorded response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    /// Status code
+    pub status: u16,
+    /// Protocol version the response arrived over (`HTTP/2`, `HTTP/3`, ...)
+    pub version: String,
+    /// Response headers as name/value pairs
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// Body chunks with their arrival timing
+    #[serde(default)]
+    pub chunks: Vec<RecordedChunk>,
+    /// Trailers as name/value pairs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trailers: Vec<(String, String)>,
+}
+
+/// One body chunk and when it arrived
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedChunk {
+    /// Milliseconds since the previous chunk (or since the response started)
+    pub delay_ms: u64,
+    /// Chunk data
+    pub data: CassetteBody,
+}
+
+/// Body bytes, stored as text when valid UTF-8
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "encoding", content = "data", rename_all = "lowercase")]
+pub enum CassetteBody {
+    /// UTF-8 text
+    Utf8(String),
+    /// Arbitrary bytes, base64 encoded
+    Base64(String),
+}
+
+impl CassetteBody {
+    /// Encode bytes, preferring readable text
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => CassetteBody::Utf8(text.to_string()),
+            Err(_) => CassetteBody::Base64(base64::engine::general_purpose::STANDARD.encode(bytes)),
+        }
+    }
+
+    /// Decode back to bytes; invalid base64 decodes to an empty body
+    pub fn to_bytes(&self) -> Bytes {
+        match self {
+            CassetteBody::Utf8(text) => Bytes::from(text.clone()),
+            CassetteBody::Base64(encoded) => base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map(Bytes::from)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl RecordedResponse {
+    /// Parsed status, falling back to `200 OK` for invalid codes
+    pub fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK)
+    }
+
+    /// Parsed protocol version, falling back to HTTP/1.1
+    pub fn http_version(&self) -> Version {
+        match self.version.as_str() {
+            "HTTP/0.9" => Version::HTTP_09,
+            "HTTP/1.0" => Version::HTTP_10,
+            "HTTP/2.0" | "HTTP/2" => Version::HTTP_2,
+            "HTTP/3.0" | "HTTP/3" => Version::HTTP_3,
+            _ => Version::HTTP_11,
+        }
+    }
+
+    /// Complete body, concatenating all chunks
+    pub fn body(&self) -> Bytes {
+        match self.chunks.as_slice() {
+            [] => Bytes::new(),
+            [chunk] => chunk.data.to_bytes(),
+            chunks => {
+                let mut body = Vec::new();
+                for chunk in chunks {
+                    body.extend_from_slice(&chunk.data.to_bytes());
+                }
+                Bytes::from(body)
+            }
+        }
+    }
+}
+
+/// Render a protocol version the way cassettes store it
+pub fn version_name(version: Version) -> String {
+    format!("{version:?}")
+}
+
+/// Header map as name/value pairs; non-UTF-8 values are stored lossily
+pub fn header_pairs(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.as_str().to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+/// Name/value pairs back to typed headers, skipping invalid entries
+pub fn typed_headers(pairs: &[(String, String)]) -> Vec<(HeaderName, HeaderValue)> {
+    pairs
+        .iter()
+        .filter_map(|(name, value)| {
+            Some((
+                HeaderName::from_bytes(name.as_bytes()).ok()?,
+                HeaderValue::from_str(value).ok()?,
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,155 @@
+//! Request matching and secret redaction for cassettes
+
+use http::HeaderName;
+use url::Url;
+
+use super::interaction::RecordedRequest;
+
+/// Placeholder written in place of redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Which parts of a request must agree for a recorded interaction to replay
+///
+/// Defaults to method and URL. Comparisons happen after redaction, so
+/// redacted headers and query parameters never prevent a match.
+#[derive(Debug, Clone)]
+pub struct MatchRules {
+    /// Compare request methods
+    pub method: bool,
+    /// Compare full URLs, including the query string
+    pub url: bool,
+    /// Compare request bodies byte for byte
+    pub body: bool,
+    /// Headers whose values must be equal
+    pub headers: Vec<HeaderName>,
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self {
+            method: true,
+            url: true,
+            body: false,
+            headers: Vec::new(),
+        }
+    }
+}
+
+impl MatchRules {
+    /// Match on method and URL only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable or disable method matching
+    pub fn method(mut self, enabled: bool) -> Self {
+        self.method = enabled;
+        self
+    }
+
+    /// Enable or disable URL matching
+    pub fn url(mut self, enabled: bool) -> Self {
+        self.url = enabled;
+        self
+    }
+
+    /// Enable or disable body matching
+    pub fn body(mut self, enabled: bool) -> Self {
+        self.body = enabled;
+        self
+    }
+
+    /// Also require this header to match
+    pub fn header(mut self, name: HeaderName) -> Self {
+        self.headers.push(name);
+        self
+    }
+
+    /// Check whether a recorded request satisfies the rules for `incoming`
+    pub fn matches(&self, recorded: &RecordedRequest, incoming: &RecordedRequest) -> bool {
+        (!self.method || recorded.method.eq_ignore_ascii_case(&incoming.method))
+            && (!self.url || recorded.url == incoming.url)
+            && (!self.body || recorded.body == incoming.body)
+            && self.headers.iter().all(|name| {
+                header_values(recorded, name) == header_values(incoming, name)
+            })
+    }
+}
+
+fn header_values<'a>(request: &'a RecordedRequest, name: &HeaderName) -> Vec<&'a str> {
+    request
+        .headers
+        .iter()
+        .filter(|(n, _)| n.eq_ignore_ascii_case(name.as_str()))
+        .map(|(_, v)| v.as_str())
+        .collect()
+}
+
+/// Secrets removed from recorded interactions
+#[derive(Debug, Clone)]
+pub struct Redaction {
+    /// Request and response headers whose values are replaced
+    pub headers: Vec<HeaderName>,
+    /// URL query parameters whose values are replaced
+    pub query_params: Vec<String>,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self {
+            headers: vec![
+                http::header::AUTHORIZATION,
+                http::header::PROXY_AUTHORIZATION,
+                http::header::COOKIE,
+                http::header::SET_COOKIE,
+                HeaderName::from_static("x-api-key"),
+                HeaderName::from_static("x-amz-security-token"),
+            ],
+            query_params: Vec::new(),
+        }
+    }
+}
+
+impl Redaction {
+    /// Replace values of sensitive headers in name/value pairs
+    pub fn headers(&self, headers: &mut [(String, String)]) {
+        for (name, value) in headers.iter_mut() {
+            if self.headers.iter().any(|h| h.as_str().eq_ignore_ascii_case(name)) {
+                *value = REDACTED.to_string();
+            }
+        }
+    }
+
+    /// Replace values of sensitive query parameters in a URL
+    pub fn url(&self, url: &str) -> String {
+        if self.query_params.is_empty() {
+            return url.to_string();
+        }
+        let Ok(mut parsed) = Url::parse(url) else {
+            return url.to_string();
+        };
+        if parsed.query().is_none() {
+            return url.to_string();
+        }
+
+        let pairs: Vec<(String, String)> = parsed
+            .query_pairs()
+            .map(|(name, value)| {
+                let value = if self.query_params.iter().any(|p| p == name.as_ref()) {
+                    REDACTED.to_string()
+                } else {
+                    value.into_owned()
+                };
+                (name.into_owned(), value)
+            })
+            .collect();
+        parsed.query_pairs_mut().clear().extend_pairs(pairs);
+        parsed.into()
+    }
+
+    /// Redact a recorded request in place
+    pub fn request(&self, request: &mut RecordedRequest) {
+        request.url = self.url(&request.url);
+        self.headers(&mut request.headers);
+    }
+}
@@ -0,0 +1,437 @@
+//! Record/replay cassettes for deterministic tests
+//!
+//! A [`Cassette`] attached to an [`HttpClient`](crate::HttpClient) sits in
+//! the request execution path. In record mode every request goes to the
+//! network and the interaction (request, status, headers, body chunks with
+//! their timing, trailers) is appended to a JSON cassette file. In replay
+//! mode matching requests are answered from the file without touching the
+//! network; what "matching" means is configured with [`MatchRules`].
+//!
+//! Secrets are redacted before anything is written: `Authorization`,
+//! cookies and API key headers by default, plus any configured headers and
+//! query parameters.
+//!
+//! ```no_run
+//! use quyc_client::HttpClient;
+//! use quyc_client::cassette::{Cassette, CassetteMode, MatchRules};
+//!
+//! let cassette = Cassette::open("tests/cassettes/users.json", CassetteMode::ReplayOrRecord)?
+//!     .match_on(MatchRules::new().body(true))
+//!     .redact_query_param("api_key");
+//! let client = HttpClient::default().with_cassette(cassette);
+//! # Ok::<(), quyc_client::HttpError>(())
+//! ```
+
+pub mod interaction;
+pub mod matcher;
+
+pub use interaction::{
+    CassetteBody, CassetteFile, Interaction, RecordedChunk, RecordedRequest, RecordedResponse,
+};
+pub use matcher::{MatchRules, REDACTED, Redaction};
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use http::{HeaderName, StatusCode};
+use ystream::{AsyncStream, emit, spawn_task};
+
+use crate::error::HttpError;
+use crate::http::request::HttpRequest;
+use crate::http::response::{HttpBodyChunk, HttpHeader, HttpResponse};
+use interaction::{CASSETTE_FORMAT_VERSION, header_pairs, typed_headers, version_name};
+
+/// How a cassette treats requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send every request to the network and record it, replacing the file
+    Record,
+    /// Answer only from the cassette; unmatched requests fail without network access
+    Replay,
+    /// Replay matching interactions and record new ones
+    ReplayOrRecord,
+}
+
+/// Recorded interactions plus which of them have been replayed
+#[derive(Debug, Default)]
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    played: Vec<bool>,
+}
+
+/// A cassette file of recorded HTTP interactions
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    rules: MatchRules,
+    redaction: Redaction,
+    replay_timing: bool,
+    state: Mutex<CassetteState>,
+}
+
+impl Cassette {
+    /// Open a cassette file in the given mode
+    ///
+    /// `Replay` requires the file to exist; `ReplayOrRecord` starts empty if
+    /// it does not; `Record` always starts empty and overwrites the file.
+    pub fn open(path: impl AsRef<Path>, mode: CassetteMode) -> Result<Self, HttpError> {
+        let path = path.as_ref().to_path_buf();
+        let interactions = match mode {
+            CassetteMode::Record => Vec::new(),
+            CassetteMode::Replay => load(&path)?.interactions,
+            CassetteMode::ReplayOrRecord if path.exists() => load(&path)?.interactions,
+            CassetteMode::ReplayOrRecord => Vec::new(),
+        };
+
+        Ok(Self {
+            path,
+            mode,
+            rules: MatchRules::default(),
+            redaction: Redaction::default(),
+            replay_timing: false,
+            state: Mutex::new(CassetteState {
+                played: vec![false; interactions.len()],
+                interactions,
+            }),
+        })
+    }
+
+    /// Start a fresh recording at `path`
+    pub fn record(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            mode: CassetteMode::Record,
+            rules: MatchRules::default(),
+            redaction: Redaction::default(),
+            replay_timing: false,
+            state: Mutex::new(CassetteState::default()),
+        }
+    }
+
+    /// Replay the cassette at `path` without network access
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self, HttpError> {
+        Self::open(path, CassetteMode::Replay)
+    }
+
+    /// Set the rules deciding which recorded interaction answers a request
+    pub fn match_on(mut self, rules: MatchRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Redact an additional request/response header
+    pub fn redact_header(mut self, name: HeaderName) -> Self {
+        self.redaction.headers.push(name);
+        self
+    }
+
+    /// Redact the value of a URL query parameter
+    pub fn redact_query_param(mut self, name: impl Into<String>) -> Self {
+        self.redaction.query_params.push(name.into());
+        self
+    }
+
+    /// Replace the redaction settings, including the default header list
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Reproduce the recorded delays between body chunks on replay
+    ///
+    /// Off by default, so replayed tests run as fast as possible.
+    pub fn replay_timing(mut self, enabled: bool) -> Self {
+        self.replay_timing = enabled;
+        self
+    }
+
+    /// Cassette mode
+    #[inline]
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Cassette file path
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Snapshot of the recorded interactions
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.state
+            .lock()
+            .map(|state| state.interactions.clone())
+            .unwrap_or_default()
+    }
+
+    /// Write the cassette file, creating parent directories as needed
+    pub fn save(&self) -> Result<(), HttpError> {
+        let file = CassetteFile {
+            version: CASSETTE_FORMAT_VERSION,
+            interactions: self.interactions(),
+        };
+        let json = serde_json::to_vec_pretty(&file).map_err(crate::error::serialization_error)?;
+
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(crate::error::configuration)?;
+        }
+        std::fs::write(&self.path, json).map_err(crate::error::configuration)
+    }
+
+    /// Redacted snapshot of a request, as it would be stored and matched
+    pub fn snapshot(&self, request: &HttpRequest) -> RecordedRequest {
+        let mut recorded = RecordedRequest {
+            method: request.method().as_str().to_string(),
+            url: request.url().as_str().to_string(),
+            headers: header_pairs(request.headers()),
+            body: request
+                .body()
+                .and_then(|body| body.to_bytes())
+                .filter(|bytes| !bytes.is_empty())
+                .map(|bytes| CassetteBody::from_bytes(&bytes)),
+        };
+        self.redaction.request(&mut recorded);
+        recorded
+    }
+
+    /// Answer `request` from the cassette, if the mode allows it
+    ///
+    /// Interactions are replayed in recording order: the first unplayed match
+    /// wins, and once all matches have been played the last one repeats. In
+    /// `Replay` mode a request without any match gets a `501` error response.
+    pub(crate) fn replay_response(&self, request: &RecordedRequest) -> Option<HttpResponse> {
+        if self.mode == CassetteMode::Record {
+            return None;
+        }
+
+        let recorded = self.state.lock().ok().and_then(|mut state| {
+            let CassetteState { interactions, played } = &mut *state;
+            let matching: Vec<usize> = interactions
+                .iter()
+                .enumerate()
+                .filter(|(_, interaction)| self.rules.matches(&interaction.request, request))
+                .map(|(index, _)| index)
+                .collect();
+            let index = matching
+                .iter()
+                .copied()
+                .find(|&index| !played[index])
+                .or_else(|| matching.last().copied())?;
+            played[index] = true;
+            Some(interactions[index].response.clone())
+        });
+
+        match recorded {
+            Some(response) => {
+                tracing::debug!(
+                    target: "quyc::cassette",
+                    method = %request.method,
+                    url = %request.url,
+                    status = response.status,
+                    "Replaying recorded interaction"
+                );
+                Some(self.build_response(response))
+            }
+            None if self.mode == CassetteMode::Replay => {
+                tracing::warn!(
+                    target: "quyc::cassette",
+                    method = %request.method,
+                    url = %request.url,
+                    cassette = %self.path.display(),
+                    "No recorded interaction matches request"
+                );
+                Some(HttpResponse::error(
+                    StatusCode::NOT_IMPLEMENTED,
+                    format!(
+                        "No interaction in cassette {} matches {} {}",
+                        self.path.display(),
+                        request.method,
+                        request.url
+                    ),
+                ))
+            }
+            None => None,
+        }
+    }
+
+    /// Turn a recorded response back into a streaming `HttpResponse`
+    fn build_response(&self, recorded: RecordedResponse) -> HttpResponse {
+        let headers = typed_headers(&recorded.headers);
+        let trailers = typed_headers(&recorded.trailers);
+        let replay_timing = self.replay_timing;
+        let chunks = recorded.chunks.clone();
+
+        let headers_stream = AsyncStream::with_channel(move |sender| {
+            for (name, value) in headers {
+                emit!(sender, HttpHeader { name, value, timestamp: Instant::now() });
+            }
+        });
+
+        let body_stream = AsyncStream::with_channel(move |sender| {
+            spawn_task(move || {
+                let mut offset = 0u64;
+                let last = chunks.len().saturating_sub(1);
+                for (index, chunk) in chunks.into_iter().enumerate() {
+                    if replay_timing && chunk.delay_ms > 0 {
+                        std::thread::sleep(Duration::from_millis(chunk.delay_ms));
+                    }
+                    let data = chunk.data.to_bytes();
+                    let len = data.len() as u64;
+                    emit!(sender, HttpBodyChunk::new(data, offset, index == last));
+                    offset += len;
+                }
+            });
+        });
+
+        let trailers_stream = AsyncStream::with_channel(move |sender| {
+            for (name, value) in trailers {
+                emit!(sender, HttpHeader { name, value, timestamp: Instant::now() });
+            }
+        });
+
+        let response = HttpResponse::new(
+            headers_stream,
+            body_stream,
+            trailers_stream,
+            recorded.http_version(),
+            0,
+        );
+        response.set_status(recorded.status_code());
+        response
+    }
+
+    /// Record `response` as it streams to the caller
+    ///
+    /// Headers, body and trailers are forwarded unchanged; once all three
+    /// streams have ended the interaction is appended and the file rewritten.
+    pub(crate) fn record_response(
+        self: &Arc<Self>,
+        request: RecordedRequest,
+        mut response: HttpResponse,
+    ) -> HttpResponse {
+        let (headers_tx, headers_rx) = AsyncStream::<HttpHeader, 256>::channel();
+        let (body_tx, body_rx) = AsyncStream::<HttpBodyChunk, 1024>::channel();
+        let (trailers_tx, trailers_rx) = AsyncStream::<HttpHeader, 64>::channel();
+        let (headers, body, trailers) = response.swap_streams(headers_rx, body_rx, trailers_rx);
+
+        let recording = Arc::new(Recording {
+            cassette: self.clone(),
+            request: Mutex::new(Some(request)),
+            response: Mutex::new(RecordedResponse {
+                status: response.status(),
+                version: version_name(response.version),
+                headers: Vec::new(),
+                chunks: Vec::new(),
+                trailers: Vec::new(),
+            }),
+            remaining: AtomicUsize::new(3),
+        });
+
+        let headers_recording = recording.clone();
+        spawn_task(move || {
+            for header in headers {
+                headers_recording.update(|r| r.headers.push(pair(&header)));
+                emit!(headers_tx, header);
+            }
+            headers_recording.finish();
+        });
+
+        let body_recording = recording.clone();
+        spawn_task(move || {
+            let mut last = Instant::now();
+            for chunk in body {
+                let now = Instant::now();
+                let delay_ms = now.duration_since(last).as_millis() as u64;
+                last = now;
+                if !chunk.data.is_empty() {
+                    let data = CassetteBody::from_bytes(&chunk.data);
+                    body_recording.update(|r| r.chunks.push(RecordedChunk { delay_ms, data }));
+                }
+                emit!(body_tx, chunk);
+            }
+            body_recording.finish();
+        });
+
+        spawn_task(move || {
+            for trailer in trailers {
+                recording.update(|r| r.trailers.push(pair(&trailer)));
+                emit!(trailers_tx, trailer);
+            }
+            recording.finish();
+        });
+
+        response
+    }
+
+    /// Append a finished interaction and persist the cassette
+    fn commit(&self, mut interaction: Interaction) {
+        self.redaction.headers(&mut interaction.response.headers);
+        self.redaction.headers(&mut interaction.response.trailers);
+
+        if let Ok(mut state) = self.state.lock() {
+            state.interactions.push(interaction);
+            // Freshly recorded interactions count as played for ReplayOrRecord
+            state.played.push(true);
+        }
+
+        if let Err(e) = self.save() {
+            tracing::warn!(
+                target: "quyc::cassette",
+                error = %e,
+                cassette = %self.path.display(),
+                "Failed to write cassette"
+            );
+        }
+    }
+}
+
+/// An interaction being recorded while its streams drain
+struct Recording {
+    cassette: Arc<Cassette>,
+    request: Mutex<Option<RecordedRequest>>,
+    response: Mutex<RecordedResponse>,
+    /// Streams still being forwarded
+    remaining: AtomicUsize,
+}
+
+impl Recording {
+    fn update(&self, f: impl FnOnce(&mut RecordedResponse)) {
+        if let Ok(mut response) = self.response.lock() {
+            f(&mut response);
+        }
+    }
+
+    /// Mark one stream as done; the last one commits the interaction
+    fn finish(&self) {
+        if self.remaining.fetch_sub(1, Ordering::AcqRel) != 1 {
+            return;
+        }
+        let request = self.request.lock().ok().and_then(|mut request| request.take());
+        let response = self.response.lock().ok().map(|response| response.clone());
+        if let (Some(request), Some(response)) = (request, response) {
+            self.cassette.commit(Interaction {
+                request,
+                response,
+                recorded_at: chrono::Utc::now(),
+            });
+        }
+    }
+}
+
+fn pair(header: &HttpHeader) -> (String, String) {
+    (
+        header.name.as_str().to_string(),
+        String::from_utf8_lossy(header.value.as_bytes()).into_owned(),
+    )
+}
+
+fn load(path: &Path) -> Result<CassetteFile, HttpError> {
+    let data = std::fs::read(path).map_err(|e| {
+        crate::error::configuration(format!("Failed to read cassette {}: {e}", path.display()))
+    })?;
+    serde_json::from_slice(&data).map_err(crate::error::deserialization_error)
+}
@@ -0,0 +1,378 @@
+//! Per-origin circuit breaker
+//!
+//! Each origin (`scheme://host:port`) gets its own circuit. A closed circuit
+//! lets requests through and watches their outcomes; too many consecutive
+//! failures, or a high failure rate over the recent window, open it. An open
+//! circuit fails requests fast with [`Kind::CircuitOpen`](crate::error::Kind::CircuitOpen)
+//! until its cooldown has passed, then admits a few half-open probes: if they
+//! all succeed the circuit closes, and any failure opens it again.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::error::HttpError;
+use crate::http::response::{HttpBodyChunk, HttpResponse};
+use crate::telemetry::circuit_stats::{CircuitBreakerStats, CircuitTransition};
+
+/// State of one origin's circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CircuitState {
+    /// Requests flow normally
+    Closed,
+    /// Requests fail fast until the cooldown has passed
+    Open,
+    /// A limited number of probes test whether the origin has recovered
+    HalfOpen,
+}
+
+/// Thresholds and timings of a circuit breaker
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// Failure rate (0.0 to 1.0) over the window that opens the circuit
+    pub failure_rate_threshold: f64,
+    /// Outcomes needed in the window before the failure rate is considered
+    pub minimum_requests: usize,
+    /// Number of recent outcomes the failure rate is computed over
+    pub window_size: usize,
+    /// Consecutive failures that open the circuit regardless of the rate
+    pub consecutive_failures: u32,
+    /// How long an open circuit fails fast before probing
+    pub cooldown: Duration,
+    /// Probes admitted while half-open; all must succeed to close the circuit
+    pub half_open_probes: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_rate_threshold: 0.5,
+            minimum_requests: 10,
+            window_size: 20,
+            consecutive_failures: 5,
+            cooldown: Duration::from_secs(30),
+            half_open_probes: 3,
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Validate configuration for consistency
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.failure_rate_threshold) {
+            return Err("failure_rate_threshold must be between 0.0 and 1.0".to_string());
+        }
+        if self.window_size == 0 {
+            return Err("window_size must be at least 1".to_string());
+        }
+        if self.minimum_requests > self.window_size {
+            return Err("minimum_requests cannot exceed window_size".to_string());
+        }
+        if self.consecutive_failures == 0 {
+            return Err("consecutive_failures must be at least 1".to_string());
+        }
+        if self.half_open_probes == 0 {
+            return Err("half_open_probes must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Circuit of a single origin
+#[derive(Debug)]
+struct Circuit {
+    state: CircuitState,
+    /// Bumped on every transition so outcomes from an earlier state are ignored
+    generation: u64,
+    /// Recent outcomes while closed, `true` for failures
+    window: VecDeque<bool>,
+    window_failures: usize,
+    consecutive_failures: u32,
+    opened_at: Instant,
+    probes_in_flight: u32,
+    probe_successes: u32,
+}
+
+impl Circuit {
+    fn new() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            generation: 0,
+            window: VecDeque::new(),
+            window_failures: 0,
+            consecutive_failures: 0,
+            opened_at: Instant::now(),
+            probes_in_flight: 0,
+            probe_successes: 0,
+        }
+    }
+}
+
+/// Client-level circuit breaker keyed by origin
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    circuits: Mutex<HashMap<String, Circuit>>,
+    stats: CircuitBreakerStats,
+}
+
+impl CircuitBreaker {
+    /// Create a circuit breaker with the given thresholds
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            circuits: Mutex::new(HashMap::new()),
+            stats: CircuitBreakerStats::default(),
+        }
+    }
+
+    /// Thresholds and timings in use
+    #[inline]
+    pub fn config(&self) -> &CircuitBreakerConfig {
+        &self.config
+    }
+
+    /// Transition and rejection statistics
+    #[inline]
+    pub fn stats(&self) -> &CircuitBreakerStats {
+        &self.stats
+    }
+
+    /// Current state of `origin`'s circuit; origins never seen are closed
+    pub fn state(&self, origin: &str) -> CircuitState {
+        self.circuits
+            .lock()
+            .ok()
+            .and_then(|circuits| circuits.get(origin).map(|circuit| circuit.state))
+            .unwrap_or(CircuitState::Closed)
+    }
+
+    /// Current state of every origin seen so far
+    pub fn states(&self) -> HashMap<String, CircuitState> {
+        self.circuits
+            .lock()
+            .map(|circuits| {
+                circuits
+                    .iter()
+                    .map(|(origin, circuit)| (origin.clone(), circuit.state))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Close `origin`'s circuit and forget its history
+    pub fn reset(&self, origin: &str) {
+        if let Ok(mut circuits) = self.circuits.lock() {
+            if let Some(circuit) = circuits.get_mut(origin) {
+                self.transition(origin, circuit, CircuitState::Closed);
+            }
+        }
+    }
+
+    /// Ask to send a request to `origin`
+    ///
+    /// Returns a permit to report the outcome with, or a
+    /// [`Kind::CircuitOpen`](crate::error::Kind::CircuitOpen) error while the
+    /// circuit is open or all half-open probes are in flight.
+    pub fn try_acquire(self: &Arc<Self>, origin: &str) -> Result<CircuitPermit, HttpError> {
+        let mut circuits = self.circuits.lock().unwrap_or_else(PoisonError::into_inner);
+        let circuit = circuits.entry(origin.to_string()).or_insert_with(Circuit::new);
+
+        if circuit.state == CircuitState::Open && circuit.opened_at.elapsed() >= self.config.cooldown {
+            self.transition(origin, circuit, CircuitState::HalfOpen);
+        }
+        let admitted = match circuit.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen if circuit.probes_in_flight < self.config.half_open_probes => {
+                circuit.probes_in_flight += 1;
+                true
+            }
+            CircuitState::HalfOpen => false,
+        };
+        if !admitted {
+            self.stats.record_rejection();
+            return Err(crate::error::circuit_open(format!("{origin} is failing fast")));
+        }
+
+        Ok(CircuitPermit {
+            breaker: self.clone(),
+            origin: origin.to_string(),
+            generation: circuit.generation,
+            settled: false,
+        })
+    }
+
+    /// Apply the outcome of a request admitted at `generation`
+    fn record(&self, origin: &str, generation: u64, failed: Option<bool>) {
+        let Ok(mut circuits) = self.circuits.lock() else {
+            return;
+        };
+        let Some(circuit) = circuits.get_mut(origin) else {
+            return;
+        };
+        if circuit.generation != generation {
+            return;
+        }
+
+        match (circuit.state, failed) {
+            (CircuitState::Closed, Some(failed)) => {
+                if circuit.window.len() == self.config.window_size && circuit.window.pop_front() == Some(true) {
+                    circuit.window_failures -= 1;
+                }
+                circuit.window.push_back(failed);
+                if failed {
+                    circuit.window_failures += 1;
+                    circuit.consecutive_failures += 1;
+                } else {
+                    circuit.consecutive_failures = 0;
+                }
+
+                let rate_exceeded = circuit.window.len() >= self.config.minimum_requests
+                    && circuit.window_failures as f64 / circuit.window.len() as f64
+                        >= self.config.failure_rate_threshold;
+                if failed && (rate_exceeded || circuit.consecutive_failures >= self.config.consecutive_failures) {
+                    self.transition(origin, circuit, CircuitState::Open);
+                }
+            }
+            (CircuitState::HalfOpen, outcome) => {
+                circuit.probes_in_flight = circuit.probes_in_flight.saturating_sub(1);
+                match outcome {
+                    Some(true) => self.transition(origin, circuit, CircuitState::Open),
+                    Some(false) => {
+                        circuit.probe_successes += 1;
+                        if circuit.probe_successes >= self.config.half_open_probes {
+                            self.transition(origin, circuit, CircuitState::Closed);
+                        }
+                    }
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn transition(&self, origin: &str, circuit: &mut Circuit, to: CircuitState) {
+        let from = circuit.state;
+        circuit.state = to;
+        circuit.generation += 1;
+        circuit.window.clear();
+        circuit.window_failures = 0;
+        circuit.consecutive_failures = 0;
+        circuit.probes_in_flight = 0;
+        circuit.probe_successes = 0;
+        if to == CircuitState::Open {
+            circuit.opened_at = Instant::now();
+        }
+        if from == to {
+            return;
+        }
+
+        tracing::info!(
+            target: "quyc::circuit_breaker",
+            origin = %origin,
+            from = ?from,
+            to = ?to,
+            "Circuit breaker state changed"
+        );
+        self.stats.record_transition(CircuitTransition {
+            origin: origin.to_string(),
+            from,
+            to,
+            at: SystemTime::now(),
+        });
+    }
+}
+
+/// Admission of one request through a circuit
+///
+/// Report the outcome with [`record_success`](Self::record_success) or
+/// [`record_failure`](Self::record_failure). Dropping the permit without
+/// reporting frees its half-open probe slot without counting an outcome.
+#[derive(Debug)]
+#[must_use = "the request outcome should be reported through the permit"]
+pub struct CircuitPermit {
+    breaker: Arc<CircuitBreaker>,
+    origin: String,
+    generation: u64,
+    settled: bool,
+}
+
+impl CircuitPermit {
+    /// The request succeeded
+    pub fn record_success(mut self) {
+        self.settle(Some(false));
+    }
+
+    /// The request failed
+    pub fn record_failure(mut self) {
+        self.settle(Some(true));
+    }
+
+    fn settle(&mut self, failed: Option<bool>) {
+        if !self.settled {
+            self.settled = true;
+            self.breaker.record(&self.origin, self.generation, failed);
+        }
+    }
+
+    /// Report the outcome once `response` shows whether the origin answered
+    ///
+    /// The outcome is decided at the first body chunk: a 5xx status is a
+    /// failure, as is a response that ends before any header or body data
+    /// arrives. The streams are forwarded untouched.
+    pub(crate) fn settle_with(self, response: HttpResponse) -> HttpResponse {
+        let saw_header = Arc::new(AtomicBool::new(false));
+        let header_seen = saw_header.clone();
+        let mut observer = Observer {
+            permit: Some(self),
+            status: response.status_handle(),
+            saw_header,
+        };
+        response
+            .map_headers(move |header| {
+                header_seen.store(true, Ordering::Release);
+                Some(header)
+            })
+            .map_body(move |chunk| {
+                observer.observe(Some(&chunk));
+                chunk
+            })
+    }
+}
+
+/// Settles a permit at the first body chunk, or when the body ends without one
+struct Observer {
+    permit: Option<CircuitPermit>,
+    status: Arc<AtomicU16>,
+    saw_header: Arc<AtomicBool>,
+}
+
+impl Observer {
+    fn observe(&mut self, chunk: Option<&HttpBodyChunk>) {
+        let Some(permit) = self.permit.take() else {
+            return;
+        };
+        let status = self.status.load(Ordering::Acquire);
+        let ended = chunk.is_none_or(|chunk| chunk.is_final);
+        let answered = status != 0 || self.saw_header.load(Ordering::Acquire) || !ended;
+        if status >= 500 || !answered {
+            permit.record_failure();
+        } else {
+            permit.record_success();
+        }
+    }
+}
+
+impl Drop for Observer {
+    fn drop(&mut self) {
+        self.observe(None);
+    }
+}
+
+impl Drop for CircuitPermit {
+    fn drop(&mut self) {
+        self.settle(None);
+    }
+}
@@ -0,0 +1,271 @@
+//! Singleflight coalescing of identical concurrent requests
+//!
+//! Safe requests with the same [`FlightKey`] (method, URL, every header and
+//! the credentials) that are sent while one of them is waiting for its
+//! response headers share that one upstream request.
+//! Once the header block is in, the flight closes to newcomers and the body
+//! is fanned out to every waiter chunk by chunk, without buffering. Errors
+//! reach every waiter alike, whether sending failed or the response did.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+
+use futures::executor::block_on;
+use http::Method;
+use ystream::{AsyncStream, spawn_task};
+
+use crate::error::HttpError;
+use crate::http::request::HttpRequest;
+use crate::http::response::{HttpHeader, HttpResponse, ResponseSlot};
+
+/// Shares in-flight requests between identical concurrent callers
+#[derive(Debug, Default)]
+pub struct Coalescer {
+    flights: Mutex<HashMap<FlightKey, Arc<Flight>>>,
+    coalesced: AtomicU64,
+}
+
+/// What requests must have in common to share a response
+///
+/// Any header may change what the server answers (tenant, API version,
+/// organization, middleware-added headers), so all of them count, along with
+/// the credentials set through the request's `auth`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct FlightKey {
+    method: String,
+    url: String,
+    /// Header names and values, sorted
+    headers: Vec<(String, Vec<u8>)>,
+}
+
+impl std::fmt::Debug for FlightKey {
+    // Header values carry credentials
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FlightKey")
+            .field("method", &self.method)
+            .field("url", &self.url)
+            .finish_non_exhaustive()
+    }
+}
+
+/// One upstream request and the responses waiting on it
+#[derive(Default)]
+struct Flight {
+    state: Mutex<FlightState>,
+    settled: Condvar,
+}
+
+#[derive(Default)]
+struct FlightState {
+    /// How sending the request went, once it has been sent
+    outcome: Option<Result<(), HttpError>>,
+    /// Responses to feed once the header block is in
+    slots: Vec<ResponseSlot>,
+}
+
+impl std::fmt::Debug for Flight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Flight").finish_non_exhaustive()
+    }
+}
+
+impl Flight {
+    fn lock(&self) -> std::sync::MutexGuard<'_, FlightState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn settle(&self, outcome: Result<(), HttpError>) {
+        self.lock().outcome = Some(outcome);
+        self.settled.notify_all();
+    }
+
+    /// Block until the leader has sent the request
+    fn wait(&self) -> Result<(), HttpError> {
+        let mut state = self.lock();
+        loop {
+            if let Some(outcome) = &state.outcome {
+                return outcome.clone();
+            }
+            state = self.settled.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+}
+
+impl Coalescer {
+    /// Create an empty coalescer
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `request` may share a response with identical requests
+    ///
+    /// Only safe methods without a body qualify.
+    pub fn is_coalescable(request: &HttpRequest) -> bool {
+        matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE)
+            && request.body().is_none()
+    }
+
+    /// Key identical requests share
+    pub fn key_for(request: &HttpRequest) -> FlightKey {
+        // Credentials set with `bearer_auth` and friends are not in the headers yet
+        let mut headers: Vec<(String, Vec<u8>)> = request
+            .headers()
+            .iter()
+            .chain(&request.auth_headers())
+            .map(|(name, value)| (name.as_str().to_string(), value.as_bytes().to_vec()))
+            .collect();
+        headers.sort();
+        FlightKey {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            headers,
+        }
+    }
+
+    /// Send `request` with `send`, unless an identical request is already
+    /// waiting for its headers, in which case its response is shared
+    pub fn execute<F>(self: &Arc<Self>, request: HttpRequest, send: F) -> Result<HttpResponse, HttpError>
+    where
+        F: FnOnce(HttpRequest) -> Result<HttpResponse, HttpError>,
+    {
+        // Invalid credentials fail on their own instead of keying a flight
+        if !Self::is_coalescable(&request) || request.try_auth_headers().is_err() {
+            return send(request);
+        }
+        let key = Self::key_for(&request);
+
+        let mut flights = self.flights.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(flight) = flights.get(&key).cloned() {
+            let (response, slot) = HttpResponse::pending(request.version(), 0);
+            flight.lock().slots.push(slot);
+            drop(flights);
+            self.coalesced.fetch_add(1, Ordering::Relaxed);
+            tracing::debug!(
+                target: "quyc::coalesce",
+                url = %request.url(),
+                "Joining identical in-flight request"
+            );
+            return flight.wait().map(|()| response);
+        }
+        let flight = Arc::new(Flight::default());
+        flights.insert(key.clone(), flight.clone());
+        drop(flights);
+
+        let leader = Leader {
+            coalescer: self.clone(),
+            key,
+            flight: Some(flight),
+        };
+        leader.lead(send(request))
+    }
+
+    /// Requests that shared another request's response instead of being sent
+    #[inline]
+    pub fn coalesced_requests(&self) -> u64 {
+        self.coalesced.load(Ordering::Relaxed)
+    }
+
+    /// Requests currently accepting identical requests as waiters
+    pub fn in_flight(&self) -> usize {
+        self.flights.lock().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    /// Stop accepting waiters for `key`, returning the responses to feed
+    fn close(&self, key: &FlightKey, flight: &Flight) -> Vec<ResponseSlot> {
+        let mut flights = self.flights.lock().unwrap_or_else(PoisonError::into_inner);
+        flights.remove(key);
+        std::mem::take(&mut flight.lock().slots)
+    }
+}
+
+/// The request actually sent on behalf of a flight
+///
+/// Dropped without leading, e.g. when sending panicked, it fails the waiters.
+struct Leader {
+    coalescer: Arc<Coalescer>,
+    key: FlightKey,
+    flight: Option<Arc<Flight>>,
+}
+
+impl Leader {
+    /// Share the result of sending the request with the waiters
+    fn lead(mut self, result: Result<HttpResponse, HttpError>) -> Result<HttpResponse, HttpError> {
+        let Some(flight) = self.flight.take() else {
+            return result;
+        };
+        let upstream = match result {
+            Ok(upstream) => upstream,
+            Err(error) => {
+                drop(self.coalescer.close(&self.key, &flight));
+                flight.settle(Err(error.clone()));
+                return Err(error);
+            }
+        };
+
+        let (response, slot) = HttpResponse::pending(upstream.version(), upstream.stream_id);
+        flight.lock().slots.push(slot);
+        flight.settle(Ok(()));
+        let coalescer = self.coalescer.clone();
+        let key = self.key.clone();
+        spawn_task(move || fan_out(&coalescer, &key, &flight, upstream));
+        Ok(response)
+    }
+}
+
+impl Drop for Leader {
+    fn drop(&mut self) {
+        if let Some(flight) = self.flight.take() {
+            drop(self.coalescer.close(&self.key, &flight));
+            flight.settle(Err(crate::error::request("coalesced request was abandoned before it was sent")));
+        }
+    }
+}
+
+/// Read `upstream`'s header block, close the flight and copy the response to every waiter
+fn fan_out(coalescer: &Coalescer, key: &FlightKey, flight: &Flight, mut upstream: HttpResponse) {
+    let (_, placeholder_headers) = AsyncStream::channel();
+    let (_, placeholder_body) = AsyncStream::channel();
+    let (_, placeholder_trailers) = AsyncStream::channel();
+    let (mut headers, body, trailers) =
+        upstream.swap_streams(placeholder_headers, placeholder_body, placeholder_trailers);
+    let mut header_block: Vec<HttpHeader> = Vec::new();
+    while let Some(header) = block_on(headers.next()) {
+        header_block.push(header);
+    }
+
+    let slots = coalescer.close(key, flight);
+    let status = upstream.status_code();
+    let (version, stream_id) = (upstream.version(), upstream.stream_id);
+    let timings = upstream.connection_timings_handle();
+    let mut body_senders = Vec::with_capacity(slots.len());
+    let mut trailer_senders = Vec::with_capacity(slots.len());
+    for slot in slots {
+        let (_, headers_stream) = AsyncStream::channel();
+        let (body_tx, body_stream) = AsyncStream::channel();
+        let (trailers_tx, trailers_stream) = AsyncStream::channel();
+        let source = HttpResponse::new(headers_stream, body_stream, trailers_stream, version, stream_id)
+            .with_connection_timings_handle(timings.clone());
+        if let Some(status) = status {
+            source.set_status(status);
+        }
+        let leading_headers = header_block.clone();
+        spawn_task(move || slot.fill(leading_headers, None, source));
+        body_senders.push(body_tx);
+        trailer_senders.push(trailers_tx);
+    }
+
+    // Waiters that went away are skipped
+    for chunk in body {
+        for sender in &body_senders {
+            drop(sender.send(chunk.clone()));
+        }
+    }
+    drop(body_senders);
+    for trailer in trailers {
+        for sender in &trailer_senders {
+            drop(sender.send(trailer.clone()));
+        }
+    }
+}
@@ -9,12 +9,44 @@ use crate::prelude::*;
 /// HTTP client builder for configuration
 pub struct HttpClientBuilder {
     config: HttpConfig,
+    cassette: Option<crate::cassette::Cassette>,
+    har: Option<std::sync::Arc<crate::har::HarRecorder>>,
+    middleware: crate::middleware::MiddlewareChain,
+    hedging: Option<crate::retry::Hedging>,
+    retry_policy: Option<crate::retry::RetryPolicy>,
+    retry_budget: Option<crate::retry::RetryBudget>,
+    circuit_breaker: Option<super::CircuitBreakerConfig>,
+    rate_limit: Option<super::RateLimitConfig>,
+    coalesce: bool,
+    endpoint_groups: Vec<super::EndpointGroup>,
+    srv_resolver: Option<super::SrvResolver>,
+    redirect_policy: Option<crate::redirect::Policy>,
+    redirect_https_downgrade: bool,
+    cookie_provider: Option<std::sync::Arc<dyn crate::cookie::CookieStore>>,
+    oauth2: Option<crate::auth::OAuth2>,
+    sigv4: Option<crate::auth::SigV4>,
 }
 
 impl HttpClientBuilder {
     pub fn new() -> Self {
         Self {
             config: HttpConfig::default(),
+            cassette: None,
+            har: None,
+            middleware: crate::middleware::MiddlewareChain::new(),
+            hedging: None,
+            retry_policy: None,
+            retry_budget: None,
+            circuit_breaker: None,
+            rate_limit: None,
+            coalesce: false,
+            endpoint_groups: Vec::new(),
+            srv_resolver: None,
+            redirect_policy: None,
+            redirect_https_downgrade: false,
+            cookie_provider: None,
+            oauth2: None,
+            sigv4: None,
         }
     }
 
@@ -117,9 +149,181 @@ impl HttpClientBuilder {
         self
     }
 
+    /// Record or replay requests through a cassette
+    pub fn cassette(mut self, cassette: crate::cassette::Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Capture traffic into a HAR recorder
+    pub fn har_recorder(mut self, recorder: std::sync::Arc<crate::har::HarRecorder>) -> Self {
+        self.har = Some(recorder);
+        self
+    }
+
+    /// Append a middleware; the first one added is the outermost layer
+    pub fn middleware<M: crate::middleware::Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.middleware.push(std::sync::Arc::new(middleware));
+        self
+    }
+
+    /// Use a prebuilt middleware chain, replacing any middleware added so far
+    pub fn middleware_chain(mut self, chain: crate::middleware::MiddlewareChain) -> Self {
+        self.middleware = chain;
+        self
+    }
+
+    /// Hedge slow idempotent requests
+    pub fn hedging(mut self, hedging: crate::retry::Hedging) -> Self {
+        self.hedging = Some(hedging);
+        self
+    }
+
+    /// Retry, and hedge as configured by the policy's `hedge` setting
+    ///
+    /// Hedging set with [`hedging`](Self::hedging) takes precedence.
+    pub fn retry_policy(mut self, policy: &crate::retry::RetryPolicy) -> Self {
+        if self.hedging.is_none() {
+            self.hedging = crate::retry::Hedging::from_retry_policy(policy);
+        }
+        self.retry_policy = Some(policy.clone());
+        self
+    }
+
+    /// Pay for retries from a client-wide budget
+    pub fn retry_budget(mut self, budget: crate::retry::RetryBudget) -> Self {
+        self.retry_budget = Some(budget);
+        self
+    }
+
+    /// Guard each origin with a circuit breaker
+    pub fn circuit_breaker(mut self, config: super::CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(config);
+        self
+    }
+
+    /// Pace requests with a token bucket per origin
+    pub fn rate_limit(mut self, config: super::RateLimitConfig) -> Self {
+        self.rate_limit = Some(config);
+        self
+    }
+
+    /// Share one upstream request between identical concurrent safe requests
+    pub fn coalesce_requests(mut self, enable: bool) -> Self {
+        self.coalesce = enable;
+        self
+    }
+
+    /// Serve a logical base URL from several endpoints
+    pub fn endpoint_group(mut self, group: super::EndpointGroup) -> Self {
+        self.endpoint_groups.push(group);
+        self
+    }
+
+    /// Resolve the services of `srv+https://` URLs with `resolver`
+    pub fn srv_resolver(mut self, resolver: super::SrvResolver) -> Self {
+        self.srv_resolver = Some(resolver);
+        self
+    }
+
+    /// Follow redirects as `policy` decides
+    pub fn redirect(mut self, policy: crate::redirect::Policy) -> Self {
+        self.redirect_policy = Some(policy);
+        self
+    }
+
+    /// Follow at most `max` redirects per request under the default policy
+    pub fn max_redirects(mut self, max: usize) -> Self {
+        self.config.max_redirects = max;
+        self
+    }
+
+    /// Follow redirects from `https` to `http` URLs
+    pub fn redirect_https_downgrade(mut self, allow: bool) -> Self {
+        self.redirect_https_downgrade = allow;
+        self
+    }
+
+    /// Keep cookies in a [`Jar`](crate::cookie::Jar) of the client's own
+    pub fn cookie_store(mut self, enable: bool) -> Self {
+        self.config.cookie_store = enable;
+        self
+    }
+
+    /// Keep cookies in `store`, e.g. a persistent [`Jar`](crate::cookie::Jar)
+    pub fn cookie_provider<C: crate::cookie::CookieStore + 'static>(mut self, store: std::sync::Arc<C>) -> Self {
+        self.config.cookie_store = true;
+        self.cookie_provider = Some(store);
+        self
+    }
+
+    /// Send every request with an OAuth 2.0 bearer token from `oauth`
+    pub fn oauth2(mut self, oauth: crate::auth::OAuth2) -> Self {
+        self.oauth2 = Some(oauth);
+        self
+    }
+
+    /// Sign every request with AWS Signature Version 4
+    pub fn sigv4(mut self, signer: crate::auth::SigV4) -> Self {
+        self.sigv4 = Some(signer);
+        self
+    }
+
     pub fn build(self) -> Result<HttpClient, HttpError> {
         // Use the core HttpClient::with_config method directly
-        Ok(crate::client::core::HttpClient::with_config(self.config))
+        let mut client = crate::client::core::HttpClient::with_config(self.config);
+        if let Some(cassette) = self.cassette {
+            client = client.with_cassette(cassette);
+        }
+        if let Some(recorder) = self.har {
+            client = client.with_har_recorder(recorder);
+        }
+        if let Some(hedging) = self.hedging {
+            hedging.policy().validate().map_err(crate::error::configuration)?;
+            client = client.with_hedging(hedging);
+        }
+        if let Some(policy) = self.retry_policy {
+            policy.validate().map_err(crate::error::configuration)?;
+            client = client.with_retry_policy(policy);
+        }
+        if let Some(budget) = self.retry_budget {
+            client = client.with_retry_budget(budget);
+        }
+        if let Some(config) = self.circuit_breaker {
+            config.validate().map_err(crate::error::configuration)?;
+            client = client.with_circuit_breaker(config);
+        }
+        if let Some(config) = self.rate_limit {
+            config.validate().map_err(crate::error::configuration)?;
+            client = client.with_rate_limit(config);
+        }
+        if self.coalesce {
+            client = client.with_coalescing();
+        }
+        if let Some(resolver) = self.srv_resolver {
+            client = client.with_srv_resolver(resolver);
+        }
+        if let Some(policy) = self.redirect_policy {
+            client = client.with_redirect_policy(policy);
+        }
+        client = client.with_redirect_https_downgrade(self.redirect_https_downgrade);
+        if let Some(store) = self.cookie_provider {
+            client = client.with_cookie_store(store);
+        }
+        // Groups come last so their health probes run through the whole client
+        client = client.with_middleware_chain(self.middleware);
+        if let Some(signer) = self.sigv4 {
+            client = client.with_sigv4(signer);
+        }
+        // Token requests go through a clone of the client as configured so far
+        if let Some(oauth) = self.oauth2 {
+            client = client.with_oauth2(oauth);
+        }
+        for group in self.endpoint_groups {
+            group.config().validate().map_err(crate::error::configuration)?;
+            client = client.with_endpoint_group(group);
+        }
+        Ok(client)
     }
 }
 
@@ -7,9 +7,23 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
+use super::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
+use super::coalesce::Coalescer;
+use super::endpoint_group::EndpointGroup;
+use super::rate_limit::{RateLimitConfig, RateLimiter};
+use super::srv::{SrvGroups, SrvResolver};
+use crate::auth::{OAuth2, SigV4};
+use crate::cassette::Cassette;
 use crate::config::HttpConfig;
+use crate::cookie::{CookieContext, CookieStore, Jar, SharedCookieStore, add_cookie_header, capture_cookies};
+use crate::har::HarRecorder;
 use crate::http::HttpRequest;
+use crate::middleware::{Middleware, MiddlewareChain};
+use crate::redirect::{Policy, Redirector};
 use crate::protocols::strategy::HttpProtocolStrategy;
+use crate::retry::hedge::{self, Hedging};
+use crate::retry::{HttpRetryExecutor, RetryBudget, RetryPolicy};
+use crate::telemetry::QuicStatsRegistry;
 
 // Telemetry module not yet implemented
 
@@ -50,6 +64,18 @@ pub struct ClientStats {
     pub compression_errors: AtomicU64,
     /// Number of decompression errors
     pub decompression_errors: AtomicU64,
+
+    // ===== Hedging Statistics =====
+    /// Duplicate attempts sent for slow requests
+    pub hedges_sent: AtomicU64,
+    /// Requests answered first by a hedged attempt
+    pub hedge_wins: AtomicU64,
+    /// Hedges skipped because the hedge budget was exhausted
+    pub hedges_denied: AtomicU64,
+
+    // ===== Transport Statistics =====
+    /// QUIC transport statistics per connection and aggregated per origin
+    pub quic: Arc<QuicStatsRegistry>,
 }
 
 impl ClientStats {
@@ -71,6 +97,10 @@ impl ClientStats {
             failed_requests: self.failed_requests.load(Ordering::Relaxed) as usize,
             cache_hits: self.cache_hits.load(Ordering::Relaxed) as usize,
             cache_misses: self.cache_misses.load(Ordering::Relaxed) as usize,
+            hedges_sent: self.hedges_sent.load(Ordering::Relaxed) as usize,
+            hedge_wins: self.hedge_wins.load(Ordering::Relaxed) as usize,
+            hedges_denied: self.hedges_denied.load(Ordering::Relaxed) as usize,
+            quic_origins: self.quic.origins(),
         }
     }
 }
@@ -85,6 +115,21 @@ pub struct HttpClient {
     stats: Arc<ClientStats>,
     strategy: HttpProtocolStrategy,
     created_at: Instant,
+    cassette: Option<Arc<Cassette>>,
+    har: Option<Arc<HarRecorder>>,
+    middleware: MiddlewareChain,
+    hedging: Option<Arc<Hedging>>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    retry_policy: Option<RetryPolicy>,
+    retry_budget: Option<Arc<RetryBudget>>,
+    coalescer: Option<Arc<Coalescer>>,
+    endpoint_groups: Vec<Arc<EndpointGroup>>,
+    srv_groups: Arc<SrvGroups>,
+    redirector: Redirector,
+    cookies: Option<SharedCookieStore>,
+    oauth2: Option<Arc<OAuth2>>,
+    sigv4: Option<Arc<SigV4>>,
 }
 
 // Default implementation moved to configuration.rs
@@ -93,11 +138,27 @@ impl HttpClient {
     /// Create HttpClient with default configuration
     #[inline]
     pub fn new() -> Self {
+        let config = HttpConfig::default();
         Self {
-            config: HttpConfig::default(),
+            strategy: configure_strategy(HttpProtocolStrategy::default(), &config),
+            cookies: default_cookie_store(&config),
+            config,
             stats: Arc::new(ClientStats::default()),
-            strategy: HttpProtocolStrategy::default(),
             created_at: Instant::now(),
+            cassette: None,
+            har: None,
+            middleware: MiddlewareChain::new(),
+            hedging: None,
+            circuit_breaker: None,
+            rate_limiter: None,
+            retry_policy: None,
+            retry_budget: None,
+            coalescer: None,
+            endpoint_groups: Vec::new(),
+            srv_groups: Arc::default(),
+            redirector: Redirector::default(),
+            oauth2: None,
+            sigv4: None,
         }
     }
 
@@ -105,10 +166,25 @@ impl HttpClient {
     #[inline]
     pub fn with_config(config: HttpConfig) -> Self {
         Self {
+            strategy: configure_strategy(HttpProtocolStrategy::default(), &config),
+            cookies: default_cookie_store(&config),
             config,
             stats: Arc::new(ClientStats::default()),
-            strategy: HttpProtocolStrategy::default(),
             created_at: Instant::now(),
+            cassette: None,
+            har: None,
+            middleware: MiddlewareChain::new(),
+            hedging: None,
+            circuit_breaker: None,
+            rate_limiter: None,
+            retry_policy: None,
+            retry_budget: None,
+            coalescer: None,
+            endpoint_groups: Vec::new(),
+            srv_groups: Arc::default(),
+            redirector: Redirector::default(),
+            oauth2: None,
+            sigv4: None,
         }
     }
 
@@ -117,10 +193,25 @@ impl HttpClient {
     #[inline]
     pub fn new_direct(config: HttpConfig, stats: ClientStats) -> Self {
         Self {
+            strategy: configure_strategy(HttpProtocolStrategy::default(), &config),
+            cookies: default_cookie_store(&config),
             config,
             stats: Arc::new(stats),
-            strategy: HttpProtocolStrategy::default(),
             created_at: Instant::now(),
+            cassette: None,
+            har: None,
+            middleware: MiddlewareChain::new(),
+            hedging: None,
+            circuit_breaker: None,
+            rate_limiter: None,
+            retry_policy: None,
+            retry_budget: None,
+            coalescer: None,
+            endpoint_groups: Vec::new(),
+            srv_groups: Arc::default(),
+            redirector: Redirector::default(),
+            oauth2: None,
+            sigv4: None,
         }
     }
 
@@ -130,15 +221,282 @@ impl HttpClient {
     #[inline]
     pub fn with_config_and_strategy(config: HttpConfig, strategy: HttpProtocolStrategy) -> Self {
         Self {
+            strategy: configure_strategy(strategy, &config),
+            cookies: default_cookie_store(&config),
             config,
             stats: Arc::new(ClientStats::default()),
-            strategy,
             created_at: Instant::now(),
+            cassette: None,
+            har: None,
+            middleware: MiddlewareChain::new(),
+            hedging: None,
+            circuit_breaker: None,
+            rate_limiter: None,
+            retry_policy: None,
+            retry_budget: None,
+            coalescer: None,
+            endpoint_groups: Vec::new(),
+            srv_groups: Arc::default(),
+            redirector: Redirector::default(),
+            oauth2: None,
+            sigv4: None,
         }
     }
 
 
 
+    /// Attach a record/replay cassette to this client
+    ///
+    /// Every request executed by the client (and its clones) goes through the
+    /// cassette: recorded in record mode, answered from the file in replay mode.
+    /// Requests are matched and recorded before endpoint routing, by the URL
+    /// the caller used, so replaying `srv+` or endpoint group URLs needs no
+    /// DNS and sends no health probes.
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(Arc::new(cassette));
+        self
+    }
+
+    /// Cassette attached to this client, if any
+    #[inline]
+    pub fn cassette(&self) -> Option<&Arc<Cassette>> {
+        self.cassette.as_ref()
+    }
+
+    /// Capture this client's traffic into a HAR recorder
+    ///
+    /// Keep a clone of the `Arc` to write the log on demand, or configure the
+    /// recorder with [`HarRecorder::save_on_drop`] to write it once the last
+    /// client holding it is dropped.
+    pub fn with_har_recorder(mut self, recorder: Arc<HarRecorder>) -> Self {
+        self.har = Some(recorder);
+        self
+    }
+
+    /// HAR recorder attached to this client, if any
+    #[inline]
+    pub fn har_recorder(&self) -> Option<&Arc<HarRecorder>> {
+        self.har.as_ref()
+    }
+
+    /// Append a middleware to the end of this client's chain
+    ///
+    /// Later middleware sits inside earlier ones: it sees requests after them
+    /// and responses before them. See [`crate::middleware`] for the ordering rules.
+    pub fn with_middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Replace this client's middleware chain
+    pub fn with_middleware_chain(mut self, chain: MiddlewareChain) -> Self {
+        self.middleware = chain;
+        self
+    }
+
+    /// Middleware chain consulted by [`execute`](Self::execute)
+    #[inline]
+    pub fn middleware(&self) -> &MiddlewareChain {
+        &self.middleware
+    }
+
+    /// Hedge slow idempotent requests
+    ///
+    /// Requests that pass [`HedgePolicy::is_hedgeable`](crate::retry::HedgePolicy::is_hedgeable)
+    /// get a duplicate attempt when no headers arrive within the policy's delay;
+    /// the first to answer is returned and the rest are cancelled. The budget
+    /// and latency history are shared with clones of this client.
+    pub fn with_hedging(mut self, hedging: Hedging) -> Self {
+        self.hedging = Some(Arc::new(hedging));
+        self
+    }
+
+    /// Hedging state of this client, if enabled
+    #[inline]
+    pub fn hedging(&self) -> Option<&Arc<Hedging>> {
+        self.hedging.as_ref()
+    }
+
+    /// Guard each origin with a circuit breaker
+    ///
+    /// While an origin's circuit is open its requests fail fast with a
+    /// `503` response carrying a [`Kind::CircuitOpen`](crate::error::Kind::CircuitOpen)
+    /// error, which middleware sees in `handle_error`. The breaker is shared
+    /// with clones of this client.
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(Arc::new(CircuitBreaker::new(config)));
+        self
+    }
+
+    /// Retry failed attempts according to `policy`
+    ///
+    /// Attempts are judged on their status or transport error before the body
+    /// streams; see [`HttpRetryExecutor`] for what is retried. Only requests
+    /// that can be replayed are retried.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Retry policy of this client, if retries are enabled
+    #[inline]
+    pub fn retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry_policy.as_ref()
+    }
+
+    /// Pay for retries from a budget shared with clones of this client
+    ///
+    /// Once the budget is exhausted requests get a single attempt until
+    /// successful answers have earned tokens back.
+    pub fn with_retry_budget(mut self, budget: RetryBudget) -> Self {
+        self.retry_budget = Some(Arc::new(budget));
+        self
+    }
+
+    /// Retry budget of this client, if any
+    #[inline]
+    pub fn retry_budget(&self) -> Option<&Arc<RetryBudget>> {
+        self.retry_budget.as_ref()
+    }
+
+    /// Circuit breaker of this client, if enabled
+    #[inline]
+    pub fn circuit_breaker(&self) -> Option<&Arc<CircuitBreaker>> {
+        self.circuit_breaker.as_ref()
+    }
+
+    /// Pace requests with a token bucket per origin
+    ///
+    /// Requests over the budget are queued and sent once their turn comes;
+    /// rate-limit headers on responses adjust the buckets. A request that
+    /// would wait longer than `max_wait` fails with a `429` response carrying
+    /// a [`Kind::RateLimited`](crate::error::Kind::RateLimited) error. The
+    /// buckets are shared with clones of this client.
+    pub fn with_rate_limit(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(config)));
+        self
+    }
+
+    /// Rate limiter of this client, if enabled
+    #[inline]
+    pub fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.rate_limiter.as_ref()
+    }
+
+    /// Share one upstream request between identical concurrent requests
+    ///
+    /// Safe requests without a body that match a request still waiting for
+    /// its response headers, by [`FlightKey`](crate::client::FlightKey), get a
+    /// copy of its response instead of being sent. The in-flight requests
+    /// are shared with clones of this client.
+    pub fn with_coalescing(mut self) -> Self {
+        self.coalescer = Some(Arc::new(Coalescer::new()));
+        self
+    }
+
+    /// Request coalescer of this client, if enabled
+    #[inline]
+    pub fn coalescer(&self) -> Option<&Arc<Coalescer>> {
+        self.coalescer.as_ref()
+    }
+
+    /// Serve requests to the group's logical base URL from its endpoints
+    ///
+    /// Requests under the base URL are sent to an endpoint picked by the
+    /// group's selection policy and fail over to other endpoints on
+    /// retryable answers; each attempt runs through the rest of the client,
+    /// so rate limits and circuit breakers apply per concrete origin. Active
+    /// health probes, if configured, start now.
+    pub fn with_endpoint_group(mut self, group: EndpointGroup) -> Self {
+        let group = Arc::new(group);
+        // The prober is a client without this group, so it does not keep the group alive
+        let prober = self.clone();
+        group.start_health_checks(move |request| prober.execute(request));
+        self.endpoint_groups.push(group);
+        self
+    }
+
+    /// Endpoint groups of this client
+    #[inline]
+    pub fn endpoint_groups(&self) -> &[Arc<EndpointGroup>] {
+        &self.endpoint_groups
+    }
+
+    /// Resolve the services of `srv+https://` and `srv+http://` URLs with `resolver`
+    ///
+    /// Such URLs name an SRV record, e.g.
+    /// `srv+https://_api._tcp.service.internal/v1/models`; requests to them
+    /// go to the records' targets as an [`EndpointGroup`] per service, with
+    /// priority and weight ordering, failover and TTL-based refresh. The
+    /// system DNS configuration is used unless a resolver is set. The
+    /// resolver also looks up Encrypted Client Hello configurations.
+    pub fn with_srv_resolver(mut self, resolver: SrvResolver) -> Self {
+        self.strategy = self.strategy.with_resolver(resolver.clone());
+        self.srv_groups = Arc::new(SrvGroups::new(resolver));
+        self
+    }
+
+    /// Follow redirects as `policy` decides
+    ///
+    /// The default policy follows up to the configured `max_redirects` and
+    /// the request's own limit, and fails on loops. Requests built with
+    /// `follow_redirects(false)` are never redirected.
+    pub fn with_redirect_policy(mut self, policy: Policy) -> Self {
+        self.redirector.policy = policy;
+        self
+    }
+
+    /// Follow redirects from `https` to `http` URLs, refused by default
+    pub fn with_redirect_https_downgrade(mut self, allow: bool) -> Self {
+        self.redirector.allow_https_downgrade = allow;
+        self
+    }
+
+    /// Attach cookies from `store` to every request and store the cookies
+    /// of every response, redirects included
+    ///
+    /// Replaces the [`Jar`] a client configured with `cookie_store` starts
+    /// with. Pass a [`Jar`] clone to keep inspecting or saving the cookies
+    /// while the client runs.
+    pub fn with_cookie_store(mut self, store: Arc<dyn CookieStore>) -> Self {
+        self.cookies = Some(SharedCookieStore(store));
+        self
+    }
+
+    /// Cookie store of this client, if any
+    #[inline]
+    pub fn cookie_store(&self) -> Option<&Arc<dyn CookieStore>> {
+        self.cookies.as_ref().map(|store| &store.0)
+    }
+
+    /// Send every request with an OAuth 2.0 bearer token from `oauth`
+    ///
+    /// Tokens are fetched before the first request, refreshed ahead of
+    /// their expiry, and refreshed once more when a request is answered
+    /// with `401`, which then goes out again. Requests that already carry
+    /// credentials are sent as they are. Token requests go through a clone
+    /// of this client unless `oauth` has a client of its own.
+    pub fn with_oauth2(mut self, oauth: OAuth2) -> Self {
+        let oauth = if oauth.has_http_client() {
+            oauth
+        } else {
+            oauth.with_http_client(self.clone())
+        };
+        self.oauth2 = Some(Arc::new(oauth));
+        self
+    }
+
+    /// Sign every request with AWS Signature Version 4
+    ///
+    /// Each attempt, retry and redirect hop is signed just before it is
+    /// sent, after body compression. Redirect hops to an origin other than
+    /// the first hop's are not signed, and requests that already carry
+    /// credentials are sent as they are.
+    pub fn with_sigv4(mut self, signer: SigV4) -> Self {
+        self.sigv4 = Some(Arc::new(signer));
+        self
+    }
+
     /// Get client statistics for monitoring and telemetry
     #[inline]
     pub fn stats(&self) -> Arc<ClientStats> {
@@ -225,6 +583,10 @@ impl HttpClient {
         self.stats.connection_pool_size.store(0, Ordering::Relaxed);
         self.stats.active_connections.store(0, Ordering::Relaxed);
         self.stats.avg_response_time_ms.store(0, Ordering::Relaxed);
+        self.stats.hedges_sent.store(0, Ordering::Relaxed);
+        self.stats.hedge_wins.store(0, Ordering::Relaxed);
+        self.stats.hedges_denied.store(0, Ordering::Relaxed);
+        self.stats.quic.clear();
     }
 
     /// Check if client is closed (always false for canonical client)
@@ -235,15 +597,136 @@ impl HttpClient {
 
     /// Execute HTTP request with telemetry tracking and protocol selection
     ///
-    /// Uses protocol strategy for intelligent protocol selection and automatic fallback.
-    /// Tracks comprehensive telemetry metrics and applies strategy-specific optimizations.
+    /// Runs the request through the client's middleware chain, then uses the
+    /// protocol strategy for intelligent protocol selection and automatic fallback.
+    /// Requests answered by middleware never reach the network and are not counted
+    /// in the client statistics.
     #[inline]
-    pub fn execute(&self, request: HttpRequest) -> crate::http::response::HttpResponse {
+    pub fn execute(&self, mut request: HttpRequest) -> crate::http::response::HttpResponse {
+        // One key for the logical request, shared by its redirect hops,
+        // endpoint failovers, retries and hedges
+        request.ensure_idempotency_key();
+        self.middleware.run(request, |request| self.dispatch(request))
+    }
+
+    /// Send a request that has passed through the middleware chain, with
+    /// an OAuth 2.0 token if configured
+    fn dispatch(&self, request: HttpRequest) -> crate::error::Result<crate::http::response::HttpResponse> {
+        match &self.oauth2 {
+            Some(oauth) if request.auth.is_none() && !request.headers().contains_key(http::header::AUTHORIZATION) => {
+                let client = self.clone();
+                let timeout = request.timeout().unwrap_or(self.config.timeout);
+                Ok(oauth.execute(request, timeout, move |request| client.follow(request)))
+            }
+            _ => self.follow(request),
+        }
+    }
+
+    /// Send a request, following its redirects
+    fn follow(&self, request: HttpRequest) -> crate::error::Result<crate::http::response::HttpResponse> {
+        let client = self.clone();
+        self.redirector
+            .follow(request, &self.config, move |request, context| client.dispatch_hop(request, context))
+    }
+
+    /// Send one hop of a request with the cookies stored for it, storing
+    /// the cookies it sets
+    fn dispatch_hop(
+        &self,
+        mut request: HttpRequest,
+        context: CookieContext,
+    ) -> crate::error::Result<crate::http::response::HttpResponse> {
+        let Some(SharedCookieStore(cookies)) = &self.cookies else {
+            return self.coalesce(request);
+        };
+        let url = request.url().clone();
+        add_cookie_header(request.headers_mut(), cookies.as_ref(), &url, context);
+        let response = self.coalesce(request)?;
+        Ok(capture_cookies(cookies.clone(), response, url))
+    }
+
+    /// Send a request, coalesced with identical in-flight requests if enabled
+    fn coalesce(&self, request: HttpRequest) -> crate::error::Result<crate::http::response::HttpResponse> {
+        match &self.coalescer {
+            Some(coalescer) => coalescer.execute(request, |request| self.route(request)),
+            None => self.route(request),
+        }
+    }
+
+    /// Send a request, through the endpoint group serving its URL if any
+    ///
+    /// Record/replay happens here, before any endpoint is picked: the
+    /// cassette matches and records the request as the caller built it, and a
+    /// replayed request never triggers SRV lookups or health probes.
+    fn route(&self, request: HttpRequest) -> crate::error::Result<crate::http::response::HttpResponse> {
+        let cassette_request = self.cassette.as_ref().map(|cassette| cassette.snapshot(&request));
+        if let Some(response) = cassette_request
+            .as_ref()
+            .and_then(|recorded| self.replay(&request, recorded))
+        {
+            return Ok(response);
+        }
+
+        let group = match self.endpoint_groups.iter().find(|group| group.matches(request.url())) {
+            Some(group) => Some(group.clone()),
+            None => self.srv_groups.group_for(request.url())?,
+        };
+        let response = match group {
+            Some(group) => {
+                let client = self.clone();
+                group.execute(request, move |request| {
+                    client
+                        .dispatch_upstream(request)
+                        .unwrap_or_else(crate::middleware::error_response)
+                })
+            }
+            None => self.dispatch_upstream(request)?,
+        };
+
+        Ok(match (&self.cassette, cassette_request) {
+            (Some(cassette), Some(recorded)) => cassette.record_response(recorded, response),
+            _ => response,
+        })
+    }
+
+    /// Answer a request from the cassette, if it has a recording for it
+    fn replay(
+        &self,
+        request: &HttpRequest,
+        recorded: &crate::cassette::RecordedRequest,
+    ) -> Option<crate::http::response::HttpResponse> {
+        let started = Instant::now();
+        let mut response = self.cassette.as_ref()?.replay_response(recorded)?;
+        self.stats.total_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        if let Some(har) = &self.har {
+            response = har.record(har.har_request(request), started, response);
+        }
+        self.track_result(&response);
+        Some(response)
+    }
+
+    /// Send a request that is not sharing another request's response
+    ///
+    /// Tracks comprehensive telemetry metrics and applies strategy-specific optimizations.
+    fn dispatch_upstream(&self, request: HttpRequest) -> crate::error::Result<crate::http::response::HttpResponse> {
         let stats = self.stats.clone();
+        let started = Instant::now();
         
         // Track request
         stats.total_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         
+        // Fail fast while the origin's circuit is open
+        let permit = match &self.circuit_breaker {
+            Some(breaker) => Some(
+                breaker
+                    .try_acquire(&request.url().origin().ascii_serialization())
+                    .inspect_err(|_| {
+                        stats.failed_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    })?,
+            ),
+            None => None,
+        };
+        
         // Apply compression headers based on configuration
         let mut modified_request = request;
         crate::http::headers::add_compression_headers(modified_request.headers_mut(), &self.config);
@@ -452,19 +935,168 @@ impl HttpClient {
             }
         }
         
-        // Build and execute strategy
-        let strategy = self.strategy.build();
-        let response = strategy.execute(modified_request);
+        // Build and execute strategy, hedging slow idempotent requests
+        let har_request = self.har.as_ref().map(|har| har.har_request(&modified_request));
+        let hedging = self
+            .hedging
+            .as_ref()
+            .inspect(|hedging| hedging.budget().record_request())
+            .filter(|hedging| hedging.policy().is_hedgeable(&modified_request));
+        let hedging = hedging.cloned();
+        let mut response = match &self.rate_limiter {
+            Some(limiter) => {
+                let key = limiter.key_for(&modified_request);
+                let wait = limiter.acquire(&key).inspect_err(|_| {
+                    stats.failed_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                })?;
+                if wait.is_zero() {
+                    self.send(hedging, modified_request)
+                } else {
+                    self.send_later(limiter.clone(), key, wait, hedging, modified_request)
+                }
+            }
+            None => self.send(hedging, modified_request),
+        };
+        if let Some(permit) = permit {
+            response = permit.settle_with(response);
+        }
         
-        // Track result
-        if response.is_success() {
-            stats.successful_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        } else if response.is_error() {
-            stats.failed_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        if let (Some(har), Some(har_request)) = (&self.har, har_request) {
+            response = har.record(har_request, started, response);
         }
         
+        self.track_result(&response);
+        Ok(response)
+    }
+
+    /// Send the request now, retried and hedged if enabled for it
+    fn send(&self, hedging: Option<Arc<Hedging>>, request: HttpRequest) -> crate::http::response::HttpResponse {
+        match &self.retry_policy {
+            Some(policy) if policy.max_attempts > 1 => {
+                let client = self.clone();
+                let mut executor = HttpRetryExecutor::new(
+                    move |request| client.send_attempt(hedging.clone(), request),
+                    policy.clone(),
+                );
+                if let Some(budget) = &self.retry_budget {
+                    executor = executor.with_budget(budget.clone());
+                }
+                if let Some(limiter) = &self.rate_limiter {
+                    executor = executor.with_rate_limiter(limiter.clone());
+                }
+                executor.execute_with_retry(request)
+            }
+            _ => self.send_attempt(hedging, request),
+        }
+    }
+
+    /// Send one attempt of the request, hedged if enabled for it
+    fn send_attempt(&self, hedging: Option<Arc<Hedging>>, mut request: HttpRequest) -> crate::http::response::HttpResponse {
+        // Keyed before signing, which would make every request's credential unique
+        let key = self.rate_limiter.as_ref().map(|limiter| limiter.key_for(&request));
+        if let Some(signer) = &self.sigv4 {
+            if !request.left_first_origin
+                && request.auth.is_none()
+                && !request.headers().contains_key(http::header::AUTHORIZATION)
+            {
+                if let Err(error) = signer.sign(&mut request) {
+                    return crate::middleware::error_response(error);
+                }
+            }
+        }
+        let response = match hedging {
+            Some(hedging) => self.execute_hedged(hedging, request),
+            None => self.strategy.build_with_quic_stats(Some(self.stats.quic.clone())).execute(request),
+        };
+        // Every attempt teaches the rate limiter, so a retry waits for the budget it reports
+        match (&self.rate_limiter, key) {
+            (Some(limiter), Some(key)) => limiter.observe(key, response),
+            _ => response,
+        }
+    }
+
+    /// Send the request once the rate limiter's wait is over
+    ///
+    /// The response is returned right away and fills in once the request
+    /// has been sent.
+    fn send_later(
+        &self,
+        limiter: Arc<RateLimiter>,
+        key: String,
+        wait: std::time::Duration,
+        hedging: Option<Arc<Hedging>>,
+        request: HttpRequest,
+    ) -> crate::http::response::HttpResponse {
+        tracing::debug!(
+            target: "quyc::rate_limit",
+            key = %key,
+            wait_ms = wait.as_millis() as u64,
+            "Rate limit reached, queueing request"
+        );
+        let (response, slot) = crate::http::response::HttpResponse::pending(request.version(), 0);
+        let client = self.clone();
+        crate::retry::timer::schedule(wait, move || client.send_when_free(limiter, key, hedging, request, slot));
         response
     }
 
+    /// Send the request into `slot`, unless a response exhausted the rate
+    /// limit budget while it waited, in which case it waits again
+    fn send_when_free(
+        self,
+        limiter: Arc<RateLimiter>,
+        key: String,
+        hedging: Option<Arc<Hedging>>,
+        request: HttpRequest,
+        slot: crate::http::response::ResponseSlot,
+    ) {
+        let wait = limiter.held_for(&key);
+        if wait.is_zero() {
+            slot.fill(Vec::new(), None, self.send(hedging, request));
+        } else {
+            crate::retry::timer::schedule(wait, move || self.send_when_free(limiter, key, hedging, request, slot));
+        }
+    }
+
+    /// Race the request against hedged duplicates
+    fn execute_hedged(&self, hedging: Arc<Hedging>, request: HttpRequest) -> crate::http::response::HttpResponse {
+        let primary = self.strategy.clone();
+        let alternative = if hedging.policy().alternate_protocol {
+            self.strategy.hedge_alternative()
+        } else {
+            self.strategy.clone()
+        };
+        let quic = self.stats.quic.clone();
+        hedge::execute(hedging, self.stats.clone(), request, move |attempt| {
+            let strategy = if attempt == 0 { &primary } else { &alternative };
+            strategy.build_with_quic_stats(Some(quic.clone()))
+        })
+    }
+
+    /// Count a finished request as successful or failed
+    #[inline]
+    fn track_result(&self, response: &crate::http::response::HttpResponse) {
+        if response.is_success() {
+            self.stats.successful_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        } else if response.is_error() {
+            self.stats.failed_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
 
-}
\ No newline at end of file
+}
+
+/// Cookie jar of a client configured with `cookie_store`
+fn default_cookie_store(config: &HttpConfig) -> Option<SharedCookieStore> {
+    config
+        .cookie_store
+        .then(|| SharedCookieStore(Arc::new(Jar::default())))
+}
+
+/// Apply client-level protocol diagnostics (qlog, TLS key log) and TLS settings to a strategy
+fn configure_strategy(strategy: HttpProtocolStrategy, config: &HttpConfig) -> HttpProtocolStrategy {
+    strategy
+        .with_qlog(config.quic_qlog())
+        .with_key_log_file(config.tls_key_log_file.clone())
+        .with_root_certificates(&config.tls_root_certificates)
+        .with_ech(config.tls_ech_policy, config.tls_ech_config_list.clone())
+}
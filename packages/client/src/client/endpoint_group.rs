@@ -0,0 +1,650 @@
+//! Load balancing and failover across replicas of one API
+//!
+//! An [`EndpointGroup`] maps a logical base URL, such as
+//! `https://llm.internal/v1`, to several concrete endpoints serving the same
+//! API, e.g. regional deployments and self-hosted replicas. Each request to
+//! the logical URL is sent to an endpoint chosen by the group's
+//! [`SelectionPolicy`] and, when the answer is retryable (a connection
+//! failure, a timeout, `429`, `502`, `503` or `504`), sent again to another
+//! endpoint right away.
+//!
+//! Endpoint health lives in [`ProtocolIntelligence`]'s per-domain tracking:
+//! request outcomes update each origin's
+//! [`EndpointHealth`](crate::protocols::EndpointHealth), endpoints failing
+//! repeatedly are ejected from rotation for a while, and optional active
+//! probes return them once they answer again.
+//!
+//! Endpoints may also come from DNS SRV records, see
+//! [`EndpointGroup::from_srv`]; they are then looked up again whenever the
+//! records' TTL has expired.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock, Weak};
+use std::time::{Duration, Instant};
+
+use url::Url;
+use ystream::spawn_task;
+
+use super::srv::{SRV_RETRY_AFTER_FAILURE, SRV_SCHEME_PREFIX, SrvRecord, SrvResolver};
+use crate::error::HttpError;
+use crate::http::request::HttpRequest;
+use crate::http::response::{HttpResponse, ResponseSlot};
+use crate::protocols::intelligence::{DomainCapabilities, ProtocolIntelligence};
+use crate::retry::executor::{Answer, first_answer};
+use crate::retry::RetryPolicy;
+use crate::telemetry::retry_stats::AttemptOutcome;
+
+/// How the group picks an endpoint for each request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionPolicy {
+    /// Take turns
+    #[default]
+    RoundRobin,
+    /// Fewest requests in flight, counted until their response body ends
+    LeastOutstanding,
+    /// Lowest average time to response headers, scaled by requests in flight
+    EwmaLatency,
+    /// Random, in proportion to each endpoint's weight
+    Weighted,
+}
+
+/// One concrete deployment of the API
+#[derive(Debug, Clone)]
+pub struct Endpoint {
+    /// Base URL the logical base URL is replaced with
+    pub url: Url,
+    /// Share of requests under [`SelectionPolicy::Weighted`]
+    pub weight: u32,
+    /// Lower values are preferred: endpoints of a higher priority only get
+    /// requests when every endpoint of a lower one has failed or is ejected
+    pub priority: u16,
+}
+
+impl Endpoint {
+    /// Endpoint at `url` with weight 1
+    pub fn new(url: &str) -> Result<Self, HttpError> {
+        Self::weighted(url, 1)
+    }
+
+    /// Endpoint at `url` with the given weight
+    pub fn weighted(url: &str, weight: u32) -> Result<Self, HttpError> {
+        let url = Url::parse(url).map_err(crate::error::url_parse_error)?;
+        Ok(Self {
+            url,
+            weight,
+            priority: 0,
+        })
+    }
+
+    /// Set the endpoint's priority
+    #[inline]
+    pub fn with_priority(mut self, priority: u16) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Origin (`scheme://host:port`) health is tracked under
+    #[inline]
+    pub fn origin(&self) -> String {
+        self.url.origin().ascii_serialization()
+    }
+}
+
+/// Active health probing of ejected and healthy endpoints alike
+#[derive(Debug, Clone)]
+pub struct HealthCheckConfig {
+    /// Path requested with `GET` on each endpoint's origin
+    pub path: String,
+    /// Time between probe rounds
+    pub interval: Duration,
+    /// Time a probe may take to answer with headers
+    pub timeout: Duration,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            path: "/health".to_string(),
+            interval: Duration::from_secs(10),
+            timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Selection, failover and ejection settings of an endpoint group
+#[derive(Debug, Clone)]
+pub struct EndpointGroupConfig {
+    /// How endpoints are picked
+    pub policy: SelectionPolicy,
+    /// Further endpoints tried after a retryable answer
+    pub max_failovers: u32,
+    /// Time an attempt may take to answer with headers before failing over
+    pub attempt_timeout: Duration,
+    /// Failures in a row that take an endpoint out of rotation
+    pub failure_threshold: u32,
+    /// How long an ejected endpoint stays out of rotation
+    pub ejection_time: Duration,
+    /// Active health probes, if enabled
+    pub health_check: Option<HealthCheckConfig>,
+}
+
+impl Default for EndpointGroupConfig {
+    fn default() -> Self {
+        Self {
+            policy: SelectionPolicy::RoundRobin,
+            max_failovers: 2,
+            attempt_timeout: Duration::from_secs(30),
+            failure_threshold: 3,
+            ejection_time: Duration::from_secs(30),
+            health_check: None,
+        }
+    }
+}
+
+impl EndpointGroupConfig {
+    /// Validate configuration for consistency
+    pub fn validate(&self) -> Result<(), String> {
+        if self.failure_threshold == 0 {
+            return Err("failure_threshold must be at least 1".to_string());
+        }
+        if self.attempt_timeout.is_zero() {
+            return Err("attempt_timeout must be greater than zero".to_string());
+        }
+        if let Some(health_check) = &self.health_check {
+            if health_check.interval.is_zero() || health_check.timeout.is_zero() {
+                return Err("health check interval and timeout must be greater than zero".to_string());
+            }
+            if !health_check.path.starts_with('/') {
+                return Err("health check path must start with '/'".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Health of one endpoint at a point in time
+#[derive(Debug, Clone)]
+pub struct EndpointStatus {
+    /// Endpoint base URL
+    pub url: Url,
+    /// Endpoint priority
+    pub priority: u16,
+    /// Whether the endpoint is in rotation
+    pub available: bool,
+    /// Requests in flight
+    pub outstanding: usize,
+    /// Average time to response headers, once known
+    pub ewma_latency: Option<Duration>,
+    /// Failures since the last success
+    pub consecutive_failures: u32,
+}
+
+/// A logical base URL served by several concrete endpoints
+#[derive(Debug)]
+pub struct EndpointGroup {
+    base: Url,
+    members: RwLock<Arc<Members>>,
+    config: EndpointGroupConfig,
+    intelligence: Arc<ProtocolIntelligence>,
+    discovery: Option<SrvDiscovery>,
+    cursor: AtomicUsize,
+}
+
+/// Keeps a request outstanding at its endpoint until dropped
+struct Outstanding(Arc<DomainCapabilities>);
+
+impl Drop for Outstanding {
+    fn drop(&mut self) {
+        self.0.health.end_request();
+    }
+}
+
+/// Endpoints of a group with their health tracking, replaced as a whole
+///
+/// Requests keep the members they started with, so endpoint indexes stay
+/// valid while the endpoints are replaced.
+#[derive(Debug, Default)]
+struct Members {
+    endpoints: Vec<Endpoint>,
+    domains: Vec<Arc<DomainCapabilities>>,
+}
+
+/// Where the endpoints of an SRV-backed group come from
+#[derive(Debug)]
+struct SrvDiscovery {
+    /// SRV name looked up, e.g. `_api._tcp.service.internal`
+    service: String,
+    /// Scheme of the endpoint URLs
+    scheme: String,
+    resolver: SrvResolver,
+    /// When the endpoints must be looked up again
+    refresh_at: Mutex<Instant>,
+}
+
+impl EndpointGroup {
+    /// Group serving requests to `base` from `endpoints`
+    pub fn new(base: &str, endpoints: Vec<Endpoint>) -> Result<Self, HttpError> {
+        Self::with_intelligence(base, endpoints, Arc::new(ProtocolIntelligence::new()))
+    }
+
+    /// Like [`new`](Self::new), tracking endpoint health in `intelligence`
+    pub fn with_intelligence(
+        base: &str,
+        endpoints: Vec<Endpoint>,
+        intelligence: Arc<ProtocolIntelligence>,
+    ) -> Result<Self, HttpError> {
+        let base = Url::parse(base).map_err(crate::error::url_parse_error)?;
+        let group = Self {
+            base,
+            members: RwLock::default(),
+            config: EndpointGroupConfig::default(),
+            intelligence,
+            discovery: None,
+            cursor: AtomicUsize::new(0),
+        };
+        group.set_endpoints(endpoints)?;
+        Ok(group)
+    }
+
+    /// Group serving requests to `base` from the targets of the SRV records of `service`
+    ///
+    /// `base` may be an `srv+https://` or `srv+http://` URL, whose host is
+    /// then usually `service` itself, or a plain URL. Endpoints use `base`'s
+    /// scheme without the `srv+` prefix and its path, and are weighted and
+    /// prioritized as the records say; the group defaults to
+    /// [`SelectionPolicy::Weighted`]. The records are looked up on the first
+    /// request, or on [`refresh`](Self::refresh).
+    pub fn from_srv(base: &str, service: &str, resolver: SrvResolver) -> Result<Self, HttpError> {
+        Self::from_srv_with_intelligence(base, service, resolver, Arc::new(ProtocolIntelligence::new()))
+    }
+
+    /// Like [`from_srv`](Self::from_srv), tracking endpoint health in `intelligence`
+    pub fn from_srv_with_intelligence(
+        base: &str,
+        service: &str,
+        resolver: SrvResolver,
+        intelligence: Arc<ProtocolIntelligence>,
+    ) -> Result<Self, HttpError> {
+        let base = Url::parse(base).map_err(crate::error::url_parse_error)?;
+        let scheme = base.scheme().strip_prefix(SRV_SCHEME_PREFIX).unwrap_or(base.scheme()).to_string();
+        if scheme != "http" && scheme != "https" {
+            return Err(crate::error::configuration(format!(
+                "SRV endpoints need an http or https scheme, not {}",
+                base.scheme()
+            )));
+        }
+        Ok(Self {
+            discovery: Some(SrvDiscovery {
+                service: service.trim_end_matches('.').to_string(),
+                scheme,
+                resolver,
+                refresh_at: Mutex::new(Instant::now()),
+            }),
+            base,
+            members: RwLock::default(),
+            config: EndpointGroupConfig {
+                policy: SelectionPolicy::Weighted,
+                ..EndpointGroupConfig::default()
+            },
+            intelligence,
+            cursor: AtomicUsize::new(0),
+        })
+    }
+
+    /// Replace the endpoints
+    ///
+    /// Health tracked for origins that stay in the group is kept. Requests
+    /// already in progress finish on the endpoints they started with.
+    pub fn set_endpoints(&self, endpoints: Vec<Endpoint>) -> Result<(), HttpError> {
+        if endpoints.is_empty() {
+            return Err(crate::error::configuration("an endpoint group needs at least one endpoint"));
+        }
+        let domains = endpoints
+            .iter()
+            .map(|endpoint| self.intelligence.domain(&endpoint.origin()))
+            .collect();
+        *self.members.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(Members { endpoints, domains });
+        Ok(())
+    }
+
+    /// Look up the SRV records again and use their targets as endpoints
+    ///
+    /// Does nothing for groups with fixed endpoints. After a failed lookup
+    /// the current endpoints stay in use and the lookup is retried on a
+    /// request a few seconds later.
+    pub fn refresh(&self) -> Result<(), HttpError> {
+        let Some(discovery) = &self.discovery else {
+            return Ok(());
+        };
+        let mut refresh_at = discovery.refresh_at.lock().unwrap_or_else(PoisonError::into_inner);
+        self.lookup_endpoints(discovery, &mut refresh_at)
+    }
+
+    /// Refresh SRV endpoints whose records have expired
+    ///
+    /// Concurrent callers wait for one lookup rather than each doing their own.
+    fn refresh_if_expired(&self) -> Result<(), HttpError> {
+        let Some(discovery) = &self.discovery else {
+            return Ok(());
+        };
+        let mut refresh_at = discovery.refresh_at.lock().unwrap_or_else(PoisonError::into_inner);
+        if Instant::now() < *refresh_at {
+            return Ok(());
+        }
+        self.lookup_endpoints(discovery, &mut refresh_at)
+    }
+
+    fn lookup_endpoints(&self, discovery: &SrvDiscovery, refresh_at: &mut Instant) -> Result<(), HttpError> {
+        let lookup = match discovery.resolver.lookup(&discovery.service) {
+            Ok(lookup) => lookup,
+            Err(error) => {
+                *refresh_at = Instant::now() + SRV_RETRY_AFTER_FAILURE;
+                tracing::warn!(
+                    target: "quyc::endpoints",
+                    service = %discovery.service,
+                    error = %error,
+                    "SRV lookup failed, keeping current endpoints"
+                );
+                return Err(error);
+            }
+        };
+        let endpoints = lookup
+            .records
+            .iter()
+            .map(|record| self.srv_endpoint(&discovery.scheme, record))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.set_endpoints(endpoints)?;
+        *refresh_at = lookup.valid_until;
+        Ok(())
+    }
+
+    /// Endpoint serving `base`'s path at the target of `record`
+    fn srv_endpoint(&self, scheme: &str, record: &SrvRecord) -> Result<Endpoint, HttpError> {
+        let url = format!("{scheme}://{}:{}{}", record.target, record.port, self.base.path());
+        Ok(Endpoint::weighted(&url, u32::from(record.weight))?.with_priority(record.priority))
+    }
+
+    /// Current endpoints with their health tracking
+    fn members(&self) -> Arc<Members> {
+        Arc::clone(&self.members.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Use `config` for selection, failover and ejection
+    pub fn with_config(mut self, config: EndpointGroupConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Pick endpoints with `policy`
+    pub fn with_policy(mut self, policy: SelectionPolicy) -> Self {
+        self.config.policy = policy;
+        self
+    }
+
+    /// Logical base URL
+    #[inline]
+    pub fn base(&self) -> &Url {
+        &self.base
+    }
+
+    /// Concrete endpoints, in the order they were given or discovered
+    pub fn endpoints(&self) -> Vec<Endpoint> {
+        self.members().endpoints.clone()
+    }
+
+    /// SRV name the endpoints are discovered from, if any
+    #[inline]
+    pub fn srv_service(&self) -> Option<&str> {
+        self.discovery.as_ref().map(|discovery| discovery.service.as_str())
+    }
+
+    /// Settings in use
+    #[inline]
+    pub fn config(&self) -> &EndpointGroupConfig {
+        &self.config
+    }
+
+    /// Protocol intelligence the endpoint health is tracked in
+    #[inline]
+    pub fn intelligence(&self) -> &Arc<ProtocolIntelligence> {
+        &self.intelligence
+    }
+
+    /// Whether `url` is under the logical base URL
+    ///
+    /// Scheme, host and port are compared one by one, as `srv+` URLs have
+    /// no origin to compare.
+    pub fn matches(&self, url: &Url) -> bool {
+        url.scheme() == self.base.scheme()
+            && url.host_str() == self.base.host_str()
+            && url.port_or_known_default() == self.base.port_or_known_default()
+            && self.relative_path(url).is_some()
+    }
+
+    /// `url` moved from the logical base URL onto endpoint `index`
+    pub fn rewrite(&self, url: &Url, index: usize) -> Option<Url> {
+        self.rewrite_onto(&self.members(), url, index)
+    }
+
+    fn rewrite_onto(&self, members: &Members, url: &Url, index: usize) -> Option<Url> {
+        let rest = self.relative_path(url)?;
+        let endpoint = &members.endpoints.get(index)?.url;
+        let mut target = endpoint.clone();
+        target.set_path(&format!("{}{rest}", endpoint.path().trim_end_matches('/')));
+        target.set_query(url.query());
+        target.set_fragment(None);
+        Some(target)
+    }
+
+    /// Path of `url` below the base path, starting with `/` unless empty
+    fn relative_path<'a>(&self, url: &'a Url) -> Option<&'a str> {
+        let rest = url.path().strip_prefix(self.base.path().trim_end_matches('/'))?;
+        (rest.is_empty() || rest.starts_with('/')).then_some(rest)
+    }
+
+    /// Pick an endpoint not in `tried`
+    ///
+    /// Only endpoints of the lowest priority among the remaining ones are
+    /// considered. Ejected endpoints are only picked when every remaining
+    /// endpoint is ejected, so an outage of all of them still lets requests
+    /// through.
+    pub fn select(&self, tried: &[usize]) -> Option<usize> {
+        self.select_from(&self.members(), tried)
+    }
+
+    fn select_from(&self, members: &Members, tried: &[usize]) -> Option<usize> {
+        let untried: Vec<usize> = (0..members.endpoints.len()).filter(|index| !tried.contains(index)).collect();
+        let available: Vec<usize> = untried
+            .iter()
+            .copied()
+            .filter(|&index| members.domains[index].health.is_available())
+            .collect();
+        let mut candidates = if available.is_empty() { untried } else { available };
+        let priority = candidates.iter().map(|&index| members.endpoints[index].priority).min()?;
+        candidates.retain(|&index| members.endpoints[index].priority == priority);
+
+        // Ties go to the next endpoint in turn
+        let start = self.cursor.fetch_add(1, Ordering::Relaxed);
+        let mut in_turn = (0..candidates.len()).map(|offset| candidates[(start + offset) % candidates.len()]);
+        let health = |index: usize| &members.domains[index].health;
+        match self.config.policy {
+            SelectionPolicy::RoundRobin => in_turn.next(),
+            SelectionPolicy::LeastOutstanding => {
+                in_turn.min_by_key(|&index| health(index).outstanding.load(Ordering::Relaxed))
+            }
+            SelectionPolicy::EwmaLatency => in_turn.min_by_key(|&index| {
+                // Endpoints without a sample go first so they get one
+                let latency = health(index).ewma_latency().map_or(0, |latency| latency.as_micros());
+                latency * (health(index).outstanding.load(Ordering::Relaxed) as u128 + 1)
+            }),
+            SelectionPolicy::Weighted => {
+                let total: u64 = candidates.iter().map(|&index| u64::from(members.endpoints[index].weight)).sum();
+                if total == 0 {
+                    return in_turn.next();
+                }
+                let mut ticket = crate::crypto::random::fast_random() % total;
+                candidates.iter().copied().find(|&index| {
+                    let weight = u64::from(members.endpoints[index].weight);
+                    if ticket < weight {
+                        return true;
+                    }
+                    ticket -= weight;
+                    false
+                })
+            }
+        }
+    }
+
+    /// Health of every endpoint
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        let members = self.members();
+        members
+            .endpoints
+            .iter()
+            .zip(&members.domains)
+            .map(|(endpoint, domain)| EndpointStatus {
+                url: endpoint.url.clone(),
+                priority: endpoint.priority,
+                available: domain.health.is_available(),
+                outstanding: domain.health.outstanding.load(Ordering::Relaxed),
+                ewma_latency: domain.health.ewma_latency(),
+                consecutive_failures: domain.health.consecutive_failures.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Send `request` to an endpoint with `send`, failing over on retryable answers
+    ///
+    /// Returns at once; the response fills in from the accepted attempt.
+    /// Expired SRV records are looked up again first. Requests that cannot
+    /// be replayed (see [`RetryPolicy::is_replayable`]) are sent to one
+    /// endpoint only.
+    pub fn execute<F>(self: &Arc<Self>, request: HttpRequest, send: F) -> HttpResponse
+    where
+        F: Fn(HttpRequest) -> HttpResponse + Send + 'static,
+    {
+        let (response, slot) = HttpResponse::pending(request.version(), 0);
+        let group = Arc::clone(self);
+        spawn_task(move || group.run(request, &send, slot));
+        response
+    }
+
+    /// Send attempts to successive endpoints until one is accepted
+    fn run<F>(&self, request: HttpRequest, send: &F, slot: ResponseSlot)
+    where
+        F: Fn(HttpRequest) -> HttpResponse,
+    {
+        if let Err(error) = self.refresh_if_expired() {
+            if self.members().endpoints.is_empty() {
+                slot.fail(error.to_string());
+                return;
+            }
+        }
+        let members = self.members();
+        let replayable = RetryPolicy::is_replayable(&request);
+        let max_attempts = self.config.max_failovers as usize + 1;
+        // A request that cannot be replayed is sent once, as is: cloning drops a streaming body
+        let mut request = Some(request);
+        let mut tried = Vec::new();
+
+        while let Some(index) = self.select_from(&members, &tried) {
+            tried.push(index);
+            let last = !replayable || tried.len() >= max_attempts || tried.len() == members.endpoints.len();
+            let Some(attempt) = (if last { request.take() } else { request.clone() }) else {
+                return;
+            };
+            let attempt = match self.rewrite_onto(&members, attempt.url(), index) {
+                Some(url) => attempt.with_url(url),
+                None => attempt,
+            };
+
+            let endpoint = &members.endpoints[index].url;
+            let health = &members.domains[index].health;
+            health.begin_request();
+            let started = Instant::now();
+            let answer = first_answer(send(attempt), self.config.attempt_timeout);
+            let (outcome, retryable, _) = answer.judge();
+            if matches!(outcome, AttemptOutcome::Answered(Some(status)) if status < 500) {
+                health.record_success(started.elapsed());
+            } else if health.record_failure(self.config.failure_threshold, self.config.ejection_time) {
+                tracing::warn!(
+                    target: "quyc::endpoints",
+                    endpoint = %endpoint,
+                    "Endpoint ejected after repeated failures"
+                );
+            }
+
+            if retryable && !last {
+                health.end_request();
+                tracing::debug!(
+                    target: "quyc::endpoints",
+                    endpoint = %endpoint,
+                    outcome = ?outcome,
+                    "Failing over to another endpoint"
+                );
+                continue;
+            }
+            // The request stays outstanding until its response body has ended
+            let outstanding = Outstanding(Arc::clone(&members.domains[index]));
+            answer
+                .map_response(|response| {
+                    response.map_body(move |chunk| {
+                        let _ = &outstanding;
+                        chunk
+                    })
+                })
+                .deliver(slot);
+            return;
+        }
+    }
+
+    /// Probe every endpoint with `probe` in the background
+    ///
+    /// Endpoints answering below `500` return to rotation; others count a
+    /// failure. Probing stops once the group is dropped. Does nothing
+    /// without a [`HealthCheckConfig`].
+    pub fn start_health_checks<F>(self: &Arc<Self>, probe: F)
+    where
+        F: Fn(HttpRequest) -> HttpResponse + Send + 'static,
+    {
+        let Some(health_check) = self.config.health_check.clone() else {
+            return;
+        };
+        let group: Weak<Self> = Arc::downgrade(self);
+        std::thread::spawn(move || {
+            loop {
+                std::thread::sleep(health_check.interval);
+                let Some(group) = group.upgrade() else {
+                    return;
+                };
+                group.probe_all(&health_check, &probe);
+            }
+        });
+    }
+
+    /// Run one round of health probes
+    fn probe_all<F>(&self, health_check: &HealthCheckConfig, probe: &F)
+    where
+        F: Fn(HttpRequest) -> HttpResponse,
+    {
+        let members = self.members();
+        for (endpoint, domain) in members.endpoints.iter().zip(&members.domains) {
+            let Ok(url) = endpoint.url.join(&health_check.path) else {
+                continue;
+            };
+            let answer = first_answer(probe(HttpRequest::get(url.clone())), health_check.timeout);
+            let healthy = matches!(&answer, Answer::Headers { response, .. }
+                if response.status_code().is_some_and(|status| status.as_u16() < 500));
+            if healthy {
+                domain.health.mark_healthy();
+            } else {
+                domain.health.record_failure(self.config.failure_threshold, self.config.ejection_time);
+                tracing::debug!(
+                    target: "quyc::endpoints",
+                    url = %url,
+                    "Health probe failed"
+                );
+            }
+        }
+    }
+}
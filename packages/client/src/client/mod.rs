@@ -3,11 +3,23 @@
 //! Provides a modular HTTP client implementation with excellent separation
 //! of concerns across core functionality, execution, statistics, and configuration.
 
+pub mod circuit_breaker;
+pub mod coalesce;
 pub mod configuration;
 pub mod core;
+pub mod endpoint_group;
+pub mod rate_limit;
+pub mod srv;
 pub mod stats;
 
 // Re-export main types for convenient access
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitPermit, CircuitState};
+pub use coalesce::{Coalescer, FlightKey};
 pub use core::HttpClient;
+pub use endpoint_group::{
+    Endpoint, EndpointGroup, EndpointGroupConfig, EndpointStatus, HealthCheckConfig, SelectionPolicy,
+};
+pub use rate_limit::{RateLimitConfig, RateLimitMetrics, RateLimiter};
+pub use srv::{SrvLookup, SrvRecord, SrvResolver};
 
 pub use stats::{ClientStats, ClientStatsSnapshot};
@@ -0,0 +1,444 @@
+//! Client-side rate limiting that learns from provider rate-limit headers
+//!
+//! Each origin (`scheme://host:port`), optionally split by API key, gets a
+//! token bucket. Requests reserve a token before they are sent; when the bucket
+//! is empty the reservation is queued behind earlier ones and the request is
+//! sent once its turn comes, so concurrent tasks share the budget instead of
+//! racing into `429`s.
+//!
+//! Responses tune the bucket: `x-ratelimit-*` (OpenAI style),
+//! `anthropic-ratelimit-*` and `Retry-After` headers set the remaining
+//! budget, the refill rate and, once a budget is exhausted, hold requests
+//! until it resets. A `429` without any hints halves the refill rate.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use dashmap::DashMap;
+use http::{HeaderMap, HeaderName, StatusCode};
+
+use crate::error::HttpError;
+use crate::http::headers::parse_retry_after;
+use crate::http::request::HttpRequest;
+use crate::http::response::HttpResponse;
+
+/// Headers carrying the API key when `per_api_key` is set
+const CREDENTIAL_HEADERS: [&str; 3] = ["authorization", "x-api-key", "api-key"];
+
+/// `(remaining, limit, reset)` header names for the request budget
+const REQUEST_HEADERS: [(&str, &str, &str); 3] = [
+    ("x-ratelimit-remaining-requests", "x-ratelimit-limit-requests", "x-ratelimit-reset-requests"),
+    (
+        "anthropic-ratelimit-requests-remaining",
+        "anthropic-ratelimit-requests-limit",
+        "anthropic-ratelimit-requests-reset",
+    ),
+    ("x-ratelimit-remaining", "x-ratelimit-limit", "x-ratelimit-reset"),
+];
+
+/// `(remaining, reset)` header names for token budgets
+const TOKEN_HEADERS: [(&str, &str); 4] = [
+    ("x-ratelimit-remaining-tokens", "x-ratelimit-reset-tokens"),
+    ("anthropic-ratelimit-tokens-remaining", "anthropic-ratelimit-tokens-reset"),
+    ("anthropic-ratelimit-input-tokens-remaining", "anthropic-ratelimit-input-tokens-reset"),
+    ("anthropic-ratelimit-output-tokens-remaining", "anthropic-ratelimit-output-tokens-reset"),
+];
+
+/// Slowest refill rate a `429` can push a bucket down to, in requests per second
+const MIN_REFILL_RATE: f64 = 0.01;
+
+/// Token bucket settings of a rate limiter
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Sustained requests per second per bucket until headers say otherwise
+    pub requests_per_second: f64,
+    /// Requests that may be sent back to back from a full bucket
+    pub burst: u32,
+    /// Longest a request may be queued; beyond it the request fails with
+    /// [`Kind::RateLimited`](crate::error::Kind::RateLimited)
+    pub max_wait: Duration,
+    /// Keep a separate bucket per API key on the same origin
+    pub per_api_key: bool,
+    /// Adjust buckets from rate-limit response headers
+    pub learn_from_headers: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 10.0,
+            burst: 10,
+            max_wait: Duration::from_secs(60),
+            per_api_key: false,
+            learn_from_headers: true,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Limit to `requests_per_second`, with a burst of as many requests
+    pub fn per_second(requests_per_second: f64) -> Self {
+        Self {
+            requests_per_second,
+            burst: (requests_per_second.ceil() as u32).max(1),
+            ..Self::default()
+        }
+    }
+
+    /// Validate configuration for consistency
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.requests_per_second > 0.0 && self.requests_per_second.is_finite()) {
+            return Err("requests_per_second must be a positive number".to_string());
+        }
+        if self.burst == 0 {
+            return Err("burst must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Token bucket of one origin or API key
+#[derive(Debug)]
+struct Bucket {
+    /// Available tokens; negative while requests are queued
+    tokens: f64,
+    capacity: f64,
+    /// Tokens added per second
+    refill_rate: f64,
+    /// Refill is accounted up to here; in the future while the bucket is held
+    last_refill: Instant,
+    total_requests: u64,
+    delayed_requests: u64,
+    rejected_requests: u64,
+}
+
+impl Bucket {
+    fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            tokens: f64::from(config.burst),
+            capacity: f64::from(config.burst),
+            refill_rate: config.requests_per_second,
+            last_refill: Instant::now(),
+            total_requests: 0,
+            delayed_requests: 0,
+            rejected_requests: 0,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        if now > self.last_refill {
+            let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+            self.tokens = (self.tokens + elapsed * self.refill_rate).min(self.capacity);
+            self.last_refill = now;
+        }
+    }
+
+    /// Send nothing before `until`, then start again from a single token
+    fn hold_until(&mut self, until: Instant) {
+        if until > self.last_refill {
+            self.last_refill = until;
+            self.tokens = self.tokens.min(1.0);
+        }
+    }
+}
+
+/// Client-level rate limiter keyed by origin and, optionally, API key
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: DashMap<String, Bucket>,
+}
+
+impl RateLimiter {
+    /// Create a rate limiter with the given bucket settings
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: DashMap::new(),
+        }
+    }
+
+    /// Bucket settings in use
+    #[inline]
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Bucket key of `request`: its origin, plus a hash of its API key when
+    /// `per_api_key` is set
+    pub fn key_for(&self, request: &HttpRequest) -> String {
+        let origin = request.url().origin().ascii_serialization();
+        if !self.config.per_api_key {
+            return origin;
+        }
+        // Credentials set with `bearer_auth` and friends are not in the headers yet
+        let auth = request.auth_headers();
+        let credential = CREDENTIAL_HEADERS
+            .iter()
+            .find_map(|name| request.headers().get(*name))
+            .or_else(|| auth.values().next());
+        match credential {
+            Some(value) => {
+                let mut hasher = DefaultHasher::new();
+                value.as_bytes().hash(&mut hasher);
+                format!("{origin}#{:016x}", hasher.finish())
+            }
+            None => origin,
+        }
+    }
+
+    /// Reserve a send slot for `key`
+    ///
+    /// Returns how long to wait before sending, or a
+    /// [`Kind::RateLimited`](crate::error::Kind::RateLimited) error when that
+    /// would exceed `max_wait`; a refused request does not consume a token.
+    pub fn acquire(&self, key: &str) -> Result<Duration, HttpError> {
+        let now = Instant::now();
+        let mut bucket = self
+            .buckets
+            .entry(key.to_string())
+            .or_insert_with(|| Bucket::new(&self.config));
+        bucket.refill(now);
+
+        let mut wait = bucket.last_refill.saturating_duration_since(now);
+        if bucket.tokens < 1.0 {
+            wait += Duration::from_secs_f64((1.0 - bucket.tokens) / bucket.refill_rate);
+        }
+        if wait > self.config.max_wait {
+            bucket.rejected_requests += 1;
+            return Err(crate::error::rate_limited(format!(
+                "{key} would wait {wait:?}, longer than the allowed {:?}",
+                self.config.max_wait
+            )));
+        }
+
+        bucket.tokens -= 1.0;
+        bucket.total_requests += 1;
+        if !wait.is_zero() {
+            bucket.delayed_requests += 1;
+        }
+        Ok(wait)
+    }
+
+    /// Time left before `key` may send again after a reset it was told to wait for
+    ///
+    /// Queued requests check this once their turn comes, in case a response
+    /// exhausted the budget while they waited.
+    pub fn held_for(&self, key: &str) -> Duration {
+        self.buckets
+            .get(key)
+            .map(|bucket| bucket.last_refill.saturating_duration_since(Instant::now()))
+            .unwrap_or_default()
+    }
+
+    /// Adjust `key`'s bucket from a response's status and headers
+    pub fn learn(&self, key: &str, status: Option<StatusCode>, headers: &HeaderMap) {
+        let now = Instant::now();
+        let Some(mut bucket) = self.buckets.get_mut(key) else {
+            return;
+        };
+        bucket.refill(now);
+        let mut hinted = false;
+
+        for (remaining, limit, reset) in REQUEST_HEADERS {
+            let Some(remaining) = header_number(headers, remaining) else {
+                continue;
+            };
+            hinted = true;
+            let limit = header_number(headers, limit);
+            let reset = headers.get(reset).and_then(|v| v.to_str().ok()).and_then(parse_reset);
+
+            if let Some(limit) = limit.filter(|limit| *limit > 0.0) {
+                bucket.capacity = limit;
+                if let Some(reset) = reset.filter(|reset| !reset.is_zero() && remaining < limit) {
+                    // The spent part of the budget comes back by the reset
+                    bucket.refill_rate = ((limit - remaining) / reset.as_secs_f64()).max(MIN_REFILL_RATE);
+                }
+            }
+            bucket.tokens = bucket.tokens.min(remaining);
+            if remaining < 1.0 {
+                if let Some(reset) = reset {
+                    bucket.hold_until(now + reset);
+                }
+            }
+            break;
+        }
+
+        for (remaining, reset) in TOKEN_HEADERS {
+            let Some(remaining) = header_number(headers, remaining) else {
+                continue;
+            };
+            hinted = true;
+            if remaining >= 1.0 {
+                continue;
+            }
+            if let Some(reset) = headers.get(reset).and_then(|v| v.to_str().ok()).and_then(parse_reset) {
+                bucket.hold_until(now + reset);
+            }
+        }
+
+        if let Some(delay) = headers
+            .get(http::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_retry_after)
+        {
+            hinted = true;
+            bucket.hold_until(now + delay);
+        }
+
+        if status == Some(StatusCode::TOO_MANY_REQUESTS) && !hinted {
+            bucket.refill_rate = (bucket.refill_rate / 2.0).max(MIN_REFILL_RATE);
+            bucket.tokens = bucket.tokens.min(0.0);
+        }
+
+        tracing::trace!(
+            target: "quyc::rate_limit",
+            key = %key,
+            tokens = bucket.tokens,
+            refill_rate = bucket.refill_rate,
+            "Rate limit bucket updated from response"
+        );
+    }
+
+    /// Learn from `response`'s headers once they have arrived
+    ///
+    /// The streams are forwarded untouched.
+    pub(crate) fn observe(self: &Arc<Self>, key: String, response: HttpResponse) -> HttpResponse {
+        if !self.config.learn_from_headers {
+            return response;
+        }
+        let mut learner = Learner {
+            limiter: self.clone(),
+            key,
+            status: response.status_handle(),
+            headers: HeaderMap::new(),
+        };
+        response.map_headers(move |header| {
+            if is_rate_limit_header(&header.name) {
+                learner.headers.append(header.name.clone(), header.value.clone());
+            }
+            Some(header)
+        })
+    }
+
+    /// Forget the bucket of `key`
+    pub fn reset(&self, key: &str) {
+        self.buckets.remove(key);
+    }
+
+    /// Forget all buckets
+    pub fn reset_all(&self) {
+        self.buckets.clear();
+    }
+
+    /// Current state of `key`'s bucket
+    pub fn get_metrics(&self, key: &str) -> Option<RateLimitMetrics> {
+        let now = Instant::now();
+        self.buckets.get_mut(key).map(|mut bucket| {
+            bucket.refill(now);
+            RateLimitMetrics {
+                tokens: bucket.tokens,
+                capacity: bucket.capacity,
+                refill_rate: bucket.refill_rate,
+                held_for: bucket.last_refill.saturating_duration_since(now),
+                total_requests: bucket.total_requests,
+                delayed_requests: bucket.delayed_requests,
+                rejected_requests: bucket.rejected_requests,
+            }
+        })
+    }
+}
+
+/// State of one rate limit bucket
+#[derive(Debug, Clone)]
+pub struct RateLimitMetrics {
+    /// Available tokens; negative while requests are queued
+    pub tokens: f64,
+    /// Tokens a full bucket holds
+    pub capacity: f64,
+    /// Tokens added per second
+    pub refill_rate: f64,
+    /// Time left before the bucket refills again after a reset it waits for
+    pub held_for: Duration,
+    /// Requests admitted, immediately or after a wait
+    pub total_requests: u64,
+    /// Requests that had to wait
+    pub delayed_requests: u64,
+    /// Requests refused for exceeding `max_wait`
+    pub rejected_requests: u64,
+}
+
+/// Collects rate-limit headers and applies them when the headers end
+struct Learner {
+    limiter: Arc<RateLimiter>,
+    key: String,
+    status: Arc<AtomicU16>,
+    headers: HeaderMap,
+}
+
+impl Drop for Learner {
+    fn drop(&mut self) {
+        let status = StatusCode::from_u16(self.status.load(Ordering::Acquire)).ok();
+        self.limiter.learn(&self.key, status, &self.headers);
+    }
+}
+
+fn is_rate_limit_header(name: &HeaderName) -> bool {
+    let name = name.as_str();
+    name == "retry-after" || name.starts_with("x-ratelimit-") || name.starts_with("anthropic-ratelimit-")
+}
+
+fn header_number(headers: &HeaderMap, name: &str) -> Option<f64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// Parse a rate limit reset into the time left until it
+///
+/// Accepts seconds, Unix timestamps, Go-style durations such as `6m0s` or
+/// `20ms`, and RFC 3339 timestamps.
+pub fn parse_reset(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(number) = value.parse::<f64>() {
+        // Values this large are Unix timestamps rather than delays
+        if number > 1_000_000_000.0 {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs_f64();
+            return Some(Duration::try_from_secs_f64(number - now).unwrap_or_default());
+        }
+        return Duration::try_from_secs_f64(number).ok();
+    }
+    if let Some(duration) = parse_go_duration(value) {
+        return Some(duration);
+    }
+    let at = chrono::DateTime::parse_from_rfc3339(value).ok()?;
+    Some((at.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().unwrap_or_default())
+}
+
+/// Parse durations like `1h2m3.5s` or `250ms`
+fn parse_go_duration(value: &str) -> Option<Duration> {
+    if value.is_empty() {
+        return None;
+    }
+    let mut rest = value;
+    let mut seconds = 0.0;
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+        let (number, tail) = rest.split_at(number_end);
+        let number: f64 = number.parse().ok()?;
+        let unit_end = tail.find(|c: char| c.is_ascii_digit() || c == '.').unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_end);
+        let scale = match unit {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 1e-3,
+            "us" | "µs" => 1e-6,
+            "ns" => 1e-9,
+            _ => return None,
+        };
+        seconds += number * scale;
+        rest = tail;
+    }
+    Duration::try_from_secs_f64(seconds).ok()
+}
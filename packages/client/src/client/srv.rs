@@ -0,0 +1,202 @@
+//! DNS SRV service discovery
+//!
+//! Services published as SRV records (RFC 2782) are reached with
+//! `srv+https://_service._proto.name/path` URLs, or with an
+//! [`EndpointGroup`](super::EndpointGroup) created by
+//! [`EndpointGroup::from_srv`](super::EndpointGroup::from_srv). The records
+//! become the group's endpoints: targets of the lowest priority are used
+//! first, in proportion to their weights, and the others only on failover.
+//! The records are looked up again once their TTL has expired.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+use hickory_resolver::TokioResolver;
+use hickory_resolver::config::{NameServerConfig, NameServerConfigGroup, ResolverConfig};
+use hickory_resolver::name_server::TokioConnectionProvider;
+use hickory_resolver::proto::xfer::Protocol;
+use url::Url;
+
+use super::endpoint_group::EndpointGroup;
+use crate::error::HttpError;
+
+/// URL scheme prefix marking a host name as an SRV service name
+pub const SRV_SCHEME_PREFIX: &str = "srv+";
+
+/// Time to wait before looking up again after a failed lookup
+pub(crate) const SRV_RETRY_AFTER_FAILURE: Duration = Duration::from_secs(5);
+
+/// One SRV record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrvRecord {
+    /// Lower values are preferred
+    pub priority: u16,
+    /// Share of requests among records of the same priority
+    pub weight: u16,
+    /// Port the service listens on
+    pub port: u16,
+    /// Host name of the target, without the trailing dot
+    pub target: String,
+}
+
+/// Records of one SRV lookup
+#[derive(Debug, Clone)]
+pub struct SrvLookup {
+    /// Records in the order the server returned them
+    pub records: Vec<SrvRecord>,
+    /// When the records must be looked up again
+    pub valid_until: Instant,
+}
+
+/// Looks up SRV records through hickory
+///
+/// A client's resolver also looks up the DNS HTTPS records that carry
+/// Encrypted Client Hello configurations.
+#[derive(Clone, Default)]
+pub struct SrvResolver {
+    /// Name servers to ask instead of the system configuration
+    nameservers: Option<Vec<SocketAddr>>,
+    /// Resolver for lookups on the connection runtime, built on first use
+    /// and shared by clones so its cache is too
+    shared: Arc<OnceLock<TokioResolver>>,
+}
+
+impl std::fmt::Debug for SrvResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SrvResolver")
+            .field("nameservers", &self.nameservers)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SrvResolver {
+    /// Resolver using the system DNS configuration
+    #[inline]
+    pub fn system() -> Self {
+        Self::default()
+    }
+
+    /// Resolver asking `nameservers` over UDP, e.g. a local DNS stand-in
+    pub fn with_nameservers(nameservers: Vec<SocketAddr>) -> Self {
+        Self {
+            nameservers: Some(nameservers),
+            shared: Arc::default(),
+        }
+    }
+
+    /// Look up the SRV records of `name`, e.g. `_api._tcp.service.internal`
+    ///
+    /// Blocks until the answer arrives. The lookup runs on its own thread
+    /// and runtime, so it may be called from within an async context.
+    pub fn lookup(&self, name: &str) -> Result<SrvLookup, HttpError> {
+        let resolver = self.clone();
+        let name = name.to_string();
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| crate::error::network_error(format!("Failed to create DNS runtime: {e}")))?;
+            runtime.block_on(resolver.lookup_async(&name))
+        })
+        .join()
+        .unwrap_or_else(|_| Err(crate::error::network_error("SRV lookup thread panicked")))
+    }
+
+    /// Resolver shared by the lookups made on the connection runtime
+    pub(crate) fn shared(&self) -> Result<TokioResolver, HttpError> {
+        if let Some(resolver) = self.shared.get() {
+            return Ok(resolver.clone());
+        }
+        let resolver = self.build()?;
+        Ok(self.shared.get_or_init(|| resolver).clone())
+    }
+
+    fn build(&self) -> Result<TokioResolver, HttpError> {
+        let builder = match &self.nameservers {
+            Some(nameservers) => {
+                let group: NameServerConfigGroup = nameservers
+                    .iter()
+                    .map(|address| NameServerConfig::new(*address, Protocol::Udp))
+                    .collect::<Vec<_>>()
+                    .into();
+                TokioResolver::builder_with_config(
+                    ResolverConfig::from_parts(None, Vec::new(), group),
+                    TokioConnectionProvider::default(),
+                )
+            }
+            None => TokioResolver::builder_tokio()
+                .map_err(|e| crate::error::network_error(format!("Failed to create DNS resolver: {e}")))?,
+        };
+        Ok(builder.build())
+    }
+
+    async fn lookup_async(&self, name: &str) -> Result<SrvLookup, HttpError> {
+        // Runs on a runtime of its own, which a shared resolver must not outlive
+        let resolver = self.build()?;
+
+        let lookup = resolver
+            .srv_lookup(name)
+            .await
+            .map_err(|e| crate::error::network_error(format!("SRV lookup for {name} failed: {e}")))?;
+        let records = lookup
+            .iter()
+            .map(|srv| SrvRecord {
+                priority: srv.priority(),
+                weight: srv.weight(),
+                port: srv.port(),
+                target: srv.target().to_utf8().trim_end_matches('.').to_string(),
+            })
+            // A single "." target means the service is not available
+            .filter(|record| !record.target.is_empty())
+            .collect::<Vec<_>>();
+        if records.is_empty() {
+            return Err(crate::error::network_error(format!("{name} publishes no SRV targets")));
+        }
+        tracing::debug!(
+            target: "quyc::srv",
+            name = %name,
+            targets = records.len(),
+            "Resolved SRV records"
+        );
+        Ok(SrvLookup {
+            records,
+            valid_until: lookup.as_lookup().valid_until(),
+        })
+    }
+}
+
+/// Endpoint groups created on demand for `srv+` URLs, one per service
+#[derive(Debug, Default)]
+pub(crate) struct SrvGroups {
+    resolver: SrvResolver,
+    groups: Mutex<HashMap<String, Arc<EndpointGroup>>>,
+}
+
+impl SrvGroups {
+    pub(crate) fn new(resolver: SrvResolver) -> Self {
+        Self {
+            resolver,
+            groups: Mutex::default(),
+        }
+    }
+
+    /// Group serving `url`, unless it is not an `srv+` URL
+    pub(crate) fn group_for(&self, url: &Url) -> Result<Option<Arc<EndpointGroup>>, HttpError> {
+        if !url.scheme().starts_with(SRV_SCHEME_PREFIX) {
+            return Ok(None);
+        }
+        let Some(service) = url.host_str() else {
+            return Err(crate::error::url_parse_error(format!("{url} names no SRV service")));
+        };
+        let base = format!("{}://{service}", url.scheme());
+        let mut groups = self.groups.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(group) = groups.get(&base) {
+            return Ok(Some(group.clone()));
+        }
+        let group = Arc::new(EndpointGroup::from_srv(&base, service, self.resolver.clone())?);
+        groups.insert(base, group.clone());
+        Ok(Some(group))
+    }
+}
@@ -99,8 +99,14 @@ impl HttpConfig {
             quic_send_window: Some(2 * 1024 * 1024),    // 2MB send window for large requests
             quic_congestion_bbr: true,                  // BBR for optimal AI provider performance
             tls_early_data: true,                       // Enable 0-RTT for repeat connections
+            tls_key_log_file: None,                     // Key logging only via SSLKEYLOGFILE
+            tls_root_certificates: Vec::new(),          // Only native/bundled roots
+            tls_ech_policy: crate::tls::EchPolicy::Disable, // ECH only when asked for
+            tls_ech_config_list: None,                  // Look up ECH configs in DNS
             h3_max_field_section_size: Some(64 * 1024), // 64KB for large AI headers
             h3_enable_grease: true,                     // Enable grease for future compatibility
+            quic_qlog_dir: None,                        // qlog only via QLOGDIR unless configured
+            quic_qlog_max_bytes: None,                  // Default 64MB cap per qlog file
             
             // Compression level configuration
             gzip_level: Some(6),     // Balanced compression/speed for AI workloads
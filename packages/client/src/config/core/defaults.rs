@@ -61,8 +61,14 @@ impl Default for HttpConfig {
             quic_send_window: Some(512 * 1024),           // 512KB send window
             quic_congestion_bbr: false,                   // Use CUBIC by default for compatibility
             tls_early_data: false,                        // Disabled by default for security
+            tls_key_log_file: None,                       // Key logging only via SSLKEYLOGFILE
+            tls_root_certificates: Vec::new(),            // Only native/bundled roots
+            tls_ech_policy: crate::tls::EchPolicy::Disable, // ECH only when asked for
+            tls_ech_config_list: None,                    // Look up ECH configs in DNS
             h3_max_field_section_size: Some(16 * 1024),   // 16KB header limit
             h3_enable_grease: true,                       // Enable grease for protocol evolution
+            quic_qlog_dir: None,                          // qlog only via QLOGDIR unless configured
+            quic_qlog_max_bytes: None,                    // Default 64MB cap per qlog file
 
             // Compression level defaults - None uses library optimal defaults
             gzip_level: None,     // Use flate2 default (level 6)
@@ -155,6 +155,22 @@ pub struct HttpConfig {
     /// Reduces connection establishment latency for resumed connections
     pub tls_early_data: bool,
 
+    /// File that receives TLS secrets in NSS key log format (off by default)
+    /// `None` still honors the `SSLKEYLOGFILE` environment variable when set
+    pub tls_key_log_file: Option<std::path::PathBuf>,
+
+    /// Additional PEM-encoded root certificates trusted for TLS and QUIC connections
+    /// Used alongside the native or bundled root store, e.g. for private CAs
+    pub tls_root_certificates: Vec<String>,
+
+    /// Encrypted Client Hello policy for TLS connections (off by default)
+    /// Hides the server name from on-path observers when the server supports ECH
+    pub tls_ech_policy: crate::tls::EchPolicy,
+
+    /// `ECHConfigList` offered instead of the one in the server's DNS HTTPS record
+    /// `None` looks the configuration up in DNS while ECH is enabled
+    pub tls_ech_config_list: Option<Vec<u8>>,
+
     /// Maximum HTTP/3 header field section size in bytes
     /// Controls maximum size of HTTP/3 headers to prevent memory exhaustion
     pub h3_max_field_section_size: Option<u64>,
@@ -163,6 +179,14 @@ pub struct HttpConfig {
     /// Sends random grease values to ensure protocol extensibility
     pub h3_enable_grease: bool,
 
+    /// Directory for per-connection qlog traces of QUIC connections
+    /// `None` disables qlog unless the `QLOGDIR` environment variable is set
+    pub quic_qlog_dir: Option<std::path::PathBuf>,
+
+    /// Maximum size of a single qlog file in bytes
+    /// `None` uses the default cap of 64MB
+    pub quic_qlog_max_bytes: Option<u64>,
+
     // ===== Compression Level Configuration =====
     /// Gzip compression level (1-9, None for default)
     /// Higher levels provide better compression at the cost of CPU usage
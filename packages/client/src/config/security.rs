@@ -127,6 +127,91 @@ impl HttpConfig {
         self
     }
 
+    /// Write TLS secrets to a key log file
+    ///
+    /// Records session secrets in the NSS key log format used by Wireshark
+    /// to decrypt packet captures of both TCP/TLS and HTTP/3 connections.
+    ///
+    /// # Security Note
+    /// Anyone with access to the file can decrypt the logged traffic. Only
+    /// enable this while debugging. Without this setting, key logging still
+    /// happens when the `SSLKEYLOGFILE` environment variable is set.
+    ///
+    /// # Arguments
+    /// * `path` - File that receives the key log lines
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use quyc::config::HttpConfig;
+    ///
+    /// let config = HttpConfig::default()
+    ///     .with_tls_key_log_file("/tmp/sslkeys.log");
+    /// assert!(config.tls_key_log_file.is_some());
+    /// ```
+    pub fn with_tls_key_log_file(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.tls_key_log_file = Some(path.into());
+        self
+    }
+
+    /// Trust an additional root certificate
+    ///
+    /// The certificate is trusted for HTTP/2 and HTTP/3 connections in
+    /// addition to the native or bundled root store. Use this for private
+    /// certificate authorities and test servers.
+    ///
+    /// # Arguments
+    /// * `pem` - PEM-encoded CA certificate
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use quyc::config::HttpConfig;
+    ///
+    /// let ca_pem = std::fs::read_to_string("ca.crt").unwrap();
+    /// let config = HttpConfig::default()
+    ///     .with_root_certificate_pem(ca_pem);
+    /// assert_eq!(config.tls_root_certificates.len(), 1);
+    /// ```
+    pub fn with_root_certificate_pem(mut self, pem: impl Into<String>) -> Self {
+        self.tls_root_certificates.push(pem.into());
+        self
+    }
+
+    /// Set the Encrypted Client Hello policy
+    ///
+    /// With ECH the server name is encrypted in the TLS handshake of HTTP/2
+    /// connections. The configuration comes from the server's DNS HTTPS
+    /// record unless set with `with_tls_ech_config_list`.
+    ///
+    /// # Arguments
+    /// * `policy` - `Prefer` falls back to a plain handshake when the server
+    ///   publishes no configuration, `Require` fails the connection instead
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use quyc::config::HttpConfig;
+    /// use quyc::tls::EchPolicy;
+    ///
+    /// let config = HttpConfig::default()
+    ///     .with_tls_ech(EchPolicy::Prefer);
+    /// assert_eq!(config.tls_ech_policy, EchPolicy::Prefer);
+    /// ```
+    pub fn with_tls_ech(mut self, policy: crate::tls::EchPolicy) -> Self {
+        self.tls_ech_policy = policy;
+        self
+    }
+
+    /// Offer this `ECHConfigList` instead of looking it up in DNS
+    ///
+    /// Only used while the ECH policy is `Prefer` or `Require`.
+    ///
+    /// # Arguments
+    /// * `config_list` - Encoded `ECHConfigList`, e.g. from
+    ///   `decode_ech_config_list`
+    pub fn with_tls_ech_config_list(mut self, config_list: impl Into<Vec<u8>>) -> Self {
+        self.tls_ech_config_list = Some(config_list.into());
+        self
+    }
+
     /// Set maximum HTTP/3 header field section size
     ///
     /// Limits the maximum size of HTTP/3 headers to prevent memory
@@ -2,9 +2,11 @@
 //!
 //! Provides builder methods for configuring QUIC connection timeouts and flow control windows.
 
+use std::path::PathBuf;
 use std::time::Duration;
 
 use super::super::core::HttpConfig;
+use crate::protocols::quiche::QlogConfig;
 
 impl HttpConfig {
     /// Set QUIC connection maximum idle timeout
@@ -91,4 +93,61 @@ impl HttpConfig {
         self.quic_send_window = Some(window_size);
         self
     }
+
+    /// Write a qlog trace for every QUIC connection to `dir`
+    ///
+    /// Each connection gets its own JSON-SEQ `.sqlog` file named after the
+    /// origin and source connection ID, which can be opened in qvis. When no
+    /// directory is configured, the `QLOGDIR` environment variable is used.
+    ///
+    /// # Arguments
+    /// * `dir` - Directory that receives the qlog files
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use quyc::config::HttpConfig;
+    ///
+    /// let config = HttpConfig::default()
+    ///     .with_quic_qlog_dir("/tmp/qlog");
+    /// assert!(config.quic_qlog_dir.is_some());
+    /// ```
+    pub fn with_quic_qlog_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.quic_qlog_dir = Some(dir.into());
+        self
+    }
+
+    /// Cap the size of each qlog file
+    ///
+    /// Once a file reaches the cap, further events for that connection are
+    /// dropped. The cap is applied between events so files stay parseable.
+    ///
+    /// # Arguments
+    /// * `max_bytes` - Maximum qlog file size in bytes
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use quyc::config::HttpConfig;
+    ///
+    /// let config = HttpConfig::default()
+    ///     .with_quic_qlog_max_bytes(8 * 1024 * 1024); // 8MB
+    /// assert_eq!(config.quic_qlog_max_bytes, Some(8 * 1024 * 1024));
+    /// ```
+    pub fn with_quic_qlog_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.quic_qlog_max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// qlog settings derived from this configuration
+    ///
+    /// Returns `None` when no qlog directory is configured; connections then
+    /// fall back to the `QLOGDIR` environment variable.
+    pub fn quic_qlog(&self) -> Option<QlogConfig> {
+        self.quic_qlog_dir.as_ref().map(|dir| {
+            let qlog = QlogConfig::new(dir.clone());
+            match self.quic_qlog_max_bytes {
+                Some(max_bytes) => qlog.with_max_bytes(max_bytes),
+                None => qlog,
+            }
+        })
+    }
 }
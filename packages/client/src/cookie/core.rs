@@ -2,19 +2,33 @@
 
 use std::convert::TryInto;
 use std::fmt;
-use std::sync::{Arc, RwLock};
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
 use std::time::SystemTime;
 
-use bytes::Bytes;
 use ystream::prelude::MessageChunk;
 use http::{HeaderValue, header::SET_COOKIE};
 
+use super::persist::{self, CookieFileFormat};
+use super::store::{CookieContext, StoredCookie, unix_now};
+use crate::error::HttpError;
+
 /// Actions for a persistent cookie store providing session support.
 pub trait CookieStore: Send + Sync {
     /// Store a set of Set-Cookie header values received from `url`
     fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &url::Url);
     /// Get any Cookie values in the store for `url`
     fn cookies(&self, url: &url::Url) -> Option<HeaderValue>;
+    /// Get the Cookie values for a request to `url` in `context`
+    ///
+    /// Stores without `SameSite` support can keep this default, which
+    /// ignores the context.
+    fn cookies_in_context(&self, url: &url::Url, context: CookieContext) -> Option<HeaderValue> {
+        let _ = context;
+        self.cookies(url)
+    }
 }
 
 /// A single HTTP cookie.
@@ -51,14 +65,21 @@ impl<'a> Default for Cookie<'a> {
 /// A good default `CookieStore` implementation.
 ///
 /// This is the implementation used when simply calling `cookie_store(true)`.
-/// This type is exposed to allow creating one and filling it with some
-/// existing cookies more easily, before creating a `Client`.
-///
-/// For more advanced scenarios, such as needing to serialize the store or
-/// manipulate it between requests, you may refer to the
-/// [http3_cookie_store crate](https://crates.io/crates/http3_cookie_store).
+/// It applies the RFC 6265 rules of [`StoredCookie`]: domain, path, `Secure`,
+/// `SameSite` and expiry, with cookies for public suffixes refused. A jar
+/// can be filled before creating a `Client`, inspected between requests,
+/// and saved to JSON or Netscape `cookies.txt` files, once or after every
+/// change.
+#[derive(Debug, Default, Clone)]
+pub struct Jar(Arc<RwLock<JarState>>);
+
 #[derive(Debug, Default)]
-pub struct Jar(Arc<RwLock<Option<cookie_store::CookieStore>>>);
+struct JarState {
+    /// Cookies in the order they were first set
+    cookies: Vec<StoredCookie>,
+    /// File rewritten after every change
+    file: Option<(PathBuf, CookieFileFormat)>,
+}
 
 // ===== impl Cookie =====
 
@@ -172,77 +193,252 @@ impl std::error::Error for CookieParseError {}
 // ===== impl Jar =====
 
 impl Jar {
+    /// Create an empty jar
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
     /// Add a cookie to this jar.
     ///
     /// # Example
     ///
     /// ```
-    /// use crate::{cookie::Jar, Url};
+    /// use quyc_client::{cookie::Jar, Url};
     ///
     /// let cookie = "foo=bar; Domain=yolo.local";
-    /// let url = "https://yolo.local".parse::<Url>()?;
+    /// let url = "https://yolo.local".parse::<Url>().unwrap();
     ///
     /// let jar = Jar::default();
     /// jar.add_cookie_str(cookie, &url);
-    ///
-    /// // and now add to a `ClientBuilder`?
+    /// assert_eq!(jar.stored_cookies()[0].value, "bar");
     /// ```
     pub fn add_cookie_str(&self, cookie: &str, url: &url::Url) {
-        let cookies = cookie::Cookie::parse(cookie)
-            .ok()
-            .map(|c| c.into_owned())
-            .into_iter();
-        if let Ok(mut store_guard) = self.0.write() {
-            if let Some(ref mut store) = store_guard.as_mut() {
-                store.store_response_cookies(cookies, url);
+        if let Ok(value) = HeaderValue::from_str(cookie) {
+            self.set_cookies(&mut std::iter::once(&value), url);
+        }
+    }
+
+    /// Store `cookie` as is, replacing the cookie with the same name,
+    /// domain and path
+    pub fn insert(&self, cookie: StoredCookie) {
+        let mut state = self.write();
+        if state.insert(cookie, true, unix_now()) {
+            state.persist();
+        }
+    }
+
+    /// Unexpired cookies, in the order they were first set
+    pub fn stored_cookies(&self) -> Vec<StoredCookie> {
+        let now = unix_now();
+        self.read()
+            .cookies
+            .iter()
+            .filter(|cookie| !cookie.is_expired(now))
+            .cloned()
+            .collect()
+    }
+
+    /// Remove every cookie
+    pub fn clear(&self) {
+        let mut state = self.write();
+        state.cookies.clear();
+        state.persist();
+    }
+
+    /// Unexpired cookies written in `format`
+    pub fn export(&self, format: CookieFileFormat) -> Result<String, HttpError> {
+        persist::encode(&self.stored_cookies(), format)
+    }
+
+    /// Add the cookies in `text`, written in `format`
+    ///
+    /// Returns how many changed the jar; expired ones are skipped.
+    pub fn import(&self, text: &str, format: CookieFileFormat) -> Result<usize, HttpError> {
+        let cookies = persist::decode(text, format)?;
+        let now = unix_now();
+        let mut state = self.write();
+        let stored = cookies
+            .into_iter()
+            .map(|cookie| state.insert(cookie, true, now))
+            .filter(|changed| *changed)
+            .count();
+        if stored > 0 {
+            state.persist();
+        }
+        Ok(stored)
+    }
+
+    /// Jar holding the cookies of the file at `path`
+    pub fn load(path: impl AsRef<Path>, format: CookieFileFormat) -> Result<Self, HttpError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            crate::error::configuration(format!("Failed to read cookie file {}: {e}", path.display()))
+        })?;
+        let jar = Self::new();
+        jar.import(&text, format)?;
+        Ok(jar)
+    }
+
+    /// Write the unexpired cookies to `path`, creating parent directories
+    /// as needed
+    pub fn save(&self, path: impl AsRef<Path>, format: CookieFileFormat) -> Result<(), HttpError> {
+        write_file(path.as_ref(), &self.export(format)?)
+    }
+
+    /// Jar kept in the file at `path`: loaded from it if it exists, and
+    /// written back after every change
+    ///
+    /// Failed writes are logged and do not fail requests.
+    pub fn persistent(path: impl Into<PathBuf>, format: CookieFileFormat) -> Result<Self, HttpError> {
+        let path = path.into();
+        let jar = if path.exists() { Self::load(&path, format)? } else { Self::new() };
+        jar.write().file = Some((path, format));
+        Ok(jar)
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, JarState> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, JarState> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl JarState {
+    /// Store `cookie`, set over a secure channel or not; returns whether
+    /// the jar changed
+    fn insert(&mut self, cookie: StoredCookie, from_secure: bool, now: u64) -> bool {
+        self.cookies.retain(|stored| !stored.is_expired(now));
+        // Insecure origins may not replace secure cookies
+        if !from_secure
+            && self
+                .cookies
+                .iter()
+                .any(|stored| stored.secure && stored.name == cookie.name && stored.domain == cookie.domain)
+        {
+            return false;
+        }
+        let existing = self.cookies.iter().position(|stored| stored.same_key(&cookie));
+        match (existing, cookie.is_expired(now)) {
+            (Some(index), true) => {
+                self.cookies.remove(index);
+                true
+            }
+            // The replacement keeps the original's place, and so its creation order
+            (Some(index), false) => {
+                let changed = self.cookies[index] != cookie;
+                self.cookies[index] = cookie;
+                changed
             }
+            (None, true) => false,
+            (None, false) => {
+                self.cookies.push(cookie);
+                true
+            }
+        }
+    }
+
+    /// `Cookie` header value for a request to `url` in `context`
+    ///
+    /// Cookies with longer paths come first, then older ones.
+    fn header(&self, url: &url::Url, context: CookieContext, now: u64) -> Option<HeaderValue> {
+        let mut cookies: Vec<&StoredCookie> = self
+            .cookies
+            .iter()
+            .filter(|cookie| cookie.matches(url, context, now))
+            .collect();
+        if cookies.is_empty() {
+            return None;
+        }
+        cookies.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+        let value = cookies
+            .iter()
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        HeaderValue::from_str(&value).ok()
+    }
+
+    /// Write the jar to its file, if it has one
+    fn persist(&self) {
+        let Some((path, format)) = &self.file else {
+            return;
+        };
+        let now = unix_now();
+        let cookies: Vec<StoredCookie> = self.cookies.iter().filter(|cookie| !cookie.is_expired(now)).cloned().collect();
+        if let Err(error) = persist::encode(&cookies, *format).and_then(|text| write_file(path, &text)) {
+            tracing::warn!(target: "quyc::cookie", path = %path.display(), error = %error, "Failed to save cookies");
         }
     }
 }
 
-impl Clone for Jar {
-    fn clone(&self) -> Self {
-        Self(Arc::clone(&self.0))
+/// Attempts at finding an unused temporary file name before giving up
+const TEMPORARY_NAME_ATTEMPTS: usize = 16;
+
+/// Replace the file at `path` with `text`, through a temporary file so
+/// readers never see it half written
+///
+/// The temporary file gets a fresh, unpredictable name next to the jar and
+/// is only readable by this user, as is the jar once it is renamed over it.
+fn write_file(path: &Path, text: &str) -> Result<(), HttpError> {
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    std::fs::create_dir_all(parent).map_err(crate::error::configuration)?;
+    let file_name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+
+    for _ in 0..TEMPORARY_NAME_ATTEMPTS {
+        let temporary = parent.join(format!(".{file_name}.{:016x}.tmp", rand::random::<u64>()));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = match options.open(&temporary) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(crate::error::configuration(e)),
+        };
+        let written = file
+            .write_all(text.as_bytes())
+            .and_then(|()| file.sync_all())
+            .and_then(|()| std::fs::rename(&temporary, path));
+        if let Err(e) = written {
+            let _ = std::fs::remove_file(&temporary);
+            return Err(crate::error::configuration(e));
+        }
+        return Ok(());
     }
+    Err(crate::error::configuration(format!(
+        "no unused temporary file name for {}",
+        path.display()
+    )))
 }
 
 impl CookieStore for Jar {
     fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &url::Url) {
-        let cookies: Vec<_> = cookie_headers
-            .filter_map(|val| {
-                cookie::Cookie::parse(val.to_str().unwrap_or(""))
-                    .map(|c| c.into_owned())
-                    .ok()
-            })
-            .collect();
-
-        if let Ok(mut store_guard) = self.0.write() {
-            if let Some(ref mut store) = store_guard.as_mut() {
-                store.store_response_cookies(cookies.into_iter(), url);
+        let now = unix_now();
+        let from_secure = matches!(url.scheme(), "https" | "wss");
+        let mut state = self.write();
+        let mut changed = false;
+        for header in cookie_headers {
+            let cookie = header
+                .to_str()
+                .ok()
+                .and_then(|value| StoredCookie::from_set_cookie(value, url, now));
+            if let Some(cookie) = cookie {
+                changed |= state.insert(cookie, from_secure, now);
             }
         }
+        if changed {
+            state.persist();
+        }
     }
 
     fn cookies(&self, url: &url::Url) -> Option<HeaderValue> {
-        let s = match self.0.read() {
-            Ok(store_guard) => {
-                if let Some(ref store) = store_guard.as_ref() {
-                    store
-                        .get_request_values(url)
-                        .map(|(name, value)| format!("{name}={value}"))
-                        .collect::<Vec<_>>()
-                        .join("; ")
-                } else {
-                    return None;
-                }
-            }
-            Err(_) => return None,
-        };
-
-        if s.is_empty() {
-            return None;
-        }
+        self.cookies_in_context(url, CookieContext::default())
+    }
 
-        HeaderValue::from_maybe_shared(Bytes::from(s)).ok()
+    fn cookies_in_context(&self, url: &url::Url, context: CookieContext) -> Option<HeaderValue> {
+        self.read().header(url, context, unix_now())
     }
 }
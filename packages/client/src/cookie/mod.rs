@@ -5,13 +5,18 @@
 //! - Cookie store implementations
 //! - Cookie header utilities
 //! - RFC 6265 compliant cookie handling
+//! - JSON and Netscape `cookies.txt` persistence
 
 #![allow(dead_code)]
 
 pub mod core;
+pub mod persist;
+pub mod store;
 pub mod utils;
 
 // Re-export all public types and functions
 pub use core::*;
 
+pub use persist::CookieFileFormat;
+pub use store::{CookieContext, SameSite, StoredCookie};
 pub use utils::*;
@@ -0,0 +1,125 @@
+//! Cookie files
+//!
+//! Jars are saved as JSON, or in the Netscape `cookies.txt` format that
+//! curl, wget and browser export extensions read and write. Session cookies
+//! are kept in both formats so a saved login survives a restart; in
+//! `cookies.txt` they carry an expiry of `0`, as curl writes them.
+
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use super::store::StoredCookie;
+use crate::error::HttpError;
+
+/// Version of the JSON cookie file format
+const COOKIE_FILE_VERSION: u32 = 1;
+
+/// Header line curl expects at the top of a `cookies.txt` file
+const NETSCAPE_HEADER: &str = "# Netscape HTTP Cookie File";
+
+/// Line prefix marking `HttpOnly` cookies in `cookies.txt`
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+/// On-disk format of a cookie file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CookieFileFormat {
+    /// JSON document with every cookie attribute, including `SameSite`
+    Json,
+    /// Netscape `cookies.txt`, one tab-separated cookie per line
+    Netscape,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CookieFile {
+    version: u32,
+    cookies: Vec<StoredCookie>,
+}
+
+/// Write `cookies` in `format`
+pub(crate) fn encode(cookies: &[StoredCookie], format: CookieFileFormat) -> Result<String, HttpError> {
+    match format {
+        CookieFileFormat::Json => {
+            let file = CookieFile {
+                version: COOKIE_FILE_VERSION,
+                cookies: cookies.to_vec(),
+            };
+            serde_json::to_string_pretty(&file).map_err(crate::error::serialization_error)
+        }
+        CookieFileFormat::Netscape => Ok(encode_netscape(cookies)),
+    }
+}
+
+/// Read the cookies in `text`, written in `format`
+pub(crate) fn decode(text: &str, format: CookieFileFormat) -> Result<Vec<StoredCookie>, HttpError> {
+    match format {
+        CookieFileFormat::Json => {
+            let file: CookieFile = serde_json::from_str(text).map_err(crate::error::deserialization_error)?;
+            if file.version != COOKIE_FILE_VERSION {
+                return Err(crate::error::deserialization_error(format!(
+                    "unsupported cookie file version {}",
+                    file.version
+                )));
+            }
+            Ok(file.cookies)
+        }
+        CookieFileFormat::Netscape => decode_netscape(text),
+    }
+}
+
+fn encode_netscape(cookies: &[StoredCookie]) -> String {
+    let mut text = format!("{NETSCAPE_HEADER}\n# This file was generated by quyc. Edit at your own risk.\n\n");
+    for cookie in cookies {
+        // Writing to a String cannot fail
+        drop(writeln!(
+            text,
+            "{}{}{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            if cookie.http_only { HTTP_ONLY_PREFIX } else { "" },
+            if cookie.host_only { "" } else { "." },
+            cookie.domain,
+            flag(!cookie.host_only),
+            cookie.path,
+            flag(cookie.secure),
+            cookie.expires.unwrap_or(0),
+            cookie.name,
+            cookie.value,
+        ));
+    }
+    text
+}
+
+fn decode_netscape(text: &str) -> Result<Vec<StoredCookie>, HttpError> {
+    let mut cookies = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        let (line, http_only) = match line.strip_prefix(HTTP_ONLY_PREFIX) {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || crate::error::deserialization_error(format!("invalid cookies.txt line {}", index + 1));
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [domain, include_subdomains, path, secure, expires, name, value] = fields[..] else {
+            return Err(invalid());
+        };
+        let expires: u64 = expires.parse().map_err(|_| invalid())?;
+        cookies.push(StoredCookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            domain: domain.trim_start_matches('.').to_ascii_lowercase(),
+            host_only: !include_subdomains.eq_ignore_ascii_case("TRUE"),
+            path: path.to_string(),
+            secure: secure.eq_ignore_ascii_case("TRUE"),
+            http_only,
+            same_site: None,
+            expires: (expires != 0).then_some(expires),
+        });
+    }
+    Ok(cookies)
+}
+
+fn flag(value: bool) -> &'static str {
+    if value { "TRUE" } else { "FALSE" }
+}
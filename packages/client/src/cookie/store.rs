@@ -0,0 +1,250 @@
+//! RFC 6265 cookie storage rules
+//!
+//! Which `Set-Cookie` headers a [`Jar`](super::Jar) accepts and which stored
+//! cookies go out with a request: domain and path matching, `Secure`,
+//! `SameSite`, expiry and the `__Secure-` and `__Host-` name prefixes.
+//! Cookies set for a public suffix such as `com` or `co.uk` are refused, so
+//! one site cannot plant cookies for every other site under it.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use http::Method;
+use serde::{Deserialize, Serialize};
+use url::{Host, Url};
+
+/// `SameSite` attribute of a stored cookie
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SameSite {
+    /// Sent only while the request chain stays on the cookie's site
+    Strict,
+    /// Also sent on cross-site requests with safe methods
+    Lax,
+    /// Sent on every request; requires `Secure`
+    None,
+}
+
+/// A cookie held in a [`Jar`](super::Jar)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredCookie {
+    /// Cookie name
+    pub name: String,
+    /// Cookie value, as sent
+    pub value: String,
+    /// Domain the cookie belongs to, lowercase and without a leading dot
+    pub domain: String,
+    /// Sent to `domain` only, not to its subdomains
+    pub host_only: bool,
+    /// Path prefix the cookie is sent for
+    pub path: String,
+    /// Sent over `https` only
+    pub secure: bool,
+    /// Hidden from scripts; kept so files written by browsers round-trip
+    pub http_only: bool,
+    /// `SameSite` attribute; a cookie without one is treated as `Lax`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub same_site: Option<SameSite>,
+    /// Expiry in seconds since the Unix epoch, `None` for session cookies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<u64>,
+}
+
+/// Where a request stands relative to the site that started it
+///
+/// A request chain starts on the site of its first URL and turns cross-site
+/// once a redirect leads to another registrable domain. Cross-site requests
+/// carry no `SameSite=Strict` cookies, and carry `Lax` ones only with safe
+/// methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CookieContext {
+    /// The chain has left the site it started on
+    pub cross_site: bool,
+    /// The request method is safe, e.g. `GET` or `HEAD`
+    pub safe_method: bool,
+}
+
+impl CookieContext {
+    /// Context of a request with `method` in a chain that is `cross_site` or not
+    #[inline]
+    pub fn new(method: &Method, cross_site: bool) -> Self {
+        Self {
+            cross_site,
+            safe_method: method.is_safe(),
+        }
+    }
+}
+
+impl Default for CookieContext {
+    /// A same-site request, which carries every matching cookie
+    fn default() -> Self {
+        Self {
+            cross_site: false,
+            safe_method: true,
+        }
+    }
+}
+
+impl StoredCookie {
+    /// Cookie set by a `Set-Cookie` header in a response from `url`, unless
+    /// the rules refuse it
+    ///
+    /// A cookie with an expiry in the past is returned as is; storing it
+    /// deletes the cookie it replaces.
+    pub(crate) fn from_set_cookie(set_cookie: &str, url: &Url, now: u64) -> Option<Self> {
+        let cookie = cookie::Cookie::parse(set_cookie).ok()?;
+        let host = url.host_str()?.to_ascii_lowercase();
+        let is_domain = matches!(url.host(), Some(Host::Domain(_)));
+        if cookie.name().is_empty() {
+            return None;
+        }
+
+        let (domain, host_only) = match cookie.domain().map(|d| d.trim_start_matches('.').to_ascii_lowercase()) {
+            None => (host.clone(), true),
+            Some(domain) if domain.is_empty() => (host.clone(), true),
+            // A public suffix may only name the host itself, and then only for the host
+            Some(domain) if is_domain && is_public_suffix(&domain) => {
+                if domain != host {
+                    tracing::debug!(target: "quyc::cookie", domain = %domain, url = %url, "Refusing cookie for a public suffix");
+                    return None;
+                }
+                (host.clone(), true)
+            }
+            Some(domain) if domain_match(&host, is_domain, &domain) => (domain, false),
+            Some(_) => return None,
+        };
+
+        let path = match cookie.path() {
+            Some(path) if path.starts_with('/') => path.to_string(),
+            _ => default_path(url),
+        };
+        let secure = cookie.secure().unwrap_or(false);
+        // Only secure origins may set secure cookies
+        if secure && !is_secure(url) {
+            return None;
+        }
+        let same_site = cookie.same_site().map(|same_site| match same_site {
+            cookie::SameSite::Strict => SameSite::Strict,
+            cookie::SameSite::Lax => SameSite::Lax,
+            cookie::SameSite::None => SameSite::None,
+        });
+        if same_site == Some(SameSite::None) && !secure {
+            return None;
+        }
+        let name = cookie.name();
+        if has_prefix(name, "__Secure-") && !secure {
+            return None;
+        }
+        if has_prefix(name, "__Host-") && !(secure && host_only && path == "/") {
+            return None;
+        }
+
+        // Max-Age wins over Expires
+        let expires = match (cookie.max_age(), cookie.expires_datetime()) {
+            (Some(max_age), _) => Some(u64::try_from(max_age.whole_seconds()).map_or(0, |secs| now.saturating_add(secs))),
+            (None, Some(expires)) => Some(u64::try_from(expires.unix_timestamp()).unwrap_or(0)),
+            (None, None) => None,
+        };
+
+        Some(Self {
+            name: name.to_string(),
+            value: cookie.value().to_string(),
+            domain,
+            host_only,
+            path,
+            secure,
+            http_only: cookie.http_only().unwrap_or(false),
+            same_site,
+            expires,
+        })
+    }
+
+    /// Whether the cookie has expired at `now`
+    #[inline]
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    /// Whether the cookie goes out with a request to `url` in `context`
+    pub(crate) fn matches(&self, url: &Url, context: CookieContext, now: u64) -> bool {
+        let Some(host) = url.host_str().map(str::to_ascii_lowercase) else {
+            return false;
+        };
+        let domain_ok = if self.host_only {
+            host == self.domain
+        } else {
+            domain_match(&host, matches!(url.host(), Some(Host::Domain(_))), &self.domain)
+        };
+        let same_site_ok = !context.cross_site
+            || match self.same_site {
+                Some(SameSite::Strict) => false,
+                Some(SameSite::Lax) | None => context.safe_method,
+                Some(SameSite::None) => true,
+            };
+        domain_ok
+            && !self.is_expired(now)
+            && path_match(url.path(), &self.path)
+            && (!self.secure || is_secure(url))
+            && same_site_ok
+    }
+
+    /// Whether `other` takes this cookie's place when stored
+    #[inline]
+    pub(crate) fn same_key(&self, other: &StoredCookie) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+}
+
+/// Seconds since the Unix epoch
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Site of `url` for `SameSite`: its registrable domain, or the host itself
+/// for IP addresses and names without one
+pub(crate) fn site(url: &Url) -> Option<String> {
+    let host = url.host_str()?.to_ascii_lowercase();
+    if !matches!(url.host(), Some(Host::Domain(_))) {
+        return Some(host);
+    }
+    Some(psl::domain_str(&host).map_or_else(|| host.clone(), str::to_string))
+}
+
+/// Whether `domain` is a public suffix, e.g. `com`, `co.uk` or `github.io`
+pub(crate) fn is_public_suffix(domain: &str) -> bool {
+    psl::suffix_str(domain) == Some(domain)
+}
+
+/// Whether requests to `url` travel over a secure channel
+fn is_secure(url: &Url) -> bool {
+    matches!(url.scheme(), "https" | "wss")
+}
+
+/// RFC 6265 domain matching; IP addresses only match themselves
+fn domain_match(host: &str, host_is_domain: bool, domain: &str) -> bool {
+    host == domain
+        || (host_is_domain
+            && host.len() > domain.len()
+            && host.ends_with(domain)
+            && host.as_bytes()[host.len() - domain.len() - 1] == b'.')
+}
+
+/// RFC 6265 path matching
+fn path_match(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path.as_bytes()[cookie_path.len()] == b'/'))
+}
+
+/// Path of a cookie set without a `Path`: the request path up to its last `/`
+fn default_path(url: &Url) -> String {
+    match url.path().rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(end) => url.path()[..end].to_string(),
+    }
+}
+
+fn has_prefix(name: &str, prefix: &str) -> bool {
+    name.len() >= prefix.len() && name.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
@@ -1,18 +1,67 @@
 //! Cookie handling utilities
 
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
 
-use http::HeaderMap;
+use http::header::{COOKIE, SET_COOKIE};
+use http::{HeaderMap, HeaderValue};
 
+use super::{CookieContext, CookieStore};
+use crate::http::response::HttpResponse;
+
+/// Cookie store shared by a client and its clones
+#[derive(Clone)]
+pub(crate) struct SharedCookieStore(pub(crate) Arc<dyn CookieStore>);
+
+impl fmt::Debug for SharedCookieStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedCookieStore")
+    }
+}
+
+/// Add the cookies `cookie_store` holds for a request to `url` in `context`
+///
+/// Cookies already on the request keep their values; stored cookies are
+/// appended unless one of the same name is there.
 #[cfg(not(target_arch = "wasm32"))]
 pub(crate) fn add_cookie_header(
     headers: &mut HeaderMap,
-    cookie_store: &dyn super::CookieStore,
+    cookie_store: &dyn CookieStore,
     url: &url::Url,
+    context: CookieContext,
 ) {
-    if let Some(header) = cookie_store.cookies(url) {
-        headers.insert(http::header::COOKIE, header);
+    let Some(stored) = cookie_store.cookies_in_context(url, context) else {
+        return;
+    };
+    let Some(existing) = headers.get(COOKIE).and_then(|value| value.to_str().ok()) else {
+        headers.insert(COOKIE, stored);
+        return;
+    };
+    let explicit = parse_cookie(existing);
+    let added: Vec<&str> = stored
+        .to_str()
+        .unwrap_or_default()
+        .split("; ")
+        .filter(|pair| pair.split_once('=').is_none_or(|(name, _)| !explicit.contains_key(name.trim())))
+        .collect();
+    if added.is_empty() {
+        return;
     }
+    if let Ok(value) = HeaderValue::from_str(&format!("{existing}; {}", added.join("; "))) {
+        headers.insert(COOKIE, value);
+    }
+}
+
+/// Store the `Set-Cookie` headers of `response` from `url` as they stream
+/// through
+pub(crate) fn capture_cookies(cookie_store: Arc<dyn CookieStore>, response: HttpResponse, url: url::Url) -> HttpResponse {
+    response.map_headers(move |header| {
+        if header.name == SET_COOKIE {
+            cookie_store.set_cookies(&mut std::iter::once(&header.value), &url);
+        }
+        Some(header)
+    })
 }
 
 /// Format cookie key-value pairs into a cookie header string
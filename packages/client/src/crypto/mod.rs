@@ -2,7 +2,7 @@
 //!
 //! This module provides cryptographic functionality including:
 //! - Random number generation
-//! - Boundary and nonce generation
+//! - Boundary, nonce and UUID generation
 //! - Security-related utilities
 
 pub mod random;
@@ -95,3 +95,49 @@ pub fn generate_boundary() -> String {
 pub fn generate_nonce() -> String {
     format!("{:016x}", fast_random())
 }
+
+/// Generate a random (version 4) UUID in its hyphenated form
+pub fn generate_uuid_v4() -> String {
+    let high = fast_random();
+    let low = fast_random();
+    format_uuid(
+        (high & 0xffff_ffff_ffff_0fff) | 0x4000,
+        (low & 0x3fff_ffff_ffff_ffff) | 0x8000_0000_0000_0000,
+    )
+}
+
+/// Generate a time-ordered (version 7) UUID in its hyphenated form
+///
+/// The leading 48 bits are the Unix time in milliseconds, so keys sort by
+/// creation time.
+pub fn generate_uuid_v7() -> String {
+    let random = fast_random();
+    format_uuid(
+        (unix_millis() << 16) | 0x7000 | (random & 0x0fff),
+        (fast_random() & 0x3fff_ffff_ffff_ffff) | 0x8000_0000_0000_0000,
+    )
+}
+
+fn format_uuid(high: u64, low: u64) -> String {
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xffff,
+        high & 0xffff,
+        low >> 48,
+        low & 0xffff_ffff_ffff
+    )
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn unix_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+        & 0xffff_ffff_ffff
+}
+
+#[cfg(target_arch = "wasm32")]
+fn unix_millis() -> u64 {
+    (js_sys::Date::now() as u64) & 0xffff_ffff_ffff
+}
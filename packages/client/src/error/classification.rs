@@ -29,6 +29,9 @@ impl Error {
 
     /// Returns true if the error is related to a timeout.
     pub fn is_timeout(&self) -> bool {
+        if matches!(self.inner.kind, Kind::Timeout) {
+            return true;
+        }
         let mut source = self.source();
 
         while let Some(err) = source {
@@ -57,19 +60,9 @@ impl Error {
         matches!(self.inner.kind, Kind::Request)
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
     /// Returns true if the error is related to connect
     pub fn is_connect(&self) -> bool {
-        let mut source = self.source();
-
-        while let Some(err) = source {
-            // Note: Removed legacy client error check since we use pure AsyncStream architecture
-            // Connection errors will be handled by other error types
-
-            source = err.source();
-        }
-
-        false
+        matches!(self.inner.kind, Kind::Connect)
     }
 
     /// Returns true if the error is related to the request or response body
@@ -82,6 +75,16 @@ impl Error {
         matches!(self.inner.kind, Kind::Decode)
     }
 
+    /// Returns true if the request was rejected by an open circuit breaker
+    pub fn is_circuit_open(&self) -> bool {
+        matches!(self.inner.kind, Kind::CircuitOpen)
+    }
+
+    /// Returns true if the client rate limiter refused to queue the request
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self.inner.kind, Kind::RateLimited)
+    }
+
     /// Returns the status code, if the error was generated from a response.
     pub fn status(&self) -> Option<crate::StatusCode> {
         match self.inner.kind {
@@ -93,3 +96,35 @@ impl Error {
         }
     }
 }
+
+/// Kind of an I/O failure on a connection to a server
+///
+/// Refused, reset and aborted connections are `Connect` errors, timeouts are
+/// `Timeout` errors. Anything else, such as a rejected certificate, is a
+/// `Request` error.
+pub(crate) fn io_error_kind(error: &io::Error) -> Kind {
+    match error.kind() {
+        io::ErrorKind::TimedOut => Kind::Timeout,
+        io::ErrorKind::ConnectionRefused
+        | io::ErrorKind::ConnectionReset
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::NotConnected
+        | io::ErrorKind::BrokenPipe => Kind::Connect,
+        _ => Kind::Request,
+    }
+}
+
+/// Kind of an HTTP/2 failure
+///
+/// A `GOAWAY` or `REFUSED_STREAM` from the server means it did not process
+/// the request, so both are `Connect` errors, as are I/O failures that
+/// [`io_error_kind`] counts as such.
+pub(crate) fn h2_error_kind(error: &h2::Error) -> Kind {
+    if let Some(io) = error.get_io() {
+        return io_error_kind(io);
+    }
+    if (error.is_go_away() && error.is_remote()) || error.reason() == Some(h2::Reason::REFUSED_STREAM) {
+        return Kind::Connect;
+    }
+    Kind::Request
+}
@@ -136,3 +136,13 @@ pub fn security_error<E: Into<BoxError>>(e: E) -> Error {
 pub fn connector_error<E: Into<BoxError>>(e: E) -> Error {
     Error::new(Kind::Connect).with(e.into())
 }
+
+/// Creates an `Error` for a request rejected by an open circuit breaker.
+pub fn circuit_open<E: Into<BoxError>>(e: E) -> Error {
+    Error::new(Kind::CircuitOpen).with(e.into())
+}
+
+/// Creates an `Error` for a request the client rate limiter would delay too long.
+pub fn rate_limited<E: Into<BoxError>>(e: E) -> Error {
+    Error::new(Kind::RateLimited).with(e.into())
+}
@@ -46,6 +46,10 @@ pub enum Kind {
     PayloadTooLarge,
     /// Stream processing error
     Stream,
+    /// Request rejected by an open circuit breaker
+    CircuitOpen,
+    /// Request would wait longer than allowed for the client rate limiter
+    RateLimited,
 }
 
 impl Error {
@@ -110,6 +114,8 @@ impl fmt::Display for Error {
             Kind::Timeout => f.write_str("request timeout"),
             Kind::PayloadTooLarge => f.write_str("payload too large"),
             Kind::Stream => f.write_str("stream processing error"),
+            Kind::CircuitOpen => f.write_str("circuit breaker open"),
+            Kind::RateLimited => f.write_str("client rate limit exceeded"),
             #[cfg(target_arch = "wasm32")]
             Kind::Status(ref code) => {
                 let prefix = if code.is_client_error() {
@@ -0,0 +1,471 @@
+//! HAR 1.2 export of client traffic
+//!
+//! A [`HarRecorder`] attached to an [`HttpClient`](crate::HttpClient)
+//! captures every request/response pair the client executes: headers,
+//! bodies up to a size cap, protocol version, server address and phase
+//! timings (DNS, connect, TLS, send, wait, receive). The log can be written
+//! on demand with [`HarRecorder::write`], or automatically when the recorder
+//! is dropped together with the last client holding it.
+//!
+//! Sensitive headers and query parameters are redacted using the same
+//! defaults as cassettes (see [`Redaction`]).
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use quyc_client::HttpClient;
+//! use quyc_client::har::HarRecorder;
+//!
+//! let recorder = Arc::new(HarRecorder::new().max_body_size(64 * 1024));
+//! let client = HttpClient::default().with_har_recorder(recorder.clone());
+//! // ... run requests ...
+//! recorder.write("traffic.har")?;
+//! # Ok::<(), quyc_client::HttpError>(())
+//! ```
+
+pub mod model;
+
+pub use model::*;
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use http::{HeaderName, StatusCode, Version};
+use ystream::{AsyncStream, emit, spawn_task};
+
+use crate::cassette::{REDACTED, Redaction};
+use crate::error::HttpError;
+use crate::http::request::HttpRequest;
+use crate::http::response::{HttpBodyChunk, HttpHeader, HttpResponse};
+use crate::telemetry::ConnectionTimingsHandle;
+
+/// Default cap on captured body bytes per request and response
+pub const DEFAULT_MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// Records client traffic as HAR 1.2 entries
+#[derive(Debug)]
+pub struct HarRecorder {
+    save_path: Option<PathBuf>,
+    max_body_size: usize,
+    redaction: Redaction,
+    entries: Mutex<Vec<HarEntry>>,
+}
+
+impl Default for HarRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HarRecorder {
+    /// Recorder with a 1 MiB body cap and default redaction
+    pub fn new() -> Self {
+        Self {
+            save_path: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            redaction: Redaction::default(),
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Write the log to `path` when the recorder is dropped
+    pub fn save_on_drop(mut self, path: impl AsRef<Path>) -> Self {
+        self.save_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Cap captured request and response bodies at `bytes`
+    ///
+    /// Sizes are still reported in full; only the stored text is truncated.
+    pub fn max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = bytes;
+        self
+    }
+
+    /// Redact an additional header
+    pub fn redact_header(mut self, name: HeaderName) -> Self {
+        self.redaction.headers.push(name);
+        self
+    }
+
+    /// Redact the value of a URL query parameter
+    pub fn redact_query_param(mut self, name: impl Into<String>) -> Self {
+        self.redaction.query_params.push(name.into());
+        self
+    }
+
+    /// Replace the redaction settings, including the default header list
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Snapshot of the entries recorded so far, in start order
+    pub fn entries(&self) -> Vec<HarEntry> {
+        let mut entries = self.entries.lock().map(|e| e.clone()).unwrap_or_default();
+        entries.sort_by(|a, b| a.started_date_time.cmp(&b.started_date_time));
+        entries
+    }
+
+    /// Number of completed entries
+    pub fn len(&self) -> usize {
+        self.entries.lock().map(|e| e.len()).unwrap_or(0)
+    }
+
+    /// Whether no entries have completed yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget all recorded entries
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    /// Build the HAR document
+    pub fn to_har(&self) -> Har {
+        Har {
+            log: HarLog {
+                version: "1.2".to_string(),
+                creator: HarCreator {
+                    name: "quyc".to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                },
+                entries: self.entries(),
+            },
+        }
+    }
+
+    /// Write the HAR document to `path`, creating parent directories as needed
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), HttpError> {
+        let path = path.as_ref();
+        let json = serde_json::to_vec_pretty(&self.to_har()).map_err(crate::error::serialization_error)?;
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(crate::error::configuration)?;
+        }
+        std::fs::write(path, json).map_err(crate::error::configuration)
+    }
+
+    /// Record `response` to `request` as it streams to the caller
+    ///
+    /// `started` is when the client began executing the request. The entry
+    /// is added once the header, body and trailer streams have all ended.
+    pub(crate) fn record(
+        self: &Arc<Self>,
+        request: HarRequest,
+        started: Instant,
+        mut response: HttpResponse,
+    ) -> HttpResponse {
+        let (headers_tx, headers_rx) = AsyncStream::<HttpHeader, 256>::channel();
+        let (body_tx, body_rx) = AsyncStream::<HttpBodyChunk, 1024>::channel();
+        let (trailers_tx, trailers_rx) = AsyncStream::<HttpHeader, 64>::channel();
+        let (headers, body, trailers) = response.swap_streams(headers_rx, body_rx, trailers_rx);
+
+        let pending = Arc::new(PendingEntry {
+            recorder: self.clone(),
+            started,
+            started_at: chrono::Utc::now(),
+            request,
+            status: response.status(),
+            version: response.version,
+            timings: response.connection_timings_handle(),
+            state: Mutex::new(ResponseCapture::default()),
+            remaining: AtomicUsize::new(3),
+        });
+
+        let headers_pending = pending.clone();
+        spawn_task(move || {
+            for header in headers {
+                headers_pending.update(|c| {
+                    c.first_byte.get_or_insert_with(Instant::now);
+                    c.headers.push((
+                        header.name.as_str().to_string(),
+                        String::from_utf8_lossy(header.value.as_bytes()).into_owned(),
+                    ));
+                });
+                emit!(headers_tx, header);
+            }
+            headers_pending.finish();
+        });
+
+        let body_pending = pending.clone();
+        let max_body_size = self.max_body_size;
+        spawn_task(move || {
+            for chunk in body {
+                body_pending.update(|c| {
+                    c.first_byte.get_or_insert_with(Instant::now);
+                    c.body_size += chunk.data.len();
+                    let room = max_body_size.saturating_sub(c.body.len());
+                    c.body.extend_from_slice(&chunk.data[..chunk.data.len().min(room)]);
+                });
+                emit!(body_tx, chunk);
+            }
+            body_pending.finish();
+        });
+
+        spawn_task(move || {
+            for trailer in trailers {
+                emit!(trailers_tx, trailer);
+            }
+            pending.finish();
+        });
+
+        response
+    }
+
+    /// Redacted HAR view of a request as it is about to be sent
+    pub(crate) fn har_request(&self, request: &HttpRequest) -> HarRequest {
+        let mut headers = crate::cassette::interaction::header_pairs(request.headers());
+        self.redaction.headers(&mut headers);
+        let url = self.redaction.url(request.url().as_str());
+        let query_string = url::Url::parse(&url)
+            .map(|u| {
+                u.query_pairs()
+                    .map(|(name, value)| HarNameValue { name: name.into_owned(), value: value.into_owned() })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let body = request.body().and_then(|body| body.to_bytes());
+        let body_size = match (request.body(), &body) {
+            (None, _) => 0,
+            (Some(_), Some(bytes)) => bytes.len() as i64,
+            (Some(_), None) => -1,
+        };
+        let mime_type = header_value(&headers, "content-type").unwrap_or_default();
+        let post_data = body.filter(|b| !b.is_empty()).map(|bytes| {
+            let (text, _, comment) = self.capture_text(&bytes, bytes.len());
+            HarPostData { mime_type: mime_type.clone(), text, comment }
+        });
+
+        HarRequest {
+            method: request.method().as_str().to_string(),
+            url,
+            http_version: version_name(request.version()),
+            cookies: request_cookies(&headers),
+            headers: name_values(headers),
+            query_string,
+            post_data,
+            headers_size: -1,
+            body_size,
+        }
+    }
+
+    /// Captured body as HAR text: UTF-8 as is, binary as base64, truncated to the cap
+    fn capture_text(&self, captured: &[u8], full_size: usize) -> (String, Option<String>, Option<String>) {
+        let truncated = full_size > captured.len();
+        let comment = truncated.then(|| {
+            format!("Body truncated to {} of {} bytes", captured.len(), full_size)
+        });
+        // A truncated UTF-8 body may end mid character; keep the valid prefix
+        let text = match std::str::from_utf8(captured) {
+            Ok(text) => Some(text),
+            Err(e) if truncated && e.error_len().is_none() => std::str::from_utf8(&captured[..e.valid_up_to()]).ok(),
+            Err(_) => None,
+        };
+        match text {
+            Some(text) => (text.to_string(), None, comment),
+            None => (
+                base64::engine::general_purpose::STANDARD.encode(captured),
+                Some("base64".to_string()),
+                comment,
+            ),
+        }
+    }
+
+    fn add(&self, entry: HarEntry) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push(entry);
+        }
+    }
+}
+
+impl Drop for HarRecorder {
+    fn drop(&mut self) {
+        let Some(path) = self.save_path.take() else {
+            return;
+        };
+        if let Err(e) = self.write(&path) {
+            tracing::warn!(
+                target: "quyc::har",
+                error = %e,
+                path = %path.display(),
+                "Failed to write HAR file"
+            );
+        }
+    }
+}
+
+/// Response data gathered while the streams drain
+#[derive(Debug, Default)]
+struct ResponseCapture {
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    body_size: usize,
+    first_byte: Option<Instant>,
+}
+
+/// An entry being recorded
+struct PendingEntry {
+    recorder: Arc<HarRecorder>,
+    started: Instant,
+    started_at: chrono::DateTime<chrono::Utc>,
+    request: HarRequest,
+    status: u16,
+    version: Version,
+    timings: ConnectionTimingsHandle,
+    state: Mutex<ResponseCapture>,
+    /// Streams still being forwarded
+    remaining: AtomicUsize,
+}
+
+impl PendingEntry {
+    fn update(&self, f: impl FnOnce(&mut ResponseCapture)) {
+        if let Ok(mut capture) = self.state.lock() {
+            f(&mut capture);
+        }
+    }
+
+    /// Mark one stream as done; the last one adds the entry
+    fn finish(&self) {
+        if self.remaining.fetch_sub(1, Ordering::AcqRel) != 1 {
+            return;
+        }
+        let ended = Instant::now();
+        let Ok(mut capture) = self.state.lock() else {
+            return;
+        };
+        let capture = std::mem::take(&mut *capture);
+        let recorder = &self.recorder;
+
+        let mut headers = capture.headers;
+        recorder.redaction.headers(&mut headers);
+
+        let connection = self.timings.read().ok().and_then(|t| *t);
+        let dns = connection.and_then(|c| c.dns);
+        let tcp = connection.and_then(|c| c.connect);
+        let tls = connection.and_then(|c| c.tls);
+        let setup = connection.map(|c| c.total()).unwrap_or_default();
+
+        let total = ended.duration_since(self.started);
+        let first_byte = capture.first_byte.unwrap_or(ended);
+        let receive = ended.duration_since(first_byte);
+        let wait = total.saturating_sub(receive).saturating_sub(setup);
+
+        // HAR counts the TLS handshake inside `connect` and reports it again as `ssl`
+        let connect = match (tcp, tls) {
+            (None, None) => None,
+            (tcp, tls) => Some(tcp.unwrap_or_default() + tls.unwrap_or_default()),
+        };
+        let timings = HarTimings {
+            blocked: -1.0,
+            dns: millis_or_unknown(dns),
+            connect: millis_or_unknown(connect),
+            send: 0.0,
+            wait: millis(wait),
+            receive: millis(receive),
+            ssl: millis_or_unknown(tls),
+        };
+        let time = [timings.dns, timings.connect, timings.send, timings.wait, timings.receive]
+            .into_iter()
+            .filter(|t| *t >= 0.0)
+            .sum();
+
+        let mime_type = header_value(&headers, "content-type").unwrap_or_default();
+        let content = if capture.body_size == 0 {
+            HarContent { size: 0, mime_type, text: None, encoding: None, comment: None }
+        } else {
+            let (text, encoding, comment) = recorder.capture_text(&capture.body, capture.body_size);
+            HarContent {
+                size: capture.body_size as i64,
+                mime_type,
+                text: Some(text),
+                encoding,
+                comment,
+            }
+        };
+
+        let status = StatusCode::from_u16(self.status).ok();
+        let response = HarResponse {
+            status: self.status,
+            status_text: status
+                .and_then(|s| s.canonical_reason())
+                .unwrap_or_default()
+                .to_string(),
+            http_version: version_name(self.version),
+            cookies: response_cookies(&headers),
+            redirect_url: header_value(&headers, "location").unwrap_or_default(),
+            headers: name_values(headers),
+            content,
+            headers_size: -1,
+            body_size: capture.body_size as i64,
+        };
+
+        recorder.add(HarEntry {
+            started_date_time: self
+                .started_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            time,
+            request: self.request.clone(),
+            response,
+            cache: HarCache::default(),
+            timings,
+            server_ip_address: connection.and_then(|c| c.remote_addr).map(|a| a.ip().to_string()),
+            connection: connection.and_then(|c| c.remote_addr).map(|a| a.port().to_string()),
+        });
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn millis_or_unknown(duration: Option<Duration>) -> f64 {
+    duration.map_or(-1.0, millis)
+}
+
+fn version_name(version: Version) -> String {
+    format!("{version:?}")
+}
+
+fn header_value(headers: &[(String, String)], name: &str) -> Option<String> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.clone())
+}
+
+fn name_values(pairs: Vec<(String, String)>) -> Vec<HarNameValue> {
+    pairs
+        .into_iter()
+        .map(|(name, value)| HarNameValue { name, value })
+        .collect()
+}
+
+/// Cookies from `Cookie` request headers that were not redacted
+fn request_cookies(headers: &[(String, String)]) -> Vec<HarCookie> {
+    headers
+        .iter()
+        .filter(|(name, value)| name.eq_ignore_ascii_case("cookie") && value != REDACTED)
+        .flat_map(|(_, value)| value.split(';'))
+        .filter_map(parse_cookie_pair)
+        .collect()
+}
+
+/// Cookies from `Set-Cookie` response headers that were not redacted
+fn response_cookies(headers: &[(String, String)]) -> Vec<HarCookie> {
+    headers
+        .iter()
+        .filter(|(name, value)| name.eq_ignore_ascii_case("set-cookie") && value != REDACTED)
+        .filter_map(|(_, value)| value.split(';').next().and_then(parse_cookie_pair))
+        .collect()
+}
+
+fn parse_cookie_pair(pair: &str) -> Option<HarCookie> {
+    let (name, value) = pair.trim().split_once('=')?;
+    Some(HarCookie { name: name.trim().to_string(), value: value.trim().to_string() })
+}
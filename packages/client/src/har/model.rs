@@ -0,0 +1,181 @@
+//! HAR 1.2 document model
+//!
+//! Field names follow the [HAR 1.2 spec](http://www.softwareishard.com/blog/har-12-spec/);
+//! times are milliseconds and `-1` marks a value that does not apply or was
+//! not observed.
+
+use serde::{Deserialize, Serialize};
+
+/// Top-level HAR document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Har {
+    /// The log
+    pub log: HarLog,
+}
+
+/// HAR log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarLog {
+    /// Format version, always `1.2`
+    pub version: String,
+    /// Application that created the log
+    pub creator: HarCreator,
+    /// Recorded requests in start order
+    pub entries: Vec<HarEntry>,
+}
+
+/// Creator application
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarCreator {
+    /// Application name
+    pub name: String,
+    /// Application version
+    pub version: String,
+}
+
+/// One request/response exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarEntry {
+    /// Request start, ISO 8601
+    pub started_date_time: String,
+    /// Total elapsed time in milliseconds
+    pub time: f64,
+    /// Request details
+    pub request: HarRequest,
+    /// Response details
+    pub response: HarResponse,
+    /// Cache usage; always empty
+    pub cache: HarCache,
+    /// Phase timings
+    pub timings: HarTimings,
+    /// Server IP address
+    #[serde(rename = "serverIPAddress", skip_serializing_if = "Option::is_none")]
+    pub server_ip_address: Option<String>,
+    /// Server port, used as connection identifier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection: Option<String>,
+}
+
+/// Request details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarRequest {
+    /// Request method
+    pub method: String,
+    /// Absolute URL
+    pub url: String,
+    /// Protocol version, e.g. `HTTP/2.0`
+    pub http_version: String,
+    /// Cookies sent
+    pub cookies: Vec<HarCookie>,
+    /// Headers sent
+    pub headers: Vec<HarNameValue>,
+    /// Parsed query string
+    pub query_string: Vec<HarNameValue>,
+    /// Posted data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<HarPostData>,
+    /// Header bytes; `-1` when unknown
+    pub headers_size: i64,
+    /// Body bytes; `-1` when unknown
+    pub body_size: i64,
+}
+
+/// Response details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarResponse {
+    /// Status code
+    pub status: u16,
+    /// Reason phrase
+    pub status_text: String,
+    /// Protocol version, e.g. `HTTP/2.0`
+    pub http_version: String,
+    /// Cookies received
+    pub cookies: Vec<HarCookie>,
+    /// Headers received
+    pub headers: Vec<HarNameValue>,
+    /// Body content
+    pub content: HarContent,
+    /// `Location` header value, or empty
+    #[serde(rename = "redirectURL")]
+    pub redirect_url: String,
+    /// Header bytes; `-1` when unknown
+    pub headers_size: i64,
+    /// Body bytes received
+    pub body_size: i64,
+}
+
+/// Name/value pair (headers, query parameters)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HarNameValue {
+    /// Name
+    pub name: String,
+    /// Value
+    pub value: String,
+}
+
+/// Cookie
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarCookie {
+    /// Cookie name
+    pub name: String,
+    /// Cookie value
+    pub value: String,
+}
+
+/// Posted request body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarPostData {
+    /// Content type
+    pub mime_type: String,
+    /// Body text, truncated to the recorder's size cap
+    pub text: String,
+    /// Note about truncation or encoding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// Response body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarContent {
+    /// Full body size in bytes
+    pub size: i64,
+    /// Content type
+    pub mime_type: String,
+    /// Body text, truncated to the recorder's size cap
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// `base64` for binary bodies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    /// Note about truncation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// Cache details (unused)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HarCache {}
+
+/// Phase timings in milliseconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarTimings {
+    /// Time queued before the request started; not observed
+    pub blocked: f64,
+    /// Name resolution
+    pub dns: f64,
+    /// Transport connect, including TLS
+    pub connect: f64,
+    /// Sending the request
+    pub send: f64,
+    /// Waiting for the first response byte
+    pub wait: f64,
+    /// Reading the response
+    pub receive: f64,
+    /// TLS handshake
+    pub ssl: f64,
+}
@@ -0,0 +1,658 @@
+//! Conversion between `HttpRequest` and curl command lines
+//!
+//! [`HttpRequest::to_curl`] renders a request as a copy-pasteable curl
+//! command; [`HttpRequest::from_curl`] parses the subset of curl flags that
+//! API documentation typically uses.
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, Version};
+use url::Url;
+
+use super::request::{HttpRequest, MultipartField, MultipartValue, RequestAuth, RequestBody};
+use crate::error::HttpError;
+
+impl HttpRequest {
+    /// Render this request as a curl command line
+    ///
+    /// Emits the method, headers, authentication, body, protocol version
+    /// (`--http2`/`--http3`), `--compressed`, redirect following and the
+    /// request timeout. Multipart file fields refer to their filename, since
+    /// the bytes only exist in memory; streaming bodies are omitted.
+    pub fn to_curl(&self) -> String {
+        let mut args: Vec<String> = vec!["curl".to_string()];
+        let body = self.body();
+
+        match (self.method(), body.is_some()) {
+            (&Method::HEAD, _) => args.push("--head".to_string()),
+            (&Method::GET, false) | (&Method::POST, true) => {}
+            (method, _) => {
+                args.push("-X".to_string());
+                args.push(method.as_str().to_string());
+            }
+        }
+
+        match self.version() {
+            Version::HTTP_2 => args.push("--http2".to_string()),
+            Version::HTTP_3 => args.push("--http3".to_string()),
+            _ => {}
+        }
+        if self.compress {
+            args.push("--compressed".to_string());
+        }
+        if self.follow_redirects {
+            args.push("-L".to_string());
+            args.push("--max-redirs".to_string());
+            args.push(self.max_redirects.to_string());
+        }
+        if let Some(timeout) = self.timeout() {
+            args.push("--max-time".to_string());
+            args.push(format_seconds(timeout));
+        }
+
+        for (name, value) in self.headers() {
+            args.push("-H".to_string());
+            args.push(quote(&format!("{}: {}", name, String::from_utf8_lossy(value.as_bytes()))));
+        }
+        if let Some(user_agent) = &self.user_agent {
+            if !self.headers().contains_key(http::header::USER_AGENT) {
+                args.push("-A".to_string());
+                args.push(quote(user_agent));
+            }
+        }
+        if let Some(referer) = &self.referer {
+            if !self.headers().contains_key(http::header::REFERER) {
+                args.push("-e".to_string());
+                args.push(quote(referer));
+            }
+        }
+
+        // Basic credentials stay readable as `-u`; anything else is sent as headers
+        if let Some(RequestAuth::Basic { username, password }) = &self.auth {
+            args.push("-u".to_string());
+            args.push(quote(&format!("{username}:{password}")));
+        } else {
+            for (name, value) in &self.auth_headers() {
+                args.push("-H".to_string());
+                args.push(quote(&format!("{}: {}", name, String::from_utf8_lossy(value.as_bytes()))));
+            }
+        }
+
+        match body {
+            Some(RequestBody::Bytes(bytes)) => {
+                // --data-binary would read a leading '@' as a file name
+                let flag = if bytes.starts_with(b"@") { "--data-raw" } else { "--data-binary" };
+                args.push(flag.to_string());
+                args.push(quote_bytes(bytes));
+            }
+            Some(RequestBody::Text(text)) => {
+                args.push("--data-raw".to_string());
+                args.push(quote(text));
+            }
+            Some(RequestBody::Json(json)) => {
+                if !self.headers().contains_key(http::header::CONTENT_TYPE) {
+                    args.push("-H".to_string());
+                    args.push(quote("Content-Type: application/json"));
+                }
+                args.push("--data-raw".to_string());
+                args.push(quote(&json.to_string()));
+            }
+            Some(RequestBody::Form(form)) => {
+                let mut pairs: Vec<_> = form.iter().collect();
+                pairs.sort();
+                for (name, value) in pairs {
+                    args.push("--data-urlencode".to_string());
+                    args.push(quote(&format!("{name}={value}")));
+                }
+            }
+            Some(RequestBody::Multipart(fields)) => {
+                for field in fields {
+                    args.push("-F".to_string());
+                    args.push(quote(&form_field_arg(field)));
+                }
+            }
+            Some(RequestBody::Stream(_)) | None => {}
+        }
+
+        args.push(quote(self.url().as_str()));
+        args.join(" ")
+    }
+
+    /// Parse a curl command line into a request
+    ///
+    /// Understands `-X`, `-H`, `-d`/`--data`/`--data-raw`/`--data-binary`
+    /// (including `@file`), `--data-urlencode`, `-F`/`--form-string`, `-u`,
+    /// `--oauth2-bearer`, `--json`, `-G`, `--url`, `-A`, `-e`, `-b`,
+    /// `--compressed`, `--http2`/`--http3`, `-L`, `--max-redirs`, `-m` and
+    /// `-I`. Output, verbosity and TLS verification flags are accepted and
+    /// ignored; any other flag is an error. As in curl, redirects are not
+    /// followed and responses are not decompressed unless requested.
+    pub fn from_curl(command: &str) -> Result<HttpRequest, HttpError> {
+        CurlCommand::parse(command)?.into_request()
+    }
+}
+
+/// Flags without a value that do not affect the request
+const IGNORED_FLAGS: &[&str] = &[
+    "-s", "--silent", "-S", "--show-error", "-v", "--verbose", "-k", "--insecure", "-i",
+    "--include", "-f", "--fail", "--fail-with-body", "-#", "--progress-bar", "-N", "--no-buffer",
+    "-O", "--remote-name", "-J", "--remote-header-name", "--globoff", "-g",
+];
+
+/// Flags with a value that do not affect the request
+const IGNORED_VALUE_FLAGS: &[&str] = &[
+    "-o", "--output", "-w", "--write-out", "--connect-timeout", "--retry", "--retry-delay",
+    "--retry-max-time", "-D", "--dump-header", "--trace", "--trace-ascii", "-c", "--cookie-jar",
+];
+
+/// Flags that take a value, used to split combined short flags like `-XPOST`
+const SHORT_VALUE_FLAGS: &str = "XHdFuAebmowDc";
+
+/// Parsed curl invocation, before it is turned into a request
+#[derive(Debug, Default)]
+struct CurlCommand {
+    method: Option<Method>,
+    url: Option<String>,
+    headers: Vec<(String, String)>,
+    data: Vec<Bytes>,
+    form: Vec<MultipartField>,
+    user: Option<String>,
+    bearer: Option<String>,
+    get: bool,
+    head: bool,
+    json: bool,
+    compressed: bool,
+    location: bool,
+    max_redirs: Option<u32>,
+    max_time: Option<Duration>,
+    version: Option<Version>,
+    user_agent: Option<String>,
+    referer: Option<String>,
+    cookies: Vec<String>,
+}
+
+impl CurlCommand {
+    fn parse(command: &str) -> Result<Self, HttpError> {
+        let mut tokens = split_shell_words(command)?.into_iter().peekable();
+        if tokens
+            .peek()
+            .is_some_and(|t| t.as_slice() == b"curl" || t.ends_with(b"/curl"))
+        {
+            tokens.next();
+        }
+
+        let mut parsed = Self::default();
+        while let Some(token) = tokens.next() {
+            let token = String::from_utf8_lossy(&token).into_owned();
+            let (flag, attached) = split_flag(&token);
+            // Values stay as bytes so `$'\xff'` bodies survive; most flags want text
+            let mut raw = |name: &str| -> Result<Vec<u8>, HttpError> {
+                match &attached {
+                    Some(value) => Ok(value.clone().into_bytes()),
+                    None => tokens
+                        .next()
+                        .ok_or_else(|| crate::error::builder(format!("curl flag {name} requires a value"))),
+                }
+            };
+            let mut value = |name: &str| raw(name).map(|v| String::from_utf8_lossy(&v).into_owned());
+
+            match flag.as_str() {
+                "-X" | "--request" => {
+                    let method = value(&flag)?;
+                    parsed.method = Some(
+                        Method::from_bytes(method.as_bytes())
+                            .map_err(|e| crate::error::builder(format!("Invalid method {method}: {e}")))?,
+                    );
+                }
+                "-H" | "--header" => {
+                    let header = value(&flag)?;
+                    let (name, value) = header
+                        .split_once(':')
+                        .ok_or_else(|| crate::error::builder(format!("Invalid header {header:?}")))?;
+                    parsed.headers.push((name.trim().to_string(), value.trim().to_string()));
+                }
+                "-d" | "--data" | "--data-ascii" => {
+                    let data = value(&flag)?;
+                    parsed.data.push(read_data(data.into_bytes(), true)?);
+                }
+                "--data-binary" => {
+                    let data = raw(&flag)?;
+                    parsed.data.push(read_data(data, false)?);
+                }
+                "--data-raw" => parsed.data.push(Bytes::from(raw(&flag)?)),
+                "--data-urlencode" => parsed.data.push(Bytes::from(url_encode_data(&value(&flag)?)?)),
+                "--json" => {
+                    let data = value(&flag)?;
+                    parsed.data.push(read_data(data.into_bytes(), false)?);
+                    parsed.json = true;
+                }
+                "-F" | "--form" => parsed.form.push(parse_form_field(&value(&flag)?, false)?),
+                "--form-string" => parsed.form.push(parse_form_field(&value(&flag)?, true)?),
+                "-u" | "--user" => parsed.user = Some(value(&flag)?),
+                "--oauth2-bearer" => parsed.bearer = Some(value(&flag)?),
+                "-G" | "--get" => parsed.get = true,
+                "-I" | "--head" => parsed.head = true,
+                "--url" => parsed.url = Some(value(&flag)?),
+                "-A" | "--user-agent" => parsed.user_agent = Some(value(&flag)?),
+                "-e" | "--referer" => parsed.referer = Some(value(&flag)?),
+                "-b" | "--cookie" => parsed.cookies.push(value(&flag)?),
+                "--compressed" => parsed.compressed = true,
+                "-L" | "--location" => parsed.location = true,
+                "--max-redirs" => {
+                    let max = value(&flag)?;
+                    parsed.max_redirs = Some(
+                        max.parse()
+                            .map_err(|_| crate::error::builder(format!("Invalid --max-redirs {max}")))?,
+                    );
+                }
+                "-m" | "--max-time" => {
+                    let seconds = value(&flag)?;
+                    let seconds: f64 = seconds
+                        .parse()
+                        .map_err(|_| crate::error::builder(format!("Invalid --max-time {seconds}")))?;
+                    parsed.max_time = Duration::try_from_secs_f64(seconds).ok();
+                }
+                "--http2" | "--http2-prior-knowledge" => parsed.version = Some(Version::HTTP_2),
+                "--http3" | "--http3-only" => parsed.version = Some(Version::HTTP_3),
+                "--http1.1" => parsed.version = Some(Version::HTTP_11),
+                "--http1.0" | "-0" => parsed.version = Some(Version::HTTP_10),
+                flag if IGNORED_FLAGS.contains(&flag) => {}
+                flag if IGNORED_VALUE_FLAGS.contains(&flag) => {
+                    value(flag)?;
+                }
+                flag if flag.starts_with('-') && flag.len() > 1 => {
+                    return Err(crate::error::builder(format!("Unsupported curl flag {flag}")));
+                }
+                _ => {
+                    if parsed.url.is_some() {
+                        return Err(crate::error::builder(format!("Unexpected curl argument {token:?}")));
+                    }
+                    parsed.url = Some(token.clone());
+                }
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn into_request(self) -> Result<HttpRequest, HttpError> {
+        let url = self
+            .url
+            .ok_or_else(|| crate::error::builder("curl command has no URL"))?;
+        // curl assumes http:// when the scheme is missing
+        let url = if url.contains("://") { url } else { format!("http://{url}") };
+        let mut url = Url::parse(&url).map_err(crate::error::url_parse_error)?;
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(crate::error::invalid_header)?;
+            let value = HeaderValue::from_str(value).map_err(crate::error::invalid_header)?;
+            headers.append(name, value);
+        }
+        if !self.cookies.is_empty() {
+            let cookie = HeaderValue::from_str(&self.cookies.join("; ")).map_err(crate::error::invalid_header)?;
+            headers.append(http::header::COOKIE, cookie);
+        }
+        if self.json {
+            headers
+                .entry(http::header::CONTENT_TYPE)
+                .or_insert(HeaderValue::from_static("application/json"));
+            headers
+                .entry(http::header::ACCEPT)
+                .or_insert(HeaderValue::from_static("application/json"));
+        }
+
+        // Multiple data arguments are joined with '&', as curl does
+        let data = match self.data.as_slice() {
+            [] => None,
+            [single] => Some(single.clone()),
+            many => Some(Bytes::from(many.join(&b'&'))),
+        };
+
+        let mut method = self.method;
+        let body = if self.get {
+            if let Some(data) = data {
+                let query = String::from_utf8_lossy(&data);
+                let combined = match url.query() {
+                    Some(existing) if !existing.is_empty() => format!("{existing}&{query}"),
+                    _ => query.into_owned(),
+                };
+                url.set_query(Some(&combined));
+            }
+            method.get_or_insert(Method::GET);
+            None
+        } else if !self.form.is_empty() {
+            method.get_or_insert(Method::POST);
+            Some(RequestBody::Multipart(self.form))
+        } else if let Some(data) = data {
+            if !self.json && !headers.contains_key(http::header::CONTENT_TYPE) {
+                headers.insert(
+                    http::header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/x-www-form-urlencoded"),
+                );
+            }
+            method.get_or_insert(Method::POST);
+            Some(match String::from_utf8(data.to_vec()) {
+                Ok(text) => RequestBody::Text(text),
+                Err(_) => RequestBody::Bytes(data),
+            })
+        } else {
+            None
+        };
+        let method = match (self.head, method) {
+            (true, _) => Method::HEAD,
+            (false, Some(method)) => method,
+            (false, None) => Method::GET,
+        };
+
+        let mut request = HttpRequest::new(method, url, Some(headers), body, self.max_time)
+            .compress(self.compressed)
+            .follow_redirects(self.location);
+        if let Some(max) = self.max_redirs {
+            request = request.max_redirects(max);
+        }
+        if let Some(version) = self.version {
+            request = request.with_version(version);
+        }
+        if let Some(user_agent) = self.user_agent {
+            request = request.user_agent(user_agent);
+        }
+        if let Some(referer) = self.referer {
+            request = request.referer(referer);
+        }
+        if let Some(user) = self.user {
+            let (username, password) = user.split_once(':').unwrap_or((user.as_str(), ""));
+            request = request.basic_auth(username, password);
+        } else if let Some(token) = self.bearer {
+            request = request.bearer_auth(token);
+        }
+        Ok(request)
+    }
+}
+
+/// Split `--flag=value` and attached short values like `-XPOST`
+fn split_flag(token: &str) -> (String, Option<String>) {
+    if let Some(long) = token.strip_prefix("--") {
+        return match long.split_once('=') {
+            Some((name, value)) => (format!("--{name}"), Some(value.to_string())),
+            None => (token.to_string(), None),
+        };
+    }
+    let mut chars = token.chars();
+    if chars.next() == Some('-') {
+        if let Some(short) = chars.next() {
+            let rest: String = chars.collect();
+            if !rest.is_empty() && SHORT_VALUE_FLAGS.contains(short) {
+                return (format!("-{short}"), Some(rest));
+            }
+        }
+    }
+    (token.to_string(), None)
+}
+
+/// Resolve `@file` data; `-d` strips newlines from files as curl does
+fn read_data(data: Vec<u8>, strip_newlines: bool) -> Result<Bytes, HttpError> {
+    let Some(path) = data.strip_prefix(b"@") else {
+        return Ok(Bytes::from(data));
+    };
+    let path = String::from_utf8_lossy(path);
+    let contents = std::fs::read(path)
+        .map_err(|e| crate::error::builder(format!("Failed to read curl data file {path}: {e}")))?;
+    Ok(if strip_newlines {
+        Bytes::from(contents.into_iter().filter(|b| *b != b'\n' && *b != b'\r').collect::<Vec<_>>())
+    } else {
+        Bytes::from(contents)
+    })
+}
+
+/// `--data-urlencode` forms: `content`, `=content`, `name=content`, `@file`, `name@file`
+fn url_encode_data(data: &str) -> Result<String, HttpError> {
+    let encode = |s: &str| urlencoding::encode(s).into_owned();
+    if let Some((name, content)) = data.split_once('=') {
+        return Ok(if name.is_empty() {
+            encode(content)
+        } else {
+            format!("{name}={}", encode(content))
+        });
+    }
+    if let Some((name, path)) = data.split_once('@') {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| crate::error::builder(format!("Failed to read curl data file {path}: {e}")))?;
+        return Ok(if name.is_empty() {
+            encode(&contents)
+        } else {
+            format!("{name}={}", encode(&contents))
+        });
+    }
+    Ok(encode(data))
+}
+
+/// Parse `-F name=value`, `name=@file;type=...;filename=...` or `name=<file`
+fn parse_form_field(spec: &str, literal: bool) -> Result<MultipartField, HttpError> {
+    let (name, rest) = spec
+        .split_once('=')
+        .ok_or_else(|| crate::error::builder(format!("Invalid form field {spec:?}")))?;
+
+    if literal {
+        return Ok(MultipartField {
+            name: name.to_string(),
+            value: MultipartValue::Text(rest.to_string()),
+            content_type: None,
+            filename: None,
+        });
+    }
+
+    let mut parts = rest.split(';');
+    let value = parts.next().unwrap_or_default();
+    let mut content_type = None;
+    let mut filename = None;
+    for attribute in parts {
+        match attribute.trim().split_once('=') {
+            Some(("type", ty)) => content_type = Some(ty.to_string()),
+            Some(("filename", name)) => filename = Some(name.trim_matches('"').to_string()),
+            _ => {}
+        }
+    }
+
+    let read = |path: &str| {
+        std::fs::read(path)
+            .map(Bytes::from)
+            .map_err(|e| crate::error::builder(format!("Failed to read form file {path}: {e}")))
+    };
+
+    let value = if let Some(path) = value.strip_prefix('@') {
+        filename.get_or_insert_with(|| {
+            std::path::Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.to_string())
+        });
+        MultipartValue::Bytes(read(path)?)
+    } else if let Some(path) = value.strip_prefix('<') {
+        MultipartValue::Text(String::from_utf8_lossy(&read(path)?).into_owned())
+    } else {
+        MultipartValue::Text(value.to_string())
+    };
+
+    Ok(MultipartField {
+        name: name.to_string(),
+        value,
+        content_type,
+        filename,
+    })
+}
+
+/// Render a multipart field as a `-F` argument
+fn form_field_arg(field: &MultipartField) -> String {
+    let mut arg = match (&field.filename, &field.value) {
+        (Some(filename), _) => format!("{}=@{}", field.name, filename),
+        (None, MultipartValue::Text(text)) => format!("{}={}", field.name, text),
+        (None, MultipartValue::Bytes(bytes)) => {
+            format!("{}={}", field.name, String::from_utf8_lossy(bytes))
+        }
+    };
+    if let Some(content_type) = field.content_type.as_deref().filter(|ct| {
+        // Text fields default to text/plain; only spell out other types
+        field.filename.is_some() || *ct != "text/plain"
+    }) {
+        arg.push_str(";type=");
+        arg.push_str(content_type);
+    }
+    arg
+}
+
+fn format_seconds(duration: Duration) -> String {
+    if duration.subsec_nanos() == 0 {
+        duration.as_secs().to_string()
+    } else {
+        format!("{}", duration.as_secs_f64())
+    }
+}
+
+/// Quote for POSIX shells, leaving simple words bare
+fn quote(value: &str) -> String {
+    let safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+    if safe {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+/// Quote raw bytes, using ANSI-C `$'...'` quoting when they are not UTF-8
+fn quote_bytes(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => quote(text),
+        Err(_) => {
+            let mut quoted = String::from("$'");
+            for &byte in bytes {
+                match byte {
+                    b'\'' => quoted.push_str(r"\'"),
+                    b'\\' => quoted.push_str(r"\\"),
+                    0x20..=0x7e => quoted.push(byte as char),
+                    _ => quoted.push_str(&format!("\\x{byte:02x}")),
+                }
+            }
+            quoted.push('\'');
+            quoted
+        }
+    }
+}
+
+/// Split a command line into words with POSIX shell quoting rules
+///
+/// Handles single and double quotes, `$'...'` ANSI-C strings, backslash
+/// escapes and line continuations (`\` followed by a newline).
+fn split_shell_words(command: &str) -> Result<Vec<Vec<u8>>, HttpError> {
+    let mut words = Vec::new();
+    let mut current: Vec<u8> = Vec::new();
+    let mut in_word = false;
+    let mut chars = command.chars().peekable();
+
+    let push_char = |buf: &mut Vec<u8>, c: char| {
+        let mut tmp = [0u8; 4];
+        buf.extend_from_slice(c.encode_utf8(&mut tmp).as_bytes());
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(escaped) => {
+                    push_char(&mut current, escaped);
+                    in_word = true;
+                }
+                None => return Err(crate::error::builder("Trailing backslash in curl command")),
+            },
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => push_char(&mut current, c),
+                        None => return Err(crate::error::builder("Unterminated single quote in curl command")),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => push_char(&mut current, c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                current.push(b'\\');
+                                push_char(&mut current, c);
+                            }
+                            None => return Err(crate::error::builder("Unterminated double quote in curl command")),
+                        },
+                        Some(c) => push_char(&mut current, c),
+                        None => return Err(crate::error::builder("Unterminated double quote in curl command")),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                in_word = true;
+                parse_ansi_c(&mut chars, &mut current)?;
+            }
+            c => {
+                push_char(&mut current, c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+/// Body of a `$'...'` string, after the opening quote
+fn parse_ansi_c(
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    out: &mut Vec<u8>,
+) -> Result<(), HttpError> {
+    loop {
+        match chars.next() {
+            Some('\'') => return Ok(()),
+            Some('\\') => match chars.next() {
+                Some('n') => out.push(b'\n'),
+                Some('r') => out.push(b'\r'),
+                Some('t') => out.push(b'\t'),
+                Some('0') => out.push(0),
+                Some('x') => {
+                    let hex: String = (0..2).filter_map(|_| chars.next_if(|c| c.is_ascii_hexdigit())).collect();
+                    let byte = u8::from_str_radix(&hex, 16)
+                        .map_err(|_| crate::error::builder("Invalid \\x escape in curl command"))?;
+                    out.push(byte);
+                }
+                Some(c) => {
+                    let mut tmp = [0u8; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut tmp).as_bytes());
+                }
+                None => break,
+            },
+            Some(c) => {
+                let mut tmp = [0u8; 4];
+                out.extend_from_slice(c.encode_utf8(&mut tmp).as_bytes());
+            }
+            None => break,
+        }
+    }
+    Err(crate::error::builder("Unterminated $'...' string in curl command"))
+}
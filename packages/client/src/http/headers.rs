@@ -320,3 +320,16 @@ pub fn add_compression_headers(headers: &mut HeaderMap, config: &crate::config::
         }
     }
 }
+
+/// Parse a `Retry-After` value into the time left to wait
+///
+/// Accepts delay-seconds and HTTP-dates; dates in the past yield zero.
+pub fn parse_retry_after(value: &str) -> Option<std::time::Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(std::time::Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(delay.to_std().unwrap_or_default())
+}
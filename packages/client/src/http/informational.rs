@@ -0,0 +1,252 @@
+//! Informational (1xx) responses
+//!
+//! Interim responses such as 100 Continue, 102 Processing and 103 Early Hints
+//! arrive before the final response on the same request. They are surfaced on
+//! `HttpResponse` as a separate stream that ends once the final header block
+//! has been received. Early Hints carry `Link` headers that callers can use to
+//! preconnect or prefetch before the final response is available.
+//!
+//! Over HTTP/2 the stream always ends empty: the `h2` crate consumes interim
+//! header blocks and only hands the final response to the client. Only the
+//! HTTP/3 and quiche strategies surface them.
+
+use std::time::Instant;
+
+use http::{HeaderMap, StatusCode};
+
+/// A single interim response received before the final response
+#[derive(Debug, Clone)]
+pub struct InformationalResponse {
+    /// 1xx status code
+    pub status: StatusCode,
+
+    /// Headers of the interim header block
+    pub headers: HeaderMap,
+
+    /// Timestamp when the interim response was received
+    pub timestamp: Instant,
+
+    /// Why the stream failed, set only on error values made by `bad_chunk`
+    pub error: Option<String>,
+}
+
+impl InformationalResponse {
+    /// Create an interim response
+    pub fn new(status: StatusCode, headers: HeaderMap) -> Self {
+        Self {
+            status,
+            headers,
+            timestamp: Instant::now(),
+            error: None,
+        }
+    }
+
+    /// Check if this is `100 Continue`
+    #[inline]
+    pub fn is_continue(&self) -> bool {
+        self.status == StatusCode::CONTINUE
+    }
+
+    /// Check if this is `102 Processing`
+    #[inline]
+    pub fn is_processing(&self) -> bool {
+        self.status == StatusCode::PROCESSING
+    }
+
+    /// Check if this is `103 Early Hints`
+    #[inline]
+    pub fn is_early_hints(&self) -> bool {
+        self.status.as_u16() == 103
+    }
+
+    /// All links from the `Link` headers of this response
+    pub fn links(&self) -> Vec<LinkHint> {
+        self.headers
+            .get_all(http::header::LINK)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(parse_link_header)
+            .collect()
+    }
+
+    /// Links with `rel=preload` or `rel=modulepreload`
+    pub fn preloads(&self) -> Vec<LinkHint> {
+        self.links().into_iter().filter(LinkHint::is_preload).collect()
+    }
+
+    /// Links with `rel=preconnect` or `rel=dns-prefetch`
+    pub fn preconnects(&self) -> Vec<LinkHint> {
+        self.links().into_iter().filter(LinkHint::is_preconnect).collect()
+    }
+}
+
+impl Default for InformationalResponse {
+    fn default() -> Self {
+        Self::new(StatusCode::CONTINUE, HeaderMap::new())
+    }
+}
+
+impl ystream::prelude::MessageChunk for InformationalResponse {
+    /// Error value with a 500 status, so it never passes for an interim response
+    #[inline]
+    fn bad_chunk(error_message: String) -> Self {
+        Self {
+            error: Some(error_message),
+            ..Self::new(StatusCode::INTERNAL_SERVER_ERROR, HeaderMap::new())
+        }
+    }
+
+    #[inline]
+    fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+/// One link from a `Link` header (RFC 8288)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkHint {
+    /// Target URI as written in the header, possibly relative
+    pub uri: String,
+
+    /// Link relation types, lowercased
+    pub rel: Vec<String>,
+
+    /// Remaining target attributes in header order, names lowercased
+    pub params: Vec<(String, Option<String>)>,
+}
+
+impl LinkHint {
+    /// Check if the link has the given relation type
+    pub fn has_rel(&self, rel: &str) -> bool {
+        self.rel.iter().any(|r| r.eq_ignore_ascii_case(rel))
+    }
+
+    /// Check if the link asks for the target to be preloaded
+    pub fn is_preload(&self) -> bool {
+        self.has_rel("preload") || self.has_rel("modulepreload")
+    }
+
+    /// Check if the link asks for a connection to the target origin
+    pub fn is_preconnect(&self) -> bool {
+        self.has_rel("preconnect") || self.has_rel("dns-prefetch")
+    }
+
+    /// Value of a target attribute; `Some("")` for attributes without a value
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_deref().unwrap_or(""))
+    }
+
+    /// Destination of a preload (`as` attribute), e.g. `style` or `script`
+    pub fn as_type(&self) -> Option<&str> {
+        self.param("as")
+    }
+
+    /// MIME type hint (`type` attribute)
+    pub fn content_type(&self) -> Option<&str> {
+        self.param("type")
+    }
+
+    /// CORS mode (`crossorigin` attribute); `Some("")` means anonymous
+    pub fn crossorigin(&self) -> Option<&str> {
+        self.param("crossorigin")
+    }
+}
+
+/// Parse a `Link` header value into its links
+///
+/// Malformed entries are skipped; commas and semicolons inside the URI
+/// reference or quoted parameter values are handled.
+pub fn parse_link_header(value: &str) -> Vec<LinkHint> {
+    split_unquoted(value, ',')
+        .into_iter()
+        .filter_map(parse_link_value)
+        .collect()
+}
+
+fn parse_link_value(entry: &str) -> Option<LinkHint> {
+    let entry = entry.trim();
+    let rest = entry.strip_prefix('<')?;
+    let end = rest.find('>')?;
+    let uri = rest[..end].trim().to_string();
+
+    let mut rel = Vec::new();
+    let mut params = Vec::new();
+
+    for param in split_unquoted(&rest[end + 1..], ';') {
+        let param = param.trim();
+        if param.is_empty() {
+            continue;
+        }
+
+        let (name, value) = match param.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(unquote(value.trim()))),
+            None => (param, None),
+        };
+        let name = name.to_ascii_lowercase();
+
+        if name == "rel" {
+            // Only the first rel attribute counts (RFC 8288, section 3.3)
+            if rel.is_empty() {
+                if let Some(value) = &value {
+                    rel.extend(value.split_ascii_whitespace().map(str::to_ascii_lowercase));
+                }
+            }
+        } else {
+            params.push((name, value));
+        }
+    }
+
+    Some(LinkHint { uri, rel, params })
+}
+
+/// Split on `separator` outside of `<...>` and double-quoted strings
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_uri = false;
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for (index, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes => escaped = true,
+            '"' if !in_uri => in_quotes = !in_quotes,
+            '<' if !in_quotes => in_uri = true,
+            '>' if !in_quotes => in_uri = false,
+            c if c == separator && !in_uri && !in_quotes => {
+                parts.push(&value[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) => {
+            let mut out = String::with_capacity(inner.len());
+            let mut chars = inner.chars();
+            while let Some(c) = chars.next() {
+                if c == '\\' {
+                    if let Some(next) = chars.next() {
+                        out.push(next);
+                    }
+                } else {
+                    out.push(c);
+                }
+            }
+            out
+        }
+        None => value.to_string(),
+    }
+}
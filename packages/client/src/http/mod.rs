@@ -6,8 +6,10 @@
 
 pub mod compression;
 pub mod conversions;
+pub mod curl;
 pub mod escape;
 pub mod headers;
+pub mod informational;
 pub mod into_url;
 pub mod request;
 pub mod resolver;
@@ -17,6 +19,7 @@ pub mod url;
 pub use conversions::*;
 pub use escape::*;
 pub use headers::*;
+pub use informational::*;
 pub use into_url::*;
 pub use request::*;
 pub use response::*;
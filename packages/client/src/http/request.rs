@@ -13,6 +13,7 @@ use url::Url;
 
 use crate::prelude::*;
 use crate::protocols::core::HttpMethod;
+use crate::retry::policy::IDEMPOTENCY_KEY;
 
 
 /// HTTP request structure with comprehensive functionality
@@ -53,6 +54,14 @@ pub struct HttpRequest {
     pub h2_prior_knowledge: bool,
     pub h3_alt_svc: bool,
 
+    /// Send an `Idempotency-Key` generated once for the whole request, so
+    /// non-idempotent methods can be retried and hedged
+    pub auto_idempotency_key: bool,
+
+    /// Redirected away from the origin of the first hop; the client's
+    /// request signer leaves such hops unsigned
+    pub(crate) left_first_origin: bool,
+
     /// Internal error state for deferred error handling
     error: Option<String>,
 }
@@ -179,6 +188,19 @@ impl RequestBody {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Body as it is sent on the wire, when it is known up front
+    ///
+    /// Returns `None` for multipart and streaming bodies.
+    pub fn to_bytes(&self) -> Option<Bytes> {
+        match self {
+            RequestBody::Bytes(bytes) => Some(bytes.clone()),
+            RequestBody::Text(text) => Some(Bytes::from(text.clone())),
+            RequestBody::Json(json) => serde_json::to_vec(json).ok().map(Bytes::from),
+            RequestBody::Form(form) => serde_urlencoded::to_string(form).ok().map(Bytes::from),
+            RequestBody::Multipart(_) | RequestBody::Stream(_) => None,
+        }
+    }
 }
 
 /// Multipart form field
@@ -336,6 +358,8 @@ impl HttpRequest {
             referer: None,
             h2_prior_knowledge: false,
             h3_alt_svc: true,
+            auto_idempotency_key: false,
+            left_first_origin: false,
             error: None,
         }
     }
@@ -524,6 +548,15 @@ impl HttpRequest {
         self.retry_attempts
     }
 
+    /// Whether the method is idempotent, so sending the request twice is safe
+    #[inline]
+    pub fn is_idempotent(&self) -> bool {
+        matches!(
+            self.method,
+            Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE | Method::PUT | Method::DELETE
+        )
+    }
+
     /// Get HTTP version
     #[inline]
     pub fn version(&self) -> Version {
@@ -632,6 +665,13 @@ impl HttpRequest {
         self
     }
 
+    /// Remove the body
+    #[inline]
+    pub fn without_body(mut self) -> Self {
+        self.body = None;
+        self
+    }
+
     /// Set timeout
     #[inline]
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
@@ -646,6 +686,37 @@ impl HttpRequest {
         self
     }
 
+    /// Set the `Idempotency-Key` header, making the request safe to retry
+    #[inline]
+    pub fn idempotency_key<K: Into<String>>(self, key: K) -> Self {
+        self.header(IDEMPOTENCY_KEY, key.into())
+    }
+
+    /// Enable/disable an automatically generated `Idempotency-Key`
+    ///
+    /// A UUIDv7 key is generated when the request is sent, unless one is
+    /// already set, and is reused by every retry and hedged attempt. Only
+    /// methods that are not idempotent, like `POST` and `PATCH`, get a key.
+    #[inline]
+    pub fn auto_idempotency_key(mut self, enable: bool) -> Self {
+        self.auto_idempotency_key = enable;
+        self
+    }
+
+    /// Generate the automatic `Idempotency-Key` if it is enabled and missing
+    ///
+    /// Returns the key the request carries, if any. Calling this again keeps
+    /// the key, so clones made afterwards share it.
+    pub fn ensure_idempotency_key(&mut self) -> Option<&str> {
+        if self.auto_idempotency_key && !self.is_idempotent() && !self.headers.contains_key(IDEMPOTENCY_KEY) {
+            let key = crate::crypto::random::generate_uuid_v7();
+            if let Ok(value) = HeaderValue::from_str(&key) {
+                self.headers.insert(IDEMPOTENCY_KEY, value);
+            }
+        }
+        self.headers.get(IDEMPOTENCY_KEY).and_then(|value| value.to_str().ok())
+    }
+
     /// Enable/disable CORS
     #[inline]
     pub fn cors(mut self, enable: bool) -> Self {
@@ -688,6 +759,41 @@ impl HttpRequest {
         self
     }
 
+    /// Headers the configured authentication is sent as
+    ///
+    /// Empty without authentication; credentials that are not valid header
+    /// values are left out.
+    pub fn auth_headers(&self) -> HeaderMap {
+        self.try_auth_headers().unwrap_or_default()
+    }
+
+    /// Headers the configured authentication is sent as, failing on
+    /// credentials that are not valid header values
+    pub fn try_auth_headers(&self) -> Result<HeaderMap, crate::error::HttpError> {
+        let mut headers = HeaderMap::new();
+        let mut insert = |name: HeaderName, value: &str| -> Result<(), crate::error::HttpError> {
+            let value = HeaderValue::from_str(value).map_err(crate::error::invalid_header)?;
+            headers.insert(name, value);
+            Ok(())
+        };
+        match &self.auth {
+            Some(RequestAuth::Basic { username, password }) => {
+                use base64::Engine;
+                let credentials =
+                    base64::engine::general_purpose::STANDARD.encode(format!("{username}:{password}"));
+                insert(http::header::AUTHORIZATION, &format!("Basic {credentials}"))?;
+            }
+            Some(RequestAuth::Bearer(token)) => insert(http::header::AUTHORIZATION, &format!("Bearer {token}"))?,
+            Some(RequestAuth::ApiKey { key, value }) => {
+                let name = HeaderName::from_bytes(key.as_bytes()).map_err(crate::error::invalid_header)?;
+                insert(name, value)?;
+            }
+            Some(RequestAuth::Custom(custom)) => headers.extend(custom.clone()),
+            None => {}
+        }
+        Ok(headers)
+    }
+
     /// Set bearer token authentication
     #[inline]
     pub fn bearer_auth<T: Into<String>>(mut self, token: T) -> Self {
@@ -12,6 +12,8 @@ use bytes::Bytes;
 use ystream::AsyncStream;
 use http::{HeaderMap, HeaderName, HeaderValue, StatusCode, Version};
 
+use super::informational::InformationalResponse;
+
 /// HTTP response with component-level streaming
 ///
 /// This is the CANONICAL HttpResponse implementation that exposes each HTTP
@@ -19,7 +21,9 @@ use http::{HeaderMap, HeaderName, HeaderValue, StatusCode, Version};
 /// the body arrives, and enabling constant-memory processing of large responses.
 pub struct HttpResponse {
     /// HTTP status code - set once, read many times (atomic, 0 = not yet received)
-    status: AtomicU16,
+    ///
+    /// Shared so the status can be published after the response is handed out.
+    status: std::sync::Arc<AtomicU16>,
 
     /// Headers stream - internal implementation detail
     headers_internal: AsyncStream<HttpHeader, 256>,
@@ -47,6 +51,18 @@ pub struct HttpResponse {
     
     /// Cached body bytes collected from the stream
     cached_body: RwLock<Option<Vec<u8>>>,
+
+    /// QUIC transport statistics of the connection that served this response
+    quic_stats: crate::telemetry::QuicStatsHandle,
+
+    /// Interim 1xx responses received before the final header block
+    informational_internal: AsyncStream<InformationalResponse, 16>,
+
+    /// DNS, connect and TLS timings of the connection that served this response
+    connection_timings: crate::telemetry::ConnectionTimingsHandle,
+
+    /// Redirects followed on the way to this response
+    redirects: crate::redirect::RedirectHistoryHandle,
 }
 
 /// HTTP status information
@@ -98,6 +114,9 @@ pub enum HttpChunk {
     
     /// Error occurred during streaming
     Error(String),
+
+    /// Transport failure of the given kind, such as a refused connection
+    Failure(crate::error::Kind, String),
     
     /// End of stream marker
     #[default]
@@ -164,6 +183,10 @@ pub struct HttpBodyChunk {
 
     /// Timestamp when chunk was received
     pub timestamp: Instant,
+
+    /// Kind of the transport failure this final chunk reports; `data` holds
+    /// its message
+    pub failure: Option<crate::error::Kind>,
 }
 
 impl HttpBodyChunk {
@@ -191,13 +214,17 @@ impl HttpChunk {
     pub fn data(&self) -> Option<&Bytes> {
         match self {
             HttpChunk::Body(data) | HttpChunk::Data(data) | HttpChunk::Chunk(data) => Some(data),
-            HttpChunk::Headers(_, _) | HttpChunk::Trailers(_) | HttpChunk::Error(_) | HttpChunk::End => None,
+            HttpChunk::Headers(_, _)
+            | HttpChunk::Trailers(_)
+            | HttpChunk::Error(_)
+            | HttpChunk::Failure(_, _)
+            | HttpChunk::End => None,
         }
     }
     
     /// Check if this is an error chunk
     pub fn is_error(&self) -> bool {
-        matches!(self, HttpChunk::Error(_))
+        matches!(self, HttpChunk::Error(_) | HttpChunk::Failure(_, _))
     }
     
     /// Check if this is the end marker
@@ -215,7 +242,7 @@ impl ystream::prelude::MessageChunk for HttpChunk {
     #[inline]
     fn error(&self) -> Option<&str> {
         match self {
-            HttpChunk::Error(msg) => Some(msg.as_str()),
+            HttpChunk::Error(msg) | HttpChunk::Failure(_, msg) => Some(msg.as_str()),
             _ => None,
         }
     }
@@ -270,7 +297,7 @@ impl HttpResponse {
         stream_id: u64,
     ) -> Self {
         Self {
-            status: AtomicU16::new(0),
+            status: std::sync::Arc::new(AtomicU16::new(0)),
             headers_internal: headers_stream,
             body_internal: body_stream,
             trailers_internal: trailers_stream,
@@ -280,6 +307,10 @@ impl HttpResponse {
             cached_last_modified: once_cell::sync::OnceCell::new(),
             cached_headers: RwLock::new(None),
             cached_body: RwLock::new(None),
+            quic_stats: std::sync::Arc::new(RwLock::new(None)),
+            informational_internal: AsyncStream::channel().1,
+            connection_timings: std::sync::Arc::new(RwLock::new(None)),
+            redirects: std::sync::Arc::new(RwLock::new(Vec::new())),
         }
     }
 
@@ -303,6 +334,43 @@ impl HttpResponse {
     pub(crate) fn set_status(&self, status: StatusCode) {
         self.status.store(status.as_u16(), Ordering::Release);
     }
+
+    /// Shared status slot, for publishing a status known only later
+    #[inline]
+    pub(crate) fn status_handle(&self) -> std::sync::Arc<AtomicU16> {
+        self.status.clone()
+    }
+
+    /// Share `handle` as the status slot, for a status published by a producer task
+    #[inline]
+    pub(crate) fn with_status_handle(mut self, handle: std::sync::Arc<AtomicU16>) -> Self {
+        self.status = handle;
+        self
+    }
+
+    /// Response to hand out now and feed from another response later
+    ///
+    /// Used when the request is sent in the background, e.g. after a delay or
+    /// as one of several attempts.
+    pub(crate) fn pending(version: Version, stream_id: u64) -> (HttpResponse, ResponseSlot) {
+        let (headers_tx, headers_stream) = AsyncStream::channel();
+        let (body_tx, body_stream) = AsyncStream::channel();
+        let (trailers_tx, trailers_stream) = AsyncStream::channel();
+        let (informational_tx, informational_stream) = AsyncStream::channel();
+        let response = HttpResponse::new(headers_stream, body_stream, trailers_stream, version, stream_id)
+            .with_informational_stream(informational_stream);
+        let slot = ResponseSlot {
+            headers_tx,
+            body_tx,
+            trailers_tx,
+            informational_tx,
+            status: response.status.clone(),
+            connection_timings: response.connection_timings.clone(),
+            quic_stats: response.quic_stats.clone(),
+            redirects: response.redirects.clone(),
+        };
+        (response, slot)
+    }
     
     /// Get HTTP version
     #[inline(always)]
@@ -434,7 +502,7 @@ impl HttpResponse {
         let (_, trailers_stream) = AsyncStream::channel();
 
         Self {
-            status: AtomicU16::new(0),
+            status: std::sync::Arc::new(AtomicU16::new(0)),
             headers_internal: headers_stream,
             body_internal: body_stream,
             trailers_internal: trailers_stream,
@@ -444,6 +512,10 @@ impl HttpResponse {
             cached_last_modified: once_cell::sync::OnceCell::new(),
             cached_headers: RwLock::new(None),
             cached_body: RwLock::new(None),
+            quic_stats: std::sync::Arc::new(RwLock::new(None)),
+            informational_internal: AsyncStream::channel().1,
+            connection_timings: std::sync::Arc::new(RwLock::new(None)),
+            redirects: std::sync::Arc::new(RwLock::new(Vec::new())),
         }
     }
 
@@ -476,7 +548,7 @@ impl HttpResponse {
         }
 
         Self {
-            status: AtomicU16::new(cache_entry.status.as_u16()),
+            status: std::sync::Arc::new(AtomicU16::new(cache_entry.status.as_u16())),
             headers_internal: headers_stream,
             body_internal: body_stream,
             trailers_internal: trailers_stream,
@@ -492,10 +564,17 @@ impl HttpResponse {
             cached_last_modified: once_cell::sync::OnceCell::new(), // Would need conversion from SystemTime
             cached_headers: RwLock::new(None),
             cached_body: RwLock::new(Some(cache_entry.body.to_vec())),
+            quic_stats: std::sync::Arc::new(RwLock::new(None)),
+            informational_internal: AsyncStream::channel().1,
+            connection_timings: std::sync::Arc::new(RwLock::new(None)),
+            redirects: std::sync::Arc::new(RwLock::new(Vec::new())),
         }
     }
 
     /// Create HttpResponse from HTTP/2 response
+    ///
+    /// The informational stream is empty: `h2` does not hand interim 1xx
+    /// responses to clients.
     pub fn from_http2_response(
         status: StatusCode,
         headers: HeaderMap,
@@ -518,7 +597,7 @@ impl HttpResponse {
         }
 
         Self {
-            status: AtomicU16::new(status.as_u16()),
+            status: std::sync::Arc::new(AtomicU16::new(status.as_u16())),
             headers_internal: headers_stream,
             body_internal: body_stream,
             trailers_internal: trailers_stream,
@@ -528,6 +607,10 @@ impl HttpResponse {
             cached_last_modified: once_cell::sync::OnceCell::new(),
             cached_headers: RwLock::new(None),
             cached_body: RwLock::new(None),
+            quic_stats: std::sync::Arc::new(RwLock::new(None)),
+            informational_internal: AsyncStream::channel().1,
+            connection_timings: std::sync::Arc::new(RwLock::new(None)),
+            redirects: std::sync::Arc::new(RwLock::new(Vec::new())),
         }
     }
 
@@ -554,7 +637,7 @@ impl HttpResponse {
         }
 
         Self {
-            status: AtomicU16::new(status.as_u16()),
+            status: std::sync::Arc::new(AtomicU16::new(status.as_u16())),
             headers_internal: headers_stream,
             body_internal: body_stream,
             trailers_internal: trailers_stream,
@@ -564,6 +647,10 @@ impl HttpResponse {
             cached_last_modified: once_cell::sync::OnceCell::new(),
             cached_headers: RwLock::new(None),
             cached_body: RwLock::new(None),
+            quic_stats: std::sync::Arc::new(RwLock::new(None)),
+            informational_internal: AsyncStream::channel().1,
+            connection_timings: std::sync::Arc::new(RwLock::new(None)),
+            redirects: std::sync::Arc::new(RwLock::new(Vec::new())),
         }
     }
 
@@ -591,12 +678,13 @@ impl HttpResponse {
             offset: 0,
             is_final: true,
             timestamp: Instant::now(),
+            failure: None,
         };
         // Intentionally ignore send result - error response setup
         drop(body_sender.send(error_chunk));
 
         Self {
-            status: AtomicU16::new(status_code.as_u16()),
+            status: std::sync::Arc::new(AtomicU16::new(status_code.as_u16())),
             headers_internal: headers_stream,
             body_internal: body_stream,
             trailers_internal: trailers_stream,
@@ -606,6 +694,10 @@ impl HttpResponse {
             cached_last_modified: once_cell::sync::OnceCell::new(),
             cached_headers: RwLock::new(None),
             cached_body: RwLock::new(None),
+            quic_stats: std::sync::Arc::new(RwLock::new(None)),
+            informational_internal: AsyncStream::channel().1,
+            connection_timings: std::sync::Arc::new(RwLock::new(None)),
+            redirects: std::sync::Arc::new(RwLock::new(Vec::new())),
         }
     }
 
@@ -721,9 +813,237 @@ impl HttpResponse {
         drop(self.cached_last_modified.set(last_modified));
     }
 
+    /// QUIC transport statistics for the connection that served this response
+    ///
+    /// Returns `None` for HTTP/1.1 and HTTP/2 responses, and for HTTP/3
+    /// responses until the protocol layer has captured the statistics.
+    #[inline]
+    pub fn quic_stats(&self) -> Option<crate::telemetry::QuicConnectionStats> {
+        self.quic_stats.read().ok().and_then(|stats| stats.clone())
+    }
+
+    /// Shared slot the protocol layer publishes QUIC statistics into
+    #[inline]
+    pub(crate) fn quic_stats_handle(&self) -> crate::telemetry::QuicStatsHandle {
+        self.quic_stats.clone()
+    }
+
+    /// DNS, connect and TLS timings of the connection that served this response
+    ///
+    /// `None` until the protocol layer has published them, and for responses
+    /// that did not open a connection (cache hits, replayed cassettes).
+    #[inline]
+    pub fn connection_timings(&self) -> Option<crate::telemetry::ConnectionTimings> {
+        self.connection_timings.read().ok().and_then(|timings| *timings)
+    }
+
+    /// Shared slot the protocol layer publishes connection timings into
+    #[inline]
+    pub(crate) fn connection_timings_handle(&self) -> crate::telemetry::ConnectionTimingsHandle {
+        self.connection_timings.clone()
+    }
+
+    /// Use a connection timings slot created before the response itself
+    #[inline]
+    pub(crate) fn with_connection_timings_handle(
+        mut self,
+        handle: crate::telemetry::ConnectionTimingsHandle,
+    ) -> Self {
+        self.connection_timings = handle;
+        self
+    }
+
+    /// Redirects followed on the way to this response, oldest first
+    ///
+    /// Empty when the request was answered without being redirected.
+    pub fn redirect_history(&self) -> Vec<crate::redirect::RedirectHop> {
+        self.redirects.read().map(|hops| hops.clone()).unwrap_or_default()
+    }
+
+    /// Attach the stream of interim 1xx responses for this request
+    ///
+    /// The protocol layer closes the stream once the final header block
+    /// arrives, so it never yields after the final status is known.
+    pub fn with_informational_stream(
+        mut self,
+        informational_stream: AsyncStream<InformationalResponse, 16>,
+    ) -> Self {
+        self.informational_internal = informational_stream;
+        self
+    }
+
+    /// Stream of interim 1xx responses (100 Continue, 102 Processing, 103 Early Hints)
+    ///
+    /// Yields each interim response as it arrives and ends when the final
+    /// response headers are received. Empty for protocols that do not surface
+    /// interim responses.
+    #[inline]
+    pub fn informational(&mut self) -> &mut AsyncStream<InformationalResponse, 16> {
+        &mut self.informational_internal
+    }
+
+    /// Replace the header, body and trailer streams, returning the previous ones
+    ///
+    /// Status, version, QUIC statistics and the informational stream stay in
+    /// place, so layers that observe the response (cassette recording) can
+    /// splice themselves in without rebuilding it.
+    pub(crate) fn swap_streams(
+        &mut self,
+        headers_stream: AsyncStream<HttpHeader, 256>,
+        body_stream: AsyncStream<HttpBodyChunk, 1024>,
+        trailers_stream: AsyncStream<HttpHeader, 64>,
+    ) -> (
+        AsyncStream<HttpHeader, 256>,
+        AsyncStream<HttpBodyChunk, 1024>,
+        AsyncStream<HttpHeader, 64>,
+    ) {
+        (
+            std::mem::replace(&mut self.headers_internal, headers_stream),
+            std::mem::replace(&mut self.body_internal, body_stream),
+            std::mem::replace(&mut self.trailers_internal, trailers_stream),
+        )
+    }
+
+    /// Set the status code, for responses synthesized outside a protocol strategy
+    pub fn with_status(self, status: StatusCode) -> Self {
+        self.set_status(status);
+        self
+    }
+
+    /// Rewrite or drop headers as they stream through
+    ///
+    /// The closure runs on a background task per header; returning `None`
+    /// drops the header. The body is not touched or buffered.
+    pub fn map_headers<F>(mut self, mut f: F) -> Self
+    where
+        F: FnMut(HttpHeader) -> Option<HttpHeader> + Send + 'static,
+    {
+        let (tx, rx) = AsyncStream::<HttpHeader, 256>::channel();
+        let headers = std::mem::replace(&mut self.headers_internal, rx);
+        ystream::spawn_task(move || {
+            for header in headers {
+                if let Some(header) = f(header) {
+                    ystream::emit!(tx, header);
+                }
+            }
+        });
+        self
+    }
+
+    /// Transform body chunks as they stream through
+    ///
+    /// Each chunk is handed to the closure and forwarded as soon as it
+    /// arrives, so the body is never buffered.
+    pub fn map_body<F>(mut self, mut f: F) -> Self
+    where
+        F: FnMut(HttpBodyChunk) -> HttpBodyChunk + Send + 'static,
+    {
+        let (tx, rx) = AsyncStream::<HttpBodyChunk, 1024>::channel();
+        let body = std::mem::replace(&mut self.body_internal, rx);
+        ystream::spawn_task(move || {
+            for chunk in body {
+                ystream::emit!(tx, f(chunk));
+            }
+        });
+        self
+    }
+
+    /// Collect all interim responses, waiting until the final header block arrives
+    pub async fn collect_informational(&mut self) -> Vec<InformationalResponse> {
+        let mut responses = Vec::new();
+        while let Some(response) = self.informational_internal.next().await {
+            responses.push(response);
+        }
+        responses
+    }
+
 
 
 
+}
+
+/// Feeds a response handed out by [`HttpResponse::pending`]
+pub(crate) struct ResponseSlot {
+    headers_tx: ystream::AsyncStreamSender<HttpHeader, 256>,
+    body_tx: ystream::AsyncStreamSender<HttpBodyChunk, 1024>,
+    trailers_tx: ystream::AsyncStreamSender<HttpHeader, 64>,
+    informational_tx: ystream::AsyncStreamSender<InformationalResponse, 16>,
+    status: std::sync::Arc<AtomicU16>,
+    connection_timings: crate::telemetry::ConnectionTimingsHandle,
+    quic_stats: crate::telemetry::QuicStatsHandle,
+    redirects: crate::redirect::RedirectHistoryHandle,
+}
+
+impl ResponseSlot {
+    /// Forward `source` into the pending response
+    ///
+    /// `headers` and `body` are parts of `source` already read from it and
+    /// go out first. Copies the status, connection timings, QUIC statistics,
+    /// redirect history and interim responses, then blocks until the source
+    /// body and trailers have ended.
+    pub(crate) fn fill(self, headers: Vec<HttpHeader>, body: Option<HttpBodyChunk>, mut source: HttpResponse) {
+        let leading_headers = headers;
+        if let Some(status) = source.status_code() {
+            self.status.store(status.as_u16(), Ordering::Release);
+        }
+        if let (Ok(source), Ok(mut target)) = (source.redirects.read(), self.redirects.write()) {
+            if target.is_empty() {
+                target.clone_from(&source);
+            }
+        }
+        let timings = source.connection_timings_handle();
+        let quic_stats = source.quic_stats_handle();
+        let publish_timings = || {
+            if let (Ok(source), Ok(mut target)) = (timings.read(), self.connection_timings.write()) {
+                if target.is_none() {
+                    *target = *source;
+                }
+            }
+            if let (Ok(source), Ok(mut target)) = (quic_stats.read(), self.quic_stats.write()) {
+                if target.is_none() {
+                    target.clone_from(&source);
+                }
+            }
+        };
+        publish_timings();
+
+        let informational = std::mem::replace(&mut source.informational_internal, AsyncStream::channel().1);
+        let informational_tx = self.informational_tx;
+        ystream::spawn_task(move || {
+            for response in informational {
+                ystream::emit!(informational_tx, response);
+            }
+        });
+
+        let leading_body = body;
+        let (headers, body, trailers) = source.into_streams();
+        let headers_tx = self.headers_tx;
+        ystream::spawn_task(move || {
+            for header in leading_headers.into_iter().chain(headers) {
+                ystream::emit!(headers_tx, header);
+            }
+        });
+        for chunk in leading_body.into_iter().chain(body) {
+            ystream::emit!(self.body_tx, chunk);
+        }
+        // Timings may be published while the response is still arriving
+        publish_timings();
+        for trailer in trailers {
+            ystream::emit!(self.trailers_tx, trailer);
+        }
+    }
+
+    /// Record the redirects followed on the way to the response
+    pub(crate) fn record_redirects(&self, hops: Vec<crate::redirect::RedirectHop>) {
+        if let Ok(mut redirects) = self.redirects.write() {
+            *redirects = hops;
+        }
+    }
+
+    /// End the pending response with an error and no status
+    pub(crate) fn fail(self, message: String) {
+        drop(self.body_tx.send(HttpBodyChunk::new(Bytes::from(message), 0, true)));
+    }
 }
 
 impl HttpStatus {
@@ -767,6 +1087,15 @@ impl HttpBodyChunk {
             offset,
             is_final,
             timestamp: Instant::now(),
+            failure: None,
+        }
+    }
+
+    /// Final chunk reporting a transport failure of `kind`
+    pub fn failed(kind: crate::error::Kind, message: impl Into<String>) -> Self {
+        Self {
+            failure: Some(kind),
+            ..Self::new(Bytes::from(message.into()), 0, true)
         }
     }
 
@@ -782,7 +1111,7 @@ impl ystream::prelude::MessageChunk for HttpResponse {
         let (_, trailers_stream) = AsyncStream::channel();
 
         Self {
-            status: AtomicU16::new(StatusCode::INTERNAL_SERVER_ERROR.as_u16()),
+            status: std::sync::Arc::new(AtomicU16::new(StatusCode::INTERNAL_SERVER_ERROR.as_u16())),
             headers_internal: headers_stream,
             body_internal: body_stream,
             trailers_internal: trailers_stream,
@@ -792,6 +1121,10 @@ impl ystream::prelude::MessageChunk for HttpResponse {
             cached_last_modified: once_cell::sync::OnceCell::new(),
             cached_headers: RwLock::new(None),
             cached_body: RwLock::new(None),
+            quic_stats: std::sync::Arc::new(RwLock::new(None)),
+            informational_internal: AsyncStream::channel().1,
+            connection_timings: std::sync::Arc::new(RwLock::new(None)),
+            redirects: std::sync::Arc::new(RwLock::new(Vec::new())),
         }
     }
 
@@ -890,6 +1223,7 @@ impl ystream::prelude::MessageChunk for HttpBodyChunk {
             offset: 0,
             is_final: true,
             timestamp: Instant::now(),
+            failure: None,
         }
     }
 
@@ -909,6 +1243,7 @@ impl Default for HttpBodyChunk {
             offset: 0,
             is_final: false,
             timestamp: Instant::now(),
+            failure: None,
         }
     }
 }
@@ -77,7 +77,7 @@ where
                             }
                         }
                     }
-                    HttpChunk::Error(e) => {
+                    HttpChunk::Error(e) | HttpChunk::Failure(_, e) => {
                         self.stats.record_processing_error();
                         if let Err(recovery_error) = self.handle_error_with_recovery(
                             crate::error::Error::new(crate::error::Kind::Request)
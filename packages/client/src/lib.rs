@@ -85,23 +85,30 @@ use std::sync::OnceLock;
 pub mod auth;
 pub mod builder;
 pub mod cache;
+pub mod cassette;
 pub mod client;
 pub mod config;
 pub mod connect;
 pub mod cookie;
 pub mod crypto;
 pub mod error;
+pub mod har;
 pub mod http;
 pub mod jsonpath;
 pub mod middleware;
 pub mod operations;
 pub mod protocols;
 pub mod proxy;
+pub mod redirect;
 pub mod retry;
 pub mod security;
+pub mod service;
 pub mod telemetry;
 pub mod tls;
 
+#[cfg(feature = "testing")]
+pub mod testing;
+
 
 // Prelude with canonical types
 pub mod prelude;
@@ -169,18 +169,18 @@ impl Middleware for CacheMiddleware {
             .collect();
         let cache_key = self.generate_cache_key(&context.method, &context.url, &headers_slice);
 
-        // Implement streaming response caching with background task
-        // Extract stream_id and version before moving the response
-        let stream_id = response.stream_id;
-        let version = response.version;
-        
-        // Clone response streams for concurrent processing - one for forwarding, one for caching
-        let (headers_stream, body_stream, trailers_stream) = response.into_streams();
-        
         // Create response streams for forwarding to client
         let (forward_headers_tx, forward_headers_stream) = AsyncStream::<HttpHeader, 256>::channel();
         let (forward_body_tx, forward_body_stream) = AsyncStream::<HttpBodyChunk, 1024>::channel();
         let (forward_trailers_tx, forward_trailers_stream) = AsyncStream::<HttpHeader, 64>::channel();
+
+        // Splice the forwarding streams in, keeping status, version and timings
+        let mut response = response;
+        let (headers_stream, body_stream, trailers_stream) = response.swap_streams(
+            forward_headers_stream,
+            forward_body_stream,
+            forward_trailers_stream,
+        );
         
         // Create response streams for caching
         let (cache_headers_tx, _cache_headers_stream) = AsyncStream::<HttpHeader, 256>::channel();
@@ -193,6 +193,7 @@ impl Middleware for CacheMiddleware {
             let mut cached_body_chunks = Vec::new();
             let mut cached_trailers = Vec::new();
             let mut total_body_size = 0usize;
+            let mut cacheable = true;
             const MAX_CACHEABLE_SIZE: usize = 10 * 1024 * 1024; // 10MB limit for cacheable responses
             
             // Process headers stream
@@ -211,6 +212,9 @@ impl Middleware for CacheMiddleware {
                 ystream::emit!(forward_body_tx, body_chunk.clone());
                 
                 // Only cache if response is within size limit
+                if !cacheable {
+                    continue;
+                }
                 if total_body_size <= MAX_CACHEABLE_SIZE {
                     cached_body_chunks.push(body_chunk.data.clone());
                     ystream::emit!(cache_body_tx, body_chunk);
@@ -223,7 +227,8 @@ impl Middleware for CacheMiddleware {
                         limit = MAX_CACHEABLE_SIZE,
                         "Response exceeds cacheable size limit - forwarding without caching"
                     );
-                    return; // Exit caching task but continue forwarding
+                    cacheable = false;
+                    cached_body_chunks.clear();
                 }
             }
             
@@ -235,7 +240,7 @@ impl Middleware for CacheMiddleware {
             }
             
             // Create cache entry from collected data
-            if !cached_body_chunks.is_empty() || !cached_headers.is_empty() {
+            if cacheable && (!cached_body_chunks.is_empty() || !cached_headers.is_empty()) {
                 // Combine body chunks into single buffer
                 let cached_body: Vec<u8> = cached_body_chunks.into_iter().flat_map(|chunk| chunk).collect();
                 
@@ -294,15 +299,6 @@ impl Middleware for CacheMiddleware {
             }
         });
         
-        // Create new response from forwarding streams
-        let cached_response = HttpResponse::new(
-            forward_headers_stream,
-            forward_body_stream,
-            forward_trailers_stream,
-            version,
-            stream_id,
-        );
-        
-        Ok(cached_response)
+        Ok(response)
     }
 }
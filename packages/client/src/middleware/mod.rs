@@ -1,5 +1,26 @@
 //! HTTP middleware for request/response processing
 //! Simplified, streaming-first processing aligned with `quyc`'s zero-allocation design
+//!
+//! # Ordering
+//!
+//! A [`MiddlewareChain`] wraps [`HttpClient::execute`](crate::HttpClient::execute)
+//! like layers of an onion. Middleware added first is the outermost layer:
+//!
+//! - requests pass through `process_request` in the order middleware was added;
+//! - after each `process_request`, `short_circuit` may answer the request
+//!   with a synthetic response, skipping the remaining middleware and the
+//!   network entirely;
+//! - responses pass through `process_response` in reverse order, and only
+//!   through middleware whose `process_request` ran (including the one that
+//!   short-circuited);
+//! - when a hook returns an error, that middleware and every outer one see it
+//!   through `handle_error` instead of `process_response`; the final error is
+//!   returned to the caller as an error response.
+//!
+//! Responses are streamed: `process_response` runs once the response object
+//! exists, before its body has arrived. Use [`HttpResponse::map_headers`] and
+//! [`HttpResponse::map_body`] to inspect or rewrite the streams without
+//! buffering them.
 
 #![allow(dead_code)]
 
@@ -14,23 +35,52 @@ pub trait Middleware: Send + Sync {
         Ok(request)
     }
 
-    /// Process response after receiving - returns Result directly  
+    /// Answer the request without sending it
+    ///
+    /// Called right after this middleware's `process_request`; returning a
+    /// response skips inner middleware and the network.
+    fn short_circuit(&self, _request: &HttpRequest) -> Option<HttpResponse> {
+        None
+    }
+
+    /// Process response after receiving - returns Result directly
     fn process_response(&self, response: HttpResponse) -> crate::error::Result<HttpResponse> {
         Ok(response)
     }
 
     /// Handle errors - returns Result directly
+    ///
+    /// Either variant carries the error on to the next outer middleware, so
+    /// implementations can observe, wrap or replace it.
     fn handle_error(&self, error: HttpError) -> crate::error::Result<HttpError> {
         Ok(error)
     }
 }
 
 /// Middleware chain for sequential processing
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct MiddlewareChain {
     middlewares: Vec<Arc<dyn Middleware>>,
 }
 
+impl std::fmt::Debug for MiddlewareChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MiddlewareChain")
+            .field("len", &self.middlewares.len())
+            .finish()
+    }
+}
+
+/// Where the request phase of the chain ended
+pub(crate) enum RequestOutcome {
+    /// Every middleware passed the request on
+    Forward(HttpRequest),
+    /// A middleware answered with a synthetic response
+    Respond(HttpResponse),
+    /// A middleware failed
+    Fail(HttpError),
+}
+
 impl MiddlewareChain {
     pub fn new() -> Self {
         Self::default()
@@ -40,6 +90,107 @@ impl MiddlewareChain {
         self.middlewares.push(Arc::new(middleware));
         self
     }
+
+    /// Append an already shared middleware
+    pub fn push(&mut self, middleware: Arc<dyn Middleware>) {
+        self.middlewares.push(middleware);
+    }
+
+    /// Number of middleware in the chain
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.middlewares.len()
+    }
+
+    /// Whether the chain is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.middlewares.is_empty()
+    }
+
+    /// Run `request` through the chain, calling `dispatch` to send it
+    ///
+    /// `dispatch` is only called when no middleware short-circuits or fails;
+    /// its errors reach `handle_error` like middleware errors do.
+    pub(crate) fn run<F>(&self, request: HttpRequest, dispatch: F) -> HttpResponse
+    where
+        F: FnOnce(HttpRequest) -> crate::error::Result<HttpResponse>,
+    {
+        if self.middlewares.is_empty() {
+            return dispatch(request).unwrap_or_else(error_response);
+        }
+
+        let (entered, outcome) = self.run_request(request);
+        let result = match outcome {
+            RequestOutcome::Forward(request) => dispatch(request),
+            RequestOutcome::Respond(response) => Ok(response),
+            RequestOutcome::Fail(error) => Err(error),
+        };
+        self.run_response(entered, result)
+    }
+
+    /// Response phase: unwind the first `entered` middleware in reverse order
+    pub(crate) fn run_response(
+        &self,
+        entered: usize,
+        mut result: crate::error::Result<HttpResponse>,
+    ) -> HttpResponse {
+        for middleware in self.middlewares[..entered].iter().rev() {
+            result = match result {
+                Ok(response) => middleware.process_response(response),
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                result = Err(middleware.handle_error(error).unwrap_or_else(|error| error));
+            }
+        }
+
+        result.unwrap_or_else(error_response)
+    }
+
+    /// Request phase: returns how many middleware saw the request and the outcome
+    pub(crate) fn run_request(&self, mut request: HttpRequest) -> (usize, RequestOutcome) {
+        for (index, middleware) in self.middlewares.iter().enumerate() {
+            request = match middleware.process_request(request) {
+                Ok(request) => request,
+                Err(error) => return (index + 1, RequestOutcome::Fail(error)),
+            };
+            if let Some(response) = middleware.short_circuit(&request) {
+                tracing::debug!(
+                    target: "quyc::middleware",
+                    url = %request.url(),
+                    position = index,
+                    "Middleware short-circuited request"
+                );
+                return (index + 1, RequestOutcome::Respond(response));
+            }
+        }
+        (self.middlewares.len(), RequestOutcome::Forward(request))
+    }
+}
+
+/// Turn an error that left the chain into the response returned to the caller
+pub(crate) fn error_response(error: HttpError) -> HttpResponse {
+    tracing::debug!(
+        target: "quyc::middleware",
+        error = %error,
+        "Request failed"
+    );
+    let status = error.status().unwrap_or(if error.is_circuit_open() {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else if error.is_rate_limited() {
+        StatusCode::TOO_MANY_REQUESTS
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    });
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(&error);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    HttpResponse::error(status, message)
 }
 
 /// Cache middleware module
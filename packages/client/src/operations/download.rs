@@ -83,6 +83,7 @@ impl DownloadOperation {
                         offset: 0,
                         is_final: true,
                         timestamp: Instant::now(),
+                        failure: None,
                     });
                 });
             }
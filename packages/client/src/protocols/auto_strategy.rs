@@ -12,6 +12,7 @@ use crate::protocols::strategy::ProtocolConfigs;
 use crate::protocols::core::HttpVersion;
 use crate::protocols::intelligence::{ProtocolIntelligence, AltSvcEndpoint};
 use crate::http::{HttpRequest, HttpResponse};
+use crate::tls::EchPolicy;
 
 /// Auto-selecting Protocol Strategy with Fallback
 ///
@@ -26,6 +27,8 @@ pub struct AutoStrategy {
     prefer: Vec<HttpVersion>,
     /// Protocol intelligence cache for learning domain capabilities
     intelligence: Arc<ProtocolIntelligence>,
+    /// HTTP/3 is never tried: ECH is required and quiche cannot offer it
+    ech_required: bool,
 }
 
 impl AutoStrategy {
@@ -36,8 +39,15 @@ impl AutoStrategy {
             h2_strategy: H2Strategy::new(configs.h2.clone()),
             prefer,
             intelligence: Arc::new(ProtocolIntelligence::new()),
+            ech_required: configs.h3.ech_policy == EchPolicy::Require,
         }
     }
+
+    /// Report QUIC transport statistics of HTTP/3 connections to `registry`
+    pub fn with_quic_stats(mut self, registry: Arc<crate::telemetry::QuicStatsRegistry>) -> Self {
+        self.h3_strategy = self.h3_strategy.with_quic_stats(registry);
+        self
+    }
     
     /// Extract domain from request URL
     fn extract_domain(&self, request: &HttpRequest) -> String {
@@ -85,6 +95,11 @@ impl AutoStrategy {
     
     /// Check if this request should skip HTTP/3 entirely
     fn should_skip_http3(&self, request: &HttpRequest) -> bool {
+        if self.ech_required {
+            tracing::debug!("Skipping HTTP/3 - Encrypted Client Hello is required and QUIC cannot offer it");
+            return true;
+        }
+
         let url = request.url();
         
         // Skip HTTP/3 for localhost/127.0.0.1 over HTTP (not HTTPS)
@@ -2,6 +2,9 @@
 //!
 //! Uses existing H2Connection infrastructure with thread-spawned streaming patterns.
 //! Follows async-stream architecture: std::thread::spawn + emit! (NO async/await).
+//!
+//! Interim 1xx responses are consumed by `h2` and never reach the response's
+//! informational stream, which ends empty once the final headers arrive.
 
 use ystream::{AsyncStream, emit};
 use bytes::Bytes;
@@ -12,6 +15,9 @@ use crate::http::response::{HttpResponse, HttpChunk};
 use crate::protocols::strategy_trait::ProtocolStrategy;
 use crate::protocols::response_converter::convert_http_chunks_to_response;
 use crate::protocols::strategy::H2Config;
+use crate::error::classification::{h2_error_kind, io_error_kind};
+use crate::error::Kind;
+use crate::telemetry::{ConnectionTimings, ConnectionTimingsHandle};
 
 /// Connection type for H2 strategy
 enum H2Stream {
@@ -39,19 +45,43 @@ impl Default for H2Strategy {
 
 impl H2Strategy {
     /// Create connection based on URL scheme
-    async fn create_connection(url: &url::Url, host: &str, port: u16) -> Result<H2Stream, String> {
+    async fn create_connection(
+        url: &url::Url,
+        host: &str,
+        port: u16,
+        h2_config: &H2Config,
+    ) -> Result<(H2Stream, ConnectionTimings), HttpChunk> {
         if url.scheme() == "https" {
-            let tls_manager = crate::tls::TlsManager::new();
-            let tls_stream = tls_manager
-                .create_connection(host, port)
+            let tls_manager = crate::tls::TlsManager::with_config(crate::tls::TlsConfig {
+                custom_root_certs: h2_config.root_certificates.clone(),
+                key_log_file: h2_config.key_log_file.clone(),
+                ech_policy: h2_config.ech_policy,
+                ech_config_list: h2_config.ech_config_list.clone(),
+                ..crate::tls::TlsConfig::default()
+            })
+            .with_resolver(h2_config.resolver.clone());
+            let (tls_stream, timings) = tls_manager
+                .create_connection_with_timings(host, port)
                 .await
-                .map_err(|e| format!("TLS connection error: {:?}", e))?;
-            Ok(H2Stream::Tls(tls_stream))
+                .map_err(|e| {
+                    let kind = match &e {
+                        crate::tls::TlsError::Io(io) => io_error_kind(io),
+                        _ => Kind::Request,
+                    };
+                    HttpChunk::Failure(kind, format!("TLS connection error: {:?}", e))
+                })?;
+            Ok((H2Stream::Tls(tls_stream), timings))
         } else {
+            let connect_start = std::time::Instant::now();
             let tcp_stream = TcpStream::connect((host, port))
                 .await
-                .map_err(|e| format!("TCP connection error: {:?}", e))?;
-            Ok(H2Stream::Plain(tcp_stream))
+                .map_err(|e| HttpChunk::Failure(io_error_kind(&e), format!("TCP connection error: {:?}", e)))?;
+            let timings = ConnectionTimings {
+                connect: Some(connect_start.elapsed()),
+                remote_addr: tcp_stream.peer_addr().ok(),
+                ..ConnectionTimings::default()
+            };
+            Ok((H2Stream::Plain(tcp_stream), timings))
         }
     }
 
@@ -60,38 +90,38 @@ impl H2Strategy {
         h2_client: h2::client::SendRequest<bytes::Bytes>,
         http_request: http::Request<()>,
         body_bytes: Option<Bytes>,
-    ) -> Result<(http::StatusCode, http::HeaderMap, h2::RecvStream), String> {
+    ) -> Result<(http::StatusCode, http::HeaderMap, h2::RecvStream), HttpChunk> {
         // Wait for client ready and send request
         let mut ready_client = h2_client
             .ready()
             .await
-            .map_err(|e| format!("H2 client ready error: {}", e))?;
+            .map_err(|e| HttpChunk::Failure(h2_error_kind(&e), format!("H2 client ready error: {}", e)))?;
         
         let (response, mut request_stream) = ready_client
             .send_request(http_request, body_bytes.is_none())
-            .map_err(|e| format!("Send request error: {}", e))?;
+            .map_err(|e| HttpChunk::Failure(h2_error_kind(&e), format!("Send request error: {}", e)))?;
         
         // Send body if present
         if let Some(body) = body_bytes {
             if !body.is_empty() {
                 request_stream
                     .send_data(body, true)
-                    .map_err(|e| format!("Send body error: {}", e))?;
+                    .map_err(|e| HttpChunk::Failure(h2_error_kind(&e), format!("Send body error: {}", e)))?;
             } else {
                 request_stream
                     .send_data(Bytes::new(), true)
-                    .map_err(|e| format!("Send empty body error: {}", e))?;
+                    .map_err(|e| HttpChunk::Failure(h2_error_kind(&e), format!("Send empty body error: {}", e)))?;
             }
         } else {
             request_stream
                 .send_data(Bytes::new(), true)
-                .map_err(|e| format!("Send no body error: {}", e))?;
+                .map_err(|e| HttpChunk::Failure(h2_error_kind(&e), format!("Send no body error: {}", e)))?;
         }
         
         // Get response
         let response = response
             .await
-            .map_err(|e| format!("Response error: {}", e))?;
+            .map_err(|e| HttpChunk::Failure(h2_error_kind(&e), format!("Response error: {}", e)))?;
             
         Ok((response.status(), response.headers().clone(), response.into_body()))
     }
@@ -104,13 +134,13 @@ impl H2Strategy {
         uri: &str,
         headers: http::HeaderMap,
         body_bytes: Option<Bytes>,
-    ) -> Result<(http::StatusCode, http::HeaderMap, h2::RecvStream), String> {
+    ) -> Result<(http::StatusCode, http::HeaderMap, h2::RecvStream), HttpChunk> {
         // Build HTTP request
         let mut http_request = http::Request::builder()
             .method(method)
             .uri(uri)
             .body(())
-            .map_err(|e| format!("Request build error: {}", e))?;
+            .map_err(|e| HttpChunk::Error(format!("Request build error: {}", e)))?;
         *http_request.headers_mut() = headers;
         
         // Configure H2 client with settings
@@ -127,7 +157,7 @@ impl H2Strategy {
                 let (h2_client, connection) = h2_builder
                     .handshake(tls_stream)
                     .await
-                    .map_err(|e| format!("H2 handshake error: {}", e))?;
+                    .map_err(|e| HttpChunk::Failure(h2_error_kind(&e), format!("H2 handshake error: {}", e)))?;
                 
                 // Spawn connection driver
                 tokio::spawn(async move {
@@ -140,7 +170,7 @@ impl H2Strategy {
                 let (h2_client, connection) = h2_builder
                     .handshake(tcp_stream)
                     .await
-                    .map_err(|e| format!("H2 handshake error: {}", e))?;
+                    .map_err(|e| HttpChunk::Failure(h2_error_kind(&e), format!("H2 handshake error: {}", e)))?;
                 
                 // Spawn connection driver
                 tokio::spawn(async move {
@@ -162,10 +192,12 @@ impl H2Strategy {
         uri: &str,
         headers: http::HeaderMap,
         body_bytes: Option<Bytes>,
-    ) -> Result<(http::StatusCode, http::HeaderMap, h2::RecvStream), String> {
+        timings_handle: &ConnectionTimingsHandle,
+    ) -> Result<(http::StatusCode, http::HeaderMap, h2::RecvStream), HttpChunk> {
         let execute_async = async {
             // Create connection (HTTPS vs HTTP abstracted)
-            let stream = Self::create_connection(url, host, port).await?;
+            let (stream, timings) = Self::create_connection(url, host, port, h2_config).await?;
+            timings.publish(timings_handle);
             
             // Execute H2 request (same logic for both connection types)
             Self::execute_h2_request(stream, h2_config, method, uri, headers, body_bytes).await
@@ -176,7 +208,7 @@ impl H2Strategy {
             handle.block_on(execute_async)
         } else {
             let rt = tokio::runtime::Runtime::new()
-                .map_err(|e| format!("Failed to create runtime: {}", e))?;
+                .map_err(|e| HttpChunk::Error(format!("Failed to create runtime: {}", e)))?;
             rt.block_on(execute_async)
         }
     }
@@ -236,6 +268,10 @@ impl ProtocolStrategy for H2Strategy {
             _ => None,
         };
 
+        // Connection timings are published from the request task into the response
+        let timings_handle = ConnectionTimingsHandle::default();
+        let task_timings = timings_handle.clone();
+
         // Create stream using with_channel pattern (thread-spawned, no async/await)  
         let chunk_stream = AsyncStream::<HttpChunk, 1024>::with_channel(move |sender| {
             // This closure runs in dedicated thread spawned by with_channel
@@ -244,7 +280,7 @@ impl ProtocolStrategy for H2Strategy {
             let connection_and_request_task = spawn_task(move || {
                 // Execute request with proper runtime handling (no duplication)
                 Self::execute_with_runtime(
-                    &url, &host, port, &h2_config, &method, &uri, headers, body_bytes
+                    &url, &host, port, &h2_config, &method, &uri, headers, body_bytes, &task_timings
                 )
             });
             
@@ -325,8 +361,8 @@ impl ProtocolStrategy for H2Strategy {
                         }
                     }
                 }
-                Ok(Err(e)) => {
-                    emit!(sender, HttpChunk::Error(e));
+                Ok(Err(error)) => {
+                    emit!(sender, error);
                 }
                 Err(e) => {
                     emit!(sender, HttpChunk::Error(format!("Connection task error: {:?}", e)));
@@ -336,6 +372,7 @@ impl ProtocolStrategy for H2Strategy {
         
         // Use existing response converter infrastructure
         convert_http_chunks_to_response(chunk_stream, 1)
+            .with_connection_timings_handle(timings_handle)
     }
     
     fn protocol_name(&self) -> &'static str {
@@ -14,9 +14,11 @@ use crate::protocols::h3::connection::H3Connection;
 use crate::protocols::h3::strategy::processing::H3RequestProcessor;
 
 use crate::protocols::strategy::H3Config;
+use crate::protocols::quiche::QlogConfig;
 use crate::protocols::core::ProtocolConfig;
 use crate::protocols::core::TimeoutConfig;
 
+use crate::http::informational::InformationalResponse;
 use crate::http::response::{HttpResponse, HttpBodyChunk};
 
 static STREAM_ID_COUNTER: AtomicU64 = AtomicU64::new(1);
@@ -177,6 +179,7 @@ pub fn execute_h3_request(
     let (headers_tx, headers_rx) = AsyncStream::<crate::http::response::HttpHeader, 256>::channel();
     let (body_tx, body_rx) = AsyncStream::<crate::http::response::HttpBodyChunk, 1024>::channel();
     let (trailers_tx, trailers_rx) = AsyncStream::<crate::http::response::HttpHeader, 64>::channel();
+    let (informational_tx, informational_rx) = AsyncStream::<InformationalResponse, 16>::channel();
     
     // Extract request components for H3RequestProcessor (same as H3Strategy)
     let method = request.method().clone();
@@ -203,9 +206,16 @@ pub fn execute_h3_request(
     let peer_addr = format!("{}:{}", host, port).parse()
         .map_err(|e| HttpError::new(crate::error::types::Kind::Request).with(e))?;
     
-    let quiche_connection = quiche::connect(None, &scid, local_addr, peer_addr, &mut quic_config)
+    let mut quiche_connection = quiche::connect(None, &scid, local_addr, peer_addr, &mut quic_config)
         .map_err(|e| HttpError::new(crate::error::types::Kind::Request).with(e))?;
     
+    if let Some(qlog) = QlogConfig::resolve(config.qlog.as_ref()) {
+        qlog.attach(&mut quiche_connection, &crate::telemetry::quic_origin(&scheme, &host, port));
+    }
+    if let Some(path) = crate::tls::key_log_path(config.key_log_file.as_deref()) {
+        crate::tls::key_log::attach_quiche_key_log(&mut quiche_connection, &path);
+    }
+    
     let timeout_config = TimeoutConfig {
         request_timeout: std::time::Duration::from_secs(60),
         connect_timeout: std::time::Duration::from_secs(5),
@@ -243,9 +253,18 @@ pub fn execute_h3_request(
         let mut compression_algorithm: Option<crate::http::headers::CompressionAlgorithm> = None;
         
         // Forward response chunks to the appropriate channels
+        let mut informational_tx = Some(informational_tx);
         for chunk in response_stream.collect() {
             match chunk {
+                HttpChunk::Headers(status, headers_map) if status.is_informational() => {
+                    if let Some(tx) = &informational_tx {
+                        let _ = tx.try_send(InformationalResponse::new(status, headers_map));
+                    }
+                },
                 HttpChunk::Headers(status, headers_map) => {
+                    // Final header block closes the informational stream
+                    informational_tx = None;
+                    
                     // Create a special header for status
                     use http::{HeaderName, HeaderValue};
                     let status_name = HeaderName::from_static("x-http-status");
@@ -326,7 +345,8 @@ pub fn execute_h3_request(
         trailers_rx,
         http::Version::HTTP_3,
         0, // stream_id
-    );
+    )
+    .with_informational_stream(informational_rx);
     
     // Set initial status
     response.set_status(http::StatusCode::OK);
@@ -374,6 +394,9 @@ fn create_quiche_config(config: &H3Config) -> Result<quiche::Config, HttpError>
     
     // Enable certificate verification
     quiche_config.verify_peer(true);
+    crate::tls::key_log::enable_quiche_key_log(&mut quiche_config, config.key_log_file.as_deref());
+    crate::tls::roots::load_quiche_root_certificates(&mut quiche_config, &config.root_certificates)
+        .map_err(|e| HttpError::new(crate::error::types::Kind::Request).with(e))?;
     
     Ok(quiche_config)
 }
@@ -403,6 +426,10 @@ fn create_h3_connection(config: &H3Config, request: &HttpRequest) -> Result<H3Co
         quiche_config.enable_early_data();
     }
     
+    crate::tls::key_log::enable_quiche_key_log(&mut quiche_config, config.key_log_file.as_deref());
+    crate::tls::roots::load_quiche_root_certificates(&mut quiche_config, &config.root_certificates)
+        .map_err(|e| HttpError::new(crate::error::Kind::Request).with(e))?;
+    
     // Generate proper connection ID (not hardcoded)
     let scid = generate_connection_id();
     let local_addr = "127.0.0.1:0".parse()
@@ -411,9 +438,22 @@ fn create_h3_connection(config: &H3Config, request: &HttpRequest) -> Result<H3Co
     // Extract peer address from request URL
     let peer_addr = extract_peer_addr_from_request(request)?;
     
-    let quiche_connection = quiche::connect(None, &scid, local_addr, peer_addr, &mut quiche_config)
+    let mut quiche_connection = quiche::connect(None, &scid, local_addr, peer_addr, &mut quiche_config)
         .map_err(|e| HttpError::new(crate::error::Kind::Request).with(e))?;
     
+    if let Some(qlog) = QlogConfig::resolve(config.qlog.as_ref()) {
+        let url = request.url();
+        let origin = crate::telemetry::quic_origin(
+            url.scheme(),
+            url.host_str().unwrap_or("localhost"),
+            peer_addr.port(),
+        );
+        qlog.attach(&mut quiche_connection, &origin);
+    }
+    if let Some(path) = crate::tls::key_log_path(config.key_log_file.as_deref()) {
+        crate::tls::key_log::attach_quiche_key_log(&mut quiche_connection, &path);
+    }
+    
     let timeout_config = TimeoutConfig {
         request_timeout: std::time::Duration::from_secs(60),
         connect_timeout: std::time::Duration::from_secs(5),
@@ -306,6 +306,19 @@ impl H3Connection {
             }
         }
     }
+
+    /// Capture the connection's transport statistics into `recorder`
+    pub fn record_stats(&self, recorder: &crate::telemetry::QuicStatsRecorder) {
+        match self.inner.lock() {
+            Ok(guard) => recorder.record(&guard),
+            Err(_poisoned) => {
+                tracing::warn!(
+                    target: "quyc::protocols::h3",
+                    "Connection mutex poisoned when capturing transport statistics, skipping"
+                );
+            }
+        }
+    }
 }
 
 /// HTTP/3 stream wrapper that bridges quiche streams to AsyncStream
@@ -753,6 +766,11 @@ impl H3Connection {
                                 loop {
                                     match h3.poll(&mut conn) {
                                         Ok((sid, quiche::h3::Event::Headers { list, .. })) if sid == created_stream_id => {
+                                            // 1xx header blocks are emitted like the final one and told apart by status
+                                            let status = list.iter()
+                                                .find(|h| h.name() == b":status")
+                                                .and_then(|h| http::StatusCode::from_bytes(h.value()).ok())
+                                                .unwrap_or(http::StatusCode::OK);
                                             let headers_map = list.iter()
                                                 .filter_map(|h| {
                                                     match (http::HeaderName::from_bytes(h.name()), http::HeaderValue::from_bytes(h.value())) {
@@ -762,7 +780,7 @@ impl H3Connection {
                                                 })
                                                 .collect();
                                             emit!(sender, crate::http::HttpChunk::Headers(
-                                                http::StatusCode::OK, 
+                                                status,
                                                 headers_map
                                             ));
                                         },
@@ -8,8 +8,11 @@ use crossbeam_utils::Backoff;
 use ystream::{AsyncStreamSender, prelude::MessageChunk};
 use quiche;
 
+use crate::error::classification::io_error_kind;
+use crate::error::Kind;
 use crate::protocols::strategy::H3Config;
 use crate::protocols::core::ProtocolConfig;
+use crate::protocols::quiche::{QlogConfig, QuicUdpSocket};
 use crate::http::response::HttpBodyChunk;
 
 use super::security::validate_destination_address;
@@ -32,6 +35,19 @@ impl H3Connection {
         Err(())
     }
 
+    /// Send a final chunk reporting a transport failure of `kind` and return error
+    fn send_failure_and_return<T>(
+        &self,
+        body_tx: &AsyncStreamSender<HttpBodyChunk>,
+        kind: Kind,
+        message: String,
+    ) -> Result<T, ()> {
+        if let Err(_) = body_tx.send(HttpBodyChunk::failed(kind, message)) {
+            // Sender closed, continue with error
+        }
+        Err(())
+    }
+
     /// Create new H3 connection manager
     pub fn new(config: H3Config, quic_config: quiche::Config) -> Self {
         Self {
@@ -52,32 +68,23 @@ impl H3Connection {
         (
             quiche::Connection,
             quiche::h3::Connection,
-            UdpSocket,
+            QuicUdpSocket,
             SocketAddr,
             SocketAddr,
         ),
         ()
     > {
         // Create UDP socket with security considerations
-        let socket = self.create_secure_socket(host, body_tx)?;
+        let mut socket = self.create_secure_socket(host, body_tx)?;
         
         // Resolve server address
         let server_addr = self.resolve_server_address(host, port, body_tx)?;
         
         // Get local address
-        let local_addr = match socket.local_addr() {
-            Ok(addr) => addr,
-            Err(e) => {
-                let error_chunk = HttpBodyChunk::bad_chunk(format!("Failed to get local address: {e}"));
-                if let Err(_) = body_tx.send(error_chunk) {
-                    // Sender closed, continue with error
-                }
-                return Err(());
-            }
-        };
+        let local_addr = socket.local_addr();
         
         // Establish QUIC connection
-        let mut quic_conn = self.establish_quic_connection(host, server_addr, local_addr, &socket, body_tx)?;
+        let mut quic_conn = self.establish_quic_connection(host, server_addr, local_addr, &mut socket, body_tx)?;
         
         // Create HTTP/3 connection
         let h3_conn = self.create_h3_connection(&mut quic_conn, body_tx)?;
@@ -90,7 +97,7 @@ impl H3Connection {
         &self,
         host: &str,
         body_tx: &AsyncStreamSender<HttpBodyChunk>,
-    ) -> Result<UdpSocket, ()> {
+    ) -> Result<QuicUdpSocket, ()> {
         // SECURITY: Bind more securely to prevent UDP amplification attacks
         // For outbound HTTP/3 client connections, we should use the system's default interface
         // but not bind to all interfaces indiscriminately
@@ -116,16 +123,18 @@ impl H3Connection {
             }
         };
         
-        // Set non-blocking for efficient I/O
-        if let Err(e) = socket.set_nonblocking(true) {
-            tracing::warn!(
-                target: "quyc::protocols::h3",
-                error = %e,
-                "Failed to set UDP socket to non-blocking mode"
-            );
+        // Wrap for batched I/O; this also switches the socket to non-blocking mode
+        match QuicUdpSocket::new(socket, self.config.udp_io_config()) {
+            Ok(socket) => Ok(socket),
+            Err(e) => {
+                tracing::error!(
+                    target: "quyc::protocols::h3",
+                    error = %e,
+                    "Failed to configure UDP socket for QUIC connection"
+                );
+                self.send_error_and_return(body_tx, format!("Failed to configure UDP socket: {e}"))
+            }
         }
-        
-        Ok(socket)
     }
 
     /// Resolve server address with security validation
@@ -198,7 +207,7 @@ impl H3Connection {
         host: &str,
         server_addr: SocketAddr,
         local_addr: SocketAddr,
-        socket: &UdpSocket,
+        socket: &mut QuicUdpSocket,
         body_tx: &AsyncStreamSender<HttpBodyChunk>,
     ) -> Result<quiche::Connection, ()> {
         // Generate connection ID
@@ -227,6 +236,14 @@ impl H3Connection {
             }
         };
         
+        if let Some(qlog) = QlogConfig::resolve(self.config.qlog.as_ref()) {
+            let origin = crate::telemetry::quic_origin("https", host, server_addr.port());
+            qlog.attach(&mut quic_conn, &origin);
+        }
+        if let Some(path) = crate::tls::key_log_path(self.config.key_log_file.as_deref()) {
+            crate::tls::key_log::attach_quiche_key_log(&mut quic_conn, &path);
+        }
+        
         // Perform initial handshake
         self.perform_handshake(&mut quic_conn, socket, body_tx)?;
         
@@ -237,36 +254,34 @@ impl H3Connection {
     fn perform_handshake(
         &self,
         quic_conn: &mut quiche::Connection,
-        socket: &UdpSocket,
+        socket: &mut QuicUdpSocket,
         body_tx: &AsyncStreamSender<HttpBodyChunk>,
     ) -> Result<(), ()> {
         // Initial handshake send
-        let mut out = [0; 1350];
-        let (write_len, send_info) = match quic_conn.send(&mut out) {
-            Ok(v) => v,
+        match socket.flush(quic_conn) {
+            Ok(0) => {
+                tracing::error!(
+                    target: "quyc::protocols::h3",
+                    "QUIC connection produced no initial handshake packet"
+                );
+                return self.send_error_and_return(body_tx, "Failed initial QUIC send: no packet produced".to_string());
+            }
+            Ok(_) => {}
             Err(e) => {
                 tracing::error!(
                     target: "quyc::protocols::h3",
                     error = %e,
-                    "Failed initial QUIC handshake send"
+                    "Failed to send initial QUIC packet"
+                );
+                return self.send_failure_and_return(
+                    body_tx,
+                    io_error_kind(&e),
+                    format!("Failed to send initial packet: {}", e),
                 );
-                return self.send_error_and_return(body_tx, format!("Failed initial QUIC send: {}", e));
             }
-        };
-        
-        if let Err(e) = socket.send_to(&out[..write_len], send_info.to) {
-            tracing::error!(
-                target: "quyc::protocols::h3",
-                error = %e,
-                packet_size = write_len,
-                destination = %send_info.to,
-                "Failed to send initial QUIC packet"
-            );
-            return self.send_error_and_return(body_tx, format!("Failed to send initial packet: {}", e));
         }
         
         // Wait for handshake to complete with elite backoff
-        let mut buf = [0; 65535];
         let start = std::time::Instant::now();
         let timeout = self.config.timeout_config().connect_timeout;
         let backoff = Backoff::new();
@@ -279,42 +294,14 @@ impl H3Connection {
                     elapsed_ms = start.elapsed().as_millis(),
                     "QUIC handshake timeout exceeded"
                 );
-                return self.send_error_and_return(body_tx, "QUIC handshake timeout".to_string());
+                return self.send_failure_and_return(body_tx, Kind::Timeout, "QUIC handshake timeout".to_string());
             }
             
             let mut data_processed = false;
             
-            // Try to receive
-            match socket.recv_from(&mut buf) {
-                Ok((len, from)) => {
-                    let recv_info = quiche::RecvInfo {
-                        from,
-                        to: match socket.local_addr() {
-                            Ok(addr) => addr,
-                            Err(e) => {
-                                let error_chunk = HttpBodyChunk::bad_chunk(format!("Failed to get local address during handshake: {}", e));
-                                if let Err(_) = body_tx.send(error_chunk) {
-                                    // Sender closed
-                                }
-                                return Err(());
-                            }
-                        },
-                    };
-                    
-                    if let Err(e) = quic_conn.recv(&mut buf[..len], recv_info) {
-                        tracing::warn!(
-                            target: "quyc::protocols::h3",
-                            error = %e,
-                            packet_len = len,
-                            "QUIC packet receive error during handshake"
-                        );
-                    } else {
-                        data_processed = true;
-                    }
-                }
-                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                    // No data available, continue
-                }
+            // Receive everything pending in as few syscalls as possible
+            match socket.recv_into(quic_conn) {
+                Ok(received) => data_processed |= received > 0,
                 Err(e) => {
                     tracing::warn!(
                         target: "quyc::protocols::h3",
@@ -325,34 +312,14 @@ impl H3Connection {
             }
             
             // Send any pending data
-            loop {
-                let mut out = [0; 1350];
-                match quic_conn.send(&mut out) {
-                    Ok((len, send_info)) => {
-                        if len == 0 {
-                            break;
-                        }
-                        if let Err(e) = socket.send_to(&out[..len], send_info.to) {
-                            tracing::warn!(
-                                target: "quyc::protocols::h3",
-                                error = %e,
-                                packet_len = len,
-                                destination = %send_info.to,
-                                "Failed to send QUIC packet during handshake"
-                            );
-                        } else {
-                            data_processed = true;
-                        }
-                    }
-                    Err(quiche::Error::Done) => break,
-                    Err(e) => {
-                        tracing::warn!(
-                            target: "quyc::protocols::h3",
-                            error = %e,
-                            "QUIC send error during handshake"
-                        );
-                        break;
-                    }
+            match socket.flush(quic_conn) {
+                Ok(sent) => data_processed |= sent > 0,
+                Err(e) => {
+                    tracing::warn!(
+                        target: "quyc::protocols::h3",
+                        error = %e,
+                        "Failed to send QUIC packets during handshake"
+                    );
                 }
             }
             
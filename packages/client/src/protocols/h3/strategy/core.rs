@@ -3,6 +3,7 @@
 //! Main H3Strategy struct and protocol strategy interface implementation.
 
 // SocketAddr import removed - not used
+use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 use ystream::{AsyncStream, spawn_task};
@@ -11,7 +12,10 @@ use crate::protocols::strategy_trait::ProtocolStrategy;
 // ProtocolConfig import removed - not used
 use crate::protocols::strategy::H3Config;
 use crate::http::{HttpRequest, HttpResponse};
+use crate::http::informational::InformationalResponse;
 use crate::http::response::{HttpBodyChunk, HttpHeader};
+use crate::protocols::quiche::QlogConfig;
+use crate::telemetry::{ConnectionTimings, QuicStatsRecorder, QuicStatsRegistry, quic_origin};
 
 use crate::protocols::h3::connection::H3Connection;
 
@@ -27,6 +31,8 @@ static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
 /// - Connection pooling
 pub struct H3Strategy {
     config: H3Config,
+    /// Client-wide registry that receives per-connection QUIC statistics
+    quic_stats: Option<Arc<QuicStatsRegistry>>,
 }
 
 impl H3Strategy {
@@ -34,8 +40,15 @@ impl H3Strategy {
     pub fn new(config: H3Config) -> Self {
         Self {
             config,
+            quic_stats: None,
         }
     }
+
+    /// Report QUIC transport statistics of every connection to `registry`
+    pub fn with_quic_stats(mut self, registry: Arc<QuicStatsRegistry>) -> Self {
+        self.quic_stats = Some(registry);
+        self
+    }
     
     /// Convert H3Config to quiche::Config
     pub(crate) fn create_quiche_config(&self) -> Result<quiche::Config, crate::error::HttpError> {
@@ -95,6 +108,15 @@ impl H3Strategy {
         // SECURITY: Enable certificate verification using TlsManager infrastructure
         config.verify_peer(true);
         
+        // Opt-in TLS key logging (explicit file or SSLKEYLOGFILE)
+        crate::tls::key_log::enable_quiche_key_log(&mut config, self.config.key_log_file.as_deref());
+        
+        // Extra trust anchors (private CAs, test servers)
+        if let Err(e) = crate::tls::roots::load_quiche_root_certificates(&mut config, &self.config.root_certificates) {
+            return Err(crate::error::HttpError::new(crate::error::Kind::Request)
+                .with(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())));
+        }
+        
         // Integrate with existing TLS infrastructure - QUICHE has its own certificate loading
         // Since QUICHE uses its own TLS backend (BoringSSL), we cannot directly integrate 
         // with rustls-based TlsManager. Instead, we let QUICHE use its default CA bundle
@@ -121,6 +143,7 @@ impl ProtocolStrategy for H3Strategy {
         let (headers_tx, headers_internal) = AsyncStream::<HttpHeader, 256>::channel();
         let (body_tx, body_internal) = AsyncStream::<HttpBodyChunk, 1024>::channel();
         let (_trailers_tx, trailers_internal) = AsyncStream::<HttpHeader, 64>::channel();
+        let (informational_tx, informational_internal) = AsyncStream::<InformationalResponse, 16>::channel();
         
         // Extract request details for task
         let method = request.method().clone();
@@ -147,14 +170,36 @@ impl ProtocolStrategy for H3Strategy {
             }
         };
         
+        // Create the response up front so the task can publish transport statistics into it
+        let response = HttpResponse::new(
+            headers_internal,
+            body_internal,
+            trailers_internal,
+            Version::HTTP_3,
+            0, // stream_id
+        )
+        .with_informational_stream(informational_internal);
+        let stats_recorder = QuicStatsRecorder::new(
+            quic_origin(&scheme, &host, port),
+            response.quic_stats_handle(),
+            self.quic_stats.clone(),
+        );
+        
+        let timings_handle = response.connection_timings_handle();
+        
         // Spawn task to handle H3 protocol
         spawn_task(move || {
             // Create quiche connection directly
             let scid = generate_connection_id();
             let local_addr = "127.0.0.1:0".parse().unwrap();
             let peer_addr = format!("{}:{}", host, port).parse().unwrap();
+            ConnectionTimings {
+                remote_addr: Some(peer_addr),
+                ..ConnectionTimings::default()
+            }
+            .publish(&timings_handle);
             
-            let quic_conn = match quiche::connect(None, &scid, local_addr, peer_addr, &mut quic_config) {
+            let mut quic_conn = match quiche::connect(None, &scid, local_addr, peer_addr, &mut quic_config) {
                 Ok(conn) => conn,
                 Err(e) => {
                     tracing::error!("Failed to create QUIC connection: {}", e);
@@ -162,6 +207,13 @@ impl ProtocolStrategy for H3Strategy {
                 }
             };
             
+            if let Some(qlog) = QlogConfig::resolve(config.qlog.as_ref()) {
+                qlog.attach(&mut quic_conn, &quic_origin(&scheme, &host, port));
+            }
+            if let Some(path) = crate::tls::key_log_path(config.key_log_file.as_deref()) {
+                crate::tls::key_log::attach_quiche_key_log(&mut quic_conn, &path);
+            }
+            
             // Create H3 connection manager with established QUIC connection
             let connection = H3Connection::new(quic_conn, crate::protocols::core::TimeoutConfig {
                 request_timeout: config.max_idle_timeout,
@@ -188,9 +240,17 @@ impl ProtocolStrategy for H3Strategy {
             let response_stream = connection.send_request(&serialized_request, 1);
             
             // Forward response chunks to appropriate channels
+            let mut informational_tx = Some(informational_tx);
             for chunk in response_stream.collect() {
                 match chunk {
+                    crate::http::HttpChunk::Headers(status, headers_map) if status.is_informational() => {
+                        if let Some(tx) = &informational_tx {
+                            let _ = tx.try_send(InformationalResponse::new(status, headers_map));
+                        }
+                    },
                     crate::http::HttpChunk::Headers(_status, headers_map) => {
+                        // Final header block closes the informational stream
+                        informational_tx = None;
                         for (name, value) in &headers_map {
                             let header = crate::http::response::HttpHeader::new(name.clone(), value.clone());
                             let _ = headers_tx.try_send(header);
@@ -207,17 +267,10 @@ impl ProtocolStrategy for H3Strategy {
                     _ => {}
                 }
             }
+            
+            connection.record_stats(&stats_recorder);
         });
         
-        // Create and return HttpResponse
-        let response = HttpResponse::new(
-            headers_internal,
-            body_internal,
-            trailers_internal,
-            Version::HTTP_3,
-            0, // stream_id
-        );
-        
         // Set initial status
         response.set_status(StatusCode::OK);
         
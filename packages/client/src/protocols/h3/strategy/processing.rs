@@ -3,12 +3,12 @@
 //! Handles HTTP/3 request sending and response processing including body handling,
 //! multipart forms, streaming, and response parsing.
 
-use std::net::{SocketAddr, UdpSocket};
+use std::net::SocketAddr;
 // HashMap import removed - not used
 
 use crossbeam_utils::Backoff;
 use ystream::{emit, AsyncStreamSender, AsyncStream, prelude::MessageChunk};
-use http::{Method, HeaderMap, HeaderName, HeaderValue};
+use http::{Method, HeaderMap, HeaderName, HeaderValue, StatusCode};
 use bytes::Bytes;
 use quiche;
 use quiche::h3::NameValue;
@@ -16,7 +16,9 @@ use quiche::h3::NameValue;
 use crate::protocols::strategy::H3Config;
 use crate::protocols::core::ProtocolConfig;
 use crate::crypto::random::generate_boundary;
+use crate::http::informational::InformationalResponse;
 use crate::http::response::{HttpHeader, HttpBodyChunk, HttpChunk};
+use crate::protocols::quiche::QuicUdpSocket;
 
 /// H3 Request Processor
 ///
@@ -26,6 +28,8 @@ pub(crate) struct H3RequestProcessor {
     compression_algorithm: Option<crate::http::headers::CompressionAlgorithm>,
     /// Configuration for compression handling
     config: Option<H3Config>,
+    /// Receives interim 1xx responses; dropped once the final headers arrive
+    informational_tx: Option<AsyncStreamSender<InformationalResponse, 16>>,
 }
 
 impl H3RequestProcessor {
@@ -34,18 +38,25 @@ impl H3RequestProcessor {
         Self {
             compression_algorithm: None,
             config: None,
+            informational_tx: None,
         }
     }
 
+    /// Forward interim 1xx responses to `sender`
+    pub fn with_informational(mut self, sender: AsyncStreamSender<InformationalResponse, 16>) -> Self {
+        self.informational_tx = Some(sender);
+        self
+    }
+
     /// Process HTTP/3 request and response
     #[allow(clippy::too_many_arguments)]
     pub fn process_request(
         &mut self,
         quic_conn: &mut quiche::Connection,
         h3_conn: &mut quiche::h3::Connection,
-        socket: &UdpSocket,
+        socket: &mut QuicUdpSocket,
         _server_addr: SocketAddr,
-        local_addr: SocketAddr,
+        _local_addr: SocketAddr,
         method: Method,
         scheme: String,
         host: String,
@@ -114,7 +125,6 @@ impl H3RequestProcessor {
             quic_conn,
             h3_conn,
             socket,
-            local_addr,
             headers_tx,
             body_tx,
         );
@@ -345,7 +355,7 @@ impl H3RequestProcessor {
                     // Stream completion marker
                     break;
                 }
-                HttpChunk::Error(err) => {
+                HttpChunk::Error(err) | HttpChunk::Failure(_, err) => {
                     tracing::error!(target: "quyc::h3", error = %err, "Stream processing error");
                     break;
                 }
@@ -383,13 +393,11 @@ impl H3RequestProcessor {
         &mut self,
         quic_conn: &mut quiche::Connection,
         h3_conn: &mut quiche::h3::Connection,
-        socket: &UdpSocket,
-        local_addr: SocketAddr,
+        socket: &mut QuicUdpSocket,
         headers_tx: AsyncStreamSender<HttpHeader, 256>,
         body_tx: AsyncStreamSender<HttpBodyChunk, 1024>,
     ) {
         let mut response_complete = false;
-        let mut buf = [0; 65535];
         
         while !response_complete {
             // Poll H3 events
@@ -407,6 +415,7 @@ impl H3RequestProcessor {
                         offset: 0,
                         is_final: true,
                         timestamp: std::time::Instant::now(),
+                        failure: None,
                     });
                     response_complete = true;
                 }
@@ -429,7 +438,7 @@ impl H3RequestProcessor {
             }
             
             // Handle QUIC I/O
-            self.handle_quic_io(quic_conn, socket, local_addr, &mut buf);
+            self.handle_quic_io(quic_conn, socket);
         }
         
         // Signal completion
@@ -438,6 +447,7 @@ impl H3RequestProcessor {
             offset: 0,
             is_final: true,
             timestamp: std::time::Instant::now(),
+            failure: None,
         });
     }
 
@@ -447,6 +457,35 @@ impl H3RequestProcessor {
         headers: Vec<quiche::h3::Header>,
         headers_tx: &AsyncStreamSender<HttpHeader, 256>,
     ) {
+        // Interim 1xx responses go to the informational stream, not the final headers
+        let status = headers
+            .iter()
+            .find(|header| header.name() == b":status")
+            .and_then(|header| StatusCode::from_bytes(header.value()).ok());
+        if let Some(status) = status.filter(StatusCode::is_informational) {
+            let interim_headers: HeaderMap = headers
+                .iter()
+                .filter_map(|header| {
+                    match (HeaderName::from_bytes(header.name()), HeaderValue::from_bytes(header.value())) {
+                        (Ok(name), Ok(value)) => Some((name, value)),
+                        _ => None,
+                    }
+                })
+                .collect();
+            tracing::debug!(
+                target: "quyc::protocols::h3",
+                status = status.as_u16(),
+                "Received HTTP/3 informational response"
+            );
+            if let Some(informational_tx) = &self.informational_tx {
+                // Intentionally ignore send result - receiver may not be interested in interim responses
+                drop(informational_tx.send(InformationalResponse::new(status, interim_headers)));
+            }
+            return;
+        }
+        // Final header block closes the informational stream
+        self.informational_tx = None;
+        
         let mut response_headers = HeaderMap::new();
         
         for header in headers {
@@ -535,6 +574,7 @@ impl H3RequestProcessor {
                         offset: 0,
                         is_final: false,
                         timestamp: std::time::Instant::now(),
+                        failure: None,
                     });
                 }
             }
@@ -550,69 +590,28 @@ impl H3RequestProcessor {
     }
 
     /// Handle QUIC I/O operations
+    ///
+    /// Reads all pending datagrams in GRO/recvmmsg batches and flushes
+    /// pending packets with GSO where available.
     fn handle_quic_io(
         &self,
         quic_conn: &mut quiche::Connection,
-        socket: &UdpSocket,
-        local_addr: SocketAddr,
-        buf: &mut [u8],
+        socket: &mut QuicUdpSocket,
     ) {
-        // Receive data
-        match socket.recv_from(buf) {
-            Ok((len, from)) => {
-                let recv_info = quiche::RecvInfo {
-                    from,
-                    to: local_addr,
-                };
-                if let Err(e) = quic_conn.recv(&mut buf[..len], recv_info) {
-                    tracing::warn!(
-                        target: "quyc::protocols::h3",
-                        error = %e,
-                        packet_len = len,
-                        "QUIC packet receive error during response processing"
-                    );
-                }
-            }
-            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                // No data available
-            }
-            Err(e) => {
-                tracing::warn!(
-                    target: "quyc::protocols::h3",
-                    error = %e,
-                    "UDP socket receive error during response processing"
-                );
-            }
+        if let Err(e) = socket.recv_into(quic_conn) {
+            tracing::warn!(
+                target: "quyc::protocols::h3",
+                error = %e,
+                "UDP socket receive error during response processing"
+            );
         }
-        
-        // Send pending data
-        loop {
-            let mut out = [0; 1350];
-            match quic_conn.send(&mut out) {
-                Ok((len, send_info)) => {
-                    if len == 0 {
-                        break;
-                    }
-                    if let Err(e) = socket.send_to(&out[..len], send_info.to) {
-                        tracing::warn!(
-                            target: "quyc::protocols::h3",
-                            error = %e,
-                            packet_len = len,
-                            destination = %send_info.to,
-                            "UDP socket send error during response processing"
-                        );
-                    }
-                }
-                Err(quiche::Error::Done) => break,
-                Err(e) => {
-                    tracing::warn!(
-                        target: "quyc::protocols::h3",
-                        error = %e,
-                        "QUIC send error during response processing"
-                    );
-                    break;
-                }
-            }
+
+        if let Err(e) = socket.flush(quic_conn) {
+            tracing::warn!(
+                target: "quyc::protocols::h3",
+                error = %e,
+                "UDP socket send error during response processing"
+            );
         }
     }
 }
\ No newline at end of file
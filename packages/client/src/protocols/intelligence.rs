@@ -5,7 +5,7 @@
 //! try the incorrect protocol twice for a domain.
 
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -45,6 +45,21 @@ pub struct DomainCapabilities {
     pub discovered_at: SystemTime,
     /// Last update timestamp
     pub last_updated: Arc<RwLock<SystemTime>>,
+    /// Request outcomes and availability when used as a load-balanced endpoint
+    pub health: EndpointHealth,
+}
+
+/// Atomic health tracking of a domain serving requests
+#[derive(Debug, Default)]
+pub struct EndpointHealth {
+    /// Requests sent and not yet finished
+    pub outstanding: AtomicUsize,
+    /// Exponentially weighted time to response headers in microseconds (0 = no sample yet)
+    pub ewma_latency_micros: AtomicU64,
+    /// Failures since the last success
+    pub consecutive_failures: AtomicU32,
+    /// Timestamp until which the domain is taken out of rotation (0 = in rotation)
+    pub ejected_until: AtomicU64,
 }
 
 /// Atomic protocol support tracking
@@ -221,6 +236,70 @@ impl AtomicProtocolSupport {
     }
 }
 
+impl EndpointHealth {
+    /// Weight of the newest sample in the latency average
+    const EWMA_WEIGHT: f64 = 0.3;
+
+    /// Track a request being sent
+    pub fn begin_request(&self) {
+        self.outstanding.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Track a request having finished
+    pub fn end_request(&self) {
+        // fetch_update only fails when the closure returns None
+        let _ = self
+            .outstanding
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| Some(count.saturating_sub(1)));
+    }
+
+    /// Track a healthy answer, returning the domain to rotation
+    pub fn record_success(&self, latency: Duration) {
+        let sample = latency.as_micros().max(1) as u64;
+        let _ = self.ewma_latency_micros.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |average| {
+            if average == 0 {
+                Some(sample)
+            } else {
+                Some((average as f64 + Self::EWMA_WEIGHT * (sample as f64 - average as f64)).max(1.0) as u64)
+            }
+        });
+        self.mark_healthy();
+    }
+
+    /// Return the domain to rotation, e.g. after a passing health probe
+    pub fn mark_healthy(&self) {
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+        self.ejected_until.store(0, Ordering::Relaxed);
+    }
+
+    /// Track a failed request
+    ///
+    /// Once `threshold` failures in a row are reached, the domain is taken
+    /// out of rotation for `ejection`. Returns whether it was ejected now.
+    pub fn record_failure(&self, threshold: u32, ejection: Duration) -> bool {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures < threshold {
+            return false;
+        }
+        let until = current_timestamp_nanos().saturating_add(ejection.as_nanos() as u64);
+        self.ejected_until.store(until, Ordering::Relaxed);
+        failures == threshold
+    }
+
+    /// Whether the domain is in rotation
+    pub fn is_available(&self) -> bool {
+        self.ejected_until.load(Ordering::Relaxed) <= current_timestamp_nanos()
+    }
+
+    /// Average time to response headers, once there is a sample
+    pub fn ewma_latency(&self) -> Option<Duration> {
+        match self.ewma_latency_micros.load(Ordering::Relaxed) {
+            0 => None,
+            micros => Some(Duration::from_micros(micros)),
+        }
+    }
+}
+
 impl DomainCapabilities {
     /// Create new domain capabilities tracker
     pub fn new(domain: String) -> Self {
@@ -233,6 +312,7 @@ impl DomainCapabilities {
             last_successful_protocol: Arc::new(RwLock::new(None)),
             discovered_at: SystemTime::now(),
             last_updated: Arc::new(RwLock::new(SystemTime::now())),
+            health: EndpointHealth::default(),
         }
     }
 
@@ -615,6 +695,11 @@ impl ProtocolIntelligence {
         }
     }
 
+    /// Capabilities and health of `domain`, tracking it from now on if it is new
+    pub fn domain(&self, domain: &str) -> Arc<DomainCapabilities> {
+        self.get_or_create_domain_capabilities(domain)
+    }
+
     /// Get domain capabilities (internal)
     fn get_domain_capabilities(&self, domain: &str) -> Option<Arc<DomainCapabilities>> {
         self.domains.read().ok()?.get(domain).cloned()
@@ -24,7 +24,7 @@ pub use core::{HttpVersion, TimeoutConfig};
 // Protocol adapters removed - using direct protocol methods instead
 
 // Re-export intelligence cache
-pub use intelligence::{ProtocolIntelligence, DomainCapabilities, IntelligenceConfig};
+pub use intelligence::{ProtocolIntelligence, DomainCapabilities, EndpointHealth, IntelligenceConfig};
 
 // Re-export connection types
 pub use connection::{Connection, ConnectionManager};
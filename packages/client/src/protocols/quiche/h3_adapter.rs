@@ -1,11 +1,12 @@
 // HashMap import removed - not used
-use std::net::SocketAddr;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, PoisonError};
 
 use ystream::prelude::*;
 use http::{HeaderMap, HeaderValue, Method, StatusCode};
 use quiche;
 
+use super::udp::QuicUdpSocket;
+
 /// HTTP/3 chunk for streaming responses
 #[derive(Debug, Clone)]
 pub struct Http3Chunk {
@@ -87,28 +88,19 @@ impl Http3Chunk {
 }
 
 /// HTTP/3 connection using quiche with AsyncStream
+///
+/// Packets go through a [`QuicUdpSocket`], so they are batched with GSO/GRO
+/// and marked for ECN as its configuration allows.
 pub struct Http3Connection {
     conn: Arc<Mutex<quiche::Connection>>,
-    socket: Arc<std::net::UdpSocket>,
-    peer_addr: SocketAddr,
+    socket: Arc<Mutex<QuicUdpSocket>>,
 }
 
 impl Http3Connection {
-    pub fn new(
-        conn: quiche::Connection,
-        socket: std::net::UdpSocket,
-        peer_addr: SocketAddr,
-    ) -> Self {
-        // Set socket to non-blocking for proper async operation
-        if let Err(io_error) = socket.set_nonblocking(true) {
-            log::warn!("Failed to set socket non-blocking: {}", io_error);
-            // Continue with blocking socket - not critical for functionality
-        }
-
+    pub fn new(conn: quiche::Connection, socket: QuicUdpSocket) -> Self {
         Self {
             conn: Arc::new(Mutex::new(conn)),
-            socket: Arc::new(socket),
-            peer_addr,
+            socket: Arc::new(Mutex::new(socket)),
         }
     }
 
@@ -126,7 +118,6 @@ impl Http3Connection {
         let body = body.map(|b| b.to_vec());
         let conn = Arc::clone(&self.conn);
         let socket = Arc::clone(&self.socket);
-        let peer_addr = self.peer_addr;
 
         AsyncStream::with_channel(move |sender| {
             // Get next available stream ID
@@ -207,82 +198,41 @@ impl Http3Connection {
             }
 
             // Process QUIC packets and read response
-            let mut buf = [0; 65535];
             let mut response_buf = [0; 65535];
 
             loop {
-                // Send any pending QUIC packets
-                loop {
-                    let (write, send_info) = {
-                        let mut conn_guard = match conn.lock() {
-                            Ok(guard) => guard,
-                            Err(_) => {
-                                emit!(
-                                    sender,
-                                    Http3Chunk::bad_chunk("Failed to lock connection".to_string())
-                                );
-                                return;
-                            }
-                        };
-
-                        match conn_guard.send(&mut buf) {
-                            Ok(v) => v,
-                            Err(quiche::Error::Done) => break,
-                            Err(e) => {
-                                emit!(
-                                    sender,
-                                    Http3Chunk::bad_chunk(format!("QUIC send error: {e}"))
-                                );
-                                return;
-                            }
-                        }
-                    };
-
-                    if let Err(e) = socket.send_to(&buf[..write], send_info.to) {
-                        if e.kind() != std::io::ErrorKind::WouldBlock {
-                            emit!(
-                                sender,
-                                Http3Chunk::bad_chunk(format!("Socket send error: {}", e))
-                            );
-                            return;
-                        }
+                let mut conn_guard = match conn.lock() {
+                    Ok(guard) => guard,
+                    Err(_) => {
+                        emit!(
+                            sender,
+                            Http3Chunk::bad_chunk("Failed to lock connection".to_string())
+                        );
+                        return;
                     }
+                };
+                let mut socket_guard = socket.lock().unwrap_or_else(PoisonError::into_inner);
+
+                // Send any pending QUIC packets
+                if let Err(e) = socket_guard.flush(&mut conn_guard) {
+                    emit!(
+                        sender,
+                        Http3Chunk::bad_chunk(format!("Socket send error: {}", e))
+                    );
+                    return;
                 }
 
                 // Receive QUIC packets
-                match socket.recv_from(&mut buf) {
-                    Ok((read, from)) => {
-                        let recv_info = quiche::RecvInfo {
-                            to: socket.local_addr().unwrap_or(peer_addr),
-                            from,
-                        };
-
-                        let mut conn_guard = match conn.lock() {
-                            Ok(guard) => guard,
-                            Err(_) => {
-                                emit!(
-                                    sender,
-                                    Http3Chunk::bad_chunk("Failed to lock connection".to_string())
-                                );
-                                return;
-                            }
-                        };
-
-                        if let Err(e) = conn_guard.recv(&mut buf[..read], recv_info) {
-                            if e != quiche::Error::Done {
-                                emit!(
-                                    sender,
-                                    Http3Chunk::bad_chunk(format!("QUIC recv error: {}", e))
-                                );
-                                return;
-                            }
-                        }
-                    }
-                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                let received = socket_guard.recv_into(&mut conn_guard);
+                drop(socket_guard);
+                drop(conn_guard);
+                match received {
+                    Ok(0) => {
                         // No more packets to read
                         std::thread::sleep(std::time::Duration::from_millis(1));
                         continue;
                     }
+                    Ok(_) => {}
                     Err(e) => {
                         emit!(
                             sender,
@@ -1,10 +1,9 @@
-use std::net::SocketAddr;
-
 use ystream::prelude::*;
 use http::{HeaderMap, Method};
 use quiche;
 
 use super::h3_adapter::{Http3Chunk, Http3Connection};
+use super::udp::QuicUdpSocket;
 
 /// Compatibility wrapper for the new AsyncStream-based HTTP/3 implementation
 pub struct Connection {
@@ -12,13 +11,9 @@ pub struct Connection {
 }
 
 impl Connection {
-    pub fn new(
-        conn: quiche::Connection,
-        socket: std::net::UdpSocket,
-        peer_addr: SocketAddr,
-    ) -> Self {
+    pub fn new(conn: quiche::Connection, socket: QuicUdpSocket) -> Self {
         Self {
-            inner: Http3Connection::new(conn, socket, peer_addr),
+            inner: Http3Connection::new(conn, socket),
         }
     }
 
@@ -6,8 +6,12 @@
 pub mod chunks;
 pub mod h3_adapter;
 pub mod h3_quiche;
+pub mod qlog;
 pub mod streaming;
+pub mod udp;
 
+pub use udp::{QuicUdpSocket, UdpIoConfig, UdpIoStats};
+pub use qlog::{CappedQlogWriter, QlogConfig, DEFAULT_QLOG_MAX_BYTES, QLOG_DIR_ENV};
 pub use chunks::{QuichePacketChunk, QuicheReadableChunk, QuicheStreamChunk, QuicheWriteResult};
 pub use streaming::QuicheConnectionChunk;
 pub use streaming::*;
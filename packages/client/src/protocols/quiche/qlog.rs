@@ -0,0 +1,160 @@
+//! qlog tracing for quiche connections
+//!
+//! Opt-in writer that streams quiche's qlog events for each QUIC connection to
+//! its own JSON-SEQ (`.sqlog`) file, suitable for loading into qvis. Tracing is
+//! enabled by an explicit directory on `H3Config`/`QuicheConfig`/`HttpConfig`
+//! or by the `QLOGDIR` environment variable.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the directory qlog files are written to
+pub const QLOG_DIR_ENV: &str = "QLOGDIR";
+
+/// Default cap on the size of a single qlog file (64MB)
+pub const DEFAULT_QLOG_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+/// JSON-SEQ record separator that prefixes every qlog event
+const RECORD_SEPARATOR: u8 = 0x1E;
+
+/// qlog output settings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QlogConfig {
+    /// Directory that receives one `.sqlog` file per connection
+    pub dir: PathBuf,
+    /// Maximum size of a single qlog file in bytes
+    pub max_bytes: u64,
+}
+
+impl QlogConfig {
+    /// Write qlog files to `dir` with the default size cap
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_bytes: DEFAULT_QLOG_MAX_BYTES,
+        }
+    }
+
+    /// Set the maximum size of a single qlog file
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Read the qlog directory from the `QLOGDIR` environment variable
+    pub fn from_env() -> Option<Self> {
+        std::env::var_os(QLOG_DIR_ENV)
+            .filter(|dir| !dir.is_empty())
+            .map(Self::new)
+    }
+
+    /// Use the explicit configuration when present, otherwise fall back to `QLOGDIR`
+    pub fn resolve(explicit: Option<&QlogConfig>) -> Option<Self> {
+        explicit.cloned().or_else(Self::from_env)
+    }
+
+    /// Path of the qlog file for a connection to `origin` with source connection ID `scid`
+    pub fn file_path(&self, origin: &str, scid: &[u8]) -> PathBuf {
+        let origin: String = origin
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+            .collect();
+        self.dir
+            .join(format!("{}-{}.sqlog", origin, hex::encode(scid)))
+    }
+
+    /// Start streaming qlog events of `connection` to its own file
+    ///
+    /// Failures to create the directory or file are logged and leave the
+    /// connection untraced; qlog is a debugging aid and never fails a request.
+    pub fn attach(&self, connection: &mut quiche::Connection, origin: &str) {
+        let path = self.file_path(origin, connection.source_id().as_ref());
+
+        let writer = match CappedQlogWriter::create(&path, self.max_bytes) {
+            Ok(writer) => writer,
+            Err(e) => {
+                tracing::warn!(
+                    target: "quyc::protocols::quiche::qlog",
+                    error = %e,
+                    path = %path.display(),
+                    "Failed to create qlog file, connection will not be traced"
+                );
+                return;
+            }
+        };
+
+        tracing::debug!(
+            target: "quyc::protocols::quiche::qlog",
+            path = %path.display(),
+            origin = %origin,
+            "Writing qlog for QUIC connection"
+        );
+
+        connection.set_qlog(
+            Box::new(writer),
+            format!("quyc {origin}"),
+            format!("quyc client connection to {origin}"),
+        );
+    }
+}
+
+/// File writer that stops recording once the size cap is reached
+///
+/// The cap is applied at record boundaries so the file always ends with a
+/// complete JSON-SEQ record; the last record may take the file slightly past
+/// the cap. [`QlogConfig::attach`] hands one to each traced connection.
+pub struct CappedQlogWriter {
+    file: BufWriter<File>,
+    path: PathBuf,
+    written: u64,
+    max_bytes: u64,
+    capped: bool,
+}
+
+impl CappedQlogWriter {
+    /// Create the file at `path`, and its parent directories, capped at `max_bytes`
+    pub fn create(path: &Path, max_bytes: u64) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(Self {
+            file: BufWriter::new(File::create(path)?),
+            path: path.to_path_buf(),
+            written: 0,
+            max_bytes,
+            capped: false,
+        })
+    }
+}
+
+impl Write for CappedQlogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.capped
+            && self.written >= self.max_bytes
+            && buf.first() == Some(&RECORD_SEPARATOR)
+        {
+            self.capped = true;
+            let _ = self.file.flush();
+            tracing::warn!(
+                target: "quyc::protocols::quiche::qlog",
+                path = %self.path.display(),
+                max_bytes = self.max_bytes,
+                "qlog file reached size cap, further events are dropped"
+            );
+        }
+
+        if self.capped {
+            // Report the bytes as consumed so quiche keeps the connection healthy
+            return Ok(buf.len());
+        }
+
+        let len = self.file.write(buf)?;
+        self.written += len as u64;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
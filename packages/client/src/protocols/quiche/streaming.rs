@@ -137,6 +137,11 @@ impl Clone for QuicheConnectionChunk {
 }
 
 /// Quiche QUIC connection wrapper providing streaming primitives
+///
+/// The socket is only held for the caller: packets come in and go out as
+/// bytes (see [`process_packets`](Self::process_packets)), so batching and
+/// ECN marking are up to whoever does the socket I/O, e.g. with
+/// [`QuicUdpSocket`](super::QuicUdpSocket).
 #[derive(Debug)]
 pub struct QuicheConnection {
     connection: QuicheConnectionWrapper,
@@ -0,0 +1,536 @@
+//! Batched UDP I/O for QUIC connections
+//!
+//! Wraps the connection's `std::net::UdpSocket` with `quinn-udp` so that, on
+//! Linux, outgoing packets of equal size are coalesced into a single
+//! `sendmsg` with `UDP_SEGMENT` (GSO) and incoming datagrams are read in
+//! batches with `recvmmsg` and `UDP_GRO`. Outgoing packets can be marked
+//! ECT(0) and the ECN codepoints of received packets are counted.
+//!
+//! Offload support is probed when the socket is created. If the kernel
+//! rejects a GSO send with `EIO` or `EINVAL`, segmentation offload is turned
+//! off for the socket and the packets are sent again one datagram per syscall.
+//!
+//! quiche's `RecvInfo` carries no ECN field, so received codepoints cannot be
+//! passed to its congestion controller; CE marks are only counted in
+//! [`UdpIoStats`] and logged. ECN is therefore off by default: a sender that
+//! marks packets ECT(0) tells routers to signal congestion with CE marks
+//! instead of drops, and quiche would never react to them.
+
+use std::io::{self, IoSliceMut};
+use std::net::{SocketAddr, UdpSocket};
+
+use quinn_udp::{EcnCodepoint, RecvMeta, Transmit, UdpSocketState, BATCH_SIZE};
+
+/// Largest datagram the receive path accepts, and the largest GRO aggregate
+const MAX_RECV_SEGMENT: usize = 65_535;
+
+/// Upper bound on segments per GSO send, matching the Linux limit
+const MAX_GSO_SEGMENTS: usize = 64;
+
+/// UDP offload and ECN settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UdpIoConfig {
+    /// Use GSO/GRO batching when the kernel supports it
+    pub offload: bool,
+    /// Mark outgoing packets ECT(0) and count received ECN codepoints
+    ///
+    /// Off by default, since quiche cannot be told about CE marks.
+    pub ecn: bool,
+    /// Maximum size of a single outgoing QUIC datagram
+    pub max_datagram_size: usize,
+}
+
+impl Default for UdpIoConfig {
+    fn default() -> Self {
+        Self {
+            offload: true,
+            ecn: false,
+            max_datagram_size: 1350,
+        }
+    }
+}
+
+/// Counters describing how datagrams moved through the socket
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UdpIoStats {
+    /// Datagrams handed to the kernel
+    pub datagrams_sent: u64,
+    /// Send syscalls issued
+    pub send_calls: u64,
+    /// Datagrams received, after splitting GRO aggregates
+    pub datagrams_received: u64,
+    /// Receive syscalls that returned data
+    pub recv_calls: u64,
+    /// Received datagrams marked ECT(0) or ECT(1)
+    pub ecn_ect_received: u64,
+    /// Received datagrams marked Congestion Experienced
+    pub ecn_ce_received: u64,
+}
+
+impl UdpIoStats {
+    /// Average datagrams per send syscall; above 1.0 when GSO is effective
+    #[inline]
+    pub fn datagrams_per_send(&self) -> f64 {
+        if self.send_calls == 0 {
+            0.0
+        } else {
+            self.datagrams_sent as f64 / self.send_calls as f64
+        }
+    }
+
+    /// Average datagrams per receive syscall; above 1.0 when GRO/recvmmsg is effective
+    #[inline]
+    pub fn datagrams_per_recv(&self) -> f64 {
+        if self.recv_calls == 0 {
+            0.0
+        } else {
+            self.datagrams_received as f64 / self.recv_calls as f64
+        }
+    }
+}
+
+/// Metadata of one received datagram
+#[derive(Debug, Clone, Copy)]
+pub struct RecvDatagram {
+    /// Sender address
+    pub from: SocketAddr,
+    /// Local address the datagram arrived on
+    pub to: SocketAddr,
+    /// ECN codepoint, when ECN reporting is enabled and the packet carried one
+    pub ecn: Option<EcnCodepoint>,
+}
+
+/// Non-blocking UDP socket with GSO/GRO batching and ECN support
+pub struct QuicUdpSocket {
+    socket: UdpSocket,
+    state: Option<UdpSocketState>,
+    local_addr: SocketAddr,
+    config: UdpIoConfig,
+    /// Set once the kernel has rejected a GSO send
+    gso_disabled: bool,
+    send_buf: Vec<u8>,
+    /// Batches in `send_buf` held back because the socket would block
+    pending: Vec<GsoBatch>,
+    recv_buf: Vec<u8>,
+    stats: UdpIoStats,
+}
+
+impl std::fmt::Debug for QuicUdpSocket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuicUdpSocket")
+            .field("local_addr", &self.local_addr)
+            .field("max_gso_segments", &self.max_gso_segments())
+            .field("gro_segments", &self.gro_segments())
+            .field("config", &self.config)
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+impl QuicUdpSocket {
+    /// Wrap a bound socket, probing offload support when enabled
+    ///
+    /// The socket is switched to non-blocking mode. If offload probing fails
+    /// the socket still works, one datagram per syscall.
+    pub fn new(socket: UdpSocket, config: UdpIoConfig) -> io::Result<Self> {
+        socket.set_nonblocking(true)?;
+        let local_addr = socket.local_addr()?;
+
+        let state = if config.offload || config.ecn {
+            match UdpSocketState::new((&socket).into()) {
+                Ok(state) => Some(state),
+                Err(e) => {
+                    tracing::debug!(
+                        target: "quyc::protocols::quiche::udp",
+                        error = %e,
+                        "UDP offload unavailable, using plain socket I/O"
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut this = Self {
+            socket,
+            state,
+            local_addr,
+            config,
+            gso_disabled: false,
+            send_buf: Vec::new(),
+            pending: Vec::new(),
+            recv_buf: Vec::new(),
+            stats: UdpIoStats::default(),
+        };
+        this.send_buf = vec![0; this.max_gso_segments() * config.max_datagram_size.max(1)];
+
+        tracing::debug!(
+            target: "quyc::protocols::quiche::udp",
+            local_addr = %local_addr,
+            max_gso_segments = this.max_gso_segments(),
+            gro_segments = this.gro_segments(),
+            ecn = config.ecn,
+            "QUIC UDP socket ready"
+        );
+
+        Ok(this)
+    }
+
+    /// Bind a new socket on `addr`
+    pub fn bind(addr: SocketAddr, config: UdpIoConfig) -> io::Result<Self> {
+        Self::new(UdpSocket::bind(addr)?, config)
+    }
+
+    /// Local address of the socket
+    #[inline]
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Underlying socket
+    #[inline]
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Segments per GSO send; 1 when GSO is off, unsupported or was rejected
+    pub fn max_gso_segments(&self) -> usize {
+        match (&self.state, self.config.offload) {
+            (Some(state), true) if !self.gso_disabled => state.max_gso_segments().clamp(1, MAX_GSO_SEGMENTS),
+            _ => 1,
+        }
+    }
+
+    /// Segments coalesced per GRO read; 1 when GRO is off or unsupported
+    pub fn gro_segments(&self) -> usize {
+        match (&self.state, self.config.offload) {
+            (Some(state), true) => state.gro_segments().max(1),
+            _ => 1,
+        }
+    }
+
+    /// I/O counters since the socket was created
+    #[inline]
+    pub fn stats(&self) -> UdpIoStats {
+        self.stats
+    }
+
+    /// Send `contents` to `destination` as `segment_size`-byte datagrams
+    ///
+    /// With GSO the whole buffer goes out in one syscall; otherwise each
+    /// segment is sent individually. The last segment may be shorter. When
+    /// the kernel rejects a GSO send, the rest goes out without offload.
+    pub fn send_segments(
+        &mut self,
+        destination: SocketAddr,
+        contents: &[u8],
+        segment_size: usize,
+    ) -> io::Result<()> {
+        self.send_segments_counted(destination, contents, segment_size).1
+    }
+
+    /// [`send_segments`](Self::send_segments), also returning how many bytes
+    /// of `contents` went out before any error
+    fn send_segments_counted(
+        &mut self,
+        destination: SocketAddr,
+        contents: &[u8],
+        segment_size: usize,
+    ) -> (usize, io::Result<()>) {
+        let segment_size = segment_size.max(1);
+        let mut offset = 0;
+        while offset < contents.len() {
+            // Split into GSO-sized sends; one datagram per send when GSO is off
+            let gso_segments = self.max_gso_segments();
+            let end = contents.len().min(offset + segment_size * gso_segments);
+            let chunk = &contents[offset..end];
+            match self.send_chunk(destination, chunk, segment_size) {
+                Ok(()) => {}
+                Err(e) if gso_segments > 1 && is_gso_rejection(&e) => {
+                    tracing::debug!(
+                        target: "quyc::protocols::quiche::udp",
+                        error = %e,
+                        "Kernel rejected GSO send, disabling segmentation offload"
+                    );
+                    self.gso_disabled = true;
+                    continue;
+                }
+                Err(e) => return (offset, Err(e)),
+            }
+            self.stats.send_calls += 1;
+            self.stats.datagrams_sent += chunk.len().div_ceil(segment_size) as u64;
+            offset = end;
+        }
+        (offset, Ok(()))
+    }
+
+    /// Send `chunk` in one syscall, as a GSO send when it spans several segments
+    fn send_chunk(&self, destination: SocketAddr, chunk: &[u8], segment_size: usize) -> io::Result<()> {
+        let Some(state) = &self.state else {
+            return self.socket.send_to(chunk, destination).map(drop);
+        };
+        let transmit = Transmit {
+            destination,
+            ecn: self.config.ecn.then_some(EcnCodepoint::Ect0),
+            contents: chunk,
+            segment_size: (chunk.len() > segment_size).then_some(segment_size),
+            src_ip: None,
+        };
+        state.try_send((&self.socket).into(), &transmit)
+    }
+
+    /// Receive one batch of datagrams, calling `on_datagram` for each
+    ///
+    /// Returns the number of datagrams delivered, or `WouldBlock` when
+    /// nothing is pending.
+    pub fn recv_batch(
+        &mut self,
+        mut on_datagram: impl FnMut(&mut [u8], RecvDatagram),
+    ) -> io::Result<usize> {
+        let Some(state) = &self.state else {
+            if self.recv_buf.len() < MAX_RECV_SEGMENT {
+                self.recv_buf.resize(MAX_RECV_SEGMENT, 0);
+            }
+            let (len, from) = self.socket.recv_from(&mut self.recv_buf)?;
+            self.stats.recv_calls += 1;
+            self.stats.datagrams_received += 1;
+            let to = self.local_addr;
+            on_datagram(&mut self.recv_buf[..len], RecvDatagram { from, to, ecn: None });
+            return Ok(1);
+        };
+
+        if self.recv_buf.len() < BATCH_SIZE * MAX_RECV_SEGMENT {
+            self.recv_buf.resize(BATCH_SIZE * MAX_RECV_SEGMENT, 0);
+        }
+
+        let mut meta = [RecvMeta::default(); BATCH_SIZE];
+        let messages = {
+            let mut slices: Vec<IoSliceMut<'_>> = self
+                .recv_buf
+                .chunks_mut(MAX_RECV_SEGMENT)
+                .map(IoSliceMut::new)
+                .collect();
+            state.recv((&self.socket).into(), &mut slices, &mut meta)?
+        };
+        self.stats.recv_calls += 1;
+
+        let mut delivered = 0;
+        for (index, meta) in meta.iter().take(messages).enumerate() {
+            let ecn = if self.config.ecn { meta.ecn } else { None };
+            let to = meta
+                .dst_ip
+                .map_or(self.local_addr, |ip| SocketAddr::new(ip, self.local_addr.port()));
+            let start = index * MAX_RECV_SEGMENT;
+            let message = &mut self.recv_buf[start..start + meta.len];
+            let stride = meta.stride.max(1);
+
+            for datagram in message.chunks_mut(stride) {
+                match ecn {
+                    Some(EcnCodepoint::Ce) => self.stats.ecn_ce_received += 1,
+                    Some(_) => self.stats.ecn_ect_received += 1,
+                    None => {}
+                }
+                on_datagram(datagram, RecvDatagram { from: meta.addr, to, ecn });
+                delivered += 1;
+            }
+        }
+
+        if self.config.ecn && ecn_ce_seen(&meta[..messages]) {
+            tracing::debug!(
+                target: "quyc::protocols::quiche::udp",
+                total_ce = self.stats.ecn_ce_received,
+                "Received ECN Congestion Experienced marks"
+            );
+        }
+
+        self.stats.datagrams_received += delivered as u64;
+        Ok(delivered)
+    }
+
+    /// Read all pending datagrams and feed them to `connection`
+    ///
+    /// Returns the number of datagrams processed; `WouldBlock` is not an error.
+    pub fn recv_into(&mut self, connection: &mut quiche::Connection) -> io::Result<usize> {
+        let mut total = 0;
+        loop {
+            let result = self.recv_batch(|datagram, info| {
+                let recv_info = quiche::RecvInfo {
+                    from: info.from,
+                    to: info.to,
+                };
+                if let Err(e) = connection.recv(datagram, recv_info) {
+                    tracing::warn!(
+                        target: "quyc::protocols::quiche::udp",
+                        error = %e,
+                        packet_len = datagram.len(),
+                        "QUIC packet receive error"
+                    );
+                }
+            });
+            match result {
+                Ok(count) => total += count,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(total),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Send every packet `connection` has pending, batching equal-size packets per GSO send
+    ///
+    /// Returns the number of packets sent. Once the socket would block,
+    /// draining stops; the packets already taken from `connection` are kept
+    /// and go out first on the next flush.
+    pub fn flush(&mut self, connection: &mut quiche::Connection) -> io::Result<usize> {
+        let mut send_buf = std::mem::take(&mut self.send_buf);
+        let result = self.flush_with(connection, &mut send_buf);
+        // Kept on errors too, so the next flush does not allocate again
+        self.send_buf = send_buf;
+        result
+    }
+
+    fn flush_with(&mut self, connection: &mut quiche::Connection, send_buf: &mut Vec<u8>) -> io::Result<usize> {
+        let max_segments = self.max_gso_segments();
+        let datagram_size = self.config.max_datagram_size.max(1);
+        if send_buf.len() < max_segments * datagram_size {
+            send_buf.resize(max_segments * datagram_size, 0);
+        }
+
+        let mut sent = 0;
+        // Packets held back by a full socket go out first, in order
+        let mut pending = std::mem::take(&mut self.pending);
+        while let Some(batch) = pending.first_mut() {
+            sent += self.send_batch(send_buf, batch)?;
+            if batch.count > 0 {
+                self.pending = pending;
+                return Ok(sent);
+            }
+            pending.remove(0);
+        }
+
+        let mut batch = GsoBatch::default();
+        loop {
+            let offset = batch.len;
+            let (len, send_info) = match connection.send(&mut send_buf[offset..offset + datagram_size]) {
+                Ok(packet) => packet,
+                Err(quiche::Error::Done) => break,
+                Err(e) => {
+                    tracing::warn!(
+                        target: "quyc::protocols::quiche::udp",
+                        error = %e,
+                        "QUIC send error"
+                    );
+                    break;
+                }
+            };
+
+            // A packet that cannot join the batch flushes it and starts a new one
+            if batch.count > 0 && (send_info.to != batch.destination || len > batch.segment_size) {
+                sent += self.send_batch(send_buf, &mut batch)?;
+                if batch.count > 0 {
+                    // The packet just taken waits behind the blocked batch
+                    let mut next = GsoBatch { start: offset, ..GsoBatch::default() };
+                    next.push(len, send_info.to);
+                    self.pending = vec![batch, next];
+                    return Ok(sent);
+                }
+                send_buf.copy_within(offset..offset + len, 0);
+                batch = GsoBatch::default();
+            }
+
+            batch.push(len, send_info.to);
+
+            // A short packet must be the last segment of a GSO send
+            let short = len < batch.segment_size;
+            if short || batch.count == max_segments {
+                sent += self.send_batch(send_buf, &mut batch)?;
+                if batch.count > 0 {
+                    self.pending = vec![batch];
+                    return Ok(sent);
+                }
+                batch = GsoBatch::default();
+            }
+        }
+
+        if batch.count > 0 {
+            sent += self.send_batch(send_buf, &mut batch)?;
+            if batch.count > 0 {
+                self.pending = vec![batch];
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Send `batch` from `send_buf`, returning the packets sent
+    ///
+    /// When the socket would block, `batch` is left holding the packets
+    /// that did not go out.
+    fn send_batch(&mut self, send_buf: &[u8], batch: &mut GsoBatch) -> io::Result<usize> {
+        let contents = &send_buf[batch.start..batch.start + batch.len];
+        let (written, result) = self.send_segments_counted(batch.destination, contents, batch.segment_size);
+        let packets = written.div_ceil(batch.segment_size.max(1));
+        batch.advance(written, packets);
+        match result {
+            Ok(()) => Ok(packets),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                tracing::debug!(
+                    target: "quyc::protocols::quiche::udp",
+                    packets = batch.count,
+                    "UDP send would block, holding packets for the next flush"
+                );
+                Ok(packets)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Packets accumulated for one GSO send, stored in the send buffer from `start`
+#[derive(Clone, Copy)]
+struct GsoBatch {
+    destination: SocketAddr,
+    segment_size: usize,
+    start: usize,
+    count: usize,
+    len: usize,
+}
+
+impl Default for GsoBatch {
+    fn default() -> Self {
+        Self {
+            destination: SocketAddr::from(([0, 0, 0, 0], 0)),
+            segment_size: 0,
+            start: 0,
+            count: 0,
+            len: 0,
+        }
+    }
+}
+
+impl GsoBatch {
+    /// Drop the first `len` bytes, `count` packets, once they were sent
+    fn advance(&mut self, len: usize, count: usize) {
+        self.start += len;
+        self.len -= len;
+        self.count -= count;
+    }
+
+    fn push(&mut self, len: usize, destination: SocketAddr) {
+        if self.count == 0 {
+            self.destination = destination;
+            self.segment_size = len;
+        }
+        self.count += 1;
+        self.len += len;
+    }
+}
+
+/// `EIO` and `EINVAL` are how Linux refuses segmentation offload it cannot do,
+/// e.g. without checksum offload on the route's device
+fn is_gso_rejection(error: &io::Error) -> bool {
+    matches!(error.raw_os_error(), Some(libc::EIO | libc::EINVAL))
+}
+
+fn ecn_ce_seen(meta: &[RecvMeta]) -> bool {
+    meta.iter().any(|m| m.ecn == Some(EcnCodepoint::Ce))
+}
@@ -3,6 +3,8 @@
 //! Converts AsyncStream<HttpChunk, 1024> from protocol implementations to canonical HttpResponse
 //! with proper header parsing, status extraction, and body stream conversion.
 
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU16, Ordering};
 use std::time::Instant;
 
 use ystream::{AsyncStream, emit, spawn_task};
@@ -10,6 +12,7 @@ use http::{HeaderMap, StatusCode};
 use bytes::Bytes;
 
 use crate::prelude::*;
+use crate::http::informational::InformationalResponse;
 use crate::http::response::{HttpResponse, HttpBodyChunk};
 
 /// Convert AsyncStream<HttpChunk, 1024> to HttpResponse
@@ -37,11 +40,21 @@ pub fn convert_http_chunks_to_response(
     let (headers_sender, headers_stream) = AsyncStream::<crate::http::response::HttpHeader, 256>::channel();
     let (_trailers_sender, _trailers_stream) = AsyncStream::<crate::http::response::HttpHeader, 64>::channel();
     
+    // Status is published before the headers so readers of the first header see it
+    let status = Arc::new(AtomicU16::new(0));
+    let task_status = status.clone();
+    
+    // Interim 1xx responses; the sender is dropped once the final header block is seen
+    let (informational_sender, informational_stream) = AsyncStream::<InformationalResponse, 16>::channel();
+    
     // Create body stream by filtering and converting HttpChunks
     let body_stream = AsyncStream::with_channel(move |sender| {
         spawn_task(move || {
             let mut parsing_headers_local = true;
             let mut header_buffer_local = Vec::new();
+            let mut informational_sender = Some(informational_sender);
+            // Dropped after the final header block so the headers stream ends before the body
+            let mut headers_sender = Some(headers_sender);
             
             for chunk in chunk_stream {
                 match chunk {
@@ -50,28 +63,33 @@ pub fn convert_http_chunks_to_response(
                             // Accumulate data for header parsing
                             header_buffer_local.extend_from_slice(&data);
                             
-                            // Look for header/body separator (\r\n\r\n)
-                            if let Some(separator_pos) = find_header_body_separator(&header_buffer_local) {
+                            // Look for header/body separator (\r\n\r\n); interim 1xx
+                            // blocks precede the final header block
+                            while let Some(separator_pos) = find_header_body_separator(&header_buffer_local) {
                                 // Parse headers from buffer
                                 let header_section = &header_buffer_local[..separator_pos];
-                                let (_parsed_status, parsed_headers) = parse_http_response_headers(header_section);
+                                let (parsed_status, parsed_headers) = parse_http_response_headers(header_section);
+                                let body_start = separator_pos + 4; // Skip \r\n\r\n
+                                
+                                if parsed_status.is_informational() {
+                                    forward_informational(&informational_sender, parsed_status, parsed_headers);
+                                    header_buffer_local.drain(..body_start);
+                                    continue;
+                                }
+                                
+                                // Final header block - no more interim responses
+                                informational_sender = None;
                                 
                                 // Emit headers to headers stream
-                                for (name, value) in parsed_headers.iter() {
-                                    let header = crate::http::response::HttpHeader {
-                                        name: name.clone(),
-                                        value: value.clone(),
-                                        timestamp: Instant::now(),
-                                    };
-                                    // Intentionally ignore send result - channel may be closed
-                                    drop(headers_sender.send(header));
+                                task_status.store(parsed_status.as_u16(), Ordering::Release);
+                                if let Some(headers_sender) = headers_sender.take() {
+                                    forward_headers(&headers_sender, &parsed_headers);
                                 }
                                 
                                 // Switch to body parsing mode
                                 parsing_headers_local = false;
                                 
                                 // Emit remaining data as first body chunk if any
-                                let body_start = separator_pos + 4; // Skip \r\n\r\n
                                 if body_start < header_buffer_local.len() {
                                     let body_data = Bytes::copy_from_slice(&header_buffer_local[body_start..]);
                                     let body_chunk = HttpBodyChunk {
@@ -79,10 +97,12 @@ pub fn convert_http_chunks_to_response(
                                         offset: 0,
                                         is_final: false,
                                         timestamp: Instant::now(),
+                                        failure: None,
                                     };
                                     emit!(sender, body_chunk);
                                 }
                                 header_buffer_local.clear();
+                                break;
                             }
                         } else {
                             // Direct body data - emit as HttpBodyChunk
@@ -91,13 +111,21 @@ pub fn convert_http_chunks_to_response(
                                 offset: 0,
                                 is_final: false,
                                 timestamp: Instant::now(),
+                                failure: None,
                             };
                             emit!(sender, body_chunk);
                         }
                     }
-                    HttpChunk::Headers(_, _) => {
-                        // Headers are processed separately - skip in body conversion
-                        continue;
+                    HttpChunk::Headers(status, headers) if status.is_informational() => {
+                        forward_informational(&informational_sender, status, headers);
+                    }
+                    HttpChunk::Headers(status, headers) => {
+                        // Final header block ends the interim responses
+                        informational_sender = None;
+                        task_status.store(status.as_u16(), Ordering::Release);
+                        if let Some(headers_sender) = headers_sender.take() {
+                            forward_headers(&headers_sender, &headers);
+                        }
                     }
                     HttpChunk::Trailers(_) => {
                         // Trailers come after body - end body stream
@@ -106,6 +134,7 @@ pub fn convert_http_chunks_to_response(
                             offset: 0,
                             is_final: true,
                             timestamp: Instant::now(),
+                            failure: None,
                         };
                         emit!(sender, final_chunk);
                         break;
@@ -117,6 +146,7 @@ pub fn convert_http_chunks_to_response(
                             offset: 0,
                             is_final: true,
                             timestamp: Instant::now(),
+                            failure: None,
                         };
                         emit!(sender, final_chunk);
                         break;
@@ -128,10 +158,15 @@ pub fn convert_http_chunks_to_response(
                             offset: 0,
                             is_final: true,
                             timestamp: Instant::now(),
+                            failure: None,
                         };
                         emit!(sender, error_chunk);
                         break;
                     }
+                    HttpChunk::Failure(kind, message) => {
+                        emit!(sender, HttpBodyChunk::failed(kind, message));
+                        break;
+                    }
                 }
             }
         });
@@ -150,6 +185,41 @@ pub fn convert_http_chunks_to_response(
         http::Version::HTTP_2, // Default to HTTP/2 for this converter
         stream_id,
     )
+    .with_informational_stream(informational_stream)
+    .with_status_handle(status)
+}
+
+/// Send a final header block to the headers stream
+fn forward_headers(
+    headers_sender: &ystream::AsyncStreamSender<crate::http::response::HttpHeader, 256>,
+    headers: &HeaderMap,
+) {
+    for (name, value) in headers {
+        let header = crate::http::response::HttpHeader {
+            name: name.clone(),
+            value: value.clone(),
+            timestamp: Instant::now(),
+        };
+        // Intentionally ignore send result - channel may be closed
+        drop(headers_sender.send(header));
+    }
+}
+
+/// Forward an interim 1xx response to the informational stream, if still open
+fn forward_informational(
+    informational_sender: &Option<ystream::AsyncStreamSender<InformationalResponse, 16>>,
+    status: StatusCode,
+    headers: HeaderMap,
+) {
+    tracing::debug!(
+        target: "quyc::protocols::response",
+        status = status.as_u16(),
+        "Received informational response"
+    );
+    if let Some(informational_sender) = informational_sender {
+        // Intentionally ignore send result - receiver may not be interested in interim responses
+        drop(informational_sender.send(InformationalResponse::new(status, headers)));
+    }
 }
 
 /// Find the header/body separator in HTTP response data
@@ -215,7 +285,8 @@ fn parse_http_response_headers(header_data: &[u8]) -> (StatusCode, HeaderMap) {
                 http::header::HeaderName::try_from(name),
                 http::header::HeaderValue::try_from(value)
             ) {
-                headers.insert(header_name, header_value);
+                // Append so repeated headers such as Link are kept
+                headers.append(header_name, header_value);
             }
         }
     }
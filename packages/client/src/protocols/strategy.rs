@@ -3,6 +3,7 @@
 //! Provides strategy enumeration for protocol selection with automatic fallback
 //! and protocol-specific configuration management.
 
+use std::path::PathBuf;
 use std::time::Duration;
 // task imports removed - not used
 
@@ -10,8 +11,11 @@ use std::time::Duration;
 // http imports removed - not used
 // futures noop_waker import removed - not used
 
+use crate::client::SrvResolver;
 use crate::config::HttpConfig;
 use crate::protocols::core::{HttpVersion, ProtocolConfig, TimeoutConfig};
+use crate::protocols::quiche::QlogConfig;
+use crate::tls::EchPolicy;
 // connection import removed - not used
 // transport imports removed - not used
 // http types removed - not used
@@ -47,16 +51,31 @@ impl Default for HttpProtocolStrategy {
 impl HttpProtocolStrategy {
     /// Build the appropriate ProtocolStrategy implementation
     pub fn build(&self) -> Box<dyn crate::protocols::strategy_trait::ProtocolStrategy> {
+        self.build_with_quic_stats(None)
+    }
+
+    /// Build the strategy, reporting QUIC transport statistics to `quic_stats` when given
+    pub fn build_with_quic_stats(
+        &self,
+        quic_stats: Option<std::sync::Arc<crate::telemetry::QuicStatsRegistry>>,
+    ) -> Box<dyn crate::protocols::strategy_trait::ProtocolStrategy> {
         use crate::protocols::h2::strategy::H2Strategy;
         use crate::protocols::h3::strategy::H3Strategy;
         use crate::protocols::auto_strategy::AutoStrategy;
         
+        let with_stats = |strategy: H3Strategy| match &quic_stats {
+            Some(registry) => strategy.with_quic_stats(registry.clone()),
+            None => strategy,
+        };
+        
         match self {
+            Self::Http3(H3Config { ech_policy: EchPolicy::Require, .. })
+            | Self::Quiche(QuicheConfig { ech_policy: EchPolicy::Require, .. }) => Box::new(EchUnavailable),
             Self::Http2(config) => Box::new(H2Strategy::new(config.clone())),
-            Self::Http3(config) => Box::new(H3Strategy::new(config.clone())),
+            Self::Http3(config) => Box::new(with_stats(H3Strategy::new(config.clone()))),
             Self::Quiche(config) => {
                 // Quiche is just H3 with specific config
-                Box::new(H3Strategy::new(H3Config {
+                Box::new(with_stats(H3Strategy::new(H3Config {
                     max_idle_timeout: config.max_idle_timeout,
                     max_udp_payload_size: config.max_udp_payload_size,
                     initial_max_data: config.initial_max_data,
@@ -68,14 +87,143 @@ impl HttpProtocolStrategy {
                     enable_early_data: config.enable_early_data,
                     enable_0rtt: config.enable_early_data,
                     congestion_control: config.congestion_control,
-                }))
+                    qlog: config.qlog.clone(),
+                    key_log_file: config.key_log_file.clone(),
+                    udp_offload: config.udp_offload,
+                    enable_ecn: config.enable_ecn,
+                    root_certificates: config.root_certificates.clone(),
+                })))
             },
             Self::Auto { prefer, fallback_chain: _, configs } => {
-                Box::new(AutoStrategy::new(prefer.clone(), configs.clone()))
+                let strategy = AutoStrategy::new(prefer.clone(), configs.clone());
+                match quic_stats {
+                    Some(registry) => Box::new(strategy.with_quic_stats(registry)),
+                    None => Box::new(strategy),
+                }
             },
         }
     }
     
+    /// Write qlog traces for HTTP/3 connections made by this strategy
+    ///
+    /// Leaves existing per-protocol qlog settings untouched when `qlog` is `None`.
+    pub fn with_qlog(mut self, qlog: Option<QlogConfig>) -> Self {
+        let Some(qlog) = qlog else {
+            return self;
+        };
+        match &mut self {
+            Self::Http2(_) => {}
+            Self::Http3(config) => config.qlog = Some(qlog),
+            Self::Quiche(config) => config.qlog = Some(qlog),
+            Self::Auto { configs, .. } => {
+                configs.h3.qlog = Some(qlog.clone());
+                configs.quiche.qlog = Some(qlog);
+            }
+        }
+        self
+    }
+
+    /// Log TLS secrets of connections made by this strategy to `path`
+    ///
+    /// Leaves existing per-protocol settings untouched when `path` is `None`.
+    pub fn with_key_log_file(mut self, path: Option<PathBuf>) -> Self {
+        let Some(path) = path else {
+            return self;
+        };
+        match &mut self {
+            Self::Http2(config) => config.key_log_file = Some(path),
+            Self::Http3(config) => config.key_log_file = Some(path),
+            Self::Quiche(config) => config.key_log_file = Some(path),
+            Self::Auto { configs, .. } => {
+                configs.h2.key_log_file = Some(path.clone());
+                configs.h3.key_log_file = Some(path.clone());
+                configs.quiche.key_log_file = Some(path);
+            }
+        }
+        self
+    }
+
+    /// Trust additional PEM root certificates for connections made by this strategy
+    ///
+    /// Certificates are appended to any already configured per protocol.
+    pub fn with_root_certificates(mut self, root_certificates: &[String]) -> Self {
+        if root_certificates.is_empty() {
+            return self;
+        }
+        match &mut self {
+            Self::Http2(config) => config.root_certificates.extend_from_slice(root_certificates),
+            Self::Http3(config) => config.root_certificates.extend_from_slice(root_certificates),
+            Self::Quiche(config) => config.root_certificates.extend_from_slice(root_certificates),
+            Self::Auto { configs, .. } => {
+                configs.h2.root_certificates.extend_from_slice(root_certificates);
+                configs.h3.root_certificates.extend_from_slice(root_certificates);
+                configs.quiche.root_certificates.extend_from_slice(root_certificates);
+            }
+        }
+        self
+    }
+
+    /// Offer Encrypted Client Hello on connections made by this strategy
+    ///
+    /// Only HTTP/2 can offer ECH; quiche has no support for it. Under
+    /// `Require`, automatic selection therefore skips HTTP/3 and forced
+    /// HTTP/3 strategies refuse every request with a configuration error.
+    /// Leaves existing per-protocol settings untouched when `policy` is
+    /// `Disable`.
+    pub fn with_ech(mut self, policy: EchPolicy, config_list: Option<Vec<u8>>) -> Self {
+        if !policy.is_enabled() {
+            return self;
+        }
+        match &mut self {
+            Self::Http2(config) => {
+                config.ech_policy = policy;
+                config.ech_config_list = config_list;
+            }
+            Self::Http3(config) => config.ech_policy = policy,
+            Self::Quiche(config) => config.ech_policy = policy,
+            Self::Auto { configs, .. } => {
+                configs.h2.ech_policy = policy;
+                configs.h2.ech_config_list = config_list;
+                configs.h3.ech_policy = policy;
+                configs.quiche.ech_policy = policy;
+            }
+        }
+        self
+    }
+
+    /// Resolve DNS HTTPS records for connections made by this strategy with `resolver`
+    pub fn with_resolver(mut self, resolver: SrvResolver) -> Self {
+        match &mut self {
+            Self::Http2(config) => config.resolver = resolver,
+            Self::Http3(_) | Self::Quiche(_) => {}
+            Self::Auto { configs, .. } => configs.h2.resolver = resolver,
+        }
+        self
+    }
+
+    /// Strategy for a hedged duplicate of a request sent with this one
+    ///
+    /// Automatic selection forces its second preferred protocol so the
+    /// duplicate takes a different path; forced protocols stay as they are and
+    /// the duplicate gets its own connection.
+    pub fn hedge_alternative(&self) -> Self {
+        match self {
+            Self::Auto { prefer, fallback_chain, configs } => {
+                let alternative = prefer.get(1).or_else(|| fallback_chain.get(1));
+                match alternative {
+                    Some(HttpVersion::Http2) => Self::Http2(configs.h2.clone()),
+                    // HTTP/3 cannot offer ECH, so a required ECH keeps hedges on HTTP/2
+                    Some(HttpVersion::Http3) if configs.h3.ech_policy == EchPolicy::Require => {
+                        Self::Http2(configs.h2.clone())
+                    }
+                    Some(HttpVersion::Http3) => Self::Http3(configs.h3.clone()),
+                    None => self.clone(),
+                }
+            }
+            other => other.clone(),
+        }
+    }
+
     /// Create AI-optimized strategy for streaming workloads
     pub fn ai_optimized() -> Self {
         Self::Auto {
@@ -141,6 +289,16 @@ pub struct H2Config {
     pub keepalive_timeout: Duration,
     pub adaptive_window: bool,
     pub max_send_buffer_size: usize,
+    /// Additional PEM root certificates trusted for TLS connections
+    pub root_certificates: Vec<String>,
+    /// TLS key log file; `None` falls back to the `SSLKEYLOGFILE` environment variable
+    pub key_log_file: Option<PathBuf>,
+    /// Encrypted Client Hello policy for TLS connections
+    pub ech_policy: EchPolicy,
+    /// Explicit `ECHConfigList`; when `None`, configs are looked up in DNS HTTPS records
+    pub ech_config_list: Option<Vec<u8>>,
+    /// Resolver for the DNS HTTPS records, shared by the connections of a client
+    pub resolver: SrvResolver,
 }
 
 impl Default for H2Config {
@@ -155,6 +313,11 @@ impl Default for H2Config {
             keepalive_timeout: Duration::from_secs(10),
             adaptive_window: true,
             max_send_buffer_size: 1024 * 1024,
+            root_certificates: Vec::new(),
+            key_log_file: None,
+            ech_policy: EchPolicy::Disable,
+            ech_config_list: None,
+            resolver: SrvResolver::default(),
         }
     }
 }
@@ -171,6 +334,11 @@ impl H2Config {
             keepalive_timeout: Duration::from_secs(5),
             adaptive_window: true,
             max_send_buffer_size: 4 * 1024 * 1024, // 4MB
+            root_certificates: Vec::new(),
+            key_log_file: None,
+            ech_policy: EchPolicy::Disable,
+            ech_config_list: None,
+            resolver: SrvResolver::default(),
         }
     }
 }
@@ -203,6 +371,24 @@ impl ProtocolConfig for H2Config {
     }
 }
 
+/// Stand-in for HTTP/3 strategies while Encrypted Client Hello is required
+///
+/// quiche cannot offer ECH, so every request fails with a configuration error
+/// instead of sending the hostname in plaintext SNI.
+struct EchUnavailable;
+
+impl crate::protocols::strategy_trait::ProtocolStrategy for EchUnavailable {
+    fn execute(&self, _request: crate::http::HttpRequest) -> crate::http::HttpResponse {
+        crate::middleware::error_response(crate::error::configuration(
+            "Encrypted Client Hello is required, but HTTP/3 connections cannot offer it",
+        ))
+    }
+
+    fn protocol_name(&self) -> &'static str {
+        "HTTP/3"
+    }
+}
+
 /// HTTP/3 protocol configuration
 #[derive(Debug, Clone)]
 pub struct H3Config {
@@ -217,6 +403,24 @@ pub struct H3Config {
     pub enable_early_data: bool,
     pub enable_0rtt: bool,
     pub congestion_control: CongestionControl,
+    /// qlog output; `None` falls back to the `QLOGDIR` environment variable
+    pub qlog: Option<QlogConfig>,
+    /// TLS key log file; `None` falls back to the `SSLKEYLOGFILE` environment variable
+    pub key_log_file: Option<PathBuf>,
+    /// Batch UDP I/O with GSO/GRO where the kernel supports it
+    pub udp_offload: bool,
+    /// Mark outgoing QUIC packets ECT(0) and count received ECN codepoints
+    ///
+    /// Off by default: quiche cannot be told about CE marks, so it would not
+    /// slow down when routers signal congestion.
+    pub enable_ecn: bool,
+    /// Additional PEM root certificates trusted for QUIC connections
+    pub root_certificates: Vec<String>,
+    /// Encrypted Client Hello policy
+    ///
+    /// quiche cannot offer ECH, so under `Require` this protocol refuses every
+    /// request instead of sending the hostname in plaintext SNI.
+    pub ech_policy: EchPolicy,
 }
 
 impl Default for H3Config {
@@ -233,6 +437,12 @@ impl Default for H3Config {
             enable_early_data: true,
             enable_0rtt: true,
             congestion_control: CongestionControl::Cubic,
+            qlog: None,
+            key_log_file: None,
+            udp_offload: true,
+            enable_ecn: false,
+            root_certificates: Vec::new(),
+            ech_policy: EchPolicy::Disable,
         }
     }
 }
@@ -251,6 +461,12 @@ impl H3Config {
             enable_early_data: true,
             enable_0rtt: true,
             congestion_control: CongestionControl::Bbr,
+            qlog: None,
+            key_log_file: None,
+            udp_offload: true,
+            enable_ecn: false,
+            root_certificates: Vec::new(),
+            ech_policy: EchPolicy::Disable,
         }
     }
 
@@ -267,12 +483,29 @@ impl H3Config {
             enable_early_data: true,
             enable_0rtt: true,
             congestion_control: CongestionControl::Bbr,
+            qlog: None,
+            key_log_file: None,
+            udp_offload: true,
+            enable_ecn: false,
+            root_certificates: Vec::new(),
+            ech_policy: EchPolicy::Disable,
         }
     }
 
 
 }
 
+impl H3Config {
+    /// UDP socket settings for connections using this configuration
+    pub fn udp_io_config(&self) -> crate::protocols::quiche::UdpIoConfig {
+        crate::protocols::quiche::UdpIoConfig {
+            offload: self.udp_offload,
+            ecn: self.enable_ecn,
+            max_datagram_size: usize::from(self.max_udp_payload_size),
+        }
+    }
+}
+
 impl ProtocolConfig for H3Config {
     fn validate(&self) -> Result<(), String> {
         if self.max_idle_timeout.as_secs() == 0 {
@@ -317,6 +550,24 @@ pub struct QuicheConfig {
     pub congestion_control: CongestionControl,
     pub max_connection_window: u64,
     pub max_stream_window: u64,
+    /// qlog output; `None` falls back to the `QLOGDIR` environment variable
+    pub qlog: Option<QlogConfig>,
+    /// TLS key log file; `None` falls back to the `SSLKEYLOGFILE` environment variable
+    pub key_log_file: Option<PathBuf>,
+    /// Batch UDP I/O with GSO/GRO where the kernel supports it
+    pub udp_offload: bool,
+    /// Mark outgoing QUIC packets ECT(0) and count received ECN codepoints
+    ///
+    /// Off by default: quiche cannot be told about CE marks, so it would not
+    /// slow down when routers signal congestion.
+    pub enable_ecn: bool,
+    /// Additional PEM root certificates trusted for QUIC connections
+    pub root_certificates: Vec<String>,
+    /// Encrypted Client Hello policy
+    ///
+    /// quiche cannot offer ECH, so under `Require` this protocol refuses every
+    /// request instead of sending the hostname in plaintext SNI.
+    pub ech_policy: EchPolicy,
 }
 
 impl Default for QuicheConfig {
@@ -335,6 +586,12 @@ impl Default for QuicheConfig {
             congestion_control: CongestionControl::Cubic,
             max_connection_window: 25165824, // 24MB
             max_stream_window: 16777216,     // 16MB
+            qlog: None,
+            key_log_file: None,
+            udp_offload: true,
+            enable_ecn: false,
+            root_certificates: Vec::new(),
+            ech_policy: EchPolicy::Disable,
         }
     }
 }
@@ -355,6 +612,12 @@ impl QuicheConfig {
             congestion_control: CongestionControl::Bbr,
             max_connection_window: 268435456, // 256MB
             max_stream_window: 134217728,     // 128MB
+            qlog: None,
+            key_log_file: None,
+            udp_offload: true,
+            enable_ecn: false,
+            root_certificates: Vec::new(),
+            ech_policy: EchPolicy::Disable,
         }
     }
 
@@ -373,6 +636,12 @@ impl QuicheConfig {
             congestion_control: CongestionControl::Bbr,
             max_connection_window: 67108864, // 64MB
             max_stream_window: 33554432,     // 32MB
+            qlog: None,
+            key_log_file: None,
+            udp_offload: true,
+            enable_ecn: false,
+            root_certificates: Vec::new(),
+            ech_policy: EchPolicy::Disable,
         }
     }
 }
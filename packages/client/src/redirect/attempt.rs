@@ -4,7 +4,6 @@
 //! and the Action types that control what happens next in the redirect chain.
 
 use std::error::Error as StdError;
-use std::fmt;
 
 use http::StatusCode;
 
@@ -0,0 +1,216 @@
+//! Redirect following in the client's execution path
+//!
+//! Each response is held until its header block is in. A `301`, `302`,
+//! `303`, `307` or `308` with a `Location` is then followed as the
+//! [`Policy`] decides: `301`, `302` and `303` turn every method but `HEAD`
+//! into a `GET` without a body, while `307` and `308` send the same method
+//! and body again, unless the body was a stream that cannot be replayed, in
+//! which case the redirect itself is returned. Credentials are dropped on
+//! hops to another origin, hops from `https` to `http` are refused unless
+//! allowed, and the hops taken are recorded on the final response. Hops
+//! away from the origin of the first URL are not request-signed. Each hop
+//! is sent with its [`CookieContext`]: the chain turns cross-site once a
+//! redirect leaves the site of the first URL.
+
+use http::header::{CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, LOCATION, REFERER, TRANSFER_ENCODING};
+use http::{Method, StatusCode};
+use ystream::spawn_task;
+
+use super::history::RedirectHop;
+use super::policy::Policy;
+use super::{ActionKind, make_referer, remove_sensitive_headers};
+use crate::config::HttpConfig;
+use crate::cookie::CookieContext;
+use crate::cookie::store::site;
+use crate::error::HttpError;
+use crate::http::request::{HttpRequest, RequestBody};
+use crate::http::response::{HttpHeader, HttpResponse, ResponseSlot};
+use crate::retry::executor::{Answer, first_answer};
+use crate::Url;
+
+/// Redirect settings of a client
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Redirector {
+    /// Which redirects to follow
+    pub(crate) policy: Policy,
+    /// Follow redirects from `https` to `http`
+    pub(crate) allow_https_downgrade: bool,
+}
+
+/// One request's way through its redirects
+struct Chain {
+    policy: Policy,
+    allow_https_downgrade: bool,
+    https_only: bool,
+    header_timeout: std::time::Duration,
+    /// Whether the original body can be sent again; streams cannot
+    replayable_body: bool,
+}
+
+impl Redirector {
+    /// Send `request` with `send`, following redirects
+    ///
+    /// The first hop is sent right away and its errors are returned as is.
+    /// The response then fills in from the last hop in the background;
+    /// errors of later hops arrive as error responses.
+    pub(crate) fn follow<F>(&self, request: HttpRequest, config: &HttpConfig, send: F) -> Result<HttpResponse, HttpError>
+    where
+        F: Fn(HttpRequest, CookieContext) -> Result<HttpResponse, HttpError> + Send + 'static,
+    {
+        let context = CookieContext::new(request.method(), false);
+        if !request.follow_redirects || self.policy.is_none() {
+            return send(request, context);
+        }
+        let policy = if self.policy.is_default() {
+            Policy::limited(config.max_redirects.min(request.max_redirects as usize))
+        } else {
+            self.policy.clone()
+        };
+        let chain = Chain {
+            policy,
+            allow_https_downgrade: self.allow_https_downgrade,
+            https_only: config.https_only,
+            header_timeout: request.timeout().unwrap_or(config.timeout),
+            replayable_body: !matches!(request.body(), Some(RequestBody::Stream(_))),
+        };
+        // Everything but a streaming body survives the clone
+        let template = request.clone();
+        let first = send(request, context)?;
+        let (response, slot) = HttpResponse::pending(first.version(), first.stream_id);
+        spawn_task(move || chain.run(template, first, &send, slot));
+        Ok(response)
+    }
+}
+
+impl Chain {
+    /// Follow redirects until a response that is not followed
+    fn run<F>(&self, mut request: HttpRequest, mut response: HttpResponse, send: &F, slot: ResponseSlot)
+    where
+        F: Fn(HttpRequest, CookieContext) -> Result<HttpResponse, HttpError>,
+    {
+        let mut visited = vec![request.url().clone()];
+        let first_site = site(request.url());
+        let mut cross_site = false;
+        let mut hops = Vec::new();
+        loop {
+            let answer = first_answer(response, self.header_timeout);
+            let next = match &answer {
+                Answer::Headers { headers, response } => {
+                    self.next_request(&request, response.status_code(), headers, &visited)
+                }
+                Answer::Headless { .. } | Answer::TimedOut => Ok(None),
+            };
+            let (status, next) = match next {
+                Ok(Some(next)) => next,
+                Ok(None) => {
+                    slot.record_redirects(hops);
+                    answer.deliver(slot);
+                    return;
+                }
+                Err(error) => {
+                    drop(answer);
+                    slot.record_redirects(hops);
+                    slot.fill(Vec::new(), None, crate::middleware::error_response(error));
+                    return;
+                }
+            };
+
+            tracing::debug!(
+                target: "quyc::redirect",
+                from = %request.url(),
+                to = %next.url(),
+                status = status.as_u16(),
+                "Following redirect"
+            );
+            hops.push(RedirectHop {
+                status,
+                from: request.url().clone(),
+                to: next.url().clone(),
+                method: next.method().clone(),
+            });
+            visited.push(next.url().clone());
+            // Once off the first site, the rest of the chain stays cross-site
+            cross_site |= site(next.url()) != first_site;
+            // Dropping the answer closes the redirect's streams
+            drop(answer);
+            let context = CookieContext::new(next.method(), cross_site);
+            response = send(next.clone(), context).unwrap_or_else(crate::middleware::error_response);
+            request = next;
+        }
+    }
+
+    /// Request to send for a redirect answering `request`, if it is followed
+    fn next_request(
+        &self,
+        request: &HttpRequest,
+        status: Option<StatusCode>,
+        headers: &[HttpHeader],
+        visited: &[Url],
+    ) -> Result<Option<(StatusCode, HttpRequest)>, HttpError> {
+        let Some(status) = status else {
+            return Ok(None);
+        };
+        let keeps_body = match status {
+            StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND | StatusCode::SEE_OTHER => false,
+            StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT => true,
+            _ => return Ok(None),
+        };
+        let Some(location) = headers.iter().find(|header| header.name == LOCATION) else {
+            return Ok(None);
+        };
+        let current = request.url();
+        let next = location
+            .value
+            .to_str()
+            .ok()
+            .and_then(|location| current.join(location).ok())
+            .ok_or_else(|| crate::error::redirect("invalid Location header", current.clone()))?;
+
+        if keeps_body && request.has_body() && !self.replayable_body {
+            tracing::debug!(
+                target: "quyc::redirect",
+                to = %next,
+                "Not following redirect: a streaming body cannot be sent again"
+            );
+            return Ok(None);
+        }
+        if next.scheme() != "http" && next.scheme() != "https" {
+            return Err(crate::error::url_bad_scheme(next));
+        }
+        if next.scheme() == "http" {
+            if self.https_only {
+                return Err(crate::error::redirect("HTTPS is required", next));
+            }
+            if current.scheme() == "https" && !self.allow_https_downgrade {
+                return Err(crate::error::redirect("refusing to follow a redirect from https to http", next));
+            }
+        }
+        match self.policy.check(status, &next, visited) {
+            ActionKind::Follow => {}
+            ActionKind::Stop => return Ok(None),
+            ActionKind::Error(error) => return Err(crate::error::redirect(error, next)),
+        }
+
+        let mut next_request = request.clone().with_url(next.clone());
+        if !keeps_body {
+            if *request.method() != Method::HEAD {
+                next_request = next_request.with_method(Method::GET);
+            }
+            next_request = next_request.without_body();
+            let headers = next_request.headers_mut();
+            for name in [CONTENT_TYPE, CONTENT_LENGTH, CONTENT_ENCODING, TRANSFER_ENCODING] {
+                headers.remove(name);
+            }
+        }
+        if next.origin() != current.origin() {
+            next_request.auth = None;
+        }
+        next_request.left_first_origin = visited.first().is_some_and(|first| next.origin() != first.origin());
+        remove_sensitive_headers(next_request.headers_mut(), &next, visited);
+        match make_referer(&next, current) {
+            Some(referer) => next_request.headers_mut().insert(REFERER, referer),
+            None => next_request.headers_mut().remove(REFERER),
+        };
+        Ok(Some((status, next_request)))
+    }
+}
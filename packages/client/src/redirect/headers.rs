@@ -6,14 +6,12 @@
 use http::{HeaderMap, HeaderValue};
 
 use crate::Url;
-use http::header::{AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, REFERER, WWW_AUTHENTICATE};
+use http::header::{AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, WWW_AUTHENTICATE};
 
-/// Remove sensitive headers when redirecting across different hosts/ports
+/// Remove sensitive headers when redirecting to a different origin
 pub(crate) fn remove_sensitive_headers(headers: &mut HeaderMap, next: &Url, previous: &[Url]) {
     if let Some(previous) = previous.last() {
-        let cross_host = next.host_str() != previous.host_str()
-            || next.port_or_known_default() != previous.port_or_known_default();
-        if cross_host {
+        if next.origin() != previous.origin() {
             headers.remove(AUTHORIZATION);
             headers.remove(COOKIE);
             headers.remove("cookie2");
@@ -0,0 +1,26 @@
+//! Redirect history recorded on responses
+//!
+//! The hops a request took before its final response, as returned by
+//! [`HttpResponse::redirect_history`](crate::HttpResponse::redirect_history).
+
+use std::sync::{Arc, RwLock};
+
+use http::{Method, StatusCode};
+
+use crate::Url;
+
+/// One redirect followed on the way to a response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedirectHop {
+    /// Status of the redirect, e.g. `301 Moved Permanently`
+    pub status: StatusCode,
+    /// URL that answered with the redirect
+    pub from: Url,
+    /// URL the redirect pointed to
+    pub to: Url,
+    /// Method the request to `to` was sent with
+    pub method: Method,
+}
+
+/// Shared slot the hops of a response are recorded into
+pub type RedirectHistoryHandle = Arc<RwLock<Vec<RedirectHop>>>;
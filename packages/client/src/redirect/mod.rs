@@ -5,16 +5,16 @@
 //! `redirect::Policy` can be used with a `ClientBuilder`.
 
 mod attempt;
+mod follow;
 mod headers;
+mod history;
 mod policy;
-mod tower;
-
-
 
 // Re-export main types for backward compatibility
 // Re-export internal types for module coordination
 pub(crate) use attempt::ActionKind;
 pub use attempt::{Action, Attempt};
+pub(crate) use follow::Redirector;
 pub(crate) use headers::{make_referer, remove_sensitive_headers};
+pub use history::{RedirectHistoryHandle, RedirectHop};
 pub use policy::Policy;
-pub(crate) use tower::TowerRedirectPolicy;
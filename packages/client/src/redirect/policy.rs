@@ -14,12 +14,15 @@ use crate::Url;
 /// A type that controls the policy on how to handle the following of redirects.
 ///
 /// The default value will catch redirect loops, and has a maximum of 10
-/// redirects it will follow in a chain before returning an error.
+/// redirects it will follow in a chain before returning an error. Unless
+/// another policy is set on the client, the limit is lowered to the
+/// client's `max_redirects` and the request's own `max_redirects`.
 ///
 /// - `limited` can be used have the same as the default behavior, but adjust
 ///   the allowed maximum redirect hops in a chain.
 /// - `none` can be used to disable all redirect behavior.
 /// - `custom` can be used to create a customized policy.
+#[derive(Clone)]
 pub struct Policy {
     inner: PolicyKind,
 }
@@ -27,7 +30,8 @@ pub struct Policy {
 impl Policy {
     /// Create a `Policy` with a maximum number of redirects.
     ///
-    /// An `Error` will be returned if the max is reached.
+    /// An `Error` will be returned if the max is reached, or if a redirect
+    /// points back to a URL already requested in the chain.
     pub fn limited(max: usize) -> Self {
         Self {
             inner: PolicyKind::Limit(max),
@@ -58,7 +62,8 @@ impl Policy {
     /// # Example
     ///
     /// ```rust
-    /// # use crate::{HttpError, redirect};
+    /// # use quyc_client::client::configuration::HttpClientBuilder;
+    /// # use quyc_client::{HttpError, redirect};
     /// #
     /// # fn run() -> Result<(), HttpError> {
     /// let custom = redirect::Policy::custom(|attempt| {
@@ -71,7 +76,7 @@ impl Policy {
     ///         attempt.follow()
     ///     }
     /// });
-    /// let client = crate::HttpClient::new()
+    /// let client = HttpClientBuilder::new()
     ///     .redirect(custom)
     ///     .build()?;
     /// # Ok(())
@@ -84,7 +89,7 @@ impl Policy {
         T: Fn(Attempt) -> Action + Send + Sync + 'static,
     {
         Self {
-            inner: PolicyKind::Custom(Box::new(policy)),
+            inner: PolicyKind::Custom(Arc::new(policy)),
         }
     }
 
@@ -98,7 +103,7 @@ impl Policy {
     /// # Example
     ///
     /// ```rust
-    /// # use crate::{HttpError, redirect};
+    /// # use quyc_client::{HttpError, redirect};
     /// #
     /// # fn run() -> Result<(), HttpError> {
     /// let custom = redirect::Policy::custom(|attempt| {
@@ -115,6 +120,8 @@ impl Policy {
                 // The first URL in the previous is the initial URL and not a redirection. It needs to be excluded.
                 if attempt.previous.len() > max {
                     attempt.error(TooManyRedirects)
+                } else if attempt.previous.contains(attempt.next) {
+                    attempt.error(RedirectLoop)
                 } else {
                     attempt.follow()
                 }
@@ -135,6 +142,10 @@ impl Policy {
     pub(crate) fn is_default(&self) -> bool {
         matches!(self.inner, PolicyKind::Limit(10))
     }
+
+    pub(crate) fn is_none(&self) -> bool {
+        matches!(self.inner, PolicyKind::None)
+    }
 }
 
 impl Default for Policy {
@@ -144,8 +155,9 @@ impl Default for Policy {
     }
 }
 
+#[derive(Clone)]
 enum PolicyKind {
-    Custom(Box<dyn Fn(Attempt) -> Action + Send + Sync + 'static>),
+    Custom(Arc<dyn Fn(Attempt) -> Action + Send + Sync + 'static>),
     Limit(usize),
     None,
 }
@@ -176,3 +188,14 @@ impl fmt::Display for TooManyRedirects {
 }
 
 impl StdError for TooManyRedirects {}
+
+#[derive(Debug)]
+struct RedirectLoop;
+
+impl fmt::Display for RedirectLoop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("redirect loop detected")
+    }
+}
+
+impl StdError for RedirectLoop {}
@@ -0,0 +1,201 @@
+//! Client-wide retry budget
+//!
+//! Retries spend tokens and successful answers earn a fraction of one back,
+//! as in Finagle's retry budget and gRPC's retry throttling. While a provider
+//! is degraded, successes dry up, the tokens run out and requests degrade to a
+//! single attempt instead of multiplying the load by `max_attempts`.
+//!
+//! A budget can be shared by every origin, kept per origin, or both; a retry
+//! then needs a token from each.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use dashmap::DashMap;
+
+use crate::telemetry::retry_stats::{RetryBudgetSnapshot, RetryBudgetStats};
+
+/// Token bucket settings of a retry budget
+#[derive(Debug, Clone)]
+pub struct RetryBudgetConfig {
+    /// Tokens a successful answer earns; a retry costs one
+    pub success_refill: f64,
+    /// Tokens a bucket holds; buckets start full
+    pub max_tokens: u32,
+}
+
+impl Default for RetryBudgetConfig {
+    /// Retry at most one request in ten over time, with up to 10 retries banked
+    fn default() -> Self {
+        Self {
+            success_refill: 0.1,
+            max_tokens: 10,
+        }
+    }
+}
+
+impl RetryBudgetConfig {
+    /// Validate configuration for consistency
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.success_refill) {
+            return Err("success_refill must be between 0.0 and 1.0".to_string());
+        }
+        if self.max_tokens == 0 {
+            return Err("max_tokens must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Balance of one bucket, in thousandths of a token
+#[derive(Debug)]
+struct Bucket {
+    deposit: u64,
+    max_balance: u64,
+    balance: AtomicU64,
+}
+
+impl Bucket {
+    fn new(config: &RetryBudgetConfig) -> Self {
+        let max_balance = u64::from(config.max_tokens) * 1000;
+        Self {
+            deposit: (config.success_refill.clamp(0.0, 1.0) * 1000.0) as u64,
+            max_balance,
+            balance: AtomicU64::new(max_balance),
+        }
+    }
+
+    fn deposit(&self) {
+        // fetch_update only fails when the closure returns None
+        let _ = self.balance.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |balance| {
+            Some((balance + self.deposit).min(self.max_balance))
+        });
+    }
+
+    fn try_withdraw(&self) -> bool {
+        self.balance
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |balance| balance.checked_sub(1000))
+            .is_ok()
+    }
+
+    fn refund(&self) {
+        let _ = self.balance.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |balance| {
+            Some((balance + 1000).min(self.max_balance))
+        });
+    }
+
+    fn tokens(&self) -> f64 {
+        self.balance.load(Ordering::Relaxed) as f64 / 1000.0
+    }
+}
+
+/// Retry allowance shared by a client and its clones
+#[derive(Debug, Default)]
+pub struct RetryBudget {
+    global: Option<Bucket>,
+    per_origin: Option<RetryBudgetConfig>,
+    origins: DashMap<String, Bucket>,
+    stats: RetryBudgetStats,
+}
+
+impl RetryBudget {
+    /// Budget shared by all origins
+    pub fn global(config: RetryBudgetConfig) -> Self {
+        Self::default().with_global(config)
+    }
+
+    /// Budget kept separately for each origin
+    pub fn per_origin(config: RetryBudgetConfig) -> Self {
+        Self::default().with_per_origin(config)
+    }
+
+    /// Also draw retries from a bucket shared by all origins
+    pub fn with_global(mut self, config: RetryBudgetConfig) -> Self {
+        self.global = Some(Bucket::new(&config));
+        self
+    }
+
+    /// Also draw retries from a bucket per origin
+    pub fn with_per_origin(mut self, config: RetryBudgetConfig) -> Self {
+        self.per_origin = Some(config);
+        self.origins.clear();
+        self
+    }
+
+    /// Allowance and denial counters
+    #[inline]
+    pub fn stats(&self) -> &RetryBudgetStats {
+        &self.stats
+    }
+
+    /// Spend a token for retrying a request to `origin`
+    ///
+    /// Returns `false`, spending nothing, when any applicable bucket is empty.
+    pub fn try_acquire(&self, origin: &str) -> bool {
+        let global_ok = self.global.as_ref().is_none_or(Bucket::try_withdraw);
+        let origin_ok = global_ok
+            && self.per_origin.as_ref().is_none_or(|config| {
+                self.origins
+                    .entry(origin.to_string())
+                    .or_insert_with(|| Bucket::new(config))
+                    .try_withdraw()
+            });
+        if origin_ok {
+            self.stats.record_allowed();
+            return true;
+        }
+        if global_ok {
+            if let Some(global) = &self.global {
+                global.refund();
+            }
+        }
+        self.stats.record_denied();
+        tracing::debug!(
+            target: "quyc::retry",
+            origin = %origin,
+            "Retry budget exhausted, not retrying"
+        );
+        false
+    }
+
+    /// Earn retry allowance for a successful answer from `origin`
+    pub fn record_success(&self, origin: &str) {
+        if let Some(global) = &self.global {
+            global.deposit();
+        }
+        if let Some(config) = &self.per_origin {
+            self.origins
+                .entry(origin.to_string())
+                .or_insert_with(|| Bucket::new(config))
+                .deposit();
+        }
+        self.stats.record_success();
+    }
+
+    /// Tokens left in the shared bucket, if there is one
+    pub fn global_tokens(&self) -> Option<f64> {
+        self.global.as_ref().map(Bucket::tokens)
+    }
+
+    /// Tokens left for `origin`, if budgets are kept per origin
+    ///
+    /// Origins not seen yet have a full bucket.
+    pub fn origin_tokens(&self, origin: &str) -> Option<f64> {
+        let config = self.per_origin.as_ref()?;
+        Some(
+            self.origins
+                .get(origin)
+                .map_or(f64::from(config.max_tokens), |bucket| bucket.tokens()),
+        )
+    }
+
+    /// Create a snapshot of budget state and counters
+    pub fn snapshot(&self) -> RetryBudgetSnapshot {
+        let origin_tokens: HashMap<String, f64> = self
+            .origins
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().tokens()))
+            .collect();
+        self.stats.snapshot(self.global_tokens(), origin_tokens)
+    }
+}
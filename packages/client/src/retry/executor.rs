@@ -1,75 +1,347 @@
-use ystream::prelude::*;
+//! Streaming-aware retry execution
+//!
+//! Each attempt is judged as soon as its response headers arrive: a `429`,
+//! `502`, `503` or `504`, a connection failure, `GOAWAY` or `REFUSED_STREAM`
+//! before any headers, or no headers within the attempt timeout, makes the
+//! attempt retryable. The request is then sent again after the server's
+//! `Retry-After` or the policy's backoff. Any other answer is accepted and
+//! handed through with its body streaming unbuffered.
+//!
+//! Only requests that can be replayed are retried: idempotent methods, and
+//! other methods carrying an `Idempotency-Key` header or asking for one with
+//! [`HttpRequest::auto_idempotency_key`]. With a
+//! [`RetryBudget`] attached, each retry also needs a token from it, and
+//! with a [`RateLimiter`] attached, each retry waits for a send slot after
+//! its backoff.
+//!
+//! Transport failures are retryable by the error kind their strategy
+//! reports with them: `Connect` and `Timeout` errors are, anything else,
+//! such as a rejected certificate, is not. An attempt that times out is
+//! dropped along with its response streams. Waiting for the next attempt
+//! holds no thread.
 
-use super::RetryPolicy;
+use std::sync::mpsc;
+use std::sync::{Arc, PoisonError};
+use std::time::{Duration, Instant};
 
-/// Retry executor for HTTP operations using pure AsyncStreams
-pub struct HttpRetryExecutor<F, T>
+use futures::channel::oneshot;
+use futures::executor::block_on;
+use futures::future::{self, Either};
+use ystream::{AsyncStream, spawn_task};
+
+use super::budget::RetryBudget;
+use super::global::GLOBAL_RETRY_STATS;
+use super::{RetryPolicy, timer};
+use crate::client::rate_limit::RateLimiter;
+use crate::error::Kind;
+use crate::http::headers::parse_retry_after;
+use crate::http::request::HttpRequest;
+use crate::http::response::{HttpBodyChunk, HttpHeader, HttpResponse, ResponseSlot};
+use crate::telemetry::retry_stats::{AttemptOutcome, RetryAttempt, RetryStatsHandle};
+
+/// Retry executor for HTTP requests
+///
+/// `operation` sends one attempt of a request; it is called again for
+/// each retry with a clone of the original request.
+pub struct HttpRetryExecutor<F>
 where
-    F: Fn() -> AsyncStream<T, 1024> + Send + Sync + 'static,
-    T: MessageChunk + Send + Default + 'static,
+    F: Fn(HttpRequest) -> HttpResponse + Send + Sync + 'static,
 {
-    operation: std::sync::Arc<F>,
+    operation: Arc<F>,
     policy: RetryPolicy,
+    budget: Option<Arc<RetryBudget>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
-impl<F, T> HttpRetryExecutor<F, T>
+impl<F> HttpRetryExecutor<F>
 where
-    F: Fn() -> AsyncStream<T, 1024> + Send + Sync + 'static,
-    T: MessageChunk + Send + Default + 'static,
+    F: Fn(HttpRequest) -> HttpResponse + Send + Sync + 'static,
 {
     /// Create new retry executor for HTTP operation
     pub fn new(operation: F, policy: RetryPolicy) -> Self {
         Self {
-            operation: std::sync::Arc::new(operation),
+            operation: Arc::new(operation),
             policy,
+            budget: None,
+            rate_limiter: None,
         }
     }
 
-    /// Execute operation with retry logic using pure streaming patterns
-    pub fn execute_with_retry(&self) -> AsyncStream<T, 1024> {
-        let operation = std::sync::Arc::clone(&self.operation);
-        let policy = self.policy.clone();
-
-        AsyncStream::<T>::with_channel(move |sender| {
-            let mut attempt = 1;
-
-            loop {
-                // Execute the operation and collect results
-                let stream = (operation)();
-                let results: Vec<T> = stream.collect();
-
-                // Check if we got any successful results
-                let successful_results: Vec<T> = results
-                    .into_iter()
-                    .filter(|chunk| !chunk.is_error())
-                    .collect();
-
-                if !successful_results.is_empty() {
-                    // Success - emit all good results
-                    for result in successful_results {
-                        emit!(sender, result);
-                    }
-                    break;
-                }
+    /// Pay for retries from `budget`, and feed it successful answers
+    pub fn with_budget(mut self, budget: Arc<RetryBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
 
-                // No successful results - check if we should retry
-                if attempt >= policy.max_attempts {
-                    // Exhausted retries - emit error chunk
-                    emit!(
-                        sender,
-                        T::bad_chunk(format!(
-                            "Max retry attempts ({}) exceeded",
-                            policy.max_attempts
-                        ))
-                    );
-                    break;
-                }
+    /// Take a send slot from `limiter` for each retry
+    ///
+    /// The first attempt is expected to hold its slot already. A retry
+    /// that would wait longer than the limiter allows fails with a `429`
+    /// response carrying the limiter's error, as a first attempt would.
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Retry policy in use
+    #[inline]
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Send `request`, retrying retryable attempts
+    ///
+    /// Returns at once; the response fills in from the accepted attempt.
+    pub fn execute_with_retry(&self, request: HttpRequest) -> HttpResponse {
+        self.execute_with_stats(request).0
+    }
+
+    /// Like [`execute_with_retry`](Self::execute_with_retry), also returning
+    /// the statistics the attempts are recorded into as they finish
+    pub fn execute_with_stats(&self, mut request: HttpRequest) -> (HttpResponse, RetryStatsHandle) {
+        let (response, slot) = HttpResponse::pending(request.version(), 0);
+        let stats = RetryStatsHandle::default();
+        // Every attempt carries the same key, so the server applies the request once
+        request.ensure_idempotency_key();
+        let url = request.url().clone();
+        let retry = Retry {
+            operation: Arc::clone(&self.operation),
+            policy: self.policy.clone(),
+            budget: self.budget.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            replayable: RetryPolicy::is_replayable(&request),
+            origin: url.origin().ascii_serialization(),
+            url,
+            // A request that cannot be replayed is sent once, as is: cloning drops a streaming body
+            request: Some(request),
+            slot,
+            stats: stats.clone(),
+            attempt: 1,
+        };
+
+        GLOBAL_RETRY_STATS.record_operation();
+        spawn_task(move || retry.send());
+        (response, stats)
+    }
+}
+
+/// A request on its way through its attempts
+struct Retry<F> {
+    operation: Arc<F>,
+    policy: RetryPolicy,
+    budget: Option<Arc<RetryBudget>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    request: Option<HttpRequest>,
+    replayable: bool,
+    url: url::Url,
+    origin: String,
+    slot: ResponseSlot,
+    stats: RetryStatsHandle,
+    attempt: u32,
+}
+
+impl<F> Retry<F>
+where
+    F: Fn(HttpRequest) -> HttpResponse + Send + Sync + 'static,
+{
+    /// Send the current attempt, and schedule the next one if it is retried
+    fn send(mut self) {
+        let attempt_request = if self.replayable { self.request.clone() } else { self.request.take() };
+        let Some(attempt_request) = attempt_request else {
+            return;
+        };
+        let started = Instant::now();
+        let answer = first_answer((self.operation)(attempt_request), self.policy.attempt_timeout());
+        let elapsed = started.elapsed();
 
-                // Calculate delay and wait before retry
-                let delay = policy.calculate_delay(attempt);
-                std::thread::sleep(delay);
-                attempt += 1;
+        let (outcome, retryable, retry_after) = answer.judge();
+        let delay = retry_after.unwrap_or_else(|| self.policy.calculate_delay(self.attempt));
+        // A server asking for a longer pause than the policy allows gets its answer back
+        let mut retry = retryable
+            && self.replayable
+            && self.attempt < self.policy.max_attempts
+            && delay <= self.policy.max_delay();
+        let succeeded = matches!(
+            outcome,
+            AttemptOutcome::Answered(status) if !status.is_some_and(|status| status >= 500 || status == 429)
+        );
+
+        let mut stats_guard = self.stats.write().unwrap_or_else(PoisonError::into_inner);
+        if let Some(budget) = &self.budget {
+            if succeeded {
+                budget.record_success(&self.origin);
+            }
+            if retry && !budget.try_acquire(&self.origin) {
+                retry = false;
+                stats_guard.budget_exhausted = true;
+            }
+        }
+        stats_guard.record_attempt(RetryAttempt {
+            attempt: self.attempt,
+            outcome: outcome.clone(),
+            elapsed,
+            retry_after,
+            delay: retry.then_some(delay),
+        });
+
+        if !retry {
+            if succeeded {
+                GLOBAL_RETRY_STATS.record_success();
+            } else {
+                GLOBAL_RETRY_STATS.record_failure();
             }
-        })
+            stats_guard.complete();
+            drop(stats_guard);
+            answer.deliver(self.slot);
+            return;
+        }
+
+        drop(stats_guard);
+        GLOBAL_RETRY_STATS.record_retry();
+        tracing::debug!(
+            target: "quyc::retry",
+            url = %self.url,
+            attempt = self.attempt,
+            outcome = ?outcome,
+            delay_ms = delay.as_millis() as u64,
+            "Retrying request"
+        );
+        // Dropping the answer closes the attempt's streams
+        drop(answer);
+        self.attempt += 1;
+        timer::schedule(delay, move || self.take_slot());
+    }
+
+    /// Send the next attempt once the rate limiter has a slot for it
+    ///
+    /// Runs after the previous attempt was dropped, so the limiter has
+    /// learned from its answer.
+    fn take_slot(self) {
+        let Some(limiter) = self.rate_limiter.clone() else {
+            return self.send();
+        };
+        let Some(key) = self.request.as_ref().map(|request| limiter.key_for(request)) else {
+            return self.send();
+        };
+        match limiter.acquire(&key) {
+            Ok(wait) => timer::schedule(wait, move || self.send()),
+            Err(error) => {
+                let mut stats_guard = self.stats.write().unwrap_or_else(PoisonError::into_inner);
+                stats_guard.rate_limited = true;
+                stats_guard.complete();
+                drop(stats_guard);
+                GLOBAL_RETRY_STATS.record_failure();
+                self.slot.fill(Vec::new(), None, crate::middleware::error_response(error));
+            }
+        }
+    }
+}
+
+/// What an attempt produced before its body
+pub(crate) enum Answer {
+    /// Response headers arrived; `headers` holds those read so far
+    Headers { headers: Vec<HttpHeader>, response: HttpResponse },
+    /// The response ended before any header; `chunk` is its first body chunk
+    Headless { chunk: Option<HttpBodyChunk>, response: HttpResponse },
+    /// Nothing arrived within the attempt timeout
+    TimedOut,
+}
+
+impl Answer {
+    /// Outcome, whether it is worth retrying, and the server's requested delay
+    pub(crate) fn judge(&self) -> (AttemptOutcome, bool, Option<Duration>) {
+        match self {
+            Answer::Headers { headers, response } => {
+                let status = response.status_code();
+                let retry_after = headers
+                    .iter()
+                    .find(|header| header.name == http::header::RETRY_AFTER)
+                    .and_then(|header| header.value.to_str().ok())
+                    .and_then(parse_retry_after);
+                let retryable = status.is_some_and(RetryPolicy::is_retryable_status);
+                (AttemptOutcome::Answered(status.map(|s| s.as_u16())), retryable, retry_after)
+            }
+            Answer::Headless { chunk, .. } => {
+                let error = match chunk {
+                    Some(chunk) if !chunk.data.is_empty() => String::from_utf8_lossy(&chunk.data).into_owned(),
+                    _ => "response ended before any headers".to_string(),
+                };
+                let failure = chunk.as_ref().and_then(|chunk| chunk.failure.as_ref());
+                let retryable = chunk.is_none() || matches!(failure, Some(Kind::Connect | Kind::Timeout));
+                (AttemptOutcome::Error(error), retryable, None)
+            }
+            Answer::TimedOut => (AttemptOutcome::TimedOut, true, None),
+        }
     }
+
+    /// Apply `f` to the attempt's response, if one arrived
+    pub(crate) fn map_response(self, f: impl FnOnce(HttpResponse) -> HttpResponse) -> Self {
+        match self {
+            Answer::Headers { headers, response } => Answer::Headers { headers, response: f(response) },
+            Answer::Headless { chunk, response } => Answer::Headless { chunk, response: f(response) },
+            Answer::TimedOut => Answer::TimedOut,
+        }
+    }
+
+    /// Hand the attempt to the caller
+    pub(crate) fn deliver(self, slot: ResponseSlot) {
+        match self {
+            Answer::Headers { headers, response } => slot.fill(headers, None, response),
+            Answer::Headless { chunk, response } => slot.fill(Vec::new(), chunk, response),
+            Answer::TimedOut => slot.fail("no response headers before the attempt timeout".to_string()),
+        }
+    }
+}
+
+/// Wait up to `timeout` for `response`'s headers
+///
+/// Reads only the first header, unless the status is retryable or a
+/// redirect, in which case the whole header block is read to find
+/// `Retry-After` or `Location`. A response without headers has its first
+/// body chunk read to learn the error. On timeout the wait is cancelled and
+/// the attempt's response, with its streams, is dropped.
+pub(crate) fn first_answer(mut response: HttpResponse, timeout: Duration) -> Answer {
+    let (answer_tx, answer_rx) = mpsc::channel();
+    let (cancel_tx, cancel_rx) = oneshot::channel::<()>();
+    spawn_task(move || {
+        let (_, placeholder_headers) = AsyncStream::channel();
+        let (_, placeholder_body) = AsyncStream::channel();
+        let (_, placeholder_trailers) = AsyncStream::channel();
+        let (mut headers, mut body, trailers) =
+            response.swap_streams(placeholder_headers, placeholder_body, placeholder_trailers);
+
+        let wait = async {
+            let mut read: Vec<HttpHeader> = headers.next().await.into_iter().collect();
+            let mut chunk = None;
+            if read.is_empty() {
+                chunk = body.next().await;
+            } else if response
+                .status_code()
+                .is_some_and(|status| RetryPolicy::is_retryable_status(status) || status.is_redirection())
+            {
+                while let Some(header) = headers.next().await {
+                    read.push(header);
+                }
+            }
+            (read, chunk)
+        };
+        // Dropping `cancel_tx` ends the wait; the streams and response drop with this task
+        let (read, chunk) = match block_on(future::select(Box::pin(wait), cancel_rx)) {
+            Either::Left((answer, _)) => answer,
+            Either::Right(_) => return,
+        };
+        let headless = read.is_empty();
+        drop(response.swap_streams(headers, body, trailers));
+
+        let answer = if headless {
+            Answer::Headless { chunk, response }
+        } else {
+            Answer::Headers { headers: read, response }
+        };
+        // After a timeout nobody is listening and the attempt drops here
+        drop(answer_tx.send(answer));
+    });
+    let answer = answer_rx.recv_timeout(timeout).unwrap_or(Answer::TimedOut);
+    drop(cancel_tx);
+    answer
 }
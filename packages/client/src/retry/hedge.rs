@@ -0,0 +1,391 @@
+//! Request hedging for tail-latency reduction
+//!
+//! A hedged request sends a duplicate attempt when the first one has not
+//! produced response headers within a delay: either a fixed delay or a latency
+//! percentile observed for the same origin. Whichever attempt answers first
+//! wins; the others are cancelled. Hedges are drawn from a client-wide
+//! [`HedgeBudget`] so a slow provider cannot double the load on it.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::channel::oneshot;
+use futures::future::Either;
+use ystream::{AsyncStream, spawn_task};
+
+use super::policy::RetryPolicy;
+use crate::client::core::ClientStats;
+use crate::http::request::HttpRequest;
+use crate::http::response::{HttpBodyChunk, HttpHeader, HttpResponse};
+use crate::protocols::strategy_trait::ProtocolStrategy;
+
+/// Latency samples kept per origin
+const LATENCY_WINDOW: usize = 256;
+
+/// When and how often to hedge a request
+#[derive(Debug, Clone)]
+pub struct HedgePolicy {
+    /// Delay before hedging when no origin percentile is available
+    pub delay: Duration,
+    /// Time-to-headers percentile (0.0 to 1.0) per origin used as the delay
+    /// once `min_samples` answers have been observed
+    pub percentile: Option<f64>,
+    /// Answers needed for an origin before its percentile is used
+    pub min_samples: usize,
+    /// Maximum duplicate attempts per request
+    pub max_hedges: u32,
+    /// Send hedges with the protocol preference reversed
+    pub alternate_protocol: bool,
+}
+
+impl Default for HedgePolicy {
+    /// Hedge once at the origin's p95, or after 100ms until it is known
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(100),
+            percentile: Some(0.95),
+            min_samples: 20,
+            max_hedges: 1,
+            alternate_protocol: true,
+        }
+    }
+}
+
+impl HedgePolicy {
+    /// Hedge once after a fixed delay
+    #[inline]
+    pub fn fixed(delay: Duration) -> Self {
+        Self {
+            delay,
+            percentile: None,
+            ..Self::default()
+        }
+    }
+
+    /// Hedge once at the given per-origin percentile, falling back to `delay`
+    #[inline]
+    pub fn at_percentile(percentile: f64, delay: Duration) -> Self {
+        Self {
+            delay,
+            percentile: Some(percentile),
+            ..Self::default()
+        }
+    }
+
+    /// Whether `request` may be sent more than once
+    ///
+    /// Idempotent methods qualify, as do other methods carrying an
+    /// `Idempotency-Key` header. Streaming bodies cannot be replayed.
+    pub fn is_hedgeable(&self, request: &HttpRequest) -> bool {
+        self.max_hedges > 0 && RetryPolicy::is_replayable(request)
+    }
+
+    /// Validate policy configuration for consistency
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(percentile) = self.percentile {
+            if !(0.0..=1.0).contains(&percentile) {
+                return Err("percentile must be between 0.0 and 1.0".to_string());
+            }
+        }
+        if self.delay.is_zero() {
+            return Err("delay must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Client-wide allowance of hedged attempts
+///
+/// Every request earns `ratio` of a hedge, up to `reserve` banked hedges;
+/// every hedge spends one. Over time hedges stay below `ratio` of the
+/// client's requests, with bursts bounded by the reserve.
+#[derive(Debug)]
+pub struct HedgeBudget {
+    /// Balance earned per request, in thousandths of a hedge
+    deposit: u64,
+    /// Maximum balance, in thousandths of a hedge
+    max_balance: u64,
+    balance: AtomicU64,
+}
+
+impl Default for HedgeBudget {
+    /// Hedge at most 10% of requests, with up to 10 hedges banked
+    fn default() -> Self {
+        Self::new(0.1, 10)
+    }
+}
+
+impl HedgeBudget {
+    /// Budget allowing `ratio` hedges per request and a burst of `reserve`
+    ///
+    /// The budget starts full.
+    pub fn new(ratio: f64, reserve: u32) -> Self {
+        let max_balance = u64::from(reserve) * 1000;
+        Self {
+            deposit: (ratio.clamp(0.0, 1.0) * 1000.0) as u64,
+            max_balance,
+            balance: AtomicU64::new(max_balance),
+        }
+    }
+
+    /// Earn hedge allowance for a request
+    pub fn record_request(&self) {
+        // fetch_update only fails when the closure returns None
+        let _ = self.balance.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |balance| {
+            Some((balance + self.deposit).min(self.max_balance))
+        });
+    }
+
+    /// Spend one hedge, returning `false` if the budget is exhausted
+    pub fn try_acquire(&self) -> bool {
+        self.balance
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |balance| balance.checked_sub(1000))
+            .is_ok()
+    }
+
+    /// Whole hedges currently available
+    #[inline]
+    pub fn available(&self) -> u64 {
+        self.balance.load(Ordering::Relaxed) / 1000
+    }
+}
+
+/// Recent time-to-headers per origin
+#[derive(Debug, Default)]
+pub struct LatencyTracker {
+    origins: Mutex<HashMap<String, VecDeque<Duration>>>,
+}
+
+impl LatencyTracker {
+    /// Record how long `origin` took to answer
+    pub fn record(&self, origin: &str, latency: Duration) {
+        if let Ok(mut origins) = self.origins.lock() {
+            let samples = origins.entry(origin.to_string()).or_default();
+            if samples.len() == LATENCY_WINDOW {
+                samples.pop_front();
+            }
+            samples.push_back(latency);
+        }
+    }
+
+    /// Latency percentile for `origin`, once it has at least `min_samples` answers
+    pub fn percentile(&self, origin: &str, percentile: f64, min_samples: usize) -> Option<Duration> {
+        let origins = self.origins.lock().ok()?;
+        let samples = origins.get(origin)?;
+        if samples.is_empty() || samples.len() < min_samples {
+            return None;
+        }
+        let mut sorted: Vec<Duration> = samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = (percentile.clamp(0.0, 1.0) * (sorted.len() - 1) as f64).round() as usize;
+        sorted.get(rank).copied()
+    }
+}
+
+/// Hedging state shared by a client and its clones
+#[derive(Debug)]
+pub struct Hedging {
+    policy: HedgePolicy,
+    budget: HedgeBudget,
+    latency: LatencyTracker,
+}
+
+impl Hedging {
+    /// Hedge according to `policy` with the default budget
+    pub fn new(policy: HedgePolicy) -> Self {
+        Self {
+            policy,
+            budget: HedgeBudget::default(),
+            latency: LatencyTracker::default(),
+        }
+    }
+
+    /// Hedging configured by a retry policy, if it enables any
+    ///
+    /// `max_hedges` is capped at `max_attempts - 1`. Hedges are sent per
+    /// attempt, and each retry may hedge again, so a request sends at most
+    /// `max_attempts * (1 + max_hedges)` times.
+    pub fn from_retry_policy(policy: &RetryPolicy) -> Option<Self> {
+        let mut hedge = policy.hedge.clone()?;
+        hedge.max_hedges = hedge.max_hedges.min(policy.max_attempts.saturating_sub(1));
+        (hedge.max_hedges > 0).then(|| Self::new(hedge))
+    }
+
+    /// Replace the budget
+    pub fn with_budget(mut self, budget: HedgeBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Policy deciding when to hedge
+    #[inline]
+    pub fn policy(&self) -> &HedgePolicy {
+        &self.policy
+    }
+
+    /// Client-wide hedge budget
+    #[inline]
+    pub fn budget(&self) -> &HedgeBudget {
+        &self.budget
+    }
+
+    /// Observed time-to-headers per origin
+    #[inline]
+    pub fn latency(&self) -> &LatencyTracker {
+        &self.latency
+    }
+
+    /// How long to wait for headers from `origin` before hedging
+    pub fn delay_for(&self, origin: &str) -> Duration {
+        self.policy
+            .percentile
+            .and_then(|p| self.latency.percentile(origin, p, self.policy.min_samples))
+            .unwrap_or(self.policy.delay)
+    }
+}
+
+/// An attempt that produced its first header, or finished without one
+struct Answer {
+    attempt: u32,
+    first: Option<HttpHeader>,
+    headers: AsyncStream<HttpHeader, 256>,
+    body: AsyncStream<HttpBodyChunk, 1024>,
+    trailers: AsyncStream<HttpHeader, 64>,
+    response: HttpResponse,
+    elapsed: Duration,
+}
+
+impl Answer {
+    /// No headers or a server error: worth waiting for another attempt
+    fn failed(&self) -> bool {
+        self.first.is_none() || self.response.status_code().is_some_and(|s| s.is_server_error())
+    }
+}
+
+/// Send `request` with hedging, returning a response fed by the winning attempt
+///
+/// `strategy` builds the strategy for each attempt, 0 being the original.
+/// The caller has already checked [`HedgePolicy::is_hedgeable`].
+pub(crate) fn execute<F>(
+    hedging: Arc<Hedging>,
+    stats: Arc<ClientStats>,
+    request: HttpRequest,
+    strategy: F,
+) -> HttpResponse
+where
+    F: Fn(u32) -> Box<dyn ProtocolStrategy> + Send + 'static,
+{
+    let origin = request.url().origin().ascii_serialization();
+    let delay = hedging.delay_for(&origin);
+    let started = Instant::now();
+    let primary = strategy(0).execute(request.clone());
+
+    let (response, slot) = HttpResponse::pending(primary.version, primary.stream_id);
+    if let Some(status) = primary.status_code() {
+        response.set_status(status);
+    }
+
+    spawn_task(move || {
+        let (answers_tx, answers) = mpsc::channel();
+        // Dropping a sender cancels the attempt it belongs to
+        let mut cancels = vec![watch(0, primary, started, answers_tx.clone())];
+        let mut hedges = 0;
+        let mut in_flight = 1;
+
+        let winner = loop {
+            let answer = if hedges < hedging.policy.max_hedges {
+                answers.recv_timeout(delay)
+            } else {
+                answers.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            };
+            match answer {
+                Ok(answer) => {
+                    in_flight -= 1;
+                    if answer.failed() && in_flight > 0 {
+                        continue;
+                    }
+                    break answer;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if !hedging.budget.try_acquire() {
+                        stats.hedges_denied.fetch_add(1, Ordering::Relaxed);
+                        hedges = hedging.policy.max_hedges;
+                        continue;
+                    }
+                    hedges += 1;
+                    in_flight += 1;
+                    stats.hedges_sent.fetch_add(1, Ordering::Relaxed);
+                    tracing::debug!(
+                        target: "quyc::retry",
+                        origin = %origin,
+                        attempt = hedges,
+                        delay_ms = delay.as_millis() as u64,
+                        "No response headers yet, sending hedged attempt"
+                    );
+                    let attempt = strategy(hedges).execute(request.clone());
+                    cancels.push(watch(hedges, attempt, started, answers_tx.clone()));
+                }
+                // The loop holds a sender, so the channel cannot disconnect
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        };
+        drop(cancels);
+
+        if !winner.failed() {
+            hedging.latency.record(&origin, winner.elapsed);
+        }
+        if winner.attempt > 0 {
+            stats.hedge_wins.fetch_add(1, Ordering::Relaxed);
+        }
+        let Answer { first, headers, body, trailers, mut response, .. } = winner;
+        drop(response.swap_streams(headers, body, trailers));
+        slot.fill(first.into_iter().collect(), None, response);
+    });
+
+    response
+}
+
+/// Wait for an attempt's first header on a background task
+///
+/// Reports the attempt to `answers`, or drops it when the returned sender is dropped.
+fn watch(
+    attempt: u32,
+    mut response: HttpResponse,
+    started: Instant,
+    answers: mpsc::Sender<Answer>,
+) -> oneshot::Sender<()> {
+    let (cancel_tx, cancel) = oneshot::channel::<()>();
+    spawn_task(move || {
+        let (_, placeholder_headers) = AsyncStream::channel();
+        let (_, placeholder_body) = AsyncStream::channel();
+        let (_, placeholder_trailers) = AsyncStream::channel();
+        let (mut headers, body, trailers) =
+            response.swap_streams(placeholder_headers, placeholder_body, placeholder_trailers);
+
+        let first = futures::executor::block_on(async {
+            let next = std::pin::pin!(headers.next());
+            match futures::future::select(next, cancel).await {
+                Either::Left((first, _)) => Some(first),
+                Either::Right(_) => None,
+            }
+        });
+        // Cancelled: the streams drop here, closing the attempt
+        let Some(first) = first else {
+            return;
+        };
+        // The winner may already be chosen, in which case this attempt drops too
+        drop(answers.send(Answer {
+            attempt,
+            first,
+            headers,
+            body,
+            trailers,
+            response,
+            elapsed: started.elapsed(),
+        }));
+    });
+    cancel_tx
+}
@@ -1,15 +1,21 @@
 //! Comprehensive retry logic with exponential backoff and jitter
 //!
 //! Provides zero-allocation retry mechanism with sophisticated policies,
-//! detailed statistics tracking, and streaming execution support.
+//! detailed statistics tracking, and streaming execution support. Attempts
+//! are judged on their headers, so accepted bodies are never buffered.
 
+pub mod budget;
 pub mod executor;
 pub mod global;
+pub mod hedge;
 pub mod helpers;
 pub mod policy;
+pub(crate) mod timer;
 
 // Re-export main types for convenient access
+pub use budget::{RetryBudget, RetryBudgetConfig};
 pub use executor::HttpRetryExecutor;
+pub use hedge::{HedgeBudget, HedgePolicy, Hedging, LatencyTracker};
 pub use helpers::{
     execute_with_aggressive_retry, execute_with_conservative_retry, execute_with_default_retry,
     execute_without_retry, with_retry,
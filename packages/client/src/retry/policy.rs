@@ -6,9 +6,15 @@
 use std::time::Duration;
 
 use fastrand::Rng;
+use http::StatusCode;
 
 // prelude import removed - not used
+use super::hedge::HedgePolicy;
 use crate::error::types::Error as HttpError;
+use crate::http::request::{HttpRequest, RequestBody};
+
+/// Header marking a non-idempotent request as safe to send more than once
+pub(crate) const IDEMPOTENCY_KEY: &str = "idempotency-key";
 
 /// Retry policy configuration - all durations in milliseconds for zero allocation
 #[derive(Debug, Clone)]
@@ -25,6 +31,9 @@ pub struct RetryPolicy {
     pub jitter_factor: f64,
     /// Timeout per individual attempt in milliseconds
     pub attempt_timeout_ms: u64,
+    /// Hedge slow idempotent requests; each attempt may send up to
+    /// `max_attempts - 1` hedges of its own
+    pub hedge: Option<HedgePolicy>,
 }
 
 impl Default for RetryPolicy {
@@ -40,6 +49,7 @@ impl Default for RetryPolicy {
             backoff_multiplier: 2.0,
             jitter_factor: 0.1,
             attempt_timeout_ms: 30000, // 30 seconds per attempt
+            hedge: None,
         }
     }
 }
@@ -58,6 +68,7 @@ impl RetryPolicy {
             backoff_multiplier: 1.5,
             jitter_factor: 0.2,
             attempt_timeout_ms: 15000, // 15 seconds per attempt
+            hedge: None,
         }
     }
 
@@ -74,6 +85,7 @@ impl RetryPolicy {
             backoff_multiplier: 3.0,
             jitter_factor: 0.05,
             attempt_timeout_ms: 60000, // 60 seconds per attempt
+            hedge: None,
         }
     }
 
@@ -90,9 +102,19 @@ impl RetryPolicy {
             backoff_multiplier: 1.0,
             jitter_factor: 0.0,
             attempt_timeout_ms: 120_000, // 2 minutes for single attempt
+            hedge: None,
         }
     }
 
+    /// Hedge slow requests according to `hedge`
+    ///
+    /// Hedged attempts count towards `max_attempts`.
+    #[inline]
+    pub fn with_hedging(mut self, hedge: HedgePolicy) -> Self {
+        self.hedge = Some(hedge);
+        self
+    }
+
     /// Calculate delay for specific attempt with exponential backoff and jitter
     ///
     /// Implements sophisticated delay calculation combining exponential backoff
@@ -142,9 +164,50 @@ impl RetryPolicy {
             crate::error::types::Kind::Timeout => true, // Timeout errors are retryable
             crate::error::types::Kind::PayloadTooLarge => false, // Payload size errors are not retryable
             crate::error::types::Kind::Stream => true, // Stream errors may be retryable     // Upgrade errors usually not retryable
+            crate::error::types::Kind::CircuitOpen => false, // Retrying would only hit the open circuit again
+            crate::error::types::Kind::RateLimited => false, // The limiter already refused to wait this long
         }
     }
 
+    /// Check if a response status is worth another attempt
+    ///
+    /// Covers rate limiting and the gateway statuses a load balancer returns
+    /// while an upstream is restarting or overloaded.
+    #[inline]
+    pub fn is_retryable_status(status: StatusCode) -> bool {
+        matches!(
+            status,
+            StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT
+        )
+    }
+
+    /// Check if a request may be sent more than once
+    ///
+    /// Idempotent methods qualify, as do other methods carrying an
+    /// `Idempotency-Key` header or having one generated automatically.
+    /// Streaming bodies cannot be replayed.
+    pub fn is_replayable(request: &HttpRequest) -> bool {
+        if matches!(request.body(), Some(RequestBody::Stream(_))) {
+            return false;
+        }
+        request.is_idempotent() || request.auto_idempotency_key || request.headers().contains_key(IDEMPOTENCY_KEY)
+    }
+
+    /// Timeout per individual attempt
+    #[inline]
+    pub fn attempt_timeout(&self) -> Duration {
+        Duration::from_millis(self.attempt_timeout_ms)
+    }
+
+    /// Longest delay allowed between attempts
+    #[inline]
+    pub fn max_delay(&self) -> Duration {
+        Duration::from_millis(self.max_delay_ms)
+    }
+
     /// Validate policy configuration for consistency
     ///
     /// Ensures policy parameters are within reasonable bounds and
@@ -167,6 +230,10 @@ impl RetryPolicy {
             return Err("initial_delay_ms cannot exceed max_delay_ms".to_string());
         }
 
+        if let Some(hedge) = &self.hedge {
+            hedge.validate()?;
+        }
+
         Ok(())
     }
 }
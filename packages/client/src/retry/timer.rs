@@ -0,0 +1,104 @@
+//! Deferred tasks for retry and rate-limit waits
+//!
+//! One shared thread keeps the pending tasks ordered by deadline and starts
+//! each with `spawn_task` once it is due, so a request waiting for its next
+//! attempt holds no thread of its own.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Condvar, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+use ystream::spawn_task;
+
+/// Task waiting for its deadline
+struct Entry {
+    deadline: Instant,
+    task: Box<dyn FnOnce() + Send>,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    // Reversed, so the earliest deadline is at the top of the heap
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.deadline.cmp(&self.deadline)
+    }
+}
+
+#[derive(Default)]
+struct Timer {
+    queue: Mutex<BinaryHeap<Entry>>,
+    wakeup: Condvar,
+}
+
+impl Timer {
+    /// Start tasks as they fall due, forever
+    fn run(&self) {
+        let mut queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            let now = Instant::now();
+            match queue.peek().map(|entry| entry.deadline) {
+                Some(deadline) if deadline <= now => {
+                    if let Some(entry) = queue.pop() {
+                        spawn_task(entry.task);
+                    }
+                }
+                Some(deadline) => {
+                    queue = self
+                        .wakeup
+                        .wait_timeout(queue, deadline - now)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0;
+                }
+                None => queue = self.wakeup.wait(queue).unwrap_or_else(PoisonError::into_inner),
+            }
+        }
+    }
+}
+
+/// Run `task` on a task thread once `delay` has passed
+pub(crate) fn schedule(delay: Duration, task: impl FnOnce() + Send + 'static) {
+    if delay.is_zero() {
+        spawn_task(task);
+        return;
+    }
+
+    let timer = timer();
+    let mut queue = timer.queue.lock().unwrap_or_else(PoisonError::into_inner);
+    queue.push(Entry {
+        deadline: Instant::now() + delay,
+        task: Box::new(task),
+    });
+    drop(queue);
+    timer.wakeup.notify_one();
+}
+
+/// Shared timer, started on first use
+fn timer() -> &'static Timer {
+    static TIMER: OnceLock<Timer> = OnceLock::new();
+    let mut started = false;
+    let timer = TIMER.get_or_init(|| {
+        started = true;
+        Timer::default()
+    });
+    if started {
+        std::thread::Builder::new()
+            .name("quyc-timer".to_string())
+            .spawn(|| timer.run())
+            .expect("failed to start the quyc timer thread");
+    }
+    timer
+}
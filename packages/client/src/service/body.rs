@@ -0,0 +1,129 @@
+//! Streaming `http_body::Body` over a response's body and trailer streams
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use http::HeaderMap;
+use http_body::{Body, Frame, SizeHint};
+use ystream::AsyncStream;
+
+use crate::error::HttpError;
+use crate::http::response::{HttpBodyChunk, HttpHeader};
+
+type BodyStream = AsyncStream<HttpBodyChunk, 1024>;
+type TrailerStream = AsyncStream<HttpHeader, 64>;
+
+/// Response body that yields chunks as they arrive from the protocol strategy
+///
+/// Data frames come from the body stream; once it ends, any trailers are
+/// collected and yielded as a final trailers frame.
+pub struct ResponseBody {
+    /// Data read ahead while the response head was assembled
+    first: Option<Bytes>,
+    state: State,
+}
+
+enum State {
+    /// Waiting for the next body chunk
+    Body(BoxFuture<'static, (BodyStream, Option<HttpBodyChunk>)>, Box<TrailerStream>),
+    /// Body finished; collecting trailers
+    Trailers(BoxFuture<'static, HeaderMap>),
+    Done,
+}
+
+impl ResponseBody {
+    /// Body over the given streams, starting with an already received chunk
+    pub(crate) fn new(first: Option<HttpBodyChunk>, body: BodyStream, trailers: TrailerStream) -> Self {
+        Self {
+            first: first.map(|chunk| chunk.data).filter(|data| !data.is_empty()),
+            state: State::Body(next_chunk(body), Box::new(trailers)),
+        }
+    }
+
+    /// Body with no data and no trailers
+    pub fn empty() -> Self {
+        Self { first: None, state: State::Done }
+    }
+}
+
+impl std::fmt::Debug for ResponseBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseBody")
+            .field("done", &self.is_end_stream())
+            .finish()
+    }
+}
+
+fn next_chunk(mut body: BodyStream) -> BoxFuture<'static, (BodyStream, Option<HttpBodyChunk>)> {
+    Box::pin(async move {
+        let chunk = body.next().await;
+        (body, chunk)
+    })
+}
+
+fn collect_trailers(mut trailers: Box<TrailerStream>) -> BoxFuture<'static, HeaderMap> {
+    Box::pin(async move {
+        let mut map = HeaderMap::new();
+        while let Some(trailer) = trailers.next().await {
+            map.append(trailer.name, trailer.value);
+        }
+        map
+    })
+}
+
+impl Body for ResponseBody {
+    type Data = Bytes;
+    type Error = HttpError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, HttpError>>> {
+        if let Some(data) = self.first.take() {
+            return Poll::Ready(Some(Ok(Frame::data(data))));
+        }
+        loop {
+            match std::mem::replace(&mut self.state, State::Done) {
+                State::Body(mut next, trailers) => match next.as_mut().poll(cx) {
+                    Poll::Pending => {
+                        self.state = State::Body(next, trailers);
+                        return Poll::Pending;
+                    }
+                    Poll::Ready((body, Some(chunk))) => {
+                        self.state = State::Body(next_chunk(body), trailers);
+                        if !chunk.data.is_empty() {
+                            return Poll::Ready(Some(Ok(Frame::data(chunk.data))));
+                        }
+                    }
+                    Poll::Ready((_, None)) => {
+                        self.state = State::Trailers(collect_trailers(trailers));
+                    }
+                },
+                State::Trailers(mut collect) => match collect.as_mut().poll(cx) {
+                    Poll::Pending => {
+                        self.state = State::Trailers(collect);
+                        return Poll::Pending;
+                    }
+                    Poll::Ready(map) if map.is_empty() => return Poll::Ready(None),
+                    Poll::Ready(map) => return Poll::Ready(Some(Ok(Frame::trailers(map)))),
+                },
+                State::Done => return Poll::Ready(None),
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.first.is_none() && matches!(self.state, State::Done)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        if self.is_end_stream() {
+            SizeHint::with_exact(0)
+        } else {
+            SizeHint::default()
+        }
+    }
+}
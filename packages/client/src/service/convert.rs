@@ -0,0 +1,176 @@
+//! Conversion between `http` crate types and `HttpRequest`/`HttpResponse`
+
+use bytes::Bytes;
+use http::StatusCode;
+use http_body::Body;
+use http_body_util::{BodyExt, Full};
+use ystream::{AsyncStream, emit, spawn_task};
+
+use super::body::ResponseBody;
+use crate::error::HttpError;
+use crate::http::request::{HttpRequest, RequestBody};
+use crate::http::response::{HttpBodyChunk, HttpHeader, HttpResponse};
+
+impl HttpRequest {
+    /// Build a request from an `http::Request`, collecting its body
+    ///
+    /// Method, URI, version and headers carry over unchanged. The protocol
+    /// strategies send request bodies in one piece, so the body is read to
+    /// the end before the request is returned.
+    pub async fn from_http<B>(request: http::Request<B>) -> Result<HttpRequest, HttpError>
+    where
+        B: Body,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let (parts, body) = request.into_parts();
+        let url = url::Url::parse(&parts.uri.to_string()).map_err(crate::error::url_parse_error)?;
+        let body = body
+            .collect()
+            .await
+            .map_err(|e| crate::error::body(e.into()))?
+            .to_bytes();
+        let body = (!body.is_empty()).then_some(RequestBody::Bytes(body));
+
+        Ok(HttpRequest::new(parts.method, url, Some(parts.headers), body, None).with_version(parts.version))
+    }
+
+    /// Convert into an `http::Request` with a buffered body
+    ///
+    /// Authentication, user agent and referer are applied as headers.
+    /// Multipart and streaming bodies have no up-front byte form and are
+    /// rejected.
+    pub fn into_http(self) -> Result<http::Request<Full<Bytes>>, HttpError> {
+        let body = match self.body() {
+            Some(body) => body.to_bytes().ok_or_else(|| {
+                crate::error::builder("Multipart and streaming bodies cannot be converted to http::Request")
+            })?,
+            None => Bytes::new(),
+        };
+
+        let mut headers = self.headers().clone();
+        headers.extend(self.try_auth_headers()?);
+        if let Some(user_agent) = &self.user_agent {
+            if !headers.contains_key(http::header::USER_AGENT) {
+                insert(&mut headers, http::header::USER_AGENT, user_agent)?;
+            }
+        }
+        if let Some(referer) = &self.referer {
+            if !headers.contains_key(http::header::REFERER) {
+                insert(&mut headers, http::header::REFERER, referer)?;
+            }
+        }
+
+        let mut request = http::Request::builder()
+            .method(self.method().clone())
+            .uri(self.url().as_str())
+            .version(self.version())
+            .body(Full::new(body))
+            .map_err(crate::error::builder)?;
+        *request.headers_mut() = headers;
+        Ok(request)
+    }
+}
+
+fn insert(headers: &mut http::HeaderMap, name: http::HeaderName, value: &str) -> Result<(), HttpError> {
+    let value = http::HeaderValue::from_str(value).map_err(crate::error::invalid_header)?;
+    headers.insert(name, value);
+    Ok(())
+}
+
+impl HttpResponse {
+    /// Convert into an `http::Response` with a streaming body
+    ///
+    /// Resolves once the response head is known: when the first body chunk
+    /// arrives or the header stream ends. The body then streams the rest of
+    /// the data followed by any trailers. Strategies that do not report a
+    /// status produce `200 OK`.
+    pub async fn into_http(self) -> http::Response<ResponseBody> {
+        let status = self.status_code();
+        let version = self.version;
+        let (mut headers, mut body, trailers) = self.into_streams();
+
+        // Headers precede the body; the header stream may stay open until the
+        // body ends, so stop collecting at the first body chunk
+        let mut header_map = http::HeaderMap::new();
+        let mut first = None;
+        let mut headers_open = true;
+        while headers_open {
+            tokio::select! {
+                biased;
+                header = headers.next() => match header {
+                    Some(header) => {
+                        header_map.append(header.name, header.value);
+                    }
+                    None => headers_open = false,
+                },
+                chunk = body.next() => {
+                    first = chunk;
+                    break;
+                }
+            }
+        }
+
+        let mut response = http::Response::new(ResponseBody::new(first, body, trailers));
+        *response.status_mut() = status.unwrap_or(StatusCode::OK);
+        *response.version_mut() = version;
+        *response.headers_mut() = header_map;
+        response
+    }
+
+    /// Build a response from an `http::Response`, streaming its body
+    ///
+    /// The body is read on a background task and forwarded chunk by chunk;
+    /// a trailers frame becomes the trailer stream.
+    pub fn from_http<B>(response: http::Response<B>) -> HttpResponse
+    where
+        B: Body<Data = Bytes> + Send + 'static,
+        B::Error: std::fmt::Display,
+    {
+        let (parts, body) = response.into_parts();
+
+        let (headers_tx, headers_stream) = AsyncStream::<HttpHeader, 256>::channel();
+        for (name, value) in &parts.headers {
+            // Intentionally ignore send result - the receiver is held below
+            drop(headers_tx.send(HttpHeader::new(name.clone(), value.clone())));
+        }
+        drop(headers_tx);
+
+        let (body_tx, body_stream) = AsyncStream::<HttpBodyChunk, 1024>::channel();
+        let (trailers_tx, trailers_stream) = AsyncStream::<HttpHeader, 64>::channel();
+        spawn_task(move || {
+            let mut body = Box::pin(body);
+            let mut offset = 0u64;
+            loop {
+                match futures::executor::block_on(body.frame()) {
+                    Some(Ok(frame)) => match frame.into_data() {
+                        Ok(data) => {
+                            let len = data.len() as u64;
+                            emit!(body_tx, HttpBodyChunk::new(data, offset, false));
+                            offset += len;
+                        }
+                        Err(frame) => {
+                            if let Ok(trailers) = frame.into_trailers() {
+                                for (name, value) in &trailers {
+                                    emit!(trailers_tx, HttpHeader::new(name.clone(), value.clone()));
+                                }
+                            }
+                        }
+                    },
+                    Some(Err(e)) => {
+                        tracing::warn!(
+                            target: "quyc::service",
+                            error = %e,
+                            "Response body failed while converting from http::Response"
+                        );
+                        break;
+                    }
+                    None => break,
+                }
+            }
+            emit!(body_tx, HttpBodyChunk::new(Bytes::new(), offset, true));
+        });
+
+        HttpResponse::new(headers_stream, body_stream, trailers_stream, parts.version, 0)
+            .with_status(parts.status)
+    }
+}
@@ -0,0 +1,72 @@
+//! `MiddlewareChain` as a tower layer
+
+use std::task::{Context, Poll};
+
+use futures::future::BoxFuture;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::error::HttpError;
+use crate::http::{HttpRequest, HttpResponse};
+use crate::middleware::{MiddlewareChain, RequestOutcome};
+
+impl<S> Layer<S> for MiddlewareChain {
+    type Service = MiddlewareService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MiddlewareService { chain: self.clone(), inner }
+    }
+}
+
+/// Runs a middleware chain around a native `HttpRequest` service
+///
+/// Ordering is the same as inside [`HttpClient`](crate::HttpClient); errors
+/// from the inner service reach `handle_error` like middleware errors do.
+#[derive(Debug, Clone)]
+pub struct MiddlewareService<S> {
+    chain: MiddlewareChain,
+    inner: S,
+}
+
+impl<S> MiddlewareService<S> {
+    /// The wrapped service
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Unwrap the inner service
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Service<HttpRequest> for MiddlewareService<S>
+where
+    S: Service<HttpRequest, Response = HttpResponse, Error = HttpError> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = HttpResponse;
+    type Error = HttpError;
+    type Future = BoxFuture<'static, Result<HttpResponse, HttpError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        // Use the instance that was polled ready, leaving a fresh clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let chain = self.chain.clone();
+
+        let (entered, outcome) = chain.run_request(request);
+        Box::pin(async move {
+            let result = match outcome {
+                RequestOutcome::Forward(request) => inner.call(request).await,
+                RequestOutcome::Respond(response) => Ok(response),
+                RequestOutcome::Fail(error) => Err(error),
+            };
+            Ok(chain.run_response(entered, result))
+        })
+    }
+}
@@ -0,0 +1,122 @@
+//! Tower integration
+//!
+//! [`HttpClient`] and [`StrategyService`] implement `tower::Service` for both
+//! `http::Request<B>` (answering with `http::Response<ResponseBody>`) and the
+//! native [`HttpRequest`]/[`HttpResponse`] pair, so they drop into tower
+//! stacks (timeout, limit, buffer, tower-http) and axum/tonic-style clients.
+//! [`MiddlewareChain`](crate::middleware::MiddlewareChain) is a `tower::Layer`
+//! over native services.
+//!
+//! Request bodies are collected before sending, since the protocol strategies
+//! send them in one piece; response bodies stream through [`ResponseBody`].
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::future::{BoxFuture, Ready, ready};
+use http_body::Body;
+use tower_service::Service;
+
+use crate::client::HttpClient;
+use crate::error::HttpError;
+use crate::http::{HttpRequest, HttpResponse};
+use crate::protocols::strategy::HttpProtocolStrategy;
+use crate::protocols::strategy_trait::ProtocolStrategy;
+
+pub mod body;
+pub mod convert;
+pub mod layer;
+
+pub use body::ResponseBody;
+pub use layer::MiddlewareService;
+
+/// A protocol strategy as a tower service
+///
+/// Sends requests straight through the strategy, without the client's
+/// middleware, cassette, HAR recording or statistics.
+#[derive(Clone)]
+pub struct StrategyService {
+    strategy: Arc<dyn ProtocolStrategy>,
+}
+
+impl StrategyService {
+    /// Wrap a built strategy
+    pub fn new<S: ProtocolStrategy + 'static>(strategy: S) -> Self {
+        Self { strategy: Arc::new(strategy) }
+    }
+
+    /// Send a request through the strategy
+    #[inline]
+    pub fn execute(&self, request: HttpRequest) -> HttpResponse {
+        self.strategy.execute(request)
+    }
+}
+
+impl std::fmt::Debug for StrategyService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StrategyService")
+            .field("protocol", &self.strategy.protocol_name())
+            .finish()
+    }
+}
+
+impl From<Box<dyn ProtocolStrategy>> for StrategyService {
+    fn from(strategy: Box<dyn ProtocolStrategy>) -> Self {
+        Self { strategy: Arc::from(strategy) }
+    }
+}
+
+impl HttpProtocolStrategy {
+    /// Build this strategy as a tower service
+    pub fn service(&self) -> StrategyService {
+        StrategyService::from(self.build())
+    }
+}
+
+/// Implements both service flavours for a cloneable type with `execute`
+macro_rules! impl_service {
+    ($ty:ty) => {
+        impl Service<HttpRequest> for $ty {
+            type Response = HttpResponse;
+            type Error = HttpError;
+            type Future = Ready<Result<HttpResponse, HttpError>>;
+
+            #[inline]
+            fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+                Poll::Ready(Ok(()))
+            }
+
+            #[inline]
+            fn call(&mut self, request: HttpRequest) -> Self::Future {
+                ready(Ok(self.execute(request)))
+            }
+        }
+
+        impl<B> Service<http::Request<B>> for $ty
+        where
+            B: Body + Send + 'static,
+            B::Data: Send,
+            B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+        {
+            type Response = http::Response<ResponseBody>;
+            type Error = HttpError;
+            type Future = BoxFuture<'static, Result<Self::Response, HttpError>>;
+
+            #[inline]
+            fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn call(&mut self, request: http::Request<B>) -> Self::Future {
+                let service = self.clone();
+                Box::pin(async move {
+                    let request = HttpRequest::from_http(request).await?;
+                    Ok(service.execute(request).into_http().await)
+                })
+            }
+        }
+    };
+}
+
+impl_service!(HttpClient);
+impl_service!(StrategyService);
@@ -0,0 +1,100 @@
+//! Circuit breaker state transition tracking
+//!
+//! Counts transitions and rejections of the client-level circuit breaker and
+//! keeps the most recent transitions for inspection.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use crate::client::circuit_breaker::CircuitState;
+
+/// Transitions kept for [`CircuitBreakerStats::recent_transitions`]
+const RECENT_TRANSITIONS: usize = 64;
+
+/// A circuit changing state for one origin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitTransition {
+    /// Origin (`scheme://host:port`) the circuit guards
+    pub origin: String,
+    /// State before the transition
+    pub from: CircuitState,
+    /// State after the transition
+    pub to: CircuitState,
+    /// When the transition happened
+    pub at: SystemTime,
+}
+
+/// Circuit breaker counters shared by a client and its clones
+#[derive(Debug, Default)]
+pub struct CircuitBreakerStats {
+    /// Circuits opened, from closed or after a failed probe
+    pub opened: AtomicU64,
+    /// Circuits moved to half-open after their cooldown
+    pub half_opened: AtomicU64,
+    /// Circuits closed after successful probes
+    pub closed: AtomicU64,
+    /// Requests failed fast by an open circuit
+    pub rejected: AtomicU64,
+    recent: Mutex<VecDeque<CircuitTransition>>,
+}
+
+impl CircuitBreakerStats {
+    /// Record a state transition
+    pub fn record_transition(&self, transition: CircuitTransition) {
+        let counter = match transition.to {
+            CircuitState::Open => &self.opened,
+            CircuitState::HalfOpen => &self.half_opened,
+            CircuitState::Closed => &self.closed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        if let Ok(mut recent) = self.recent.lock() {
+            if recent.len() == RECENT_TRANSITIONS {
+                recent.pop_front();
+            }
+            recent.push_back(transition);
+        }
+    }
+
+    /// Record a request failed fast
+    #[inline]
+    pub fn record_rejection(&self) {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Most recent transitions, oldest first
+    pub fn recent_transitions(&self) -> Vec<CircuitTransition> {
+        self.recent
+            .lock()
+            .map(|recent| recent.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Create a snapshot of current statistics
+    pub fn snapshot(&self) -> CircuitBreakerStatsSnapshot {
+        CircuitBreakerStatsSnapshot {
+            opened: self.opened.load(Ordering::Relaxed),
+            half_opened: self.half_opened.load(Ordering::Relaxed),
+            closed: self.closed.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            recent_transitions: self.recent_transitions(),
+        }
+    }
+}
+
+/// Immutable snapshot of circuit breaker statistics
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitBreakerStatsSnapshot {
+    /// Circuits opened
+    pub opened: u64,
+    /// Circuits moved to half-open
+    pub half_opened: u64,
+    /// Circuits closed after recovery
+    pub closed: u64,
+    /// Requests failed fast
+    pub rejected: u64,
+    /// Most recent transitions, oldest first
+    pub recent_transitions: Vec<CircuitTransition>,
+}
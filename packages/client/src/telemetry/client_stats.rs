@@ -3,6 +3,7 @@
 //! Provides blazing-fast thread-safe statistics collection for HTTP client operations
 //! using zero-allocation lock-free atomic operations with optimal CPU cache utilization.
 
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 
 use crossbeam_utils::CachePadded;
@@ -87,6 +88,14 @@ pub struct ClientStatsSnapshot {
     pub cache_hits: usize,
     /// Number of cache misses
     pub cache_misses: usize,
+    /// Duplicate attempts sent for slow requests
+    pub hedges_sent: usize,
+    /// Requests answered first by a hedged attempt
+    pub hedge_wins: usize,
+    /// Hedges skipped because the hedge budget was exhausted
+    pub hedges_denied: usize,
+    /// QUIC transport statistics aggregated per origin (`scheme://host:port`)
+    pub quic_origins: HashMap<String, super::quic_stats::QuicOriginStats>,
 }
 
 impl ClientStats {
@@ -107,6 +116,10 @@ impl ClientStats {
             failed_requests: self.failed_requests.load(Ordering::Relaxed),
             cache_hits: self.cache_hits.load(Ordering::Relaxed),
             cache_misses: self.cache_misses.load(Ordering::Relaxed),
+            hedges_sent: 0,
+            hedge_wins: 0,
+            hedges_denied: 0,
+            quic_origins: HashMap::new(),
         }
     }
 }
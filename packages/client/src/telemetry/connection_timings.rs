@@ -0,0 +1,41 @@
+//! Connection setup timings per response
+//!
+//! The protocol layer publishes how long name resolution, the transport
+//! connect and the TLS handshake took for the connection that served a
+//! response, together with the server address. Phases that did not happen
+//! (an IP literal needs no DNS, plain HTTP has no TLS) or that a protocol
+//! cannot observe are left as `None`.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Shared slot through which the protocol layer publishes connection timings
+pub type ConnectionTimingsHandle = Arc<RwLock<Option<ConnectionTimings>>>;
+
+/// Time spent establishing the connection that served a response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectionTimings {
+    /// Name resolution
+    pub dns: Option<Duration>,
+    /// Transport connect (TCP handshake, or the QUIC handshake for HTTP/3)
+    pub connect: Option<Duration>,
+    /// TLS handshake; for HTTP/3 it is part of `connect`
+    pub tls: Option<Duration>,
+    /// Address of the server the connection was made to
+    pub remote_addr: Option<SocketAddr>,
+}
+
+impl ConnectionTimings {
+    /// Total setup time across all observed phases
+    pub fn total(&self) -> Duration {
+        [self.dns, self.connect, self.tls].into_iter().flatten().sum()
+    }
+
+    /// Publish these timings into a response's handle
+    pub fn publish(self, handle: &ConnectionTimingsHandle) {
+        if let Ok(mut slot) = handle.write() {
+            *slot = Some(self);
+        }
+    }
+}
@@ -4,15 +4,21 @@
 //! from across the HTTP3 package into a single, well-organized location.
 
 pub mod cache_stats;
+pub mod circuit_stats;
 pub mod client_stats;
+pub mod connection_timings;
 pub mod jsonpath;
 pub mod metrics;
+pub mod quic_stats;
 pub mod retry_stats;
 pub mod types;
 
 // Re-export key types for convenience
 pub use cache_stats::*;
+pub use circuit_stats::*;
 pub use client_stats::*;
+pub use connection_timings::*;
 pub use jsonpath::*;
 pub use metrics::*;
+pub use quic_stats::*;
 pub use retry_stats::*;
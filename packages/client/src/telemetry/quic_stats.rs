@@ -0,0 +1,326 @@
+//! QUIC transport statistics collected from quiche connections
+//!
+//! Captures `quiche::Connection::stats()` and `path_stats()` into plain snapshot
+//! types that can be attached to an `HttpResponse` and aggregated per origin
+//! for `ClientStatsSnapshot`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Shared slot through which the protocol layer publishes the transport
+/// statistics of the connection that served a response
+pub type QuicStatsHandle = Arc<RwLock<Option<QuicConnectionStats>>>;
+
+/// Point-in-time transport statistics for a single QUIC connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuicConnectionStats {
+    /// Origin (`scheme://host:port`) the connection was made to
+    pub origin: String,
+    /// Hex-encoded source connection ID
+    pub connection_id: String,
+    /// Smoothed round-trip time of the active path
+    pub rtt: Duration,
+    /// Minimum observed round-trip time of the active path
+    pub min_rtt: Option<Duration>,
+    /// Round-trip time variation of the active path
+    pub rttvar: Duration,
+    /// Congestion window of the active path in bytes
+    pub cwnd: u64,
+    /// Bytes sent but neither acknowledged nor declared lost
+    pub bytes_in_flight: u64,
+    /// Path MTU of the active path in bytes
+    pub pmtu: u64,
+    /// Most recent delivery rate estimate in bytes per second
+    pub delivery_rate: u64,
+    /// QUIC packets sent
+    pub packets_sent: u64,
+    /// QUIC packets received
+    pub packets_received: u64,
+    /// QUIC packets declared lost
+    pub packets_lost: u64,
+    /// QUIC packets retransmitted
+    pub packets_retransmitted: u64,
+    /// Bytes sent on the wire
+    pub bytes_sent: u64,
+    /// Bytes received on the wire
+    pub bytes_received: u64,
+    /// Bytes declared lost
+    pub bytes_lost: u64,
+    /// Number of network paths the connection has used
+    pub paths_count: u64,
+}
+
+impl QuicConnectionStats {
+    /// Capture statistics from a live quiche connection
+    ///
+    /// Path-level values (RTT, cwnd, PMTU, delivery rate) are taken from the
+    /// first active path, falling back to the first known path.
+    pub fn capture(origin: &str, connection: &quiche::Connection) -> Self {
+        let stats = connection.stats();
+        let mut paths: Vec<quiche::PathStats> = connection.path_stats().collect();
+        let path_index = paths.iter().position(|p| p.active).unwrap_or(0);
+        let path = if paths.is_empty() {
+            None
+        } else {
+            Some(paths.swap_remove(path_index))
+        };
+
+        let bytes_in_flight = stats
+            .sent_bytes
+            .saturating_sub(stats.acked_bytes)
+            .saturating_sub(stats.lost_bytes);
+
+        Self {
+            origin: origin.to_string(),
+            connection_id: hex::encode(connection.source_id().as_ref()),
+            rtt: path.as_ref().map_or(Duration::ZERO, |p| p.rtt),
+            min_rtt: path.as_ref().and_then(|p| p.min_rtt),
+            rttvar: path.as_ref().map_or(Duration::ZERO, |p| p.rttvar),
+            cwnd: path.as_ref().map_or(0, |p| p.cwnd as u64),
+            bytes_in_flight,
+            pmtu: path.as_ref().map_or(0, |p| p.pmtu as u64),
+            delivery_rate: path.as_ref().map_or(0, |p| p.delivery_rate),
+            packets_sent: stats.sent as u64,
+            packets_received: stats.recv as u64,
+            packets_lost: stats.lost as u64,
+            packets_retransmitted: stats.retrans as u64,
+            bytes_sent: stats.sent_bytes,
+            bytes_received: stats.recv_bytes,
+            bytes_lost: stats.lost_bytes,
+            paths_count: stats.paths_count as u64,
+        }
+    }
+
+    /// Fraction of sent packets that were declared lost (0.0 to 1.0)
+    #[inline]
+    pub fn loss_rate(&self) -> f64 {
+        if self.packets_sent == 0 {
+            0.0
+        } else {
+            self.packets_lost as f64 / self.packets_sent as f64
+        }
+    }
+}
+
+/// Transport statistics aggregated across all QUIC connections to one origin
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct QuicOriginStats {
+    /// Number of connections that reported statistics
+    pub connections: u64,
+    /// Sum of smoothed RTTs, used to derive the average
+    pub total_rtt: Duration,
+    /// Lowest minimum RTT seen on any connection
+    pub min_rtt: Option<Duration>,
+    /// Total QUIC packets sent
+    pub packets_sent: u64,
+    /// Total QUIC packets received
+    pub packets_received: u64,
+    /// Total QUIC packets declared lost
+    pub packets_lost: u64,
+    /// Total QUIC packets retransmitted
+    pub packets_retransmitted: u64,
+    /// Total bytes sent on the wire
+    pub bytes_sent: u64,
+    /// Total bytes received on the wire
+    pub bytes_received: u64,
+    /// Most recent per-connection snapshot for this origin
+    pub last: Option<QuicConnectionStats>,
+}
+
+impl QuicOriginStats {
+    /// Fold a connection snapshot into the aggregate
+    ///
+    /// `previous` is the snapshot recorded earlier for the same connection,
+    /// if any; it is replaced rather than counted twice.
+    fn record(&mut self, stats: &QuicConnectionStats, previous: Option<&QuicConnectionStats>) {
+        match previous {
+            Some(previous) => {
+                self.total_rtt = self.total_rtt.saturating_sub(previous.rtt);
+                self.packets_sent = self.packets_sent.saturating_sub(previous.packets_sent);
+                self.packets_received = self.packets_received.saturating_sub(previous.packets_received);
+                self.packets_lost = self.packets_lost.saturating_sub(previous.packets_lost);
+                self.packets_retransmitted =
+                    self.packets_retransmitted.saturating_sub(previous.packets_retransmitted);
+                self.bytes_sent = self.bytes_sent.saturating_sub(previous.bytes_sent);
+                self.bytes_received = self.bytes_received.saturating_sub(previous.bytes_received);
+            }
+            None => self.connections += 1,
+        }
+        self.total_rtt += stats.rtt;
+        self.min_rtt = match (self.min_rtt, stats.min_rtt) {
+            (Some(current), Some(new)) => Some(current.min(new)),
+            (current, new) => current.or(new),
+        };
+        self.packets_sent += stats.packets_sent;
+        self.packets_received += stats.packets_received;
+        self.packets_lost += stats.packets_lost;
+        self.packets_retransmitted += stats.packets_retransmitted;
+        self.bytes_sent += stats.bytes_sent;
+        self.bytes_received += stats.bytes_received;
+        self.last = Some(stats.clone());
+    }
+
+    /// Average smoothed RTT across recorded connections
+    #[inline]
+    pub fn avg_rtt(&self) -> Duration {
+        if self.connections == 0 {
+            Duration::ZERO
+        } else {
+            self.total_rtt / self.connections as u32
+        }
+    }
+
+    /// Fraction of sent packets that were declared lost (0.0 to 1.0)
+    #[inline]
+    pub fn loss_rate(&self) -> f64 {
+        if self.packets_sent == 0 {
+            0.0
+        } else {
+            self.packets_lost as f64 / self.packets_sent as f64
+        }
+    }
+}
+
+/// Registry of QUIC transport statistics keyed by origin
+///
+/// Keeps the latest snapshot of every connection that is still being tracked
+/// and a running aggregate per origin.
+#[derive(Debug, Default)]
+pub struct QuicStatsRegistry {
+    connections: RwLock<HashMap<String, (Instant, QuicConnectionStats)>>,
+    origins: RwLock<HashMap<String, QuicOriginStats>>,
+}
+
+impl QuicStatsRegistry {
+    /// Maximum number of per-connection snapshots retained
+    const MAX_TRACKED_CONNECTIONS: usize = 1024;
+
+    /// Create an empty registry
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a connection snapshot, replacing any earlier snapshot for the same connection
+    ///
+    /// The origin aggregate counts each tracked connection once, with its
+    /// latest snapshot.
+    pub fn record(&self, stats: QuicConnectionStats) {
+        if let Ok(mut connections) = self.connections.write() {
+            if let Ok(mut origins) = self.origins.write() {
+                let previous = connections
+                    .get(&stats.connection_id)
+                    .map(|(_, previous)| previous)
+                    .filter(|previous| previous.origin == stats.origin);
+                origins.entry(stats.origin.clone()).or_default().record(&stats, previous);
+            }
+
+            if connections.len() >= Self::MAX_TRACKED_CONNECTIONS
+                && !connections.contains_key(&stats.connection_id)
+            {
+                // Evict the oldest snapshot to bound memory
+                let oldest = connections
+                    .iter()
+                    .min_by_key(|(_, (recorded_at, _))| *recorded_at)
+                    .map(|(id, _)| id.clone());
+                if let Some(id) = oldest {
+                    connections.remove(&id);
+                }
+            }
+            connections.insert(stats.connection_id.clone(), (Instant::now(), stats));
+        }
+    }
+
+    /// Latest snapshot for a connection ID
+    pub fn connection(&self, connection_id: &str) -> Option<QuicConnectionStats> {
+        self.connections
+            .read()
+            .ok()
+            .and_then(|connections| connections.get(connection_id).map(|(_, s)| s.clone()))
+    }
+
+    /// Aggregated statistics for an origin
+    pub fn origin(&self, origin: &str) -> Option<QuicOriginStats> {
+        self.origins
+            .read()
+            .ok()
+            .and_then(|origins| origins.get(origin).cloned())
+    }
+
+    /// Latest per-connection snapshots
+    pub fn connections(&self) -> Vec<QuicConnectionStats> {
+        self.connections
+            .read()
+            .map(|connections| connections.values().map(|(_, s)| s.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Aggregated statistics for every origin
+    pub fn origins(&self) -> HashMap<String, QuicOriginStats> {
+        self.origins
+            .read()
+            .map(|origins| origins.clone())
+            .unwrap_or_default()
+    }
+
+    /// Drop all recorded statistics
+    pub fn clear(&self) {
+        if let Ok(mut connections) = self.connections.write() {
+            connections.clear();
+        }
+        if let Ok(mut origins) = self.origins.write() {
+            origins.clear();
+        }
+    }
+}
+
+/// Publishes QUIC statistics for one request to its response and, when
+/// attached, to the client-wide registry
+#[derive(Debug, Clone)]
+pub struct QuicStatsRecorder {
+    origin: String,
+    handle: QuicStatsHandle,
+    registry: Option<Arc<QuicStatsRegistry>>,
+}
+
+impl QuicStatsRecorder {
+    /// Create a recorder for the given origin and response handle
+    pub fn new(origin: String, handle: QuicStatsHandle, registry: Option<Arc<QuicStatsRegistry>>) -> Self {
+        Self {
+            origin,
+            handle,
+            registry,
+        }
+    }
+
+    /// Capture the current statistics of `connection` and publish them
+    pub fn record(&self, connection: &quiche::Connection) {
+        let stats = QuicConnectionStats::capture(&self.origin, connection);
+
+        tracing::debug!(
+            target: "quyc::telemetry::quic",
+            origin = %stats.origin,
+            connection_id = %stats.connection_id,
+            rtt_us = stats.rtt.as_micros() as u64,
+            cwnd = stats.cwnd,
+            lost = stats.packets_lost,
+            retrans = stats.packets_retransmitted,
+            pmtu = stats.pmtu,
+            "QUIC transport statistics captured"
+        );
+
+        if let Some(registry) = &self.registry {
+            registry.record(stats.clone());
+        }
+        if let Ok(mut slot) = self.handle.write() {
+            *slot = Some(stats);
+        }
+    }
+}
+
+/// Format the origin key used for QUIC statistics
+#[inline]
+pub fn quic_origin(scheme: &str, host: &str, port: u16) -> String {
+    format!("{scheme}://{host}:{port}")
+}
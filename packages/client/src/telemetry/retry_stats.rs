@@ -1,10 +1,43 @@
 //! Individual retry operation statistics tracking
 //!
 //! Provides detailed statistics collection for individual retry sequences
-//! with comprehensive timing and error tracking.
+//! with comprehensive timing and error tracking, and the counters of the
+//! client-wide retry budget.
 
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
+/// Shared slot the retry executor records attempts into while it runs
+pub type RetryStatsHandle = Arc<RwLock<RetryStats>>;
+
+/// What one attempt of a retry sequence produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttemptOutcome {
+    /// Response headers arrived, with this status if known
+    Answered(Option<u16>),
+    /// The attempt failed before any response headers
+    Error(String),
+    /// No response headers arrived within the attempt timeout
+    TimedOut,
+}
+
+/// A single attempt of a retry sequence
+#[derive(Debug, Clone)]
+pub struct RetryAttempt {
+    /// Attempt number, starting at 1
+    pub attempt: u32,
+    /// What the attempt produced
+    pub outcome: AttemptOutcome,
+    /// Time from sending to the outcome
+    pub elapsed: Duration,
+    /// Delay requested by the server through `Retry-After`
+    pub retry_after: Option<Duration>,
+    /// Wait before the next attempt; `None` if the sequence ended here
+    pub delay: Option<Duration>,
+}
+
 /// Retry statistics for monitoring and observability
 #[derive(Debug, Clone)]
 pub struct RetryStats {
@@ -22,6 +55,12 @@ pub struct RetryStats {
     pub end_time: Option<Instant>,
     /// List of errors encountered during retries
     pub retry_errors: Vec<String>,
+    /// Every attempt made, in order
+    pub attempts: Vec<RetryAttempt>,
+    /// Whether the retry budget ended the sequence before the policy did
+    pub budget_exhausted: bool,
+    /// Whether the client rate limiter refused a retry, failing the request
+    pub rate_limited: bool,
 }
 
 impl Default for RetryStats {
@@ -38,11 +77,41 @@ impl Default for RetryStats {
             start_time: Instant::now(),
             end_time: None,
             retry_errors: Vec::new(),
+            attempts: Vec::new(),
+            budget_exhausted: false,
+            rate_limited: false,
         }
     }
 }
 
 impl RetryStats {
+    /// Record an attempt and fold it into the totals
+    ///
+    /// An answered attempt that ends the sequence after earlier attempts
+    /// counts as a successful retry.
+    pub fn record_attempt(&mut self, attempt: RetryAttempt) {
+        self.total_attempts += 1;
+        self.total_retry_time += attempt.elapsed;
+        if let Some(delay) = attempt.delay {
+            self.total_delay_time += delay;
+        }
+        match &attempt.outcome {
+            AttemptOutcome::Answered(status) if attempt.delay.is_some() => {
+                let status = status.map_or_else(|| "unknown".to_string(), |status| status.to_string());
+                self.retry_errors.push(format!("attempt {} answered with status {status}", attempt.attempt));
+            }
+            AttemptOutcome::Answered(status) => {
+                let failed = status.is_some_and(|status| status >= 500 || status == 429);
+                if attempt.attempt > 1 && !failed {
+                    self.successful_retries += 1;
+                }
+            }
+            AttemptOutcome::Error(error) => self.retry_errors.push(format!("attempt {}: {error}", attempt.attempt)),
+            AttemptOutcome::TimedOut => self.retry_errors.push(format!("attempt {} timed out", attempt.attempt)),
+        }
+        self.attempts.push(attempt);
+    }
+
     /// Mark retry sequence as completed
     ///
     /// Records the completion timestamp for accurate total elapsed
@@ -117,3 +186,60 @@ impl RetryStats {
         }
     }
 }
+
+/// Retry budget counters shared by a client and its clones
+#[derive(Debug, Default)]
+pub struct RetryBudgetStats {
+    /// Retries the budget paid for
+    pub allowed: AtomicU64,
+    /// Retries refused because the budget was exhausted
+    pub denied: AtomicU64,
+    /// Successful answers that earned allowance
+    pub successes: AtomicU64,
+}
+
+impl RetryBudgetStats {
+    /// Record a retry paid for by the budget
+    #[inline]
+    pub fn record_allowed(&self) {
+        self.allowed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a retry refused by the budget
+    #[inline]
+    pub fn record_denied(&self) {
+        self.denied.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a successful answer
+    #[inline]
+    pub fn record_success(&self) {
+        self.successes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Create a snapshot of the counters along with current token balances
+    pub fn snapshot(&self, global_tokens: Option<f64>, origin_tokens: HashMap<String, f64>) -> RetryBudgetSnapshot {
+        RetryBudgetSnapshot {
+            allowed: self.allowed.load(Ordering::Relaxed),
+            denied: self.denied.load(Ordering::Relaxed),
+            successes: self.successes.load(Ordering::Relaxed),
+            global_tokens,
+            origin_tokens,
+        }
+    }
+}
+
+/// Immutable snapshot of a retry budget
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryBudgetSnapshot {
+    /// Retries the budget paid for
+    pub allowed: u64,
+    /// Retries refused because the budget was exhausted
+    pub denied: u64,
+    /// Successful answers that earned allowance
+    pub successes: u64,
+    /// Tokens left in the bucket shared by all origins
+    pub global_tokens: Option<f64>,
+    /// Tokens left per origin seen so far
+    pub origin_tokens: HashMap<String, f64>,
+}
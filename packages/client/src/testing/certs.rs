@@ -0,0 +1,104 @@
+//! Throwaway certificate authority and server certificate for the mock server
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+use crate::tls::Tls;
+
+static NEXT_DIR: AtomicU64 = AtomicU64::new(0);
+
+/// Certificates generated for one mock server instance
+#[derive(Debug)]
+pub(crate) struct MockCertificates {
+    /// Directory holding the generated files; removed on drop
+    pub dir: PathBuf,
+    /// PEM of the generated certificate authority
+    pub ca_pem: String,
+    /// Server certificate file (PEM), signed by the CA
+    pub cert_path: PathBuf,
+    /// Server private key file (PEM)
+    pub key_path: PathBuf,
+}
+
+impl MockCertificates {
+    /// Create a CA with `Tls::authority` and a `localhost` certificate signed by it
+    pub(crate) async fn generate() -> io::Result<Self> {
+        let dir = std::env::temp_dir().join(format!(
+            "quyc-mock-{}-{}",
+            std::process::id(),
+            NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let ca_response = Tls::authority("quyc-mock-ca")
+            .path(dir.join("ca"))
+            .common_name("quyc mock server CA")
+            .valid_for_years(1)
+            .create()
+            .await;
+        let authority = ca_response
+            .authority()
+            .cloned()
+            .ok_or_else(|| io::Error::other(format!("Failed to create mock CA: {:?}", ca_response.issues)))?;
+
+        let server_dir = dir.join("server");
+        let cert_response = Tls::certificate()
+            .generator()
+            .domains(&["localhost"])
+            .authority(&authority)
+            .valid_for_days(7)
+            .save_to(&server_dir)
+            .generate()
+            .await;
+        if !cert_response.success {
+            return Err(io::Error::other(format!(
+                "Failed to create mock server certificate: {:?}",
+                cert_response.issues
+            )));
+        }
+
+        Ok(Self {
+            dir,
+            ca_pem: authority.certificate_pem,
+            cert_path: server_dir.join("cert.pem"),
+            key_path: server_dir.join("key.pem"),
+        })
+    }
+
+    /// rustls server configuration offering `alpn` protocols
+    pub(crate) fn rustls_server_config(&self, alpn: &[&[u8]]) -> io::Result<Arc<rustls::ServerConfig>> {
+        let certs = load_certs(&self.cert_path)?;
+        let key = load_key(&self.key_path)?;
+
+        let mut config = rustls::ServerConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .map_err(io::Error::other)?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(io::Error::other)?;
+        config.alpn_protocols = alpn.iter().map(|p| p.to_vec()).collect();
+        Ok(Arc::new(config))
+    }
+}
+
+impl Drop for MockCertificates {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn load_certs(path: &Path) -> io::Result<Vec<CertificateDer<'static>>> {
+    let mut reader = io::BufReader::new(std::fs::File::open(path)?);
+    rustls_pemfile::certs(&mut reader).collect()
+}
+
+fn load_key(path: &Path) -> io::Result<PrivateKeyDer<'static>> {
+    let mut reader = io::BufReader::new(std::fs::File::open(path)?);
+    rustls_pemfile::private_key(&mut reader)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "No private key in mock server key file"))
+}
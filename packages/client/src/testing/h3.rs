@@ -0,0 +1,413 @@
+//! HTTP/3 listener of the mock server
+//!
+//! A small single-threaded quiche server: accepts QUIC connections on the
+//! UDP socket, collects requests from HTTP/3 streams and plays back the
+//! scripted responses, honoring delays without blocking other streams.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, Version};
+use quiche::h3::NameValue;
+
+use super::certs::MockCertificates;
+use super::recorded::{MockState, RecordedRequest};
+use super::response::{BodyPart, MockResponse, ResponseEnd};
+
+/// `H3_REQUEST_CANCELLED`, used when a script resets the stream
+const H3_REQUEST_CANCELLED: u64 = 0x010c;
+
+const MAX_DATAGRAM_SIZE: usize = 1350;
+
+/// Idle pause when no socket or stream work is pending
+const IDLE_SLEEP: Duration = Duration::from_millis(1);
+
+/// quiche server configuration using the mock certificates
+pub(crate) fn server_config(certs: &MockCertificates) -> io::Result<quiche::Config> {
+    let path_str = |path: &std::path::Path| {
+        path.to_str()
+            .map(str::to_string)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Certificate path is not UTF-8"))
+    };
+
+    let mut config = quiche::Config::new(quiche::PROTOCOL_VERSION).map_err(io::Error::other)?;
+    config
+        .load_cert_chain_from_pem_file(&path_str(&certs.cert_path)?)
+        .map_err(io::Error::other)?;
+    config
+        .load_priv_key_from_pem_file(&path_str(&certs.key_path)?)
+        .map_err(io::Error::other)?;
+    config
+        .set_application_protos(quiche::h3::APPLICATION_PROTOCOL)
+        .map_err(io::Error::other)?;
+    config.set_max_idle_timeout(30_000);
+    config.set_max_recv_udp_payload_size(MAX_DATAGRAM_SIZE);
+    config.set_max_send_udp_payload_size(MAX_DATAGRAM_SIZE);
+    config.set_initial_max_data(10_000_000);
+    config.set_initial_max_stream_data_bidi_local(1_000_000);
+    config.set_initial_max_stream_data_bidi_remote(1_000_000);
+    config.set_initial_max_stream_data_uni(1_000_000);
+    config.set_initial_max_streams_bidi(100);
+    config.set_initial_max_streams_uni(100);
+    config.set_disable_active_migration(true);
+    Ok(config)
+}
+
+/// Request being received on one stream
+#[derive(Default)]
+struct PartialRequest {
+    method: Option<Method>,
+    path: String,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+/// Response being played back on one stream
+struct PendingResponse {
+    script: MockResponse,
+    next_part: usize,
+    /// Bytes of the current data part already written
+    written: usize,
+    headers_sent: bool,
+    ready_at: Instant,
+}
+
+struct Client {
+    conn: quiche::Connection,
+    h3: Option<quiche::h3::Connection>,
+    requests: HashMap<u64, PartialRequest>,
+    responses: HashMap<u64, PendingResponse>,
+}
+
+/// Serve HTTP/3 on `socket` until `stop` is set
+pub(crate) fn serve(
+    socket: UdpSocket,
+    mut config: quiche::Config,
+    state: Arc<MockState>,
+    stop: Arc<AtomicBool>,
+) {
+    let local_addr = match socket.local_addr() {
+        Ok(addr) => addr,
+        Err(e) => {
+            tracing::error!(target: "quyc::testing", error = %e, "Mock HTTP/3 socket has no local address");
+            return;
+        }
+    };
+    let h3_config = match quiche::h3::Config::new() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!(target: "quyc::testing", error = %e, "Failed to create HTTP/3 config");
+            return;
+        }
+    };
+
+    let mut clients: HashMap<Vec<u8>, Client> = HashMap::new();
+    // Client-chosen initial DCIDs, mapped to the server SCID we assigned
+    let mut aliases: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+    let mut buf = vec![0u8; 65535];
+    let mut out = vec![0u8; MAX_DATAGRAM_SIZE];
+
+    while !stop.load(Ordering::Acquire) {
+        let mut busy = false;
+
+        // Read everything pending on the socket
+        loop {
+            let (len, from) = match socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    tracing::debug!(target: "quyc::testing", error = %e, "Mock HTTP/3 receive failed");
+                    break;
+                }
+            };
+            busy = true;
+
+            let packet = &mut buf[..len];
+            let header = match quiche::Header::from_slice(packet, quiche::MAX_CONN_ID_LEN) {
+                Ok(header) => header,
+                Err(_) => continue,
+            };
+            let dcid = header.dcid.to_vec();
+            let key = if clients.contains_key(&dcid) {
+                dcid
+            } else if let Some(scid) = aliases.get(&dcid) {
+                scid.clone()
+            } else {
+                if header.ty != quiche::Type::Initial {
+                    continue;
+                }
+                if !quiche::version_is_supported(header.version) {
+                    if let Ok(len) = quiche::negotiate_version(&header.scid, &header.dcid, &mut out) {
+                        let _ = socket.send_to(&out[..len], from);
+                    }
+                    continue;
+                }
+
+                let scid: [u8; quiche::MAX_CONN_ID_LEN] = rand::random();
+                let scid_ref = quiche::ConnectionId::from_ref(&scid);
+                let conn = match quiche::accept(&scid_ref, None, local_addr, from, &mut config) {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        tracing::debug!(target: "quyc::testing", error = %e, "Mock HTTP/3 accept failed");
+                        continue;
+                    }
+                };
+                aliases.insert(dcid, scid.to_vec());
+                clients.insert(
+                    scid.to_vec(),
+                    Client {
+                        conn,
+                        h3: None,
+                        requests: HashMap::new(),
+                        responses: HashMap::new(),
+                    },
+                );
+                scid.to_vec()
+            };
+
+            if let Some(client) = clients.get_mut(&key) {
+                let info = quiche::RecvInfo { from, to: local_addr };
+                if let Err(e) = client.conn.recv(packet, info) {
+                    tracing::debug!(target: "quyc::testing", error = %e, "Mock HTTP/3 packet rejected");
+                }
+            }
+        }
+
+        let now = Instant::now();
+        for client in clients.values_mut() {
+            if client.conn.timeout_instant().is_some_and(|at| at <= now) {
+                client.conn.on_timeout();
+            }
+
+            if client.h3.is_none() && (client.conn.is_established() || client.conn.is_in_early_data()) {
+                match quiche::h3::Connection::with_transport(&mut client.conn, &h3_config) {
+                    Ok(h3) => client.h3 = Some(h3),
+                    Err(e) => {
+                        tracing::debug!(target: "quyc::testing", error = %e, "Mock HTTP/3 setup failed");
+                        let _ = client.conn.close(false, 0x0101, b"h3 setup failed");
+                    }
+                }
+            }
+
+            busy |= poll_requests(client, &state);
+            busy |= play_responses(client, now);
+
+            // Flush everything quiche wants to send
+            loop {
+                match client.conn.send(&mut out) {
+                    Ok((len, info)) => {
+                        busy = true;
+                        if let Err(e) = socket.send_to(&out[..len], info.to) {
+                            if e.kind() != io::ErrorKind::WouldBlock {
+                                tracing::debug!(target: "quyc::testing", error = %e, "Mock HTTP/3 send failed");
+                            }
+                            break;
+                        }
+                    }
+                    Err(quiche::Error::Done) => break,
+                    Err(e) => {
+                        tracing::debug!(target: "quyc::testing", error = %e, "Mock HTTP/3 packet build failed");
+                        let _ = client.conn.close(false, 0x1, b"send failed");
+                        break;
+                    }
+                }
+            }
+        }
+
+        clients.retain(|_, client| !client.conn.is_closed());
+        aliases.retain(|_, scid| clients.contains_key(scid));
+
+        if !busy {
+            std::thread::sleep(IDLE_SLEEP);
+        }
+    }
+}
+
+/// Collect request headers and bodies; returns whether any event was handled
+fn poll_requests(client: &mut Client, state: &MockState) -> bool {
+    let Some(h3) = client.h3.as_mut() else {
+        return false;
+    };
+    let mut handled = false;
+    let mut body_buf = vec![0u8; 16 * 1024];
+
+    loop {
+        match h3.poll(&mut client.conn) {
+            Ok((stream_id, quiche::h3::Event::Headers { list, .. })) => {
+                handled = true;
+                let request = client.requests.entry(stream_id).or_default();
+                for header in &list {
+                    match header.name() {
+                        b":method" => request.method = Method::from_bytes(header.value()).ok(),
+                        b":path" => request.path = String::from_utf8_lossy(header.value()).into_owned(),
+                        name if name.starts_with(b":") => {}
+                        name => {
+                            if let (Ok(name), Ok(value)) =
+                                (HeaderName::from_bytes(name), HeaderValue::from_bytes(header.value()))
+                            {
+                                request.headers.append(name, value);
+                            }
+                        }
+                    }
+                }
+            }
+            Ok((stream_id, quiche::h3::Event::Data)) => {
+                handled = true;
+                while let Ok(read) = h3.recv_body(&mut client.conn, stream_id, &mut body_buf) {
+                    client
+                        .requests
+                        .entry(stream_id)
+                        .or_default()
+                        .body
+                        .extend_from_slice(&body_buf[..read]);
+                }
+            }
+            Ok((stream_id, quiche::h3::Event::Finished)) => {
+                handled = true;
+                if let Some(request) = client.requests.remove(&stream_id) {
+                    let script = state.handle(RecordedRequest {
+                        method: request.method.unwrap_or(Method::GET),
+                        path: request.path,
+                        version: Version::HTTP_3,
+                        headers: request.headers,
+                        body: Bytes::from(request.body),
+                        received_at: Instant::now(),
+                    });
+                    client.responses.insert(
+                        stream_id,
+                        PendingResponse {
+                            ready_at: Instant::now() + script.header_delay,
+                            script,
+                            next_part: 0,
+                            written: 0,
+                            headers_sent: false,
+                        },
+                    );
+                }
+            }
+            Ok((stream_id, quiche::h3::Event::Reset(_))) => {
+                handled = true;
+                client.requests.remove(&stream_id);
+                client.responses.remove(&stream_id);
+            }
+            Ok(_) => handled = true,
+            Err(quiche::h3::Error::Done) => break,
+            Err(e) => {
+                tracing::debug!(target: "quyc::testing", error = %e, "Mock HTTP/3 poll failed");
+                break;
+            }
+        }
+    }
+    handled
+}
+
+/// Advance scripted responses; returns whether anything was written
+fn play_responses(client: &mut Client, now: Instant) -> bool {
+    let Some(h3) = client.h3.as_mut() else {
+        return false;
+    };
+    let mut progressed = false;
+    let mut finished = Vec::new();
+
+    for (&stream_id, pending) in client.responses.iter_mut() {
+        if pending.ready_at > now {
+            continue;
+        }
+        match advance(h3, &mut client.conn, stream_id, pending, now) {
+            Ok(done) => {
+                progressed = true;
+                if done {
+                    finished.push(stream_id);
+                }
+            }
+            // Flow control or congestion; retry on the next iteration
+            Err(quiche::h3::Error::Done | quiche::h3::Error::StreamBlocked) => {}
+            Err(e) => {
+                tracing::debug!(target: "quyc::testing", error = %e, stream_id, "Mock HTTP/3 response failed");
+                finished.push(stream_id);
+            }
+        }
+    }
+
+    for stream_id in finished {
+        client.responses.remove(&stream_id);
+    }
+    progressed
+}
+
+/// Write as much of one scripted response as possible; `Ok(true)` when complete
+fn advance(
+    h3: &mut quiche::h3::Connection,
+    conn: &mut quiche::Connection,
+    stream_id: u64,
+    pending: &mut PendingResponse,
+    now: Instant,
+) -> Result<bool, quiche::h3::Error> {
+    let script = &pending.script;
+    let has_tail = !script.body.is_empty()
+        || script.trailers.is_some()
+        || script.end == ResponseEnd::Reset;
+
+    if !pending.headers_sent {
+        let mut headers = vec![quiche::h3::Header::new(b":status", script.status.as_str().as_bytes())];
+        headers.extend(
+            script
+                .headers
+                .iter()
+                .map(|(name, value)| quiche::h3::Header::new(name.as_str().as_bytes(), value.as_bytes())),
+        );
+        h3.send_response(conn, stream_id, &headers, !has_tail)?;
+        pending.headers_sent = true;
+        if !has_tail {
+            return Ok(true);
+        }
+    }
+
+    while let Some(part) = script.body.get(pending.next_part) {
+        match part {
+            BodyPart::Delay(delay) => {
+                pending.next_part += 1;
+                pending.ready_at = now + *delay;
+                return Ok(false);
+            }
+            BodyPart::Data(data) => {
+                let written = h3.send_body(conn, stream_id, &data[pending.written..], false)?;
+                pending.written += written;
+                if pending.written < data.len() {
+                    return Ok(false);
+                }
+                pending.written = 0;
+                pending.next_part += 1;
+            }
+        }
+    }
+
+    match (&script.end, &script.trailers) {
+        (ResponseEnd::Reset, _) => {
+            let _ = conn.stream_shutdown(stream_id, quiche::Shutdown::Write, H3_REQUEST_CANCELLED);
+        }
+        (ResponseEnd::Complete, Some(trailers)) => {
+            let trailers: Vec<quiche::h3::Header> = trailers
+                .iter()
+                .map(|(name, value)| quiche::h3::Header::new(name.as_str().as_bytes(), value.as_bytes()))
+                .collect();
+            h3.send_additional_headers(conn, stream_id, &trailers, true, true)?;
+        }
+        (ResponseEnd::Complete, None) => {
+            h3.send_body(conn, stream_id, b"", true)?;
+        }
+    }
+    Ok(true)
+}
+
+/// Bind the UDP socket used for HTTP/3 on `addr`
+pub(crate) fn bind(addr: SocketAddr) -> io::Result<UdpSocket> {
+    let socket = UdpSocket::bind(addr)?;
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
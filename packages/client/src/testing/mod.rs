@@ -0,0 +1,200 @@
+//! Local mock server for integration tests
+//!
+//! [`MockServer`] listens on an ephemeral port of `127.0.0.1` and speaks
+//! HTTP/1.1 and HTTP/2 over TLS on TCP and HTTP/3 over QUIC on UDP. Its
+//! certificates come from a throwaway authority created with
+//! [`Tls::authority`](crate::tls::Tls::authority), and the clients handed out
+//! by [`MockServer::client`] trust that authority.
+//!
+//! Tests script responses with [`Mock`] and [`MockResponse`] and inspect the
+//! requests the server received:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use quyc_client::testing::{Mock, MockResponse, MockServer};
+//!
+//! # async fn example() -> std::io::Result<()> {
+//! let server = MockServer::start().await?;
+//! server.mock(
+//!     Mock::get("/events").respond(
+//!         MockResponse::ok()
+//!             .sse_event(Some("tick"), "1")
+//!             .delay(Duration::from_millis(50))
+//!             .sse_event(Some("tick"), "2"),
+//!     ),
+//! );
+//!
+//! let client = server.client();
+//! // ... drive the client against server.url("/events") ...
+//!
+//! server.assert_received("GET", "/events", 1);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Enabled by the `testing` feature.
+
+mod certs;
+mod h3;
+mod recorded;
+mod response;
+mod tcp;
+
+pub use recorded::RecordedRequest;
+pub use response::{BodyPart, Mock, MockResponse, ResponseEnd};
+
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+use crate::HttpClient;
+use crate::config::HttpConfig;
+
+use certs::MockCertificates;
+use recorded::MockState;
+
+/// Local HTTP/1.1, HTTP/2 and HTTP/3 server with scripted responses
+///
+/// Shuts down when dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    tcp_addr: SocketAddr,
+    udp_addr: SocketAddr,
+    state: Arc<MockState>,
+    certs: MockCertificates,
+    tcp_shutdown: Option<oneshot::Sender<()>>,
+    udp_stop: Arc<AtomicBool>,
+}
+
+impl MockServer {
+    /// Generate certificates and start listening on an ephemeral port
+    ///
+    /// HTTP/3 uses the same port number as TCP when it is free on UDP, and
+    /// another ephemeral port otherwise. Must be called inside a Tokio runtime.
+    pub async fn start() -> io::Result<Self> {
+        let certs = MockCertificates::generate().await?;
+        let tls = certs.rustls_server_config(&[b"h2", b"http/1.1"])?;
+        let quic_config = h3::server_config(&certs)?;
+
+        let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))).await?;
+        let tcp_addr = listener.local_addr()?;
+        let udp_socket = h3::bind(tcp_addr)
+            .or_else(|_| h3::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))))?;
+        let udp_addr = udp_socket.local_addr()?;
+
+        let state = Arc::new(MockState::default());
+
+        let (tcp_shutdown, shutdown_rx) = oneshot::channel();
+        tokio::spawn(tcp::serve(listener, tls, state.clone(), shutdown_rx));
+
+        let udp_stop = Arc::new(AtomicBool::new(false));
+        let thread_state = state.clone();
+        let thread_stop = udp_stop.clone();
+        std::thread::Builder::new()
+            .name("quyc-mock-h3".to_string())
+            .spawn(move || h3::serve(udp_socket, quic_config, thread_state, thread_stop))?;
+
+        tracing::debug!(
+            target: "quyc::testing",
+            tcp = %tcp_addr,
+            udp = %udp_addr,
+            "Mock server started"
+        );
+
+        Ok(Self {
+            tcp_addr,
+            udp_addr,
+            state,
+            certs,
+            tcp_shutdown: Some(tcp_shutdown),
+            udp_stop,
+        })
+    }
+
+    /// `https://localhost:{port}{path}` for HTTP/1.1 and HTTP/2
+    pub fn url(&self, path: &str) -> String {
+        format!("https://localhost:{}{}", self.tcp_addr.port(), path)
+    }
+
+    /// URL of `path` on the HTTP/3 listener
+    ///
+    /// Uses the IP address, since the HTTP/3 strategy connects to a socket
+    /// address parsed from the URL authority.
+    pub fn h3_url(&self, path: &str) -> String {
+        format!("https://{}{}", self.udp_addr, path)
+    }
+
+    /// TCP address serving HTTP/1.1 and HTTP/2
+    pub fn addr(&self) -> SocketAddr {
+        self.tcp_addr
+    }
+
+    /// UDP address serving HTTP/3
+    pub fn h3_addr(&self) -> SocketAddr {
+        self.udp_addr
+    }
+
+    /// PEM of the certificate authority that signed the server certificate
+    pub fn ca_pem(&self) -> &str {
+        &self.certs.ca_pem
+    }
+
+    /// Default client configuration that trusts the mock CA
+    pub fn http_config(&self) -> HttpConfig {
+        HttpConfig::default().with_root_certificate_pem(self.certs.ca_pem.clone())
+    }
+
+    /// Client that trusts the mock CA
+    pub fn client(&self) -> HttpClient {
+        HttpClient::with_config(self.http_config())
+    }
+
+    /// Register a route
+    ///
+    /// Routes are matched in registration order.
+    pub fn mock(&self, mock: Mock) {
+        self.state.register(mock);
+    }
+
+    /// Remove all routes and forget received requests
+    pub fn reset(&self) {
+        self.state.reset();
+    }
+
+    /// All requests received so far, in arrival order
+    pub fn received_requests(&self) -> Vec<RecordedRequest> {
+        self.state.requests()
+    }
+
+    /// Received requests with `method` to `path`
+    pub fn received(&self, method: &str, path: &str) -> Vec<RecordedRequest> {
+        self.state
+            .requests()
+            .into_iter()
+            .filter(|request| request.method.as_str().eq_ignore_ascii_case(method) && request.path == path)
+            .collect()
+    }
+
+    /// Panic unless exactly `times` requests with `method` to `path` arrived
+    #[track_caller]
+    pub fn assert_received(&self, method: &str, path: &str, times: usize) {
+        let received = self.received(method, path).len();
+        assert_eq!(
+            received, times,
+            "expected {times} {method} {path} request(s), mock server received {received}"
+        );
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.tcp_shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        self.udp_stop.store(true, Ordering::Release);
+    }
+}
@@ -0,0 +1,109 @@
+//! Requests received by the mock server
+
+use std::sync::Mutex;
+use std::time::Instant;
+
+use bytes::Bytes;
+use http::{HeaderMap, Method, StatusCode, Version};
+
+use super::response::{Mock, MockResponse};
+
+/// A request as received by the mock server
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// Request method
+    pub method: Method,
+    /// Path and query
+    pub path: String,
+    /// Protocol the request arrived over
+    pub version: Version,
+    /// Request headers, without HTTP/2 and HTTP/3 pseudo-headers
+    pub headers: HeaderMap,
+    /// Complete request body
+    pub body: Bytes,
+    /// When the request was fully received
+    pub received_at: Instant,
+}
+
+impl RecordedRequest {
+    /// Header value as a string, if present and valid UTF-8
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    /// Body as UTF-8 text, replacing invalid sequences
+    pub fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Body parsed as JSON
+    pub fn body_json<T: serde::de::DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+/// Routes and request log shared by all listeners of one server
+#[derive(Debug, Default)]
+pub(crate) struct MockState {
+    mocks: Mutex<Vec<Mock>>,
+    requests: Mutex<Vec<RecordedRequest>>,
+}
+
+impl MockState {
+    pub(crate) fn register(&self, mock: Mock) {
+        if let Ok(mut mocks) = self.mocks.lock() {
+            mocks.push(mock);
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        if let Ok(mut mocks) = self.mocks.lock() {
+            mocks.clear();
+        }
+        if let Ok(mut requests) = self.requests.lock() {
+            requests.clear();
+        }
+    }
+
+    /// Record `request` and pick the scripted response for it
+    ///
+    /// The first registered route that matches answers; unmatched requests
+    /// get `404 Not Found`.
+    pub(crate) fn handle(&self, request: RecordedRequest) -> MockResponse {
+        let response = self
+            .mocks
+            .lock()
+            .ok()
+            .and_then(|mut mocks| {
+                mocks
+                    .iter_mut()
+                    .find(|mock| mock.matches(&request.method, &request.path, &request.headers))
+                    .map(Mock::next_response)
+            })
+            .unwrap_or_else(|| {
+                MockResponse::new(StatusCode::NOT_FOUND)
+                    .text(&format!("no mock for {} {}", request.method, request.path))
+            });
+
+        tracing::debug!(
+            target: "quyc::testing",
+            method = %request.method,
+            path = %request.path,
+            version = ?request.version,
+            status = response.status.as_u16(),
+            "Mock server answered request"
+        );
+
+        if let Ok(mut requests) = self.requests.lock() {
+            requests.push(request);
+        }
+        response
+    }
+
+    pub(crate) fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests
+            .lock()
+            .map(|requests| requests.clone())
+            .unwrap_or_default()
+    }
+}
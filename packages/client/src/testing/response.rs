@@ -0,0 +1,331 @@
+//! Scripted mock responses and request matchers
+
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use ystream::{AsyncStream, emit};
+
+use crate::http::response::{HttpBodyChunk, HttpHeader, HttpResponse};
+
+/// One step of a scripted response body
+#[derive(Debug, Clone)]
+pub enum BodyPart {
+    /// Send a chunk of body data
+    Data(Bytes),
+    /// Pause before the next step
+    Delay(Duration),
+}
+
+/// How a scripted response ends
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ResponseEnd {
+    /// Finish the stream normally
+    #[default]
+    Complete,
+    /// Abort the stream after the scripted parts
+    ///
+    /// HTTP/2 and HTTP/3 reset the stream; HTTP/1.1 closes the connection.
+    Reset,
+}
+
+/// A scripted response
+///
+/// Built fluently: status, headers, body parts (chunks and delays), trailers,
+/// and how the stream ends.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    /// Response status
+    pub status: StatusCode,
+    /// Response headers
+    pub headers: HeaderMap,
+    /// Delay before the response headers are sent
+    pub header_delay: Duration,
+    /// Body parts in send order
+    pub body: Vec<BodyPart>,
+    /// Trailers sent after the body
+    pub trailers: Option<HeaderMap>,
+    /// How the stream ends
+    pub end: ResponseEnd,
+}
+
+impl MockResponse {
+    /// Response with the given status and no body
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            header_delay: Duration::ZERO,
+            body: Vec::new(),
+            trailers: None,
+            end: ResponseEnd::Complete,
+        }
+    }
+
+    /// `200 OK` with no body
+    pub fn ok() -> Self {
+        Self::new(StatusCode::OK)
+    }
+
+    /// Response with the given status code
+    ///
+    /// Invalid codes fall back to `500 Internal Server Error`.
+    pub fn status(code: u16) -> Self {
+        Self::new(StatusCode::from_u16(code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR))
+    }
+
+    /// Add a header; invalid names or values are ignored
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            self.headers.append(name, value);
+        }
+        self
+    }
+
+    /// Set the whole body in one chunk
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = vec![BodyPart::Data(body.into())];
+        self
+    }
+
+    /// JSON body with `content-type: application/json`
+    pub fn json(self, value: &serde_json::Value) -> Self {
+        self.header("content-type", "application/json")
+            .body(value.to_string())
+    }
+
+    /// Plain text body with `content-type: text/plain`
+    pub fn text(self, text: &str) -> Self {
+        self.header("content-type", "text/plain; charset=utf-8")
+            .body(text.to_string())
+    }
+
+    /// Append a body chunk, sent as its own frame
+    pub fn chunk(mut self, chunk: impl Into<Bytes>) -> Self {
+        self.body.push(BodyPart::Data(chunk.into()));
+        self
+    }
+
+    /// Append several body chunks, pausing `interval` between them
+    pub fn chunks<I, B>(mut self, chunks: I, interval: Duration) -> Self
+    where
+        I: IntoIterator<Item = B>,
+        B: Into<Bytes>,
+    {
+        for (index, chunk) in chunks.into_iter().enumerate() {
+            if index > 0 && !interval.is_zero() {
+                self.body.push(BodyPart::Delay(interval));
+            }
+            self.body.push(BodyPart::Data(chunk.into()));
+        }
+        self
+    }
+
+    /// Append a server-sent event with `content-type: text/event-stream`
+    pub fn sse_event(mut self, event: Option<&str>, data: &str) -> Self {
+        if !self.headers.contains_key(http::header::CONTENT_TYPE) {
+            self = self.header("content-type", "text/event-stream");
+        }
+        let mut frame = String::new();
+        if let Some(event) = event {
+            frame.push_str("event: ");
+            frame.push_str(event);
+            frame.push('\n');
+        }
+        for line in data.lines() {
+            frame.push_str("data: ");
+            frame.push_str(line);
+            frame.push('\n');
+        }
+        frame.push('\n');
+        self.chunk(frame)
+    }
+
+    /// Pause before the next body part
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.body.push(BodyPart::Delay(delay));
+        self
+    }
+
+    /// Pause before sending the response headers
+    pub fn delay_headers(mut self, delay: Duration) -> Self {
+        self.header_delay = delay;
+        self
+    }
+
+    /// Add a trailer sent after the body
+    pub fn trailer(mut self, name: &str, value: &str) -> Self {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            self.trailers.get_or_insert_with(HeaderMap::new).append(name, value);
+        }
+        self
+    }
+
+    /// Abort the stream after the scripted body parts instead of finishing it
+    pub fn reset(mut self) -> Self {
+        self.end = ResponseEnd::Reset;
+        self
+    }
+
+    /// Total number of body bytes in the script
+    pub fn body_len(&self) -> usize {
+        self.body
+            .iter()
+            .map(|part| match part {
+                BodyPart::Data(data) => data.len(),
+                BodyPart::Delay(_) => 0,
+            })
+            .sum()
+    }
+}
+
+impl MockResponse {
+    /// Play the script straight into an [`HttpResponse`], without a server
+    ///
+    /// For code that consumes responses directly, such as retry operations
+    /// and middleware. Body delays are honoured; the header delay is not, as
+    /// the headers are part of the returned value. A [`ResponseEnd::Reset`]
+    /// script stops the body without a final chunk.
+    pub fn into_response(self) -> HttpResponse {
+        let Self { status, headers, body, trailers, end, .. } = self;
+
+        let last_data = body.iter().rposition(|part| matches!(part, BodyPart::Data(_)));
+        let body_stream = AsyncStream::with_channel(move |sender| {
+            let mut offset = 0;
+            for (index, part) in body.into_iter().enumerate() {
+                match part {
+                    BodyPart::Data(data) => {
+                        let is_final = end == ResponseEnd::Complete && Some(index) == last_data;
+                        let len = data.len() as u64;
+                        emit!(sender, HttpBodyChunk::new(data, offset, is_final));
+                        offset += len;
+                    }
+                    BodyPart::Delay(delay) => std::thread::sleep(delay),
+                }
+            }
+            if end == ResponseEnd::Complete && last_data.is_none() {
+                emit!(sender, HttpBodyChunk::new(Bytes::new(), 0, true));
+            }
+        });
+
+        let trailers_stream = AsyncStream::with_channel(move |sender| {
+            for (name, value) in trailers.iter().flatten() {
+                emit!(
+                    sender,
+                    HttpHeader {
+                        name: name.clone(),
+                        value: value.clone(),
+                        timestamp: Instant::now(),
+                    }
+                );
+            }
+        });
+
+        HttpResponse::from_http2_response(status, headers, body_stream, trailers_stream, 1)
+    }
+}
+
+impl Default for MockResponse {
+    fn default() -> Self {
+        Self::ok()
+    }
+}
+
+/// Route that answers matching requests with scripted responses
+#[derive(Debug, Clone)]
+pub struct Mock {
+    method: Option<Method>,
+    path: String,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    responses: Vec<MockResponse>,
+    served: usize,
+}
+
+impl Mock {
+    /// Match requests with `method` to `path` (path and query, without the origin)
+    pub fn new(method: Method, path: &str) -> Self {
+        Self {
+            method: Some(method),
+            path: path.to_string(),
+            headers: Vec::new(),
+            responses: Vec::new(),
+            served: 0,
+        }
+    }
+
+    /// Match `GET` requests to `path`
+    pub fn get(path: &str) -> Self {
+        Self::new(Method::GET, path)
+    }
+
+    /// Match `POST` requests to `path`
+    pub fn post(path: &str) -> Self {
+        Self::new(Method::POST, path)
+    }
+
+    /// Match `PUT` requests to `path`
+    pub fn put(path: &str) -> Self {
+        Self::new(Method::PUT, path)
+    }
+
+    /// Match `DELETE` requests to `path`
+    pub fn delete(path: &str) -> Self {
+        Self::new(Method::DELETE, path)
+    }
+
+    /// Match requests with any method to `path`
+    pub fn any(path: &str) -> Self {
+        Self {
+            method: None,
+            ..Self::new(Method::GET, path)
+        }
+    }
+
+    /// Only match requests carrying this header value
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            self.headers.push((name, value));
+        }
+        self
+    }
+
+    /// Append a response to the script
+    ///
+    /// Responses are served in order; the last one repeats once the script
+    /// is exhausted.
+    pub fn respond(mut self, response: MockResponse) -> Self {
+        self.responses.push(response);
+        self
+    }
+
+    /// Check if a request matches this route
+    pub fn matches(&self, method: &Method, path: &str, headers: &HeaderMap) -> bool {
+        self.method.as_ref().is_none_or(|m| m == method)
+            && self.path == path
+            && self
+                .headers
+                .iter()
+                .all(|(name, value)| headers.get_all(name).iter().any(|v| v == value))
+    }
+
+    /// Next scripted response for a matching request
+    pub(crate) fn next_response(&mut self) -> MockResponse {
+        let index = self.served.min(self.responses.len().saturating_sub(1));
+        self.served += 1;
+        self.responses.get(index).cloned().unwrap_or_default()
+    }
+
+    /// Number of requests this route has answered
+    pub fn hits(&self) -> usize {
+        self.served
+    }
+}
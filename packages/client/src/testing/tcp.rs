@@ -0,0 +1,133 @@
+//! HTTP/1.1 and HTTP/2 listener of the mock server
+//!
+//! Accepts TLS connections and negotiates `h2` or `http/1.1` via ALPN; the
+//! hyper auto builder also accepts cleartext HTTP/2 prior knowledge.
+
+use std::convert::Infallible;
+use std::io;
+use std::sync::Arc;
+use std::time::Instant;
+
+use bytes::Bytes;
+use futures_util::StreamExt;
+use http_body_util::{BodyExt, StreamBody};
+use hyper::body::{Frame, Incoming};
+use hyper::service::service_fn;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio_rustls::TlsAcceptor;
+
+use super::recorded::{MockState, RecordedRequest};
+use super::response::{BodyPart, MockResponse, ResponseEnd};
+
+type MockBody = StreamBody<futures_util::stream::BoxStream<'static, Result<Frame<Bytes>, io::Error>>>;
+
+/// Serve h1/h2 on `listener` until `shutdown` fires
+pub(crate) async fn serve(
+    listener: TcpListener,
+    tls: Arc<rustls::ServerConfig>,
+    state: Arc<MockState>,
+    mut shutdown: oneshot::Receiver<()>,
+) {
+    let acceptor = TlsAcceptor::from(tls);
+
+    loop {
+        let (stream, peer) = tokio::select! {
+            _ = &mut shutdown => break,
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    tracing::warn!(target: "quyc::testing", error = %e, "Mock server accept failed");
+                    continue;
+                }
+            },
+        };
+
+        let acceptor = acceptor.clone();
+        let state = state.clone();
+        tokio::spawn(async move {
+            let tls_stream = match acceptor.accept(stream).await {
+                Ok(tls_stream) => tls_stream,
+                Err(e) => {
+                    tracing::debug!(target: "quyc::testing", error = %e, peer = %peer, "Mock server TLS handshake failed");
+                    return;
+                }
+            };
+
+            let service = service_fn(move |request| handle(state.clone(), request));
+            if let Err(e) = Builder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(tls_stream), service)
+                .await
+            {
+                tracing::debug!(target: "quyc::testing", error = %e, peer = %peer, "Mock server connection ended with error");
+            }
+        });
+    }
+}
+
+async fn handle(
+    state: Arc<MockState>,
+    request: http::Request<Incoming>,
+) -> Result<http::Response<MockBody>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let body = match body.collect().await {
+        Ok(collected) => collected.to_bytes(),
+        Err(e) => {
+            tracing::debug!(target: "quyc::testing", error = %e, "Failed to read mock request body");
+            Bytes::new()
+        }
+    };
+
+    let path = parts
+        .uri
+        .path_and_query()
+        .map_or_else(|| parts.uri.path().to_string(), |pq| pq.as_str().to_string());
+    let scripted = state.handle(RecordedRequest {
+        method: parts.method,
+        path,
+        version: parts.version,
+        headers: parts.headers,
+        body,
+        received_at: Instant::now(),
+    });
+
+    if !scripted.header_delay.is_zero() {
+        tokio::time::sleep(scripted.header_delay).await;
+    }
+
+    let mut response = http::Response::new(body_stream(&scripted));
+    *response.status_mut() = scripted.status;
+    *response.headers_mut() = scripted.headers;
+    Ok(response)
+}
+
+/// Turn the scripted body parts into a frame stream
+///
+/// A reset ends the stream with an error, which makes hyper send
+/// `RST_STREAM` on HTTP/2 and drop the connection on HTTP/1.1.
+fn body_stream(scripted: &MockResponse) -> MockBody {
+    let parts = scripted.body.clone();
+    let trailers = scripted.trailers.clone();
+    let reset = scripted.end == ResponseEnd::Reset;
+
+    let frames = futures_util::stream::iter(parts)
+        .then(|part| async move {
+            match part {
+                BodyPart::Data(data) => Some(Ok(Frame::data(data))),
+                BodyPart::Delay(delay) => {
+                    tokio::time::sleep(delay).await;
+                    None
+                }
+            }
+        })
+        .filter_map(|frame| async move { frame })
+        .chain(futures_util::stream::iter(if reset {
+            vec![Err(io::Error::new(io::ErrorKind::ConnectionReset, "scripted reset"))]
+        } else {
+            trailers.map(|t| Ok(Frame::trailers(t))).into_iter().collect()
+        }));
+
+    StreamBody::new(frames.boxed())
+}
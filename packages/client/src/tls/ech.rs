@@ -0,0 +1,126 @@
+//! Encrypted Client Hello (ECH) support
+//!
+//! Hides the real server name from on-path observers by encrypting the inner
+//! ClientHello with a key published by the server. ECH configurations come
+//! either from explicit configuration or from the `ech` parameter of the
+//! server's DNS HTTPS record. When a server rejects the offered configuration
+//! and returns retry configs, the handshake is retried once with those.
+
+use std::sync::Arc;
+
+use base64::Engine;
+use hickory_resolver::proto::rr::rdata::svcb::{SvcParamKey, SvcParamValue};
+use hickory_resolver::proto::rr::{RData, RecordType};
+use rustls::client::{EchConfig, EchMode};
+use rustls::pki_types::EchConfigListBytes;
+
+use super::errors::TlsError;
+use crate::client::SrvResolver;
+
+/// Whether and how Encrypted Client Hello is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EchPolicy {
+    /// Never offer ECH
+    #[default]
+    Disable,
+    /// Offer ECH when a configuration is available, otherwise connect normally
+    Prefer,
+    /// Fail the connection unless ECH is negotiated
+    Require,
+}
+
+impl EchPolicy {
+    /// Check if ECH should be attempted at all
+    #[inline]
+    pub fn is_enabled(self) -> bool {
+        !matches!(self, Self::Disable)
+    }
+}
+
+/// Decode a base64 `ECHConfigList`, as published in DNS zone files and by providers
+pub fn decode_ech_config_list(encoded: &str) -> Result<Vec<u8>, TlsError> {
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| TlsError::EncryptedClientHello(format!("Invalid base64 ECH config list: {e}")))
+}
+
+/// Look up the `ECHConfigList` for `host` in its DNS HTTPS records
+///
+/// Returns `Ok(None)` when the host publishes no HTTPS record with an `ech`
+/// parameter. Service-mode records are preferred in priority order; alias-mode
+/// records (priority 0) carry no parameters and are skipped. Lookups go
+/// through `resolver`, so the records are cached across connections for as
+/// long as their TTL allows.
+pub async fn lookup_ech_config_list(resolver: &SrvResolver, host: &str) -> Result<Option<Vec<u8>>, TlsError> {
+    let resolver = resolver
+        .shared()
+        .map_err(|e| TlsError::NetworkError(format!("Failed to create DNS resolver: {e}")))?;
+
+    let lookup = match resolver.lookup(host, RecordType::HTTPS).await {
+        Ok(lookup) => lookup,
+        Err(e) if e.is_no_records_found() => return Ok(None),
+        Err(e) => {
+            return Err(TlsError::NetworkError(format!(
+                "HTTPS record lookup for {host} failed: {e}"
+            )));
+        }
+    };
+
+    let mut candidates: Vec<(u16, Vec<u8>)> = lookup
+        .iter()
+        .filter_map(|rdata| match rdata {
+            RData::HTTPS(https) if https.svc_priority() > 0 => {
+                https.svc_params().iter().find_map(|(key, value)| match (key, value) {
+                    (SvcParamKey::EchConfigList, SvcParamValue::EchConfigList(list)) => {
+                        Some((https.svc_priority(), list.0.clone()))
+                    }
+                    _ => None,
+                })
+            }
+            _ => None,
+        })
+        .collect();
+
+    candidates.sort_by_key(|(priority, _)| *priority);
+    Ok(candidates.into_iter().next().map(|(_, list)| list))
+}
+
+/// Build the rustls ECH mode for an `ECHConfigList`
+///
+/// Fails when none of the configurations uses an HPKE suite supported by the
+/// crypto provider.
+pub(crate) fn ech_mode(config_list: &[u8]) -> Result<EchMode, TlsError> {
+    let list = EchConfigListBytes::from(config_list.to_vec());
+    EchConfig::new(list, rustls::crypto::aws_lc_rs::hpke::ALL_SUPPORTED_SUITES)
+        .map(EchMode::Enable)
+        .map_err(|e| TlsError::EncryptedClientHello(format!("Unusable ECH config list: {e}")))
+}
+
+/// Extract server-provided retry configs from a handshake error
+///
+/// Returns the encoded `ECHConfigList` when the server rejected ECH and sent
+/// replacement configurations.
+pub(crate) fn retry_configs(error: &std::io::Error) -> Option<Vec<u8>> {
+    match rustls_error(error)? {
+        rustls::Error::RejectedEch(rejected) => rejected.retry_configs().map(|list| list.to_vec()),
+        _ => None,
+    }
+}
+
+/// Check if a handshake error is an ECH rejection
+pub(crate) fn is_ech_rejection(error: &std::io::Error) -> bool {
+    matches!(rustls_error(error), Some(rustls::Error::RejectedEch(_)))
+}
+
+fn rustls_error(error: &std::io::Error) -> Option<&rustls::Error> {
+    error.get_ref()?.downcast_ref::<rustls::Error>()
+}
+
+/// Crypto provider used for client TLS configurations
+///
+/// Both ring and aws-lc-rs are compiled in, so configs and verifiers must name
+/// a provider explicitly. Key exchange and cipher suites stay on ring; ECH's
+/// HPKE suites come from aws-lc-rs.
+pub(crate) fn crypto_provider() -> Arc<rustls::crypto::CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
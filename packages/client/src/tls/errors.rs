@@ -33,4 +33,6 @@ pub enum TlsError {
     CertificateRevoked(String),
     #[error("OCSP validation failed: {0}")]
     OcspValidationFailed(String),
+    #[error("Encrypted Client Hello failed: {0}")]
+    EncryptedClientHello(String),
 }
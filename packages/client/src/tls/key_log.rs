@@ -0,0 +1,139 @@
+//! TLS key logging for packet capture decryption
+//!
+//! Writes TLS secrets in the NSS key log format so captures can be decrypted
+//! in Wireshark. Key logging is off unless the `SSLKEYLOGFILE` environment
+//! variable is set or a key log file is configured explicitly on `TlsConfig`.
+//! Anyone with access to the file can decrypt the logged sessions, so it must
+//! never be enabled in production.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Environment variable naming the key log file, as understood by browsers and curl
+pub const SSLKEYLOGFILE_ENV: &str = "SSLKEYLOGFILE";
+
+/// Resolve the key log file: the explicit path wins, then `SSLKEYLOGFILE`
+pub fn key_log_path(explicit: Option<&Path>) -> Option<PathBuf> {
+    explicit.map(Path::to_path_buf).or_else(|| {
+        std::env::var_os(SSLKEYLOGFILE_ENV)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+    })
+}
+
+/// Key logger for rustls client configurations, or `None` when key logging is off
+pub(crate) fn rustls_key_log(explicit: Option<&Path>) -> Option<Arc<dyn rustls::KeyLog>> {
+    let path = key_log_path(explicit)?;
+    warn_enabled(&path);
+
+    match explicit {
+        Some(path) => Some(Arc::new(KeyLogWriter::new(path.to_path_buf()))),
+        // rustls reads SSLKEYLOGFILE itself
+        None => Some(Arc::new(rustls::KeyLogFile::new())),
+    }
+}
+
+/// Enable key logging on a quiche configuration when a key log file is resolved
+///
+/// Returns the resolved path so connections created from `config` can be
+/// attached with [`attach_quiche_key_log`].
+pub(crate) fn enable_quiche_key_log(
+    config: &mut quiche::Config,
+    explicit: Option<&Path>,
+) -> Option<PathBuf> {
+    let path = key_log_path(explicit)?;
+    warn_enabled(&path);
+    config.log_keys();
+    Some(path)
+}
+
+/// Stream the TLS secrets of a quiche connection to the key log file
+pub(crate) fn attach_quiche_key_log(connection: &mut quiche::Connection, path: &Path) {
+    match open_key_log_file(path) {
+        Ok(file) => connection.set_keylog(Box::new(file)),
+        Err(e) => {
+            tracing::warn!(
+                target: "quyc::tls::key_log",
+                error = %e,
+                path = %path.display(),
+                "Failed to open TLS key log file for QUIC connection"
+            );
+        }
+    }
+}
+
+/// Open the key log file for appending, creating it if needed
+///
+/// On Unix a new file is readable by its owner only.
+fn open_key_log_file(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+fn warn_enabled(path: &Path) {
+    tracing::warn!(
+        target: "quyc::tls::key_log",
+        path = %path.display(),
+        "TLS key logging is enabled; session secrets are written to disk"
+    );
+}
+
+/// rustls key logger writing NSS key log lines to an explicit file
+#[derive(Debug)]
+struct KeyLogWriter {
+    path: PathBuf,
+    file: Mutex<Option<File>>,
+}
+
+impl KeyLogWriter {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            file: Mutex::new(None),
+        }
+    }
+}
+
+impl rustls::KeyLog for KeyLogWriter {
+    fn log(&self, label: &str, client_random: &[u8], secret: &[u8]) {
+        let Ok(mut guard) = self.file.lock() else {
+            return;
+        };
+
+        if guard.is_none() {
+            match open_key_log_file(&self.path) {
+                Ok(file) => *guard = Some(file),
+                Err(e) => {
+                    tracing::warn!(
+                        target: "quyc::tls::key_log",
+                        error = %e,
+                        path = %self.path.display(),
+                        "Failed to open TLS key log file"
+                    );
+                    return;
+                }
+            }
+        }
+
+        if let Some(file) = guard.as_mut() {
+            let line = format!(
+                "{} {} {}\n",
+                label,
+                hex::encode(client_random),
+                hex::encode(secret)
+            );
+            if let Err(e) = file.write_all(line.as_bytes()) {
+                tracing::warn!(
+                    target: "quyc::tls::key_log",
+                    error = %e,
+                    "Failed to write TLS key log entry"
+                );
+            }
+        }
+    }
+}
@@ -8,9 +8,12 @@
 // Internal modules - not exposed publicly
 pub(crate) mod certificate;
 pub(crate) mod crl_cache;
+pub(crate) mod ech;
 pub mod errors;
 pub(crate) mod key_encryption;
+pub(crate) mod key_log;
 pub(crate) mod ocsp;
+pub(crate) mod roots;
 
 pub(crate) mod tls_manager;
 pub(crate) mod types;
@@ -22,5 +25,11 @@ pub use builder::{CertificateAuthority, Tls};
 // Public TLS manager for enterprise connections
 pub use tls_manager::{TlsManager, TlsConfig};
 
+// Encrypted Client Hello policy and configuration sources
+pub use ech::{decode_ech_config_list, lookup_ech_config_list, EchPolicy};
+
+// Opt-in key logging for packet capture decryption
+pub use key_log::{key_log_path, SSLKEYLOGFILE_ENV};
+
 // Public error types for TLS operations
 pub use errors::TlsError;
@@ -0,0 +1,110 @@
+//! Additional root certificates for QUIC connections
+//!
+//! quiche verifies peers with BoringSSL, which only loads extra trust anchors
+//! from files. Configured PEM roots are written to a fresh file with an
+//! unpredictable name in a private per-process directory, loaded, and removed
+//! again. A file that already exists is never reused, so other local users
+//! cannot slip their own CA into the trust store.
+
+use std::fs::{DirBuilder, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use super::errors::TlsError;
+
+/// Attempts at finding an unused random name before giving up
+const NAME_ATTEMPTS: usize = 16;
+
+/// Private directory the bundles of this process are written to
+static BUNDLE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Trust the given PEM root certificates on a quiche configuration
+pub(crate) fn load_quiche_root_certificates(
+    config: &mut quiche::Config,
+    root_certificates: &[String],
+) -> Result<(), TlsError> {
+    if root_certificates.is_empty() {
+        return Ok(());
+    }
+
+    let bundle = root_certificates.join("\n");
+    let path = write_bundle(bundle.as_bytes())?;
+    let loaded = match path.to_str() {
+        Some(path_str) => config.load_verify_locations_from_file(path_str).map_err(|e| {
+            TlsError::CertificateParsing(format!("Failed to load root certificates for QUIC: {e}"))
+        }),
+        None => Err(TlsError::Internal("Root certificate bundle path is not UTF-8".to_string())),
+    };
+    // BoringSSL has read the file by now; nothing needs it afterwards
+    if let Err(e) = std::fs::remove_file(&path) {
+        tracing::debug!(target: "quyc::tls", path = %path.display(), error = %e, "Failed to remove root certificate bundle");
+    }
+    loaded?;
+
+    tracing::debug!(
+        target: "quyc::tls",
+        count = root_certificates.len(),
+        "Loaded additional root certificates for QUIC"
+    );
+    Ok(())
+}
+
+/// Write `bundle` to a new file only this user can read
+fn write_bundle(bundle: &[u8]) -> Result<PathBuf, TlsError> {
+    let dir = bundle_dir()?;
+    for _ in 0..NAME_ATTEMPTS {
+        let path = dir.join(format!("{:016x}.pem", rand::random::<u64>()));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(bundle).map_err(|e| {
+                    TlsError::FileOperation(format!("Failed to write root certificate bundle: {e}"))
+                })?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => {
+                return Err(TlsError::FileOperation(format!(
+                    "Failed to create root certificate bundle: {e}"
+                )));
+            }
+        }
+    }
+    Err(TlsError::FileOperation(
+        "Failed to find an unused name for the root certificate bundle".to_string(),
+    ))
+}
+
+/// Per-process directory with an unpredictable name, created `0700`
+fn bundle_dir() -> Result<&'static Path, TlsError> {
+    if let Some(dir) = BUNDLE_DIR.get() {
+        return Ok(dir);
+    }
+    let mut builder = DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    for _ in 0..NAME_ATTEMPTS {
+        let dir = std::env::temp_dir().join(format!(
+            "quyc-roots-{}-{:016x}",
+            std::process::id(),
+            rand::random::<u64>()
+        ));
+        // `create` fails on anything already there, including symlinks
+        match builder.create(&dir) {
+            Ok(()) => return Ok(BUNDLE_DIR.get_or_init(|| dir)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => {
+                return Err(TlsError::FileOperation(format!(
+                    "Failed to create root certificate directory: {e}"
+                )));
+            }
+        }
+    }
+    Err(TlsError::FileOperation(
+        "Failed to find an unused name for the root certificate directory".to_string(),
+    ))
+}
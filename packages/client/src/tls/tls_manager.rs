@@ -17,8 +17,11 @@ use super::crl_cache::CrlCache;
 use super::certificate::parser::parse_certificate_from_der;
 use super::builder::CertificateAuthority;
 use super::errors::TlsError;
+use super::ech::{self, EchPolicy};
 // ParsedCertificate alias import removed - not used
+use crate::client::SrvResolver;
 use crate::config::HttpConfig;
+use crate::telemetry::ConnectionTimings;
 
 /// Detailed TLS cache statistics for monitoring and troubleshooting
 #[derive(Debug, Clone)]
@@ -97,6 +100,8 @@ pub struct TlsManager {
     custom_cas: Arc<RwLock<HashMap<String, CertificateAuthority>>>,
     /// TLS configuration
     config: TlsConfig,
+    /// Resolver for the DNS HTTPS records that carry ECH configurations
+    resolver: SrvResolver,
 }
 
 /// TLS configuration for enterprise features
@@ -116,6 +121,15 @@ pub struct TlsConfig {
     pub connect_timeout: Duration,
     /// Certificate validation timeout
     pub validation_timeout: Duration,
+    /// Write TLS secrets to this file for packet capture decryption
+    ///
+    /// Off by default. When `None`, the `SSLKEYLOGFILE` environment variable
+    /// is honored if set.
+    pub key_log_file: Option<std::path::PathBuf>,
+    /// Encrypted Client Hello policy
+    pub ech_policy: EchPolicy,
+    /// Explicit `ECHConfigList`; when `None`, configs are looked up in DNS HTTPS records
+    pub ech_config_list: Option<Vec<u8>>,
 }
 
 impl Default for TlsConfig {
@@ -128,6 +142,9 @@ impl Default for TlsConfig {
             enable_early_data: false,
             connect_timeout: Duration::from_secs(10),
             validation_timeout: Duration::from_secs(5),
+            key_log_file: None,
+            ech_policy: EchPolicy::Disable,
+            ech_config_list: None,
         }
     }
 }
@@ -139,10 +156,13 @@ impl TlsConfig {
             enable_ocsp: true, // Always enable for enterprise
             enable_crl: true,  // Always enable for enterprise
             use_system_certs: http_config.use_native_certs,
-            custom_root_certs: Vec::new(),
+            custom_root_certs: http_config.tls_root_certificates.clone(),
             enable_early_data: http_config.tls_early_data,
             connect_timeout: Duration::from_secs(10),
             validation_timeout: Duration::from_secs(5),
+            key_log_file: http_config.tls_key_log_file.clone(),
+            ech_policy: http_config.tls_ech_policy,
+            ech_config_list: http_config.tls_ech_config_list.clone(),
         }
     }
     
@@ -156,6 +176,9 @@ impl TlsConfig {
             enable_early_data: true, // Enable for AI performance
             connect_timeout: Duration::from_secs(5), // Faster for AI workloads
             validation_timeout: Duration::from_secs(3),
+            key_log_file: None,
+            ech_policy: EchPolicy::Disable,
+            ech_config_list: None,
         }
     }
 }
@@ -173,8 +196,17 @@ impl TlsManager {
             crl_cache: Arc::new(CrlCache::new()),
             custom_cas: Arc::new(RwLock::new(HashMap::new())),
             config,
+            resolver: SrvResolver::default(),
         }
     }
+
+    /// Look up ECH configurations through `resolver`, e.g. the one of a client
+    ///
+    /// Without it each manager resolves with the system configuration.
+    pub fn with_resolver(mut self, resolver: SrvResolver) -> Self {
+        self.resolver = resolver;
+        self
+    }
     
     /// Create TLS manager from HttpConfig
     pub fn from_http_config(http_config: &HttpConfig) -> Self {
@@ -222,41 +254,163 @@ impl TlsManager {
     }
     
     /// Create enterprise TLS connection with full validation
+    ///
+    /// Offers Encrypted Client Hello according to `TlsConfig::ech_policy`. If
+    /// the server rejects ECH and supplies retry configs, the handshake is
+    /// retried once with them.
     pub async fn create_connection(
         &self,
         host: &str,
         port: u16,
     ) -> Result<tokio_rustls::client::TlsStream<TcpStream>, TlsError> {
+        self.create_connection_with_timings(host, port)
+            .await
+            .map(|(stream, _)| stream)
+    }
+
+    /// Create a TLS connection and report how long DNS, TCP connect and the
+    /// TLS handshake took
+    ///
+    /// When the server rejects ECH and the connection is retried, the timings
+    /// describe the attempt that succeeded.
+    pub async fn create_connection_with_timings(
+        &self,
+        host: &str,
+        port: u16,
+    ) -> Result<(tokio_rustls::client::TlsStream<TcpStream>, ConnectionTimings), TlsError> {
         tracing::debug!("Creating enterprise TLS connection to {}:{}", host, port);
-        
+
+        let ech_config_list = self.resolve_ech_config_list(host).await?;
+
+        let Some(config_list) = ech_config_list else {
+            if self.config.ech_policy == EchPolicy::Require {
+                return Err(TlsError::EncryptedClientHello(format!(
+                    "ECH required but no ECH configuration is available for {host}"
+                )));
+            }
+            let client_config = self.create_client_config_sync()?;
+            return self.handshake(host, port, client_config).await
+                .map_err(handshake_error);
+        };
+
+        let client_config = self.create_client_config_with_ech(Some(ech::ech_mode(&config_list)?))?;
+        match self.handshake(host, port, client_config).await {
+            Ok(stream) => Ok(stream),
+            Err(e) if ech::is_ech_rejection(&e) => {
+                if let Some(retry_list) = ech::retry_configs(&e) {
+                    tracing::debug!(
+                        target: "quyc::tls::ech",
+                        host = %host,
+                        "Server rejected ECH, retrying with server-provided retry configs"
+                    );
+                    let retry_config = self.create_client_config_with_ech(Some(ech::ech_mode(&retry_list)?))?;
+                    return self.handshake(host, port, retry_config).await
+                        .map_err(|e| TlsError::EncryptedClientHello(format!("TLS handshake with ECH retry configs failed: {}", e)));
+                }
+
+                if self.config.ech_policy == EchPolicy::Require {
+                    return Err(TlsError::EncryptedClientHello(format!(
+                        "Server {host} rejected ECH without retry configs"
+                    )));
+                }
+
+                tracing::warn!(
+                    target: "quyc::tls::ech",
+                    host = %host,
+                    "Server rejected ECH without retry configs, connecting without ECH"
+                );
+                let client_config = self.create_client_config_sync()?;
+                self.handshake(host, port, client_config).await
+                    .map_err(handshake_error)
+            }
+            Err(e) => Err(handshake_error(e)),
+        }
+    }
+
+    /// Resolve the ECH configuration for `host` according to the policy
+    ///
+    /// Explicit configuration wins over DNS. DNS lookup failures are fatal only
+    /// when ECH is required.
+    async fn resolve_ech_config_list(&self, host: &str) -> Result<Option<Vec<u8>>, TlsError> {
+        if !self.config.ech_policy.is_enabled() {
+            return Ok(None);
+        }
+        if let Some(list) = &self.config.ech_config_list {
+            return Ok(Some(list.clone()));
+        }
+
+        match ech::lookup_ech_config_list(&self.resolver, host).await {
+            Ok(list) => Ok(list),
+            Err(e) if self.config.ech_policy == EchPolicy::Require => Err(e),
+            Err(e) => {
+                tracing::debug!(
+                    target: "quyc::tls::ech",
+                    host = %host,
+                    error = %e,
+                    "ECH config lookup failed, connecting without ECH"
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Open a TCP connection and perform the TLS handshake with `client_config`
+    async fn handshake(
+        &self,
+        host: &str,
+        port: u16,
+        client_config: ClientConfig,
+    ) -> Result<(tokio_rustls::client::TlsStream<TcpStream>, ConnectionTimings), std::io::Error> {
+        let mut timings = ConnectionTimings::default();
+
+        // Resolve explicitly so name resolution and connect are timed separately
+        let addrs: Vec<std::net::SocketAddr> = match host.parse::<std::net::IpAddr>() {
+            Ok(ip) => vec![std::net::SocketAddr::new(ip, port)],
+            Err(_) => {
+                let dns_start = std::time::Instant::now();
+                let addrs = tokio::net::lookup_host((host, port)).await?.collect();
+                timings.dns = Some(dns_start.elapsed());
+                addrs
+            }
+        };
+
         // Create TCP connection with timeout
+        let connect_start = std::time::Instant::now();
         let tcp_stream = tokio::time::timeout(
             self.config.connect_timeout,
-            TcpStream::connect((host, port))
+            TcpStream::connect(addrs.as_slice())
         ).await
-            .map_err(|_| TlsError::Internal("Connection timeout".to_string()))?
-            .map_err(|e| TlsError::Internal(format!("Failed to connect to {}:{}: {}", host, port, e)))?;
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "Connection timeout"))??;
+        timings.connect = Some(connect_start.elapsed());
+        timings.remote_addr = tcp_stream.peer_addr().ok();
 
-        // Create enterprise TLS client configuration
-        let client_config = self.create_client_config_sync()?;
-        
         // Create TLS connector
         let connector = TlsConnector::from(Arc::new(client_config));
-        
+
         // Create server name for TLS
         let server_name = rustls::pki_types::ServerName::try_from(host.to_string())
-            .map_err(|e| TlsError::Internal(format!("Invalid hostname '{}': {}", host, e)))?;
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Invalid hostname '{}': {}", host, e)))?;
 
         // Perform TLS handshake
-        let tls_stream = connector.connect(server_name, tcp_stream).await
-            .map_err(|e| TlsError::Internal(format!("TLS handshake failed: {}", e)))?;
+        let tls_start = std::time::Instant::now();
+        let tls_stream = connector.connect(server_name, tcp_stream).await?;
+        timings.tls = Some(tls_start.elapsed());
 
-        tracing::info!("Enterprise TLS connection established to {}:{}", host, port);
-        Ok(tls_stream)
+        let (_, connection) = tls_stream.get_ref();
+        tracing::info!(
+            ech_status = ?connection.ech_status(),
+            "Enterprise TLS connection established to {}:{}", host, port
+        );
+        Ok((tls_stream, timings))
     }
     
     /// Create enterprise client configuration with full certificate validation
     fn create_client_config_sync(&self) -> Result<ClientConfig, TlsError> {
+        self.create_client_config_with_ech(None)
+    }
+
+    /// Create enterprise client configuration, optionally offering ECH
+    fn create_client_config_with_ech(&self, ech_mode: Option<rustls::client::EchMode>) -> Result<ClientConfig, TlsError> {
         // Create root certificate store
         let mut root_store = RootCertStore::empty();
         
@@ -320,6 +474,7 @@ impl TlsManager {
         
         // Create verifier that includes OCSP and CRL validation
         let verifier = Arc::new(EnterpriseServerCertVerifier::new(
+            Arc::new(root_store),
             self.ocsp_cache.clone(),
             self.crl_cache.clone(),
             self.config.enable_ocsp,
@@ -328,7 +483,17 @@ impl TlsManager {
         ));
         
         // Build configuration with enterprise verifier
-        let mut client_config = ClientConfig::builder()
+        let builder = match ech_mode {
+            // ECH implies TLS 1.3 only
+            Some(mode) => ClientConfig::builder_with_provider(ech::crypto_provider())
+                .with_ech(mode)
+                .map_err(|e| TlsError::EncryptedClientHello(format!("Failed to enable ECH: {}", e)))?,
+            // Explicit provider: both ring and aws-lc-rs are compiled in
+            None => ClientConfig::builder_with_provider(ech::crypto_provider())
+                .with_safe_default_protocol_versions()
+                .map_err(|e| TlsError::Internal(format!("Failed to configure TLS protocol versions: {}", e)))?,
+        };
+        let mut client_config = builder
             .dangerous()
             .with_custom_certificate_verifier(verifier)
             .with_no_client_auth();
@@ -338,6 +503,11 @@ impl TlsManager {
             client_config.enable_early_data = true;
         }
         
+        // Install key logger only when explicitly configured or SSLKEYLOGFILE is set
+        if let Some(key_log) = super::key_log::rustls_key_log(self.config.key_log_file.as_deref()) {
+            client_config.key_log = key_log;
+        }
+        
         Ok(client_config)
     }
     
@@ -462,6 +632,7 @@ impl TlsManager {
 /// Enterprise server certificate verifier with OCSP and CRL validation
 #[derive(Debug)]
 struct EnterpriseServerCertVerifier {
+    roots: Arc<RootCertStore>,
     ocsp_cache: Arc<OcspCache>,
     crl_cache: Arc<CrlCache>,
     enable_ocsp: bool,
@@ -471,6 +642,7 @@ struct EnterpriseServerCertVerifier {
 
 impl EnterpriseServerCertVerifier {
     fn new(
+        roots: Arc<RootCertStore>,
         ocsp_cache: Arc<OcspCache>,
         crl_cache: Arc<CrlCache>,
         enable_ocsp: bool,
@@ -478,6 +650,7 @@ impl EnterpriseServerCertVerifier {
         validation_timeout: Duration,
     ) -> Self {
         Self {
+            roots,
             ocsp_cache,
             crl_cache,
             enable_ocsp,
@@ -496,9 +669,10 @@ impl rustls::client::danger::ServerCertVerifier for EnterpriseServerCertVerifier
         ocsp_response: &[u8],
         now: rustls::pki_types::UnixTime,
     ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
-        // First perform standard certificate validation
-        let webpki_verifier = rustls::client::WebPkiServerVerifier::builder(
-            Arc::new(webpki_roots::TLS_SERVER_ROOTS.iter().cloned().collect())
+        // First perform standard certificate validation against the configured roots
+        let webpki_verifier = rustls::client::WebPkiServerVerifier::builder_with_provider(
+            self.roots.clone(),
+            ech::crypto_provider(),
         ).build().map_err(|e| rustls::Error::General(format!("Failed to create webpki verifier: {}", e)))?;
         
         // Perform standard validation
@@ -607,4 +781,10 @@ impl rustls::client::danger::ServerCertVerifier for EnterpriseServerCertVerifier
             .signature_verification_algorithms
             .supported_schemes()
     }
-}
\ No newline at end of file
+}
+
+/// Keep the I/O error kind of a failed handshake, so refused or reset
+/// connections can be told apart from rejected certificates
+fn handshake_error(error: std::io::Error) -> TlsError {
+    TlsError::Io(std::io::Error::new(error.kind(), format!("TLS handshake failed: {error}")))
+}
@@ -18,6 +18,7 @@ mod tests {
             keepalive_timeout: Duration::from_secs(10),
             adaptive_window: true,
             max_send_buffer_size: 1024 * 1024,
+            ..H2Config::default()
         };
         
         // Create H2Strategy
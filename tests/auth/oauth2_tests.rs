@@ -0,0 +1,191 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::StatusCode;
+use serde_json::json;
+
+use quyc_client::HttpRequest;
+use quyc_client::auth::{OAuth2, OAuth2Grant, OAuth2Token};
+use quyc_client::testing::{Mock, MockResponse, MockServer};
+
+fn token(access_token: &str, expires_in: u64) -> MockResponse {
+    MockResponse::ok().json(&json!({
+        "access_token": access_token,
+        "token_type": "Bearer",
+        "expires_in": expires_in,
+    }))
+}
+
+#[tokio::test]
+async fn test_client_credentials_token_is_cached() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(Mock::post("/token").respond(token("tok-1", 3600)));
+    server.mock(
+        Mock::get("/data")
+            .with_header("authorization", "Bearer tok-1")
+            .respond(MockResponse::ok().text("data")),
+    );
+    let oauth = OAuth2::client_credentials(&server.url("/token"), "app", "s3cret")
+        .unwrap()
+        .scope("read");
+    let client = server.client().with_oauth2(oauth);
+
+    for _ in 0..2 {
+        let mut response = client.execute(HttpRequest::get(server.url("/data").as_str()));
+        assert_eq!(response.collect_body().await, Bytes::from("data"));
+    }
+    server.assert_received("POST", "/token", 1);
+
+    let request = &server.received("POST", "/token")[0];
+    // "app:s3cret"
+    assert_eq!(request.header("authorization"), Some("Basic YXBwOnMzY3JldA=="));
+    assert_eq!(request.body_text(), "grant_type=client_credentials&scope=read");
+}
+
+#[tokio::test]
+async fn test_rejected_token_is_refreshed_and_request_replayed_once() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(Mock::post("/token").respond(token("tok-1", 3600)).respond(token("tok-2", 3600)));
+    server.mock(
+        Mock::post("/data")
+            .with_header("authorization", "Bearer tok-2")
+            .respond(MockResponse::ok().text("fresh")),
+    );
+    server.mock(Mock::any("/data").respond(MockResponse::status(401)));
+    server.mock(Mock::get("/denied").respond(MockResponse::status(401).text("still no")));
+    let oauth = OAuth2::client_credentials(&server.url("/token"), "app", "s3cret").unwrap();
+    let client = server.client().with_oauth2(oauth);
+
+    let mut response = client.execute(HttpRequest::post(server.url("/data").as_str()).body_text("payload"));
+    assert_eq!(response.collect_body().await, Bytes::from("fresh"));
+    let sent = server.received("POST", "/data");
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[1].body_text(), "payload");
+
+    // A second rejection is the server's answer, not a reason to retry again
+    let mut response = client.execute(HttpRequest::get(server.url("/denied").as_str()));
+    assert_eq!(response.status_code(), Some(StatusCode::UNAUTHORIZED));
+    assert_eq!(response.collect_body().await, Bytes::from("still no"));
+    server.assert_received("GET", "/denied", 2);
+    server.assert_received("POST", "/token", 3);
+}
+
+#[tokio::test]
+async fn test_concurrent_requests_share_one_token_request() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(Mock::post("/token").respond(token("tok-1", 3600).delay_headers(Duration::from_millis(200))));
+    server.mock(Mock::get("/data").respond(MockResponse::ok().text("data")));
+    let oauth = OAuth2::client_credentials(&server.url("/token"), "app", "s3cret").unwrap();
+    let client = server.client().with_oauth2(oauth);
+
+    let mut responses: Vec<_> = (0..4)
+        .map(|_| client.execute(HttpRequest::get(server.url("/data").as_str())))
+        .collect();
+    for response in &mut responses {
+        assert_eq!(response.collect_body().await, Bytes::from("data"));
+    }
+    server.assert_received("POST", "/token", 1);
+    assert!(
+        server
+            .received("GET", "/data")
+            .iter()
+            .all(|request| request.header("authorization") == Some("Bearer tok-1"))
+    );
+}
+
+#[tokio::test]
+async fn test_refresh_token_grant_refreshes_before_expiry() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(
+        Mock::post("/token")
+            .respond(MockResponse::ok().json(&json!({
+                "access_token": "tok-1",
+                "expires_in": 2,
+                "refresh_token": "r-2",
+            })))
+            .respond(token("tok-2", 3600)),
+    );
+    server.mock(Mock::get("/data").respond(MockResponse::ok().text("data")));
+    let oauth = OAuth2::refresh_token(&server.url("/token"), "app", "r-1")
+        .unwrap()
+        .refresh_margin(Duration::from_secs(1));
+    assert_eq!(oauth.grant(), &OAuth2Grant::RefreshToken);
+    let client = server.client().with_oauth2(oauth.clone());
+
+    let mut response = client.execute(HttpRequest::get(server.url("/data").as_str()));
+    response.collect_body().await;
+    tokio::time::sleep(Duration::from_millis(1200)).await;
+    let mut response = client.execute(HttpRequest::get(server.url("/data").as_str()));
+    response.collect_body().await;
+
+    let token_requests = server.received("POST", "/token");
+    assert_eq!(token_requests.len(), 2);
+    assert!(token_requests[0].body_text().contains("refresh_token=r-1"));
+    assert!(token_requests[1].body_text().contains("refresh_token=r-2"));
+    assert_eq!(oauth.cached_token().unwrap().access_token, "tok-2");
+    // The refresh token is kept when the answer carries no new one
+    assert_eq!(oauth.cached_token().unwrap().refresh_token.as_deref(), Some("r-2"));
+    let sent = server.received("GET", "/data");
+    assert_eq!(sent[0].header("authorization"), Some("Bearer tok-1"));
+    assert_eq!(sent[1].header("authorization"), Some("Bearer tok-2"));
+}
+
+#[tokio::test]
+async fn test_device_authorization_grant() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(Mock::post("/device").respond(MockResponse::ok().json(&json!({
+        "device_code": "dev-1",
+        "user_code": "WDJB-MJHT",
+        "verification_uri": "https://example.com/device",
+        "expires_in": 60,
+        "interval": 1,
+    }))));
+    server.mock(
+        Mock::post("/token")
+            .respond(MockResponse::status(400).json(&json!({"error": "authorization_pending"})))
+            .respond(token("tok-1", 3600)),
+    );
+    server.mock(Mock::get("/data").respond(MockResponse::ok().text("data")));
+    let oauth = OAuth2::device_code(&server.url("/device"), &server.url("/token"), "cli")
+        .unwrap()
+        .with_http_client(server.client());
+
+    let authorization = oauth.device_authorization().await.unwrap();
+    assert_eq!(authorization.user_code, "WDJB-MJHT");
+    assert_eq!(authorization.interval, Duration::from_secs(1));
+    let token = oauth.wait_for_device_token(&authorization).await.unwrap();
+    assert_eq!(token.access_token, "tok-1");
+
+    let polls = server.received("POST", "/token");
+    assert_eq!(polls.len(), 2);
+    assert!(polls[0].body_text().contains("device_code=dev-1"));
+    // A public client names itself in the body
+    assert!(polls[0].body_text().contains("client_id=cli"));
+
+    // Requests use the approved token
+    let client = server.client().with_oauth2(oauth);
+    let mut response = client.execute(HttpRequest::get(server.url("/data").as_str()));
+    assert_eq!(response.collect_body().await, Bytes::from("data"));
+    assert_eq!(server.received("GET", "/data")[0].header("authorization"), Some("Bearer tok-1"));
+}
+
+#[test]
+fn test_debug_output_redacts_secrets() {
+    let oauth = OAuth2::refresh_token("https://auth.example.com/token", "app", "refresh-secret")
+        .unwrap()
+        .client_secret("client-secret");
+    oauth.set_token(OAuth2Token {
+        access_token: "access-secret".to_owned(),
+        token_type: "Bearer".to_owned(),
+        refresh_token: Some("next-refresh-secret".to_owned()),
+        scope: None,
+        expires_at: None,
+    });
+
+    let debug = format!("{oauth:?} {:?}", oauth.cached_token().unwrap());
+    for secret in ["refresh-secret", "client-secret", "access-secret"] {
+        assert!(!debug.contains(secret), "{secret} leaked: {debug}");
+    }
+    assert!(debug.contains("app"));
+    assert!(debug.contains("<redacted>"));
+}
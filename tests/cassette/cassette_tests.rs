@@ -0,0 +1,155 @@
+use http::HeaderName;
+
+use quyc_client::cassette::{
+    Cassette, CassetteBody, CassetteMode, MatchRules, REDACTED, RecordedRequest, Redaction,
+};
+use quyc_client::client::SrvResolver;
+use quyc_client::{HttpClient, HttpRequest};
+
+#[path = "../support/mod.rs"]
+mod support;
+
+use support::cassette::exchange;
+
+fn write_cassette(name: &str) -> std::path::PathBuf {
+    support::cassette::write(
+        &format!("cassette-{name}"),
+        [
+            exchange("GET", "https://api.example.com/users/1")
+                .request_header("authorization", REDACTED)
+                .header("content-type", "application/json")
+                .chunk(0, r#"{"id":1,"#)
+                .chunk(5, r#""name":"Ada"}"#),
+            // Not UTF-8, so the body is stored base64 encoded
+            exchange("POST", "https://api.example.com/users")
+                .request_body(r#"{"name":"Grace"}"#)
+                .status(201)
+                .version("HTTP/3.0")
+                .body([0x00, 0xFF, 0x02]),
+        ],
+    )
+}
+
+fn recorded(method: &str, url: &str, body: Option<&str>) -> RecordedRequest {
+    RecordedRequest {
+        method: method.to_string(),
+        url: url.to_string(),
+        headers: Vec::new(),
+        body: body.map(|b| CassetteBody::Utf8(b.to_string())),
+    }
+}
+
+#[test]
+fn test_cassette_body_encoding_round_trip() {
+    assert_eq!(CassetteBody::from_bytes(b"hello"), CassetteBody::Utf8("hello".to_string()));
+    let binary = CassetteBody::from_bytes(&[0xff, 0x00, 0x01]);
+    assert!(matches!(binary, CassetteBody::Base64(_)));
+    assert_eq!(binary.to_bytes().as_ref(), &[0xff, 0x00, 0x01]);
+}
+
+#[test]
+fn test_match_rules() {
+    let a = recorded("POST", "https://x/y", Some("1"));
+    let b = recorded("post", "https://x/y", Some("2"));
+
+    assert!(MatchRules::default().matches(&a, &b));
+    assert!(!MatchRules::new().body(true).matches(&a, &b));
+    assert!(!MatchRules::default().matches(&a, &recorded("POST", "https://x/z", Some("1"))));
+    assert!(MatchRules::new().url(false).matches(&a, &recorded("POST", "https://x/z", None)));
+
+    let mut with_header = a.clone();
+    with_header.headers.push(("X-Tenant".to_string(), "acme".to_string()));
+    let rules = MatchRules::new().header(HeaderName::from_static("x-tenant"));
+    assert!(!rules.matches(&with_header, &a));
+    assert!(rules.matches(&with_header, &with_header.clone()));
+}
+
+#[test]
+fn test_redaction_of_headers_and_query_params() {
+    let redaction = Redaction {
+        query_params: vec!["api_key".to_string()],
+        ..Redaction::default()
+    };
+
+    let mut request = recorded("GET", "https://x/y?api_key=s3cret&page=2", None);
+    request.headers = vec![
+        ("Authorization".to_string(), "Bearer token".to_string()),
+        ("accept".to_string(), "application/json".to_string()),
+    ];
+    redaction.request(&mut request);
+
+    assert_eq!(request.url, "https://x/y?api_key=%5BREDACTED%5D&page=2");
+    assert_eq!(request.headers[0].1, REDACTED);
+    assert_eq!(request.headers[1].1, "application/json");
+}
+
+#[test]
+fn test_snapshot_redacts_authorization() {
+    let cassette = Cassette::record(std::env::temp_dir().join("quyc-unused.json"));
+    let request = HttpRequest::get("https://api.example.com/users/1")
+        .header("authorization", "Bearer token");
+    let snapshot = cassette.snapshot(&request);
+
+    assert_eq!(snapshot.method, "GET");
+    let authorization = snapshot.headers.iter().find(|(name, _)| name == "authorization");
+    assert_eq!(authorization.map(|(_, value)| value.as_str()), Some(REDACTED));
+}
+
+#[test]
+fn test_replay_mode_requires_existing_file() {
+    assert!(Cassette::replay("/nonexistent/quyc/cassette.json").is_err());
+    assert!(Cassette::open("/nonexistent/quyc/cassette.json", CassetteMode::ReplayOrRecord).is_ok());
+}
+
+#[tokio::test]
+async fn test_client_replays_recorded_interactions() {
+    let path = write_cassette("replay");
+    let client = HttpClient::default()
+        .with_cassette(Cassette::replay(&path).unwrap().match_on(MatchRules::new().body(true)));
+
+    let mut response = client.execute(HttpRequest::get("https://api.example.com/users/1"));
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.version, http::Version::HTTP_2);
+    assert_eq!(response.collect_body().await.as_ref(), br#"{"id":1,"name":"Ada"}"#);
+
+    let mut created = client.execute(
+        HttpRequest::post("https://api.example.com/users").body_text(r#"{"name":"Grace"}"#),
+    );
+    assert_eq!(created.status(), 201);
+    assert_eq!(created.collect_body().await.as_ref(), &[0x00, 0xFF, 0x02]);
+
+    std::fs::remove_file(path).ok();
+}
+
+#[tokio::test]
+async fn test_client_replay_miss_does_not_touch_network() {
+    let path = write_cassette("miss");
+    let client = HttpClient::default().with_cassette(Cassette::replay(&path).unwrap());
+
+    let mut response = client.execute(HttpRequest::delete("https://api.example.com/users/1"));
+    assert_eq!(response.status(), 501);
+    let body = String::from_utf8(response.collect_body().await.to_vec()).unwrap();
+    assert!(body.contains("DELETE https://api.example.com/users/1"));
+
+    std::fs::remove_file(path).ok();
+}
+
+#[tokio::test]
+async fn test_client_replays_srv_urls_without_dns() {
+    let url = "srv+https://_api._tcp.service.internal/v1/models";
+    let path = support::cassette::write("cassette-srv", [exchange("GET", url).chunk(0, "models")]);
+    // Any SRV lookup would land on this socket
+    let nameserver = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    nameserver.set_nonblocking(true).unwrap();
+    let client = HttpClient::default()
+        .with_srv_resolver(SrvResolver::with_nameservers(vec![nameserver.local_addr().unwrap()]))
+        .with_cassette(Cassette::replay(&path).unwrap());
+
+    let mut response = client.execute(HttpRequest::get(url));
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.collect_body().await.as_ref(), b"models");
+    let mut query = [0u8; 512];
+    assert!(nameserver.recv_from(&mut query).is_err());
+
+    std::fs::remove_file(path).ok();
+}
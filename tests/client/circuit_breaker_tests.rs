@@ -0,0 +1,129 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use quyc_client::client::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
+use quyc_client::middleware::Middleware;
+use quyc_client::{HttpClient, HttpError, HttpRequest};
+
+const ORIGIN: &str = "https://api.example.com";
+
+fn breaker(config: CircuitBreakerConfig) -> Arc<CircuitBreaker> {
+    Arc::new(CircuitBreaker::new(config))
+}
+
+fn fail(breaker: &Arc<CircuitBreaker>, times: usize) {
+    for _ in 0..times {
+        breaker.try_acquire(ORIGIN).unwrap().record_failure();
+    }
+}
+
+#[test]
+fn test_consecutive_failures_open_the_circuit() {
+    let breaker = breaker(CircuitBreakerConfig { consecutive_failures: 3, ..Default::default() });
+    fail(&breaker, 2);
+    breaker.try_acquire(ORIGIN).unwrap().record_success();
+    fail(&breaker, 2);
+    assert_eq!(breaker.state(ORIGIN), CircuitState::Closed);
+
+    fail(&breaker, 1);
+    assert_eq!(breaker.state(ORIGIN), CircuitState::Open);
+    let error = breaker.try_acquire(ORIGIN).unwrap_err();
+    assert!(error.is_circuit_open());
+    assert_eq!(breaker.state("https://other.example.com"), CircuitState::Closed);
+
+    let stats = breaker.stats().snapshot();
+    assert_eq!(stats.opened, 1);
+    assert_eq!(stats.rejected, 1);
+    assert_eq!(stats.recent_transitions[0].origin, ORIGIN);
+    assert_eq!(stats.recent_transitions[0].to, CircuitState::Open);
+}
+
+#[test]
+fn test_failure_rate_opens_the_circuit() {
+    let breaker = breaker(CircuitBreakerConfig {
+        failure_rate_threshold: 0.5,
+        minimum_requests: 4,
+        window_size: 4,
+        consecutive_failures: 10,
+        ..Default::default()
+    });
+    breaker.try_acquire(ORIGIN).unwrap().record_success();
+    breaker.try_acquire(ORIGIN).unwrap().record_failure();
+    breaker.try_acquire(ORIGIN).unwrap().record_success();
+    assert_eq!(breaker.state(ORIGIN), CircuitState::Closed);
+    breaker.try_acquire(ORIGIN).unwrap().record_failure();
+    assert_eq!(breaker.state(ORIGIN), CircuitState::Open);
+}
+
+#[test]
+fn test_half_open_probes_close_or_reopen() {
+    let config = CircuitBreakerConfig {
+        consecutive_failures: 1,
+        cooldown: Duration::from_millis(20),
+        half_open_probes: 2,
+        ..Default::default()
+    };
+    let breaker = breaker(config);
+    fail(&breaker, 1);
+    std::thread::sleep(Duration::from_millis(30));
+
+    // Only two probes at a time
+    let first = breaker.try_acquire(ORIGIN).unwrap();
+    assert_eq!(breaker.state(ORIGIN), CircuitState::HalfOpen);
+    let second = breaker.try_acquire(ORIGIN).unwrap();
+    assert!(breaker.try_acquire(ORIGIN).unwrap_err().is_circuit_open());
+
+    // A dropped permit frees its slot without counting
+    drop(second);
+    first.record_success();
+    breaker.try_acquire(ORIGIN).unwrap().record_success();
+    assert_eq!(breaker.state(ORIGIN), CircuitState::Closed);
+
+    fail(&breaker, 1);
+    std::thread::sleep(Duration::from_millis(30));
+    breaker.try_acquire(ORIGIN).unwrap().record_failure();
+    assert_eq!(breaker.state(ORIGIN), CircuitState::Open);
+
+    let stats = breaker.stats().snapshot();
+    assert_eq!((stats.opened, stats.half_opened, stats.closed), (3, 2, 1));
+}
+
+#[test]
+fn test_outcomes_from_an_earlier_state_are_ignored() {
+    let breaker = breaker(CircuitBreakerConfig { consecutive_failures: 1, ..Default::default() });
+    let slow = breaker.try_acquire(ORIGIN).unwrap();
+    fail(&breaker, 1);
+    breaker.reset(ORIGIN);
+    assert_eq!(breaker.state(ORIGIN), CircuitState::Closed);
+
+    // Admitted before the circuit opened; must not open the fresh circuit
+    slow.record_failure();
+    assert_eq!(breaker.state(ORIGIN), CircuitState::Closed);
+}
+
+struct SeesCircuitOpen(Arc<AtomicBool>);
+
+impl Middleware for SeesCircuitOpen {
+    fn handle_error(&self, error: HttpError) -> quyc_client::error::Result<HttpError> {
+        self.0.store(error.is_circuit_open(), Ordering::SeqCst);
+        Ok(error)
+    }
+}
+
+#[tokio::test]
+async fn test_client_fails_fast_while_open() {
+    let seen = Arc::new(AtomicBool::new(false));
+    let client = HttpClient::default()
+        .with_circuit_breaker(CircuitBreakerConfig { consecutive_failures: 1, ..Default::default() })
+        .with_middleware(SeesCircuitOpen(seen.clone()));
+    let breaker = client.circuit_breaker().unwrap().clone();
+    fail(&breaker, 1);
+
+    let mut response = client.execute(HttpRequest::get("https://api.example.com/v1/models"));
+    assert_eq!(response.status(), 503);
+    let body = response.collect_body().await;
+    assert!(String::from_utf8_lossy(&body).contains("circuit breaker open"));
+    assert!(seen.load(Ordering::SeqCst));
+    assert_eq!(breaker.stats().snapshot().rejected, 1);
+}
@@ -0,0 +1,118 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use bytes::Bytes;
+
+use quyc_client::client::Coalescer;
+use quyc_client::testing::MockResponse;
+use quyc_client::{HttpRequest, HttpResponse};
+
+const MODELS: &str = "https://api.example.com/v1/models";
+
+/// Models listing, streamed in two chunks so joiners replay every chunk
+fn models() -> HttpResponse {
+    MockResponse::ok()
+        .header("content-type", "application/json")
+        .chunk("{\"data\":")
+        .chunk("[]}")
+        .into_response()
+}
+
+/// Wait until `count` requests have joined the flight in progress
+fn await_joined(coalescer: &Coalescer, count: u64) {
+    while coalescer.coalesced_requests() < count {
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[tokio::test]
+async fn test_concurrent_requests_share_one_upstream_request() {
+    let coalescer = Arc::new(Coalescer::new());
+    let sent = Arc::new(AtomicU32::new(0));
+
+    let waiters: Vec<_> = (0..3)
+        .map(|_| {
+            let coalescer = coalescer.clone();
+            let sent = sent.clone();
+            thread::spawn(move || {
+                let leader_view = coalescer.clone();
+                coalescer.execute(HttpRequest::get(MODELS), move |_request| {
+                    sent.fetch_add(1, Ordering::SeqCst);
+                    await_joined(&leader_view, 2);
+                    Ok(models())
+                })
+            })
+        })
+        .collect();
+
+    for waiter in waiters {
+        let mut response = waiter.join().unwrap().unwrap();
+        assert_eq!(response.collect_body().await, Bytes::from("{\"data\":[]}"));
+        assert_eq!(response.status(), 200);
+    }
+    assert_eq!(sent.load(Ordering::SeqCst), 1);
+    assert_eq!(coalescer.coalesced_requests(), 2);
+    assert_eq!(coalescer.in_flight(), 0);
+}
+
+#[test]
+fn test_send_errors_reach_every_waiter() {
+    let coalescer = Arc::new(Coalescer::new());
+    let leader_view = coalescer.clone();
+    let leader = {
+        let coalescer = coalescer.clone();
+        thread::spawn(move || {
+            coalescer.execute(HttpRequest::get(MODELS), move |_request| {
+                await_joined(&leader_view, 1);
+                Err(quyc_client::error::rate_limited("budget exhausted"))
+            })
+        })
+    };
+    // Wait for the leader's flight before joining it
+    while coalescer.in_flight() == 0 {
+        thread::sleep(Duration::from_millis(1));
+    }
+    let follower = coalescer.execute(HttpRequest::get(MODELS), |_request| panic!("follower must not send"));
+
+    assert!(follower.unwrap_err().is_rate_limited());
+    assert!(leader.join().unwrap().unwrap_err().is_rate_limited());
+    assert_eq!(coalescer.in_flight(), 0);
+}
+
+#[test]
+fn test_only_identical_safe_requests_coalesce() {
+    let get = HttpRequest::get(MODELS);
+    assert!(Coalescer::is_coalescable(&get));
+    assert!(!Coalescer::is_coalescable(&HttpRequest::post(MODELS).body_text("{}")));
+    assert!(!Coalescer::is_coalescable(&HttpRequest::get(MODELS).body_text("{}")));
+
+    assert_eq!(Coalescer::key_for(&get), Coalescer::key_for(&HttpRequest::get(MODELS)));
+    assert_ne!(
+        Coalescer::key_for(&HttpRequest::get(MODELS).bearer_auth("key-a")),
+        Coalescer::key_for(&HttpRequest::get(MODELS).bearer_auth("key-b"))
+    );
+    assert_ne!(
+        Coalescer::key_for(&HttpRequest::get(MODELS).header("x-api-key", "key-a")),
+        Coalescer::key_for(&HttpRequest::get(MODELS).header("x-api-key", "key-b"))
+    );
+    // Any header may change the answer, so every one of them splits requests
+    for name in ["x-tenant-id", "anthropic-version", "openai-organization", "proxy-authorization"] {
+        assert_ne!(
+            Coalescer::key_for(&HttpRequest::get(MODELS).header(name, "a")),
+            Coalescer::key_for(&HttpRequest::get(MODELS).header(name, "b"))
+        );
+    }
+    assert_eq!(
+        Coalescer::key_for(&HttpRequest::get(MODELS).header("x-tenant-id", "a").header("accept", "*/*")),
+        Coalescer::key_for(&HttpRequest::get(MODELS).header("accept", "*/*").header("x-tenant-id", "a"))
+    );
+
+    // Requests that cannot be coalesced are always sent
+    let coalescer = Arc::new(Coalescer::new());
+    let post = coalescer.execute(HttpRequest::post(MODELS).body_text("{}"), |_request| Ok(models()));
+    assert!(post.is_ok());
+    assert_eq!(coalescer.in_flight(), 0);
+    assert_eq!(coalescer.coalesced_requests(), 0);
+}
@@ -0,0 +1,154 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use url::Url;
+
+use quyc_client::client::{Endpoint, EndpointGroup, EndpointGroupConfig, SelectionPolicy};
+use quyc_client::testing::MockResponse;
+use quyc_client::HttpRequest;
+
+const US: &str = "https://us.api.example.com/openai/v1";
+const EU: &str = "https://eu.api.example.com/v1";
+const LOCAL: &str = "http://10.0.0.7:8000/v1";
+
+fn group(policy: SelectionPolicy) -> EndpointGroup {
+    let endpoints = vec![
+        Endpoint::new(US).unwrap(),
+        Endpoint::new(EU).unwrap(),
+        Endpoint::new(LOCAL).unwrap(),
+    ];
+    EndpointGroup::new("https://llm.internal/v1", endpoints)
+        .unwrap()
+        .with_policy(policy)
+}
+
+#[test]
+fn test_requests_are_moved_onto_endpoints() {
+    let group = group(SelectionPolicy::RoundRobin);
+    let url = Url::parse("https://llm.internal/v1/chat/completions?stream=true").unwrap();
+    assert!(group.matches(&url));
+    assert!(!group.matches(&Url::parse("https://llm.internal/v10/models").unwrap()));
+    assert!(!group.matches(&Url::parse("https://other.internal/v1/models").unwrap()));
+
+    assert_eq!(
+        group.rewrite(&url, 0).unwrap().as_str(),
+        "https://us.api.example.com/openai/v1/chat/completions?stream=true"
+    );
+    assert_eq!(
+        group.rewrite(&url, 2).unwrap().as_str(),
+        "http://10.0.0.7:8000/v1/chat/completions?stream=true"
+    );
+}
+
+#[test]
+fn test_selection_policies() {
+    let round_robin = group(SelectionPolicy::RoundRobin);
+    let picks: Vec<usize> = (0..6).map(|_| round_robin.select(&[]).unwrap()).collect();
+    assert_eq!(picks, vec![0, 1, 2, 0, 1, 2]);
+    assert_eq!(round_robin.select(&[0, 1]), Some(2));
+    assert_eq!(round_robin.select(&[0, 1, 2]), None);
+
+    let least = group(SelectionPolicy::LeastOutstanding);
+    least.intelligence().domain("https://us.api.example.com").health.begin_request();
+    least.intelligence().domain("http://10.0.0.7:8000").health.begin_request();
+    for _ in 0..3 {
+        assert_eq!(least.select(&[]), Some(1));
+    }
+
+    let ewma = group(SelectionPolicy::EwmaLatency);
+    let latencies = [("https://us.api.example.com", 120), ("https://eu.api.example.com", 40), ("http://10.0.0.7:8000", 300)];
+    for (origin, millis) in latencies {
+        ewma.intelligence().domain(origin).health.record_success(Duration::from_millis(millis));
+    }
+    assert_eq!(ewma.select(&[]), Some(1));
+
+    let weighted = EndpointGroup::new(
+        "https://llm.internal/v1",
+        vec![Endpoint::weighted(US, 0).unwrap(), Endpoint::weighted(EU, 5).unwrap()],
+    )
+    .unwrap()
+    .with_policy(SelectionPolicy::Weighted);
+    for _ in 0..50 {
+        assert_eq!(weighted.select(&[]), Some(1));
+    }
+}
+
+#[test]
+fn test_failing_endpoints_are_ejected() {
+    let group = group(SelectionPolicy::RoundRobin).with_config(EndpointGroupConfig {
+        failure_threshold: 2,
+        ejection_time: Duration::from_secs(60),
+        ..Default::default()
+    });
+    let health = &group.intelligence().domain("https://us.api.example.com").health;
+    assert!(!health.record_failure(2, Duration::from_secs(60)));
+    assert!(health.is_available());
+    assert!(health.record_failure(2, Duration::from_secs(60)));
+    assert!(!health.is_available());
+
+    for _ in 0..6 {
+        assert_ne!(group.select(&[]), Some(0));
+    }
+    // With every other endpoint tried, the ejected one still gets the request
+    assert_eq!(group.select(&[1, 2]), Some(0));
+
+    health.mark_healthy();
+    assert!(group.status()[0].available);
+}
+
+#[tokio::test]
+async fn test_retryable_answers_fail_over() {
+    let group = Arc::new(group(SelectionPolicy::RoundRobin));
+    let hosts = Arc::new(Mutex::new(Vec::new()));
+    let seen = hosts.clone();
+    let send = move |request: HttpRequest| {
+        let host = request.url().host_str().unwrap().to_string();
+        seen.lock().unwrap().push(host.clone());
+        if host.starts_with("us.") {
+            MockResponse::status(503).body("unavailable").into_response()
+        } else {
+            MockResponse::ok().body("ok").into_response()
+        }
+    };
+
+    let mut response = group.execute(HttpRequest::get("https://llm.internal/v1/models"), send.clone());
+    assert_eq!(response.collect_body().await, Bytes::from("ok"));
+    assert_eq!(response.status(), 200);
+    assert_eq!(*hosts.lock().unwrap(), vec!["us.api.example.com", "10.0.0.7"]);
+
+    let status = group.status();
+    assert_eq!(status[0].consecutive_failures, 1);
+    assert!(status[2].ewma_latency.is_some());
+
+    // Requests that cannot be replayed stay on the endpoint they were sent to
+    hosts.lock().unwrap().clear();
+    group.select(&[]);
+    let post = HttpRequest::post("https://llm.internal/v1/chat/completions").body_text("{}");
+    let mut response = group.execute(post, send);
+    assert_eq!(response.collect_body().await, Bytes::from("unavailable"));
+    assert_eq!(*hosts.lock().unwrap(), vec!["us.api.example.com"]);
+}
+
+#[tokio::test]
+async fn test_requests_stay_outstanding_until_the_body_ends() {
+    let group = Arc::new(group(SelectionPolicy::RoundRobin));
+    let send = |_: HttpRequest| {
+        MockResponse::ok()
+            .chunks(["data: 1\n\n", "data: 2\n\n"], Duration::from_millis(300))
+            .into_response()
+    };
+
+    let mut response = group.execute(HttpRequest::get("https://llm.internal/v1/stream"), send);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(response.status(), 200);
+    assert_eq!(group.status()[0].outstanding, 1);
+
+    assert_eq!(response.collect_body().await, Bytes::from("data: 1\n\ndata: 2\n\n"));
+    let mut waited = Duration::ZERO;
+    while group.status()[0].outstanding > 0 && waited < Duration::from_secs(1) {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        waited += Duration::from_millis(10);
+    }
+    assert_eq!(group.status()[0].outstanding, 0);
+}
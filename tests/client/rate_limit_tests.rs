@@ -0,0 +1,201 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, StatusCode};
+use quyc_client::client::rate_limit::parse_reset;
+use quyc_client::client::{RateLimitConfig, RateLimiter};
+use quyc_client::http::headers::parse_retry_after;
+use quyc_client::protocols::strategy::{H2Config, HttpProtocolStrategy};
+use quyc_client::retry::RetryPolicy;
+use quyc_client::testing::{Mock, MockResponse, MockServer};
+use quyc_client::{HttpClient, HttpRequest, Url};
+
+const ORIGIN: &str = "https://api.example.com";
+
+fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        headers.insert(*name, HeaderValue::from_static(value));
+    }
+    headers
+}
+
+fn limiter(requests_per_second: f64, burst: u32) -> RateLimiter {
+    RateLimiter::new(RateLimitConfig {
+        requests_per_second,
+        burst,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_burst_then_queue_behind_refill() {
+    let limiter = limiter(10.0, 2);
+    assert_eq!(limiter.acquire(ORIGIN).unwrap(), Duration::ZERO);
+    assert_eq!(limiter.acquire(ORIGIN).unwrap(), Duration::ZERO);
+
+    // Each queued request waits one refill interval longer than the last
+    let third = limiter.acquire(ORIGIN).unwrap();
+    let fourth = limiter.acquire(ORIGIN).unwrap();
+    assert!(third > Duration::from_millis(80) && third <= Duration::from_millis(100));
+    assert!(fourth > Duration::from_millis(180) && fourth <= Duration::from_millis(200));
+
+    let metrics = limiter.get_metrics(ORIGIN).unwrap();
+    assert_eq!(metrics.total_requests, 4);
+    assert_eq!(metrics.delayed_requests, 2);
+    assert!(limiter.get_metrics("https://other.example.com").is_none());
+}
+
+#[test]
+fn test_wait_beyond_max_wait_is_rejected() {
+    let limiter = RateLimiter::new(RateLimitConfig {
+        requests_per_second: 1.0,
+        burst: 1,
+        max_wait: Duration::from_millis(1500),
+        ..Default::default()
+    });
+    limiter.acquire(ORIGIN).unwrap();
+    limiter.acquire(ORIGIN).unwrap();
+
+    let error = limiter.acquire(ORIGIN).unwrap_err();
+    assert!(error.is_rate_limited());
+    let metrics = limiter.get_metrics(ORIGIN).unwrap();
+    assert_eq!(metrics.rejected_requests, 1);
+    assert_eq!(metrics.total_requests, 2);
+}
+
+#[test]
+fn test_learns_budget_and_rate_from_openai_headers() {
+    let limiter = limiter(100.0, 100);
+    limiter.acquire(ORIGIN).unwrap();
+    limiter.learn(
+        ORIGIN,
+        Some(StatusCode::OK),
+        &headers(&[
+            ("x-ratelimit-limit-requests", "60"),
+            ("x-ratelimit-remaining-requests", "30"),
+            ("x-ratelimit-reset-requests", "30s"),
+        ]),
+    );
+
+    let metrics = limiter.get_metrics(ORIGIN).unwrap();
+    assert_eq!(metrics.capacity, 60.0);
+    assert!((metrics.refill_rate - 1.0).abs() < 1e-9);
+    assert!(metrics.tokens <= 30.5);
+    assert_eq!(metrics.held_for, Duration::ZERO);
+}
+
+#[test]
+fn test_exhausted_budgets_hold_until_reset() {
+    let limiter = limiter(10.0, 10);
+    limiter.acquire(ORIGIN).unwrap();
+    limiter.learn(
+        ORIGIN,
+        Some(StatusCode::OK),
+        &headers(&[
+            ("anthropic-ratelimit-requests-remaining", "5"),
+            ("anthropic-ratelimit-output-tokens-remaining", "0"),
+            ("anthropic-ratelimit-output-tokens-reset", "2s"),
+        ]),
+    );
+    let held = limiter.held_for(ORIGIN);
+    assert!(held > Duration::from_millis(1800) && held <= Duration::from_secs(2));
+    assert!(limiter.acquire(ORIGIN).unwrap() > Duration::from_millis(1800));
+
+    let limiter = self::limiter(10.0, 10);
+    limiter.acquire(ORIGIN).unwrap();
+    limiter.learn(ORIGIN, Some(StatusCode::TOO_MANY_REQUESTS), &headers(&[("retry-after", "3")]));
+    assert!(limiter.held_for(ORIGIN) > Duration::from_millis(2800));
+}
+
+#[test]
+fn test_bare_429_halves_refill_rate() {
+    let limiter = limiter(8.0, 8);
+    limiter.acquire(ORIGIN).unwrap();
+    limiter.learn(ORIGIN, Some(StatusCode::TOO_MANY_REQUESTS), &HeaderMap::new());
+
+    let metrics = limiter.get_metrics(ORIGIN).unwrap();
+    assert_eq!(metrics.refill_rate, 4.0);
+    assert!(metrics.tokens < 1.0);
+    assert!(limiter.acquire(ORIGIN).unwrap() > Duration::ZERO);
+}
+
+#[test]
+fn test_per_api_key_buckets() {
+    let shared = limiter(1.0, 1);
+    let first = HttpRequest::get("https://api.example.com/v1/models").bearer_auth("key-a");
+    let second = HttpRequest::get("https://api.example.com/v1/chat").bearer_auth("key-b");
+    assert_eq!(shared.key_for(&first), ORIGIN);
+    assert_eq!(shared.key_for(&second), ORIGIN);
+
+    let split = RateLimiter::new(RateLimitConfig {
+        per_api_key: true,
+        ..Default::default()
+    });
+    assert_ne!(split.key_for(&first), split.key_for(&second));
+    assert!(split.key_for(&first).starts_with(ORIGIN));
+    assert_eq!(split.key_for(&HttpRequest::get("https://api.example.com/")), ORIGIN);
+}
+
+#[test]
+fn test_reset_and_retry_after_formats() {
+    assert_eq!(parse_reset("6m0s"), Some(Duration::from_secs(360)));
+    assert_eq!(parse_reset("1h2m3.5s"), Some(Duration::from_secs_f64(3723.5)));
+    assert_eq!(parse_reset("20ms"), Some(Duration::from_millis(20)));
+    assert_eq!(parse_reset("12"), Some(Duration::from_secs(12)));
+    assert_eq!(parse_reset("2001-01-01T00:00:00Z"), Some(Duration::ZERO));
+    assert_eq!(parse_reset("soon"), None);
+
+    assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+    assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+    assert_eq!(parse_retry_after("later"), None);
+}
+
+fn retrying_client(server: &MockServer, rate_limit: RateLimitConfig) -> HttpClient {
+    HttpClient::with_config_and_strategy(server.http_config(), HttpProtocolStrategy::Http2(H2Config::default()))
+        .with_retry_policy(RetryPolicy {
+            max_attempts: 3,
+            initial_delay_ms: 1,
+            ..RetryPolicy::default()
+        })
+        .with_rate_limit(rate_limit)
+}
+
+#[tokio::test]
+async fn test_retries_take_a_rate_limit_token() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(
+        Mock::get("/flaky")
+            .respond(MockResponse::status(503).header("retry-after", "0"))
+            .respond(MockResponse::ok().text("ok")),
+    );
+    let client = retrying_client(&server, RateLimitConfig::default());
+
+    let mut response = client.execute(HttpRequest::get(server.url("/flaky").as_str()));
+    assert_eq!(response.collect_body().await, Bytes::from("ok"));
+    server.assert_received("GET", "/flaky", 2);
+
+    let origin = Url::parse(&server.url("/")).unwrap().origin().ascii_serialization();
+    let metrics = client.rate_limiter().unwrap().get_metrics(&origin).unwrap();
+    assert_eq!(metrics.total_requests, 2);
+}
+
+#[tokio::test]
+async fn test_retries_beyond_max_wait_fail_rate_limited() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(Mock::get("/flaky").respond(MockResponse::status(503).header("retry-after", "0")));
+    let client = retrying_client(
+        &server,
+        RateLimitConfig {
+            requests_per_second: 0.5,
+            burst: 1,
+            max_wait: Duration::from_millis(100),
+            ..Default::default()
+        },
+    );
+
+    let mut response = client.execute(HttpRequest::get(server.url("/flaky").as_str()));
+    response.collect_body().await;
+    assert_eq!(response.status(), 429);
+    server.assert_received("GET", "/flaky", 1);
+}
@@ -0,0 +1,193 @@
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use url::Url;
+
+use quyc_client::client::{EndpointGroup, SrvRecord, SrvResolver};
+use quyc_client::testing::MockResponse;
+use quyc_client::HttpRequest;
+
+const SERVICE: &str = "_api._tcp.service.internal";
+const BASE: &str = "srv+https://_api._tcp.service.internal";
+
+/// Records served by the stand-in: (priority, weight, port, target) and TTL
+type Zone = Arc<Mutex<(Vec<(u16, u16, u16, &'static str)>, u32)>>;
+
+/// Local DNS stand-in answering every SRV query from `zone`
+fn dns_stand_in(zone: Zone) -> SocketAddr {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let address = socket.local_addr().unwrap();
+    thread::spawn(move || {
+        let mut query = [0u8; 512];
+        while let Ok((len, peer)) = socket.recv_from(&mut query) {
+            // Question: labels up to the root label, then type and class
+            let mut end = 12;
+            while end < len && query[end] != 0 {
+                end += usize::from(query[end]) + 1;
+            }
+            end += 5;
+            if end > len {
+                continue;
+            }
+            let is_srv = query[end - 4..end - 2] == [0, 33];
+
+            let (records, ttl) = zone.lock().unwrap().clone();
+            let answers = if is_srv { records.len() as u16 } else { 0 };
+            let mut reply = Vec::new();
+            reply.extend_from_slice(&query[..2]);
+            reply.extend_from_slice(&[0x81, 0x80, 0, 1]);
+            reply.extend_from_slice(&answers.to_be_bytes());
+            reply.extend_from_slice(&[0, 0, 0, 0]);
+            reply.extend_from_slice(&query[12..end]);
+            for &(priority, weight, port, target) in records.iter().take(usize::from(answers)) {
+                let mut rdata = Vec::new();
+                for value in [priority, weight, port] {
+                    rdata.extend_from_slice(&value.to_be_bytes());
+                }
+                for label in target.split('.').filter(|label| !label.is_empty()) {
+                    rdata.push(label.len() as u8);
+                    rdata.extend_from_slice(label.as_bytes());
+                }
+                rdata.push(0);
+                // Name: pointer to the question, type SRV, class IN
+                reply.extend_from_slice(&[0xC0, 0x0C, 0, 33, 0, 1]);
+                reply.extend_from_slice(&ttl.to_be_bytes());
+                reply.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+                reply.extend_from_slice(&rdata);
+            }
+            drop(socket.send_to(&reply, peer));
+        }
+    });
+    address
+}
+
+#[test]
+fn test_lookup_reads_srv_records() {
+    let zone: Zone = Arc::new(Mutex::new((
+        vec![(10, 60, 8443, "api-1.service.internal."), (20, 0, 9443, "api-2.service.internal.")],
+        300,
+    )));
+    let resolver = SrvResolver::with_nameservers(vec![dns_stand_in(zone.clone())]);
+
+    let before = Instant::now();
+    let lookup = resolver.lookup(SERVICE).unwrap();
+    assert_eq!(
+        lookup.records,
+        vec![
+            SrvRecord {
+                priority: 10,
+                weight: 60,
+                port: 8443,
+                target: "api-1.service.internal".to_string()
+            },
+            SrvRecord {
+                priority: 20,
+                weight: 0,
+                port: 9443,
+                target: "api-2.service.internal".to_string()
+            },
+        ]
+    );
+    assert!(lookup.valid_until >= before + Duration::from_secs(299));
+    assert!(lookup.valid_until <= Instant::now() + Duration::from_secs(300));
+
+    // A service without records is an error
+    zone.lock().unwrap().0.clear();
+    assert!(resolver.lookup(SERVICE).is_err());
+}
+
+#[tokio::test]
+async fn test_priorities_and_weights_order_failover() {
+    let zone: Zone = Arc::new(Mutex::new((
+        vec![
+            (0, 5, 8443, "a.service.internal."),
+            (1, 1, 9443, "c.service.internal."),
+            (0, 0, 8443, "b.service.internal."),
+        ],
+        300,
+    )));
+    let resolver = SrvResolver::with_nameservers(vec![dns_stand_in(zone)]);
+    let group = Arc::new(EndpointGroup::from_srv(BASE, SERVICE, resolver).unwrap());
+    assert_eq!(group.srv_service(), Some(SERVICE));
+    assert!(group.endpoints().is_empty());
+
+    group.refresh().unwrap();
+    let endpoints: Vec<(String, u32, u16)> = group
+        .endpoints()
+        .iter()
+        .map(|endpoint| (endpoint.url.to_string(), endpoint.weight, endpoint.priority))
+        .collect();
+    assert_eq!(
+        endpoints,
+        vec![
+            ("https://a.service.internal:8443/".to_string(), 5, 0),
+            ("https://c.service.internal:9443/".to_string(), 1, 1),
+            ("https://b.service.internal:8443/".to_string(), 0, 0),
+        ]
+    );
+
+    let url: Url = "srv+https://_api._tcp.service.internal/v1/models?limit=5".parse().unwrap();
+    assert!(group.matches(&url));
+    // Only the lowest priority is picked while it has endpoints left, by weight
+    for _ in 0..20 {
+        assert_eq!(group.select(&[]), Some(0));
+    }
+    assert_eq!(group.select(&[0]), Some(2));
+    assert_eq!(group.select(&[0, 2]), Some(1));
+
+    let urls = Arc::new(Mutex::new(Vec::new()));
+    let seen = urls.clone();
+    let send = move |request: HttpRequest| {
+        seen.lock().unwrap().push(request.url().to_string());
+        if request.url().port() == Some(9443) {
+            MockResponse::ok().body("ok").into_response()
+        } else {
+            MockResponse::status(503).body("unavailable").into_response()
+        }
+    };
+    let mut response = group.execute(HttpRequest::get(url), send);
+    assert_eq!(response.collect_body().await, Bytes::from("ok"));
+    assert_eq!(
+        *urls.lock().unwrap(),
+        vec![
+            "https://a.service.internal:8443/v1/models?limit=5",
+            "https://b.service.internal:8443/v1/models?limit=5",
+            "https://c.service.internal:9443/v1/models?limit=5",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_endpoints_refresh_once_the_ttl_expires() {
+    let zone: Zone = Arc::new(Mutex::new((vec![(0, 1, 8443, "old.service.internal.")], 1)));
+    let resolver = SrvResolver::with_nameservers(vec![dns_stand_in(zone.clone())]);
+    let group = Arc::new(EndpointGroup::from_srv(BASE, SERVICE, resolver).unwrap());
+
+    let hosts = Arc::new(Mutex::new(Vec::new()));
+    let seen = hosts.clone();
+    let send = move |request: HttpRequest| {
+        seen.lock().unwrap().push(request.url().host_str().unwrap().to_string());
+        MockResponse::ok().body("ok").into_response()
+    };
+
+    // The first request looks the records up
+    let mut response = group.execute(HttpRequest::get(format!("{BASE}/v1/models").as_str()), send.clone());
+    assert_eq!(response.collect_body().await, Bytes::from("ok"));
+
+    zone.lock().unwrap().0 = vec![(0, 1, 8443, "new.service.internal.")];
+    let mut response = group.execute(HttpRequest::get(format!("{BASE}/v1/models").as_str()), send.clone());
+    assert_eq!(response.collect_body().await, Bytes::from("ok"));
+
+    thread::sleep(Duration::from_millis(1500));
+    let mut response = group.execute(HttpRequest::get(format!("{BASE}/v1/models").as_str()), send);
+    assert_eq!(response.collect_body().await, Bytes::from("ok"));
+
+    assert_eq!(
+        *hosts.lock().unwrap(),
+        vec!["old.service.internal", "old.service.internal", "new.service.internal"]
+    );
+    assert_eq!(group.status().len(), 1);
+}
@@ -0,0 +1,226 @@
+use std::sync::Arc;
+
+use bytes::Bytes;
+use http::Method;
+use http::header::COOKIE;
+
+use quyc_client::cassette::{Cassette, MatchRules, Redaction};
+use quyc_client::client::configuration::HttpClientBuilder;
+use quyc_client::cookie::{CookieContext, CookieFileFormat, CookieStore, Jar, SameSite};
+use quyc_client::{HttpRequest, Url};
+
+#[path = "../support/mod.rs"]
+mod support;
+
+use support::cassette::exchange;
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("quyc-cookies-{}-{name}", std::process::id()))
+}
+
+#[tokio::test]
+async fn test_client_sends_and_stores_cookies_across_redirects() {
+    let path = support::cassette::write(
+        "cookies-cassette",
+        [
+            exchange("POST", "https://dash.example.com/login")
+                .request_body("user=ada")
+                .status(302)
+                .header("set-cookie", "session=abc; Path=/; Secure; HttpOnly; SameSite=Lax")
+                .header("set-cookie", "csrf=t0k; Path=/; Secure; SameSite=Strict")
+                .header("set-cookie", "theme=dark; Domain=example.com; Path=/")
+                .header("set-cookie", "tracker=1; Domain=com; Path=/")
+                .header("location", "/home"),
+            exchange("GET", "https://dash.example.com/home")
+                .request_header("cookie", "session=abc; csrf=t0k; theme=dark")
+                .body("welcome"),
+            exchange("GET", "https://dash.example.com/sso")
+                .request_header("cookie", "session=abc; csrf=t0k; theme=dark")
+                .status(302)
+                .header("location", "https://auth.other.org/start"),
+            exchange("GET", "https://auth.other.org/start")
+                .status(302)
+                .header("location", "https://dash.example.com/callback"),
+            exchange("GET", "https://dash.example.com/callback")
+                .request_header("cookie", "session=abc; theme=dark")
+                .body("signed in"),
+        ],
+    );
+    // Cookie values are compared as sent, so nothing is redacted
+    let cassette = Cassette::replay(&path)
+        .unwrap()
+        .with_redaction(Redaction {
+            headers: Vec::new(),
+            query_params: Vec::new(),
+        })
+        .match_on(MatchRules::new().body(true).header(COOKIE));
+    let jar = Jar::new();
+    let browser = HttpClientBuilder::new()
+        .cookie_provider(Arc::new(jar.clone()))
+        .build()
+        .unwrap()
+        .with_cassette(cassette);
+
+    // The login redirect's cookies go out with the next hop
+    let mut response = browser.execute(HttpRequest::post("https://dash.example.com/login").body_text("user=ada"));
+    assert_eq!(response.collect_body().await, Bytes::from("welcome"));
+    let names: Vec<String> = jar.stored_cookies().into_iter().map(|cookie| cookie.name).collect();
+    assert_eq!(names, ["session", "csrf", "theme"]);
+
+    // After a detour through another site, the Strict cookie stays home
+    let mut response = browser.execute(HttpRequest::get("https://dash.example.com/sso"));
+    assert_eq!(response.collect_body().await, Bytes::from("signed in"));
+    assert_eq!(response.redirect_history().len(), 2);
+}
+
+#[test]
+fn test_domain_path_and_secure_rules() {
+    let jar = Jar::new();
+    let page = url("https://www.example.co.uk/docs/index.html");
+    jar.add_cookie_str("wide=1; Domain=example.co.uk; Path=/", &page);
+    jar.add_cookie_str("suffix=1; Domain=co.uk", &page);
+    jar.add_cookie_str("other=1; Domain=example.org", &page);
+    jar.add_cookie_str("local=1", &page);
+    jar.add_cookie_str("docs=1; Path=/docs", &page);
+    jar.add_cookie_str("secret=1; Secure; Path=/", &page);
+
+    // Cookies for a public suffix or another domain are refused
+    assert_eq!(jar.stored_cookies().len(), 4);
+    let docs = jar.stored_cookies().into_iter().find(|cookie| cookie.name == "docs").unwrap();
+    assert!(docs.host_only);
+    assert_eq!(docs.domain, "www.example.co.uk");
+    // Without a Path, the cookie belongs to the page's directory
+    let local = jar.stored_cookies().into_iter().find(|cookie| cookie.name == "local").unwrap();
+    assert_eq!(local.path, "/docs");
+
+    assert_eq!(cookie_header(&jar, "https://api.example.co.uk/"), Some("wide=1".to_string()));
+    assert_eq!(
+        cookie_header(&jar, "https://www.example.co.uk/docs/a"),
+        Some("local=1; docs=1; wide=1; secret=1".to_string())
+    );
+    assert_eq!(cookie_header(&jar, "https://www.example.co.uk/docsearch"), Some("wide=1; secret=1".to_string()));
+    assert_eq!(cookie_header(&jar, "http://www.example.co.uk/"), Some("wide=1".to_string()));
+
+    // Plain http cannot set or replace secure cookies
+    let plain = url("http://www.example.co.uk/");
+    jar.add_cookie_str("secret=2", &plain);
+    jar.add_cookie_str("new=1; Secure", &plain);
+    assert_eq!(cookie_header(&jar, "https://www.example.co.uk/"), Some("wide=1; secret=1".to_string()));
+
+    // Max-Age=0 deletes
+    jar.add_cookie_str("wide=; Domain=example.co.uk; Path=/; Max-Age=0", &page);
+    assert_eq!(cookie_header(&jar, "https://api.example.co.uk/"), None);
+}
+
+#[test]
+fn test_name_prefixes_and_same_site() {
+    let jar = Jar::new();
+    let site = url("https://app.example.com/");
+    jar.add_cookie_str("__Host-id=1; Secure; Path=/", &site);
+    jar.add_cookie_str("__Host-bad=1; Secure; Path=/; Domain=example.com", &site);
+    jar.add_cookie_str("__Secure-bad=1", &site);
+    jar.add_cookie_str("none=1; SameSite=None", &site);
+    jar.add_cookie_str("strict=1; SameSite=Strict", &site);
+    jar.add_cookie_str("lax=1; SameSite=Lax", &site);
+    jar.add_cookie_str("open=1; SameSite=None; Secure", &site);
+    let names: Vec<String> = jar.stored_cookies().into_iter().map(|cookie| cookie.name).collect();
+    assert_eq!(names, ["__Host-id", "strict", "lax", "open"]);
+    assert_eq!(jar.stored_cookies()[1].same_site, Some(SameSite::Strict));
+
+    let header = |context: CookieContext| {
+        jar.cookies_in_context(&site, context)
+            .map(|value| value.to_str().unwrap().to_string())
+    };
+    assert_eq!(header(CookieContext::default()), Some("__Host-id=1; strict=1; lax=1; open=1".to_string()));
+    assert_eq!(
+        header(CookieContext::new(&Method::GET, true)),
+        Some("__Host-id=1; lax=1; open=1".to_string())
+    );
+    assert_eq!(header(CookieContext::new(&Method::POST, true)), Some("open=1".to_string()));
+}
+
+#[test]
+fn test_netscape_cookies_txt_interop() {
+    let cookies_txt = "# Netscape HTTP Cookie File\n\
+        # https://curl.se/docs/http-cookies.html\n\
+        \n\
+        .example.com\tTRUE\t/\tFALSE\t4102444800\tpref\tdark\n\
+        #HttpOnly_dash.example.com\tFALSE\t/\tTRUE\t0\tsession\tabc\n\
+        old.example.com\tFALSE\t/\tFALSE\t1\tgone\tx\n";
+    let jar = Jar::new();
+    assert_eq!(jar.import(cookies_txt, CookieFileFormat::Netscape).unwrap(), 2);
+
+    let cookies = jar.stored_cookies();
+    assert!(!cookies[0].host_only);
+    assert_eq!(cookies[0].expires, Some(4_102_444_800));
+    assert!(cookies[1].http_only && cookies[1].secure && cookies[1].host_only);
+    assert_eq!(cookies[1].expires, None);
+    assert_eq!(cookie_header(&jar, "https://dash.example.com/"), Some("pref=dark; session=abc".to_string()));
+    assert_eq!(cookie_header(&jar, "https://api.example.com/"), Some("pref=dark".to_string()));
+
+    let exported = jar.export(CookieFileFormat::Netscape).unwrap();
+    assert!(exported.starts_with("# Netscape HTTP Cookie File"));
+    assert!(exported.contains(".example.com\tTRUE\t/\tFALSE\t4102444800\tpref\tdark\n"));
+    assert!(exported.contains("#HttpOnly_dash.example.com\tFALSE\t/\tTRUE\t0\tsession\tabc\n"));
+    let reloaded = Jar::new();
+    reloaded.import(&exported, CookieFileFormat::Netscape).unwrap();
+    assert_eq!(reloaded.stored_cookies(), cookies);
+
+    assert!(Jar::new().import("example.com\tTRUE\t/\n", CookieFileFormat::Netscape).is_err());
+}
+
+#[test]
+fn test_json_files_and_persistent_jars() {
+    let jar = Jar::new();
+    let site = url("https://dash.example.com/");
+    jar.add_cookie_str("session=abc; Secure; HttpOnly; SameSite=Strict", &site);
+    jar.add_cookie_str("pref=dark; Domain=example.com; Max-Age=3600", &site);
+
+    let path = temp_path("jar.json");
+    jar.save(&path, CookieFileFormat::Json).unwrap();
+    let loaded = Jar::load(&path, CookieFileFormat::Json).unwrap();
+    assert_eq!(loaded.stored_cookies(), jar.stored_cookies());
+
+    // A persistent jar starts from the file and writes every change back
+    let persistent = Jar::persistent(&path, CookieFileFormat::Json).unwrap();
+    assert_eq!(persistent.stored_cookies().len(), 2);
+    persistent.add_cookie_str("session=; Max-Age=0", &site);
+    let reloaded = Jar::load(&path, CookieFileFormat::Json).unwrap();
+    let names: Vec<String> = reloaded.stored_cookies().into_iter().map(|cookie| cookie.name).collect();
+    assert_eq!(names, ["pref"]);
+
+    let missing = temp_path("missing/jar.txt");
+    let fresh = Jar::persistent(&missing, CookieFileFormat::Netscape).unwrap();
+    assert!(fresh.stored_cookies().is_empty());
+    fresh.add_cookie_str("a=1", &site);
+    assert!(std::fs::read_to_string(&missing).unwrap().contains("dash.example.com\tFALSE\t/\tFALSE\t0\ta\t1"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_saved_jars_are_private_and_ignore_planted_temporary_files() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = temp_path("private");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("jar.json");
+    let decoy = dir.join("decoy");
+    std::fs::write(&decoy, "untouched").unwrap();
+    // A predictable temporary name must not be followed into another file
+    let _ = std::fs::remove_file(dir.join("jar.json.tmp"));
+    std::os::unix::fs::symlink(&decoy, dir.join("jar.json.tmp")).unwrap();
+
+    let jar = Jar::new();
+    jar.add_cookie_str("session=abc; Max-Age=3600", &url("https://dash.example.com/"));
+    jar.save(&path, CookieFileFormat::Json).unwrap();
+
+    assert_eq!(std::fs::read_to_string(&decoy).unwrap(), "untouched");
+    assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    assert_eq!(Jar::load(&path, CookieFileFormat::Json).unwrap().stored_cookies().len(), 1);
+    let leftovers: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(".jar.json."))
+        .collect();
+    assert!(leftovers.is_empty());
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use quyc_client::cassette::Cassette;
+use quyc_client::har::HarRecorder;
+use quyc_client::telemetry::ConnectionTimings;
+use quyc_client::{HttpClient, HttpRequest};
+
+#[path = "../support/mod.rs"]
+mod support;
+
+use support::cassette::exchange;
+
+async fn wait_for_entries(recorder: &HarRecorder, count: usize) {
+    for _ in 0..200 {
+        if recorder.len() >= count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    panic!("HAR recorder did not reach {count} entries");
+}
+
+fn replay_client(name: &str, recorder: Arc<HarRecorder>) -> (HttpClient, std::path::PathBuf) {
+    let path = support::cassette::write(
+        &format!("har-{name}"),
+        [exchange("POST", "https://api.example.com/items?page=2&token=abc")
+            .status(201)
+            .header("content-type", "application/json")
+            .header("set-cookie", "session=xyz; Path=/")
+            .header("location", "/items/7")
+            .body(r#"{"id":7,"name":"widget"}"#)],
+    );
+    let client = HttpClient::default()
+        .with_cassette(Cassette::replay(&path).unwrap())
+        .with_har_recorder(recorder);
+    (client, path)
+}
+
+#[test]
+fn test_connection_timings_total() {
+    let timings = ConnectionTimings {
+        dns: Some(Duration::from_millis(3)),
+        connect: Some(Duration::from_millis(10)),
+        tls: None,
+        remote_addr: None,
+    };
+    assert_eq!(timings.total(), Duration::from_millis(13));
+    assert_eq!(ConnectionTimings::default().total(), Duration::ZERO);
+}
+
+#[tokio::test]
+async fn test_har_entry_captures_request_and_response() {
+    let recorder = Arc::new(HarRecorder::new().redact_query_param("token"));
+    let (client, cassette) = replay_client("entry", recorder.clone());
+
+    let request = HttpRequest::post("https://api.example.com/items?page=2&token=abc")
+        .header("authorization", "Bearer secret")
+        .header("content-type", "text/plain")
+        .body_text("hello");
+    let mut response = client.execute(request);
+    response.collect_body().await;
+    wait_for_entries(&recorder, 1).await;
+
+    let har = recorder.to_har();
+    assert_eq!(har.log.version, "1.2");
+    let entry = &har.log.entries[0];
+
+    assert_eq!(entry.request.method, "POST");
+    assert!(entry.request.url.contains("token=%5BREDACTED%5D"));
+    assert!(entry.request.query_string.iter().any(|q| q.name == "page" && q.value == "2"));
+    assert!(entry
+        .request
+        .headers
+        .iter()
+        .any(|h| h.name == "authorization" && h.value == "[REDACTED]"));
+    let post_data = entry.request.post_data.as_ref().unwrap();
+    assert_eq!(post_data.text, "hello");
+    assert_eq!(post_data.mime_type, "text/plain");
+
+    assert_eq!(entry.response.status, 201);
+    assert_eq!(entry.response.status_text, "Created");
+    assert_eq!(entry.response.http_version, "HTTP/2.0");
+    assert_eq!(entry.response.redirect_url, "/items/7");
+    assert_eq!(entry.response.content.mime_type, "application/json");
+    assert_eq!(entry.response.content.text.as_deref(), Some(r#"{"id":7,"name":"widget"}"#));
+    assert_eq!(entry.response.body_size, 24);
+    // Set-Cookie is redacted by default, so no cookie values leak
+    assert!(entry.response.cookies.is_empty());
+
+    // Replayed responses open no connection
+    assert_eq!(entry.timings.dns, -1.0);
+    assert_eq!(entry.timings.connect, -1.0);
+    assert!(entry.timings.wait >= 0.0 && entry.timings.receive >= 0.0);
+    assert!(entry.server_ip_address.is_none());
+
+    std::fs::remove_file(cassette).ok();
+}
+
+#[tokio::test]
+async fn test_har_body_size_cap() {
+    let recorder = Arc::new(HarRecorder::new().max_body_size(8));
+    let (client, cassette) = replay_client("cap", recorder.clone());
+
+    let mut response = client.execute(HttpRequest::post("https://api.example.com/items?page=2&token=abc"));
+    response.collect_body().await;
+    wait_for_entries(&recorder, 1).await;
+
+    let content = &recorder.entries()[0].response.content;
+    assert_eq!(content.size, 24);
+    assert_eq!(content.text.as_deref(), Some(r#"{"id":7,"#));
+    assert!(content.comment.as_deref().unwrap().contains("8 of 24"));
+
+    std::fs::remove_file(cassette).ok();
+}
+
+#[tokio::test]
+async fn test_har_written_on_drop() {
+    let har_path = std::env::temp_dir().join(format!("quyc-har-{}-drop.har", std::process::id()));
+    let recorder = Arc::new(HarRecorder::new().save_on_drop(&har_path));
+    let (client, cassette) = replay_client("drop", recorder.clone());
+
+    let mut response = client.execute(HttpRequest::post("https://api.example.com/items?page=2&token=abc"));
+    response.collect_body().await;
+    wait_for_entries(&recorder, 1).await;
+
+    drop(client);
+    drop(recorder);
+    // The forwarding task may still hold the recorder for a moment
+    for _ in 0..200 {
+        if har_path.exists() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    let written: serde_json::Value = serde_json::from_slice(&std::fs::read(&har_path).unwrap()).unwrap();
+    assert_eq!(written["log"]["version"], "1.2");
+    assert_eq!(written["log"]["entries"].as_array().unwrap().len(), 1);
+    assert_eq!(written["log"]["entries"][0]["response"]["status"], 201);
+
+    std::fs::remove_file(har_path).ok();
+    std::fs::remove_file(cassette).ok();
+}
@@ -0,0 +1,164 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::{Method, Version};
+use quyc_client::http::request::{MultipartField, MultipartValue, RequestAuth, RequestBody};
+use quyc_client::HttpRequest;
+
+#[test]
+fn test_to_curl_get() {
+    let request = HttpRequest::get("https://api.example.com/items?page=2")
+        .header("accept", "application/json")
+        .with_version(Version::HTTP_2)
+        .compress(true)
+        .follow_redirects(false);
+
+    let curl = request.to_curl();
+    assert!(curl.starts_with("curl --http2 --compressed"));
+    assert!(curl.contains("-H 'accept: application/json'"));
+    assert!(!curl.contains("-X"));
+    assert!(curl.ends_with("'https://api.example.com/items?page=2'"));
+}
+
+#[test]
+fn test_to_curl_escapes_single_quotes() {
+    let request = HttpRequest::put("https://example.com/notes")
+        .body_text("it's here")
+        .bearer_auth("tok");
+
+    let curl = request.to_curl();
+    assert!(curl.contains("-X PUT"));
+    assert!(curl.contains(r"--data-raw 'it'\''s here'"));
+    assert!(curl.contains("-H 'authorization: Bearer tok'"));
+}
+
+#[test]
+fn test_curl_round_trip() {
+    let original = HttpRequest::post("https://example.com/upload")
+        .header("x-trace", "abc")
+        .basic_auth("user", "p@ss word")
+        .body_bytes(vec![0xff, 0x00, b'\'', b'a'])
+        .with_timeout(Duration::from_millis(1500))
+        .max_redirects(3);
+
+    let parsed = HttpRequest::from_curl(&original.to_curl()).unwrap();
+    assert_eq!(parsed.method(), &Method::POST);
+    assert_eq!(parsed.url().as_str(), "https://example.com/upload");
+    assert_eq!(parsed.headers()["x-trace"], "abc");
+    assert_eq!(parsed.version(), original.version());
+    assert_eq!(parsed.timeout(), Some(Duration::from_millis(1500)));
+    assert!(parsed.follow_redirects);
+    assert_eq!(parsed.max_redirects, 3);
+    assert!(parsed.compress);
+    match &parsed.auth {
+        Some(RequestAuth::Basic { username, password }) => {
+            assert_eq!(username, "user");
+            assert_eq!(password, "p@ss word");
+        }
+        other => panic!("unexpected auth {other:?}"),
+    }
+    match parsed.body() {
+        Some(RequestBody::Bytes(bytes)) => assert_eq!(bytes.as_ref(), &[0xff, 0x00, b'\'', b'a']),
+        other => panic!("unexpected body {other:?}"),
+    }
+}
+
+#[test]
+fn test_from_curl_data_and_headers() {
+    let request = HttpRequest::from_curl(
+        "curl -sS 'https://api.example.com/v1/items' \\\n  -H 'Content-Type: application/json' \\\n  -H \"X-Api-Key: k1\" \\\n  -d '{\"name\":\"widget\"}'",
+    )
+    .unwrap();
+
+    assert_eq!(request.method(), &Method::POST);
+    assert_eq!(request.headers()["content-type"], "application/json");
+    assert_eq!(request.headers()["x-api-key"], "k1");
+    assert!(!request.compress);
+    assert!(!request.follow_redirects);
+    match request.body() {
+        Some(RequestBody::Text(text)) => assert_eq!(text, r#"{"name":"widget"}"#),
+        other => panic!("unexpected body {other:?}"),
+    }
+}
+
+#[test]
+fn test_from_curl_combined_flags_and_json() {
+    let request = HttpRequest::from_curl(
+        r#"curl -sSL -XPATCH --json '{"a":1}' --url https://example.com/x --http3 --compressed"#,
+    )
+    .unwrap();
+
+    assert_eq!(request.method(), &Method::PATCH);
+    assert_eq!(request.url().as_str(), "https://example.com/x");
+    assert_eq!(request.headers()["content-type"], "application/json");
+    assert_eq!(request.headers()["accept"], "application/json");
+    assert_eq!(request.version(), Version::HTTP_3);
+    assert!(request.follow_redirects);
+    assert!(request.compress);
+}
+
+#[test]
+fn test_from_curl_get_moves_data_to_query() {
+    let request = HttpRequest::from_curl(
+        "curl -G https://example.com/search?lang=en -d q=rust --data-urlencode 'tag=a b'",
+    )
+    .unwrap();
+
+    assert_eq!(request.method(), &Method::GET);
+    assert!(request.body().is_none());
+    assert_eq!(request.url().query(), Some("lang=en&q=rust&tag=a%20b"));
+}
+
+#[test]
+fn test_from_curl_multipart() {
+    let path = std::env::temp_dir().join(format!("quyc-curl-{}.txt", std::process::id()));
+    std::fs::write(&path, b"file body").unwrap();
+
+    let command = format!(
+        "curl -F title=Report -F 'doc=@{};type=text/plain' https://example.com/upload",
+        path.display()
+    );
+    let request = HttpRequest::from_curl(&command).unwrap();
+    assert_eq!(request.method(), &Method::POST);
+
+    let Some(RequestBody::Multipart(fields)) = request.body() else {
+        panic!("expected multipart body");
+    };
+    assert_eq!(fields.len(), 2);
+    assert_eq!(fields[0].name, "title");
+    assert!(matches!(&fields[0].value, MultipartValue::Text(t) if t == "Report"));
+    assert_eq!(fields[1].name, "doc");
+    assert_eq!(fields[1].content_type.as_deref(), Some("text/plain"));
+    assert_eq!(
+        fields[1].filename.as_deref(),
+        path.file_name().and_then(|n| n.to_str())
+    );
+    assert!(matches!(&fields[1].value, MultipartValue::Bytes(b) if b.as_ref() == b"file body"));
+
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn test_to_curl_multipart_file() {
+    let request = HttpRequest::post("https://example.com/upload").multipart(vec![
+        MultipartField::text("title", "Report"),
+        MultipartField::file(
+            "doc",
+            "report.pdf",
+            Some("application/pdf".to_string()),
+            Bytes::from_static(&[1, 2, 3]),
+        ),
+    ]);
+
+    let curl = request.to_curl();
+    assert!(curl.contains("-F title=Report"));
+    assert!(curl.contains("-F 'doc=@report.pdf;type=application/pdf'"));
+}
+
+#[test]
+fn test_from_curl_errors() {
+    assert!(HttpRequest::from_curl("curl -X POST").is_err());
+    assert!(HttpRequest::from_curl("curl --frobnicate https://example.com").is_err());
+    assert!(HttpRequest::from_curl("curl 'https://example.com").is_err());
+    assert!(HttpRequest::from_curl("curl -H").is_err());
+}
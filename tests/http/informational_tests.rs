@@ -0,0 +1,99 @@
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, StatusCode};
+use ystream::prelude::MessageChunk;
+use ystream::{AsyncStream, emit};
+
+use quyc_client::http::{parse_link_header, HttpChunk, InformationalResponse};
+use quyc_client::protocols::convert_http_chunks_to_response;
+use quyc_client::protocols::strategy::{H2Config, HttpProtocolStrategy};
+use quyc_client::testing::{Mock, MockResponse, MockServer};
+use quyc_client::{HttpClient, HttpRequest};
+
+#[test]
+fn test_parse_link_header_preload_and_preconnect() {
+    let links = parse_link_header(
+        "</style.css>; rel=preload; as=style, <https://cdn.example.com>; rel=preconnect; crossorigin",
+    );
+    assert_eq!(links.len(), 2);
+
+    assert_eq!(links[0].uri, "/style.css");
+    assert!(links[0].is_preload());
+    assert_eq!(links[0].as_type(), Some("style"));
+
+    assert_eq!(links[1].uri, "https://cdn.example.com");
+    assert!(links[1].is_preconnect());
+    assert_eq!(links[1].crossorigin(), Some(""));
+}
+
+#[test]
+fn test_parse_link_header_quoted_values_and_commas() {
+    let links = parse_link_header(
+        r#"</a,b.js>; rel="preload modulepreload"; as=script; title="x, \"y\"", <broken"#,
+    );
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].uri, "/a,b.js");
+    assert_eq!(links[0].rel, vec!["preload", "modulepreload"]);
+    assert_eq!(links[0].param("title"), Some(r#"x, "y""#));
+}
+
+#[test]
+fn test_early_hints_preloads() {
+    let mut headers = HeaderMap::new();
+    headers.append(http::header::LINK, HeaderValue::from_static("</app.js>; rel=preload; as=script"));
+    headers.append(http::header::LINK, HeaderValue::from_static("</next>; rel=prefetch"));
+
+    let hints = InformationalResponse::new(StatusCode::from_u16(103).unwrap(), headers);
+    assert!(hints.is_early_hints());
+    assert_eq!(hints.links().len(), 2);
+
+    let preloads = hints.preloads();
+    assert_eq!(preloads.len(), 1);
+    assert_eq!(preloads[0].uri, "/app.js");
+}
+
+#[tokio::test]
+async fn test_interim_responses_surface_before_final_response() {
+    let chunks = AsyncStream::<HttpChunk, 1024>::with_channel(|sender| {
+        emit!(sender, HttpChunk::Data(Bytes::from_static(
+            b"HTTP/1.1 100 Continue\r\n\r\n\
+              HTTP/1.1 103 Early Hints\r\nLink: </style.css>; rel=preload; as=style\r\n\r\n\
+              HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\n\r\nhello"
+        )));
+        emit!(sender, HttpChunk::End);
+    });
+
+    let mut response = convert_http_chunks_to_response(chunks, 1);
+    let interim = response.collect_informational().await;
+
+    assert_eq!(interim.len(), 2);
+    assert!(interim[0].is_continue());
+    assert!(interim[1].is_early_hints());
+    assert_eq!(interim[1].preloads()[0].uri, "/style.css");
+
+    let headers = response.collect_headers().await;
+    assert_eq!(headers.get("content-type").unwrap(), "text/plain");
+    assert!(headers.get("link").is_none());
+}
+
+#[test]
+fn test_bad_chunk_is_an_error_not_continue() {
+    let chunk = InformationalResponse::bad_chunk("stream failed".to_string());
+    assert_eq!(chunk.error(), Some("stream failed"));
+    assert!(!chunk.is_continue());
+    assert_eq!(chunk.status, StatusCode::INTERNAL_SERVER_ERROR);
+
+    assert_eq!(InformationalResponse::new(StatusCode::CONTINUE, HeaderMap::new()).error(), None);
+}
+
+#[tokio::test]
+async fn test_http2_responses_have_no_interim_responses() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(Mock::get("/slow").respond(MockResponse::ok().text("done")));
+    let client =
+        HttpClient::with_config_and_strategy(server.http_config(), HttpProtocolStrategy::Http2(H2Config::default()));
+
+    // h2 consumes 1xx header blocks; the stream ends with the final headers
+    let mut response = client.execute(HttpRequest::get(server.url("/slow").as_str()));
+    assert!(response.collect_informational().await.is_empty());
+    assert_eq!(response.collect_body().await, Bytes::from("done"));
+}
@@ -0,0 +1,146 @@
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use http::{HeaderValue, StatusCode};
+use quyc_client::http::response::{HttpBodyChunk, HttpHeader};
+use quyc_client::middleware::{Middleware, MiddlewareChain};
+use quyc_client::{HttpClient, HttpError, HttpRequest, HttpResponse};
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+/// Records every hook call and optionally answers or fails requests
+struct Probe {
+    name: &'static str,
+    log: Log,
+    respond: bool,
+    fail: bool,
+}
+
+impl Probe {
+    fn new(name: &'static str, log: &Log) -> Self {
+        Self { name, log: log.clone(), respond: false, fail: false }
+    }
+
+    fn push(&self, event: &str) {
+        self.log.lock().unwrap().push(format!("{}:{event}", self.name));
+    }
+}
+
+impl Middleware for Probe {
+    fn process_request(&self, request: HttpRequest) -> quyc_client::error::Result<HttpRequest> {
+        self.push("request");
+        if self.fail {
+            return Err(quyc_client::error::request(format!("{} rejected", self.name)));
+        }
+        let mut request = request;
+        request
+            .headers_mut()
+            .append("x-tenant", HeaderValue::from_static(self.name));
+        Ok(request)
+    }
+
+    fn short_circuit(&self, request: &HttpRequest) -> Option<HttpResponse> {
+        if !self.respond {
+            return None;
+        }
+        let tenants: Vec<_> = request
+            .headers()
+            .get_all("x-tenant")
+            .iter()
+            .map(|v| v.to_str().unwrap().to_string())
+            .collect();
+        Some(HttpResponse::error(StatusCode::OK, tenants.join(",")))
+    }
+
+    fn process_response(&self, response: HttpResponse) -> quyc_client::error::Result<HttpResponse> {
+        self.push("response");
+        Ok(response)
+    }
+
+    fn handle_error(&self, error: HttpError) -> quyc_client::error::Result<HttpError> {
+        self.push("error");
+        Ok(error)
+    }
+}
+
+/// Upper-cases the body and tags the headers as they stream
+struct Shout;
+
+impl Middleware for Shout {
+    fn process_response(&self, response: HttpResponse) -> quyc_client::error::Result<HttpResponse> {
+        Ok(response
+            .map_headers(|header| {
+                Some(HttpHeader::new(header.name, HeaderValue::from_static("text/shout")))
+            })
+            .map_body(|chunk| {
+                HttpBodyChunk::new(
+                    Bytes::from(chunk.data.to_ascii_uppercase()),
+                    chunk.offset,
+                    chunk.is_final,
+                )
+            }))
+    }
+}
+
+fn log() -> Log {
+    Arc::new(Mutex::new(Vec::new()))
+}
+
+#[tokio::test]
+async fn test_short_circuit_ordering() {
+    let log = log();
+    let mut inner = Probe::new("inner", &log);
+    inner.respond = true;
+    let client = HttpClient::default()
+        .with_middleware(Probe::new("outer", &log))
+        .with_middleware(inner)
+        .with_middleware(Probe::new("never", &log));
+
+    let mut response = client.execute(HttpRequest::get("https://example.invalid/"));
+    assert_eq!(response.status(), 200);
+    // Requests see middleware in insertion order
+    assert_eq!(response.collect_body().await, Bytes::from("outer,inner"));
+    // Responses unwind in reverse, skipping middleware the request never reached
+    assert_eq!(
+        *log.lock().unwrap(),
+        ["outer:request", "inner:request", "inner:response", "outer:response"]
+    );
+    // Short-circuited requests never reach the network
+    assert_eq!(client.stats().total_requests.load(std::sync::atomic::Ordering::Relaxed), 0);
+}
+
+#[tokio::test]
+async fn test_request_error_reaches_handle_error() {
+    let log = log();
+    let mut failing = Probe::new("auth", &log);
+    failing.fail = true;
+    let client = quyc_client::client::configuration::HttpClientBuilder::new()
+        .middleware(Probe::new("logging", &log))
+        .middleware(failing)
+        .middleware(Probe::new("never", &log))
+        .build()
+        .unwrap();
+
+    let mut response = client.execute(HttpRequest::get("https://example.invalid/"));
+    assert!(response.is_error());
+    let body = response.collect_body().await;
+    assert!(String::from_utf8_lossy(&body).contains("auth rejected"));
+    assert_eq!(
+        *log.lock().unwrap(),
+        ["logging:request", "auth:request", "auth:error", "logging:error"]
+    );
+}
+
+#[tokio::test]
+async fn test_streaming_response_transform() {
+    let log = log();
+    let mut responder = Probe::new("mock", &log);
+    responder.respond = true;
+    let chain = MiddlewareChain::new().add(Shout).add(responder);
+    assert_eq!(chain.len(), 2);
+    let client = HttpClient::default().with_middleware_chain(chain);
+
+    let mut response = client.execute(HttpRequest::get("https://example.invalid/"));
+    assert_eq!(response.collect_headers().await["content-type"], "text/shout");
+    assert_eq!(response.collect_body().await, Bytes::from("MOCK"));
+}
@@ -0,0 +1,70 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use quyc_client::config::HttpConfig;
+use quyc_client::protocols::quiche::{CappedQlogWriter, QlogConfig, DEFAULT_QLOG_MAX_BYTES};
+
+fn temp_qlog(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("quyc-qlog-{}", std::process::id())).join(name)
+}
+
+#[test]
+fn test_qlog_file_named_by_origin_and_connection_id() {
+    let qlog = QlogConfig::new("/tmp/qlog");
+    let path = qlog.file_path("https://api.example.com:443", &[0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(
+        path,
+        PathBuf::from("/tmp/qlog/https___api.example.com_443-deadbeef.sqlog")
+    );
+}
+
+#[test]
+fn test_explicit_qlog_config_wins_over_environment() {
+    let explicit = QlogConfig::new("/var/tmp/explicit").with_max_bytes(1024);
+    let resolved = QlogConfig::resolve(Some(&explicit)).expect("explicit config should resolve");
+    assert_eq!(resolved, explicit);
+}
+
+#[test]
+fn test_http_config_qlog_settings() {
+    assert!(HttpConfig::default().quic_qlog().is_none());
+
+    let config = HttpConfig::default().with_quic_qlog_dir("/tmp/qlog");
+    let qlog = config.quic_qlog().expect("qlog should be configured");
+    assert_eq!(qlog.max_bytes, DEFAULT_QLOG_MAX_BYTES);
+
+    let capped = config.with_quic_qlog_max_bytes(4096).quic_qlog();
+    assert_eq!(capped.map(|q| q.max_bytes), Some(4096));
+}
+
+#[test]
+fn test_capped_writer_stops_at_the_next_record_boundary() {
+    let path = temp_qlog("capped/conn.sqlog");
+    let mut writer = CappedQlogWriter::create(&path, 16).unwrap();
+
+    // quiche may write one record in several calls; the cap never splits one
+    writer.write_all(b"\x1e{\"time\":0,\"name\":\"a\"}").unwrap();
+    writer.write_all(b"\n").unwrap();
+    // Past the cap: the next record and everything after it is dropped,
+    // but reported as written so quiche keeps going
+    assert_eq!(writer.write(b"\x1e{\"time\":1}\n").unwrap(), 12);
+    assert_eq!(writer.write(b"{\"late\":true}\n").unwrap(), 14);
+    writer.flush().unwrap();
+
+    let written = std::fs::read(&path).unwrap();
+    assert_eq!(written, b"\x1e{\"time\":0,\"name\":\"a\"}\n");
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn test_capped_writer_keeps_everything_under_the_cap() {
+    let path = temp_qlog("uncapped.sqlog");
+    let mut writer = CappedQlogWriter::create(&path, 1024).unwrap();
+
+    writer.write_all(b"\x1e{\"time\":0}\n").unwrap();
+    writer.write_all(b"\x1e{\"time\":1}\n").unwrap();
+    drop(writer);
+
+    assert_eq!(std::fs::read(&path).unwrap(), b"\x1e{\"time\":0}\n\x1e{\"time\":1}\n");
+    std::fs::remove_file(path).ok();
+}
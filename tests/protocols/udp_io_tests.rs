@@ -0,0 +1,92 @@
+use std::time::{Duration, Instant};
+
+use quyc_client::protocols::quiche::{QuicUdpSocket, UdpIoConfig};
+use quyc_client::protocols::strategy::H3Config;
+
+fn receive_all(socket: &mut QuicUdpSocket, expected: usize) -> Vec<Vec<u8>> {
+    let mut datagrams = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while datagrams.len() < expected && Instant::now() < deadline {
+        let _ = socket.recv_batch(|datagram, _info| datagrams.push(datagram.to_vec()));
+    }
+    datagrams
+}
+
+fn round_trip(config: UdpIoConfig) {
+    let addr = "127.0.0.1:0".parse().unwrap();
+    let mut sender = QuicUdpSocket::bind(addr, config).expect("bind sender");
+    let mut receiver = QuicUdpSocket::bind(addr, config).expect("bind receiver");
+
+    // Three full segments and a short trailing one
+    let contents: Vec<u8> = (0..3 * 100 + 40).map(|i| (i % 251) as u8).collect();
+    sender
+        .send_segments(receiver.local_addr(), &contents, 100)
+        .expect("send segments");
+
+    let datagrams = receive_all(&mut receiver, 4);
+    assert_eq!(datagrams.len(), 4);
+    assert_eq!(datagrams.concat(), contents);
+    assert_eq!(datagrams[3].len(), 40);
+
+    assert_eq!(sender.stats().datagrams_sent, 4);
+    assert_eq!(receiver.stats().datagrams_received, 4);
+}
+
+#[test]
+fn test_segments_round_trip_with_offload() {
+    round_trip(UdpIoConfig::default());
+}
+
+#[test]
+fn test_segments_round_trip_without_offload() {
+    let config = UdpIoConfig {
+        offload: false,
+        ecn: false,
+        ..UdpIoConfig::default()
+    };
+    round_trip(config);
+
+    let socket = QuicUdpSocket::bind("127.0.0.1:0".parse().unwrap(), config).unwrap();
+    assert_eq!(socket.max_gso_segments(), 1);
+}
+
+#[test]
+fn test_plain_fallback_sends_one_datagram_per_call() {
+    let config = UdpIoConfig {
+        offload: false,
+        ecn: false,
+        ..UdpIoConfig::default()
+    };
+    let addr = "127.0.0.1:0".parse().unwrap();
+    let mut sender = QuicUdpSocket::bind(addr, config).unwrap();
+    let receiver = QuicUdpSocket::bind(addr, config).unwrap();
+
+    sender.send_segments(receiver.local_addr(), &[0u8; 500], 100).unwrap();
+    let stats = sender.stats();
+    assert_eq!(stats.send_calls, 5);
+    assert_eq!(stats.datagrams_per_send(), 1.0);
+}
+
+#[test]
+fn test_ecn_off_by_default() {
+    assert!(!UdpIoConfig::default().ecn);
+    assert!(!H3Config::default().enable_ecn);
+    assert!(!H3Config::default().udp_io_config().ecn);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_ecn_marks_are_counted_when_enabled() {
+    let config = UdpIoConfig {
+        ecn: true,
+        ..UdpIoConfig::default()
+    };
+    let addr = "127.0.0.1:0".parse().unwrap();
+    let mut sender = QuicUdpSocket::bind(addr, config).unwrap();
+    let mut receiver = QuicUdpSocket::bind(addr, config).unwrap();
+
+    sender.send_segments(receiver.local_addr(), &[7u8; 300], 100).unwrap();
+    assert_eq!(receive_all(&mut receiver, 3).len(), 3);
+    assert_eq!(receiver.stats().ecn_ect_received, 3);
+    assert_eq!(receiver.stats().ecn_ce_received, 0);
+}
@@ -0,0 +1,140 @@
+use bytes::Bytes;
+use http::header::AUTHORIZATION;
+use http::{Method, StatusCode};
+
+use quyc_client::cassette::{Cassette, MatchRules, REDACTED};
+use quyc_client::client::configuration::HttpClientBuilder;
+use quyc_client::redirect::Policy;
+use quyc_client::{HttpClient, HttpRequest};
+
+#[path = "../support/mod.rs"]
+mod support;
+
+use support::cassette::exchange;
+
+/// Client replaying the recorded redirects, matching bodies and credentials
+fn client(name: &str, builder: HttpClientBuilder) -> HttpClient {
+    let path = support::cassette::write(
+        &format!("redirects-{name}"),
+        [
+            exchange("POST", "https://api.example.com/login")
+                .request_header("authorization", REDACTED)
+                .request_body("user=ada")
+                .status(302)
+                .header("location", "/home"),
+            exchange("GET", "https://api.example.com/home")
+                .request_header("authorization", REDACTED)
+                .body("home"),
+            exchange("PUT", "https://api.example.com/upload")
+                .request_header("authorization", REDACTED)
+                .request_body("payload")
+                .status(307)
+                .header("location", "https://storage.example.com/upload"),
+            exchange("PUT", "https://storage.example.com/upload")
+                .request_body("payload")
+                .status(201)
+                .body("stored"),
+            exchange("GET", "https://api.example.com/legacy")
+                .status(301)
+                .header("location", "http://api.example.com/plain"),
+            exchange("GET", "http://api.example.com/plain")
+                .version("HTTP/1.1")
+                .body("plain"),
+            exchange("GET", "https://api.example.com/a")
+                .status(302)
+                .header("location", "/b"),
+            exchange("GET", "https://api.example.com/b")
+                .status(302)
+                .header("location", "/a"),
+        ],
+    );
+    let rules = MatchRules::new().body(true).header(AUTHORIZATION);
+    builder
+        .build()
+        .unwrap()
+        .with_cassette(Cassette::replay(&path).unwrap().match_on(rules))
+}
+
+#[tokio::test]
+async fn test_see_other_style_redirects_switch_to_get() {
+    let browser = client("get", HttpClientBuilder::new());
+    let login = HttpRequest::post("https://api.example.com/login")
+        .header("authorization", "Bearer token")
+        .body_text("user=ada");
+
+    let mut response = browser.execute(login);
+    assert_eq!(response.collect_body().await, Bytes::from("home"));
+    assert_eq!(response.status(), 200);
+
+    let history = response.redirect_history();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].status, StatusCode::FOUND);
+    assert_eq!(history[0].from.as_str(), "https://api.example.com/login");
+    assert_eq!(history[0].to.as_str(), "https://api.example.com/home");
+    assert_eq!(history[0].method, Method::GET);
+}
+
+#[tokio::test]
+async fn test_temporary_redirects_keep_method_and_body_but_not_credentials() {
+    let uploader = client("put", HttpClientBuilder::new());
+    let upload = HttpRequest::put("https://api.example.com/upload")
+        .header("authorization", "Bearer token")
+        .body_text("payload");
+
+    // The storage interaction only matches without the authorization header
+    let mut response = uploader.execute(upload);
+    assert_eq!(response.collect_body().await, Bytes::from("stored"));
+    assert_eq!(response.status(), 201);
+    assert_eq!(response.redirect_history()[0].method, Method::PUT);
+}
+
+#[tokio::test]
+async fn test_https_downgrades_are_refused_unless_allowed() {
+    let refusing = client("downgrade", HttpClientBuilder::new());
+    let mut response = refusing.execute(HttpRequest::get("https://api.example.com/legacy"));
+    let body = response.collect_body().await;
+    assert!(String::from_utf8_lossy(&body).contains("from https to http"), "{body:?}");
+    assert!(response.redirect_history().is_empty());
+
+    let allowing = client("downgrade-allowed", HttpClientBuilder::new().redirect_https_downgrade(true));
+    let mut response = allowing.execute(HttpRequest::get("https://api.example.com/legacy"));
+    assert_eq!(response.collect_body().await, Bytes::from("plain"));
+    assert_eq!(response.redirect_history()[0].to.as_str(), "http://api.example.com/plain");
+}
+
+#[tokio::test]
+async fn test_loops_and_limits_fail_the_request() {
+    let looping = client("loop", HttpClientBuilder::new());
+    let mut response = looping.execute(HttpRequest::get("https://api.example.com/a"));
+    let body = response.collect_body().await;
+    assert!(String::from_utf8_lossy(&body).contains("redirect loop"), "{body:?}");
+
+    let limited = client("limit", HttpClientBuilder::new().max_redirects(0));
+    let mut response = limited.execute(HttpRequest::get("https://api.example.com/a"));
+    let body = response.collect_body().await;
+    assert!(String::from_utf8_lossy(&body).contains("too many redirects"), "{body:?}");
+}
+
+#[tokio::test]
+async fn test_custom_policies_and_opting_out_return_the_redirect() {
+    let stopping = Policy::custom(|attempt| {
+        if attempt.url().path() == "/home" {
+            attempt.stop()
+        } else {
+            attempt.follow()
+        }
+    });
+    let custom = client("custom", HttpClientBuilder::new().redirect(stopping));
+    let login = HttpRequest::post("https://api.example.com/login")
+        .header("authorization", "Bearer token")
+        .body_text("user=ada");
+    let mut response = custom.execute(login);
+    response.collect_body().await;
+    assert_eq!(response.status(), 302);
+    assert!(response.redirect_history().is_empty());
+
+    let opted_out = client("opt-out", HttpClientBuilder::new());
+    let mut response = opted_out.execute(HttpRequest::get("https://api.example.com/a").follow_redirects(false));
+    response.collect_body().await;
+    assert_eq!(response.status(), 302);
+}
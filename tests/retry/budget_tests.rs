@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use bytes::Bytes;
+
+use quyc_client::retry::{HttpRetryExecutor, RetryBudget, RetryBudgetConfig, RetryPolicy};
+use quyc_client::testing::MockResponse;
+use quyc_client::HttpRequest;
+
+const ORIGIN: &str = "https://api.example.com";
+
+fn config(success_refill: f64, max_tokens: u32) -> RetryBudgetConfig {
+    RetryBudgetConfig { success_refill, max_tokens }
+}
+
+#[test]
+fn test_retries_spend_and_successes_refill() {
+    let budget = RetryBudget::global(config(0.5, 2));
+    assert!(budget.try_acquire(ORIGIN));
+    assert!(budget.try_acquire("https://other.example.com"));
+    assert!(!budget.try_acquire(ORIGIN));
+
+    // Two successes earn one retry back
+    budget.record_success(ORIGIN);
+    assert!(!budget.try_acquire(ORIGIN));
+    budget.record_success(ORIGIN);
+    assert!(budget.try_acquire(ORIGIN));
+
+    // Tokens never exceed the bucket size
+    for _ in 0..100 {
+        budget.record_success(ORIGIN);
+    }
+    assert_eq!(budget.global_tokens(), Some(2.0));
+
+    let snapshot = budget.snapshot();
+    assert_eq!(snapshot.allowed, 3);
+    assert_eq!(snapshot.denied, 2);
+    assert_eq!(snapshot.successes, 102);
+}
+
+#[test]
+fn test_per_origin_buckets_and_global_refund() {
+    let budget = RetryBudget::per_origin(config(0.1, 1)).with_global(config(0.1, 5));
+    assert!(budget.try_acquire(ORIGIN));
+    assert!(!budget.try_acquire(ORIGIN));
+    assert!(budget.try_acquire("https://other.example.com"));
+
+    // The denied retry did not keep its global token
+    assert_eq!(budget.global_tokens(), Some(3.0));
+    assert_eq!(budget.origin_tokens(ORIGIN), Some(0.0));
+    assert_eq!(budget.origin_tokens("https://unseen.example.com"), Some(1.0));
+    assert_eq!(budget.snapshot().origin_tokens.len(), 2);
+}
+
+#[tokio::test]
+async fn test_exhausted_budget_degrades_to_single_attempt() {
+    let budget = Arc::new(RetryBudget::global(config(0.0, 1)));
+    let calls = Arc::new(AtomicU32::new(0));
+    let counter = calls.clone();
+    let policy = RetryPolicy {
+        max_attempts: 5,
+        initial_delay_ms: 1,
+        max_delay_ms: 10,
+        ..RetryPolicy::default()
+    };
+    let executor = HttpRetryExecutor::new(
+        move |_request| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            MockResponse::status(503).body("unavailable").into_response()
+        },
+        policy,
+    )
+    .with_budget(budget.clone());
+
+    let (mut response, stats) = executor.execute_with_stats(HttpRequest::get("https://api.example.com/v1/models"));
+    assert_eq!(response.collect_body().await, Bytes::from("unavailable"));
+    assert_eq!(response.status(), 503);
+    // One retry paid for, then the budget ran dry
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert!(stats.read().unwrap().budget_exhausted);
+
+    let (mut response, _) = executor.execute_with_stats(HttpRequest::get("https://api.example.com/v1/models"));
+    response.collect_body().await;
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(budget.snapshot().denied, 2);
+}
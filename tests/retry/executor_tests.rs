@@ -0,0 +1,251 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::HeaderValue;
+use ystream::{AsyncStream, emit};
+
+use quyc_client::error::Kind;
+use quyc_client::http::{HttpBodyChunk, HttpChunk, HttpHeader};
+use quyc_client::protocols::convert_http_chunks_to_response;
+use quyc_client::protocols::strategy::{H2Config, HttpProtocolStrategy};
+use quyc_client::protocols::strategy_trait::ProtocolStrategy;
+use quyc_client::retry::{HttpRetryExecutor, RetryPolicy};
+use quyc_client::telemetry::{AttemptOutcome, RetryAttempt, RetryStats};
+use quyc_client::testing::{Mock, MockResponse, MockServer};
+use quyc_client::{HttpRequest, HttpResponse};
+
+fn transport_error(message: &'static str) -> HttpResponse {
+    let chunks = AsyncStream::<HttpChunk, 1024>::with_channel(move |sender| {
+        emit!(sender, HttpChunk::Error(message.to_string()));
+    });
+    convert_http_chunks_to_response(chunks, 1)
+}
+
+fn fast_policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_delay_ms: 1,
+        max_delay_ms: 50,
+        attempt_timeout_ms: 2000,
+        ..RetryPolicy::default()
+    }
+}
+
+/// Executor answering with `first` until `failures` calls have been made, then 200
+fn flaky<F>(failures: u32, first: F) -> (HttpRetryExecutor<impl Fn(HttpRequest) -> HttpResponse + Send + Sync + 'static>, Arc<AtomicU32>)
+where
+    F: Fn() -> HttpResponse + Send + Sync + 'static,
+{
+    let calls = Arc::new(AtomicU32::new(0));
+    let counter = calls.clone();
+    let executor = HttpRetryExecutor::new(
+        move |_request| {
+            if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                first()
+            } else {
+                MockResponse::ok().header("content-type", "text/plain").body("ok").into_response()
+            }
+        },
+        fast_policy(3),
+    );
+    (executor, calls)
+}
+
+#[tokio::test]
+async fn test_retryable_status_is_retried_and_body_streams_through() {
+    let (executor, calls) = flaky(2, || MockResponse::status(503).header("retry-after", "0").body("unavailable").into_response());
+    let (mut response, stats) = executor.execute_with_stats(HttpRequest::get("https://api.example.com/v1/models"));
+
+    assert_eq!(response.collect_body().await, Bytes::from("ok"));
+    assert_eq!(response.status(), 200);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let stats = stats.read().unwrap();
+    assert_eq!(stats.total_attempts, 3);
+    assert_eq!(stats.successful_retries, 1);
+    assert_eq!(stats.attempts[0].outcome, AttemptOutcome::Answered(Some(503)));
+    assert_eq!(stats.attempts[0].retry_after, Some(Duration::ZERO));
+    assert_eq!(stats.attempts[1].delay, Some(Duration::ZERO));
+    assert_eq!(stats.attempts[2].outcome, AttemptOutcome::Answered(Some(200)));
+    assert_eq!(stats.attempts[2].delay, None);
+}
+
+#[tokio::test]
+async fn test_non_idempotent_requests_need_an_idempotency_key() {
+    let (executor, calls) = flaky(1, || MockResponse::status(502).body("bad gateway").into_response());
+    let post = HttpRequest::post("https://api.example.com/v1/messages").body_text("{}");
+    let mut response = executor.execute_with_retry(post.clone());
+    assert_eq!(response.collect_body().await, Bytes::from("bad gateway"));
+    assert_eq!(response.status(), 502);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let (executor, calls) = flaky(1, || MockResponse::status(502).body("bad gateway").into_response());
+    let mut keyed = post;
+    keyed.headers_mut().insert("idempotency-key", HeaderValue::from_static("req-1"));
+    let mut response = executor.execute_with_retry(keyed);
+    assert_eq!(response.collect_body().await, Bytes::from("ok"));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_retry_after_beyond_max_delay_returns_the_answer() {
+    let (executor, calls) = flaky(1, || MockResponse::status(429).header("retry-after", "120").body("slow down").into_response());
+    let (mut response, stats) = executor.execute_with_stats(HttpRequest::get("https://api.example.com/v1/models"));
+
+    assert_eq!(response.collect_body().await, Bytes::from("slow down"));
+    assert_eq!(response.status(), 429);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    let stats = stats.read().unwrap();
+    assert_eq!(stats.attempts[0].retry_after, Some(Duration::from_secs(120)));
+    assert_eq!(stats.attempts[0].delay, None);
+}
+
+#[tokio::test]
+async fn test_transport_errors_are_classified_by_kind() {
+    let (executor, calls) = flaky(1, || transport_error("invalid peer certificate: UnknownIssuer"));
+    let (mut response, stats) = executor.execute_with_stats(HttpRequest::get("https://api.example.com/"));
+    assert_eq!(response.collect_body().await, Bytes::from("invalid peer certificate: UnknownIssuer"));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(matches!(stats.read().unwrap().attempts[0].outcome, AttemptOutcome::Error(_)));
+
+    // Mentioning a connection does not make a failure a connect error
+    let (executor, calls) = flaky(1, || transport_error("TLS connection error: connection refused"));
+    let mut response = executor.execute_with_retry(HttpRequest::get("https://api.example.com/"));
+    response.collect_body().await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Neither does a message that reads like one: the kind travels with the failure
+    let (executor, calls) = flaky(1, || transport_error("connection/connector creation error: refused"));
+    let mut response = executor.execute_with_retry(HttpRequest::get("https://api.example.com/"));
+    response.collect_body().await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let (executor, calls) = flaky(1, || {
+        let chunks = AsyncStream::<HttpChunk, 1024>::with_channel(|sender| {
+            emit!(sender, HttpChunk::Failure(Kind::Connect, "connection refused".to_string()));
+        });
+        convert_http_chunks_to_response(chunks, 1)
+    });
+    let mut response = executor.execute_with_retry(HttpRequest::get("https://api.example.com/"));
+    assert_eq!(response.collect_body().await, Bytes::from("ok"));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_timed_out_attempts_release_their_streams() {
+    let (headers_tx, headers) = AsyncStream::<HttpHeader, 256>::channel();
+    let (_body_tx, body) = AsyncStream::<HttpBodyChunk, 1024>::channel();
+    let (_trailers_tx, trailers) = AsyncStream::<HttpHeader, 64>::channel();
+    let pending = Mutex::new(Some(HttpResponse::new(headers, body, trailers, http::Version::HTTP_2, 1)));
+    let executor = HttpRetryExecutor::new(
+        move |_request| pending.lock().unwrap().take().expect("a single attempt"),
+        RetryPolicy {
+            attempt_timeout_ms: 100,
+            ..fast_policy(1)
+        },
+    );
+
+    let mut response = executor.execute_with_retry(HttpRequest::get("https://api.example.com/"));
+    response.collect_body().await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    // Nobody is left reading the attempt's headers
+    let header = HttpHeader::new(http::header::SERVER, HeaderValue::from_static("late"));
+    assert!(headers_tx.try_send(header).is_err());
+}
+
+fn strategy_executor(
+    strategy: HttpProtocolStrategy,
+) -> HttpRetryExecutor<impl Fn(HttpRequest) -> HttpResponse + Send + Sync + 'static> {
+    HttpRetryExecutor::new(move |request| strategy.build().execute(request), fast_policy(2))
+}
+
+#[tokio::test]
+async fn test_refused_connections_are_retried() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+
+    let executor = strategy_executor(HttpProtocolStrategy::Http2(H2Config::default()));
+    let (mut response, stats) = executor.execute_with_stats(HttpRequest::get(&format!("http://127.0.0.1:{port}/")));
+    response.collect_body().await;
+
+    let stats = stats.read().unwrap();
+    assert_eq!(stats.total_attempts, 2);
+    assert!(stats
+        .attempts
+        .iter()
+        .all(|attempt| matches!(&attempt.outcome, AttemptOutcome::Error(error) if error.contains("TCP connection error"))));
+}
+
+#[tokio::test]
+async fn test_rejected_certificates_are_not_retried() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(Mock::get("/").respond(MockResponse::ok().text("ok")));
+
+    // Without the server's CA the handshake fails on its certificate
+    let executor = strategy_executor(HttpProtocolStrategy::Http2(H2Config::default()));
+    let (mut response, stats) = executor.execute_with_stats(HttpRequest::get(server.url("/").as_str()));
+    response.collect_body().await;
+
+    let stats = stats.read().unwrap();
+    assert_eq!(stats.total_attempts, 1);
+    assert!(matches!(&stats.attempts[0].outcome, AttemptOutcome::Error(error) if error.contains("TLS connection error")));
+    assert!(server.received_requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_waiting_retries_hold_no_thread() {
+    // Far more waiting requests than task threads; each still gets its retry
+    let calls = Arc::new(AtomicU32::new(0));
+    let counter = calls.clone();
+    let executor = Arc::new(HttpRetryExecutor::new(
+        move |_request| {
+            if counter.fetch_add(1, Ordering::SeqCst) % 2 == 0 {
+                MockResponse::status(503).header("retry-after", "1").body("unavailable").into_response()
+            } else {
+                MockResponse::ok().body("ok").into_response()
+            }
+        },
+        RetryPolicy {
+            max_delay_ms: 2000,
+            ..fast_policy(2)
+        },
+    ));
+
+    let started = std::time::Instant::now();
+    let mut responses: Vec<HttpResponse> = (0..64)
+        .map(|_| executor.execute_with_retry(HttpRequest::get("https://api.example.com/")))
+        .collect();
+    for response in &mut responses {
+        response.collect_body().await;
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 128);
+    assert!(started.elapsed() < Duration::from_secs(10), "{:?}", started.elapsed());
+}
+
+#[test]
+fn test_retry_stats_record_attempts() {
+    let mut stats = RetryStats::default();
+    stats.record_attempt(RetryAttempt {
+        attempt: 1,
+        outcome: AttemptOutcome::TimedOut,
+        elapsed: Duration::from_millis(30),
+        retry_after: None,
+        delay: Some(Duration::from_millis(10)),
+    });
+    stats.record_attempt(RetryAttempt {
+        attempt: 2,
+        outcome: AttemptOutcome::Answered(Some(200)),
+        elapsed: Duration::from_millis(20),
+        retry_after: None,
+        delay: None,
+    });
+
+    assert_eq!(stats.total_attempts, 2);
+    assert_eq!(stats.total_retry_time, Duration::from_millis(50));
+    assert_eq!(stats.total_delay_time, Duration::from_millis(10));
+    assert!(stats.had_successful_retry());
+    assert_eq!(stats.retry_errors, vec!["attempt 1 timed out".to_string()]);
+}
@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use http::HeaderValue;
+use quyc_client::client::configuration::HttpClientBuilder;
+use quyc_client::protocols::core::HttpVersion;
+use quyc_client::protocols::strategy::{H2Config, HttpProtocolStrategy};
+use quyc_client::retry::{HedgeBudget, HedgePolicy, Hedging, LatencyTracker, RetryPolicy};
+use quyc_client::testing::{Mock, MockResponse, MockServer};
+use quyc_client::{HttpClient, HttpRequest};
+
+#[test]
+fn test_only_idempotent_or_keyed_requests_are_hedged() {
+    let policy = HedgePolicy::default();
+    assert!(policy.is_hedgeable(&HttpRequest::get("https://api.example.com/v1/models")));
+    assert!(policy.is_hedgeable(&HttpRequest::delete("https://api.example.com/v1/files/1")));
+
+    let post = HttpRequest::post("https://api.example.com/v1/embeddings").body_text("{}");
+    assert!(!policy.is_hedgeable(&post));
+    let mut keyed = post;
+    keyed.headers_mut().insert("idempotency-key", HeaderValue::from_static("req-1"));
+    assert!(policy.is_hedgeable(&keyed));
+
+    let disabled = HedgePolicy { max_hedges: 0, ..HedgePolicy::default() };
+    assert!(!disabled.is_hedgeable(&HttpRequest::get("https://api.example.com/v1/models")));
+}
+
+#[test]
+fn test_budget_limits_hedges_to_ratio_of_requests() {
+    let budget = HedgeBudget::new(0.5, 2);
+    assert!(budget.try_acquire());
+    assert!(budget.try_acquire());
+    assert!(!budget.try_acquire());
+
+    // Two requests earn one hedge
+    budget.record_request();
+    assert!(!budget.try_acquire());
+    budget.record_request();
+    assert!(budget.try_acquire());
+
+    // The reserve caps banked hedges
+    for _ in 0..100 {
+        budget.record_request();
+    }
+    assert_eq!(budget.available(), 2);
+}
+
+#[test]
+fn test_delay_uses_origin_percentile_once_known() {
+    let hedging = Hedging::new(HedgePolicy {
+        min_samples: 10,
+        ..HedgePolicy::at_percentile(0.9, Duration::from_millis(250))
+    });
+    let origin = "https://api.example.com";
+    assert_eq!(hedging.delay_for(origin), Duration::from_millis(250));
+
+    for ms in 1..=10 {
+        hedging.latency().record(origin, Duration::from_millis(ms * 10));
+    }
+    assert_eq!(hedging.delay_for(origin), Duration::from_millis(90));
+    assert_eq!(hedging.delay_for("https://other.example.com"), Duration::from_millis(250));
+
+    let tracker = LatencyTracker::default();
+    tracker.record(origin, Duration::from_millis(5));
+    assert_eq!(tracker.percentile(origin, 0.5, 1), Some(Duration::from_millis(5)));
+    assert_eq!(tracker.percentile(origin, 0.5, 2), None);
+}
+
+#[test]
+fn test_retry_policy_bounds_hedges() {
+    assert!(Hedging::from_retry_policy(&RetryPolicy::default()).is_none());
+
+    let hedge = HedgePolicy { max_hedges: 4, ..HedgePolicy::fixed(Duration::from_millis(50)) };
+    let policy = RetryPolicy::default().with_hedging(hedge.clone());
+    assert!(policy.validate().is_ok());
+    let hedging = Hedging::from_retry_policy(&policy).unwrap();
+    assert_eq!(hedging.policy().max_hedges, 2);
+
+    assert!(Hedging::from_retry_policy(&RetryPolicy::no_retry().with_hedging(hedge)).is_none());
+
+    let invalid = RetryPolicy::default().with_hedging(HedgePolicy::at_percentile(1.5, Duration::from_millis(50)));
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn test_hedge_alternative_switches_protocol() {
+    match HttpProtocolStrategy::default().hedge_alternative() {
+        HttpProtocolStrategy::Http2(_) => {}
+        other => panic!("expected HTTP/2 hedge, got {other:?}"),
+    }
+    let single = HttpProtocolStrategy::Auto {
+        prefer: vec![HttpVersion::Http3],
+        fallback_chain: vec![HttpVersion::Http3],
+        configs: Default::default(),
+    };
+    assert!(matches!(single.hedge_alternative(), HttpProtocolStrategy::Auto { .. }));
+    assert!(matches!(
+        HttpProtocolStrategy::streaming_optimized().hedge_alternative(),
+        HttpProtocolStrategy::Http3(_)
+    ));
+}
+
+#[test]
+fn test_explicit_hedging_survives_a_retry_policy() {
+    let explicit = HedgePolicy { max_hedges: 3, ..HedgePolicy::fixed(Duration::from_millis(50)) };
+    let policy = RetryPolicy::default().with_hedging(HedgePolicy::fixed(Duration::from_millis(10)));
+
+    let client = HttpClientBuilder::new()
+        .hedging(Hedging::new(explicit))
+        .retry_policy(&policy)
+        .build()
+        .unwrap();
+    let hedging = client.hedging().unwrap();
+    assert_eq!(hedging.policy().max_hedges, 3);
+    assert_eq!(hedging.policy().delay, Duration::from_millis(50));
+
+    let derived = HttpClientBuilder::new().retry_policy(&policy).build().unwrap();
+    assert_eq!(derived.hedging().unwrap().policy().delay, Duration::from_millis(10));
+}
+
+#[tokio::test]
+async fn test_every_retry_attempt_may_hedge() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(
+        Mock::get("/slow").respond(
+            MockResponse::status(503)
+                .header("retry-after", "0")
+                .delay_headers(Duration::from_millis(300)),
+        ),
+    );
+    let policy = RetryPolicy {
+        max_attempts: 2,
+        initial_delay_ms: 1,
+        max_delay_ms: 10,
+        ..RetryPolicy::default()
+    }
+    .with_hedging(HedgePolicy::fixed(Duration::from_millis(20)));
+    let client =
+        HttpClient::with_config_and_strategy(server.http_config(), HttpProtocolStrategy::Http2(H2Config::default()))
+            .with_hedging(Hedging::from_retry_policy(&policy).unwrap())
+            .with_retry_policy(policy);
+
+    let mut response = client.execute(HttpRequest::get(server.url("/slow").as_str()));
+    response.collect_body().await;
+    assert_eq!(response.status(), 503);
+    // max_attempts * (1 + max_hedges) upstream requests
+    server.assert_received("GET", "/slow", 4);
+}
@@ -0,0 +1,121 @@
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+
+use quyc_client::client::{Endpoint, EndpointGroup, SelectionPolicy};
+use quyc_client::crypto::random::{generate_uuid_v4, generate_uuid_v7};
+use quyc_client::protocols::strategy::{H2Config, HttpProtocolStrategy};
+use quyc_client::retry::{HttpRetryExecutor, RetryPolicy};
+use quyc_client::testing::{Mock, MockResponse, MockServer};
+use quyc_client::{HttpClient, HttpRequest};
+
+#[test]
+fn test_uuid_versions_and_variant() {
+    let v4 = generate_uuid_v4();
+    let v7 = generate_uuid_v7();
+    for uuid in [&v4, &v7] {
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(uuid.matches('-').count(), 4);
+        assert!(matches!(uuid.as_bytes()[19], b'8' | b'9' | b'a' | b'b'));
+    }
+    assert_eq!(v4.as_bytes()[14], b'4');
+    assert_eq!(v7.as_bytes()[14], b'7');
+    assert_ne!(generate_uuid_v4(), v4);
+
+    // Version 7 keys lead with the creation time, so later keys sort after
+    std::thread::sleep(std::time::Duration::from_millis(2));
+    assert!(generate_uuid_v7() > v7);
+}
+
+#[test]
+fn test_key_is_generated_once_for_non_idempotent_methods() {
+    let mut post = HttpRequest::post("https://api.example.com/v1/messages")
+        .body_text("{}")
+        .auto_idempotency_key(true);
+    let key = post.ensure_idempotency_key().map(str::to_string).unwrap();
+    assert_eq!(post.ensure_idempotency_key(), Some(key.as_str()));
+    assert_eq!(post.clone().ensure_idempotency_key(), Some(key.as_str()));
+
+    let mut get = HttpRequest::get("https://api.example.com/v1/models").auto_idempotency_key(true);
+    assert_eq!(get.ensure_idempotency_key(), None);
+
+    // Keys set by the caller are kept, and nothing is generated unless asked for
+    let mut explicit = HttpRequest::patch("https://api.example.com/v1/batches/1")
+        .idempotency_key("batch-1")
+        .auto_idempotency_key(true);
+    assert_eq!(explicit.ensure_idempotency_key(), Some("batch-1"));
+    let mut plain = HttpRequest::post("https://api.example.com/v1/messages");
+    assert_eq!(plain.ensure_idempotency_key(), None);
+    assert!(!RetryPolicy::is_replayable(&plain));
+}
+
+#[tokio::test]
+async fn test_retries_reuse_the_generated_key() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorder = seen.clone();
+    let policy = RetryPolicy {
+        max_attempts: 3,
+        initial_delay_ms: 1,
+        max_delay_ms: 10,
+        ..RetryPolicy::default()
+    };
+    let executor = HttpRetryExecutor::new(
+        move |request: HttpRequest| {
+            let mut seen = recorder.lock().unwrap();
+            seen.push(request.headers().get("idempotency-key").cloned());
+            if seen.len() < 3 {
+                MockResponse::status(503).body("unavailable").into_response()
+            } else {
+                MockResponse::ok().body("ok").into_response()
+            }
+        },
+        policy,
+    );
+
+    let post = HttpRequest::post("https://api.example.com/v1/messages")
+        .body_text("{}")
+        .auto_idempotency_key(true);
+    assert!(RetryPolicy::is_replayable(&post));
+    let mut response = executor.execute_with_retry(post);
+    assert_eq!(response.collect_body().await, Bytes::from("ok"));
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 3);
+    assert!(seen[0].is_some());
+    assert!(seen.iter().all(|key| *key == seen[0]));
+}
+
+#[tokio::test]
+async fn test_failovers_and_redirects_keep_the_key() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(Mock::post("/a/messages").respond(MockResponse::status(503)));
+    server.mock(
+        Mock::post("/b/messages")
+            .respond(MockResponse::status(307).header("location", &server.url("/c/messages"))),
+    );
+    server.mock(Mock::post("/c/messages").respond(MockResponse::ok().text("sent")));
+
+    let endpoints = vec![
+        Endpoint::new(&server.url("/a")).unwrap(),
+        Endpoint::new(&server.url("/b")).unwrap(),
+    ];
+    let group = EndpointGroup::new("https://llm.internal/v1", endpoints)
+        .unwrap()
+        .with_policy(SelectionPolicy::RoundRobin);
+    let client =
+        HttpClient::with_config_and_strategy(server.http_config(), HttpProtocolStrategy::Http2(H2Config::default()))
+            .with_endpoint_group(group);
+
+    let post = HttpRequest::post("https://llm.internal/v1/messages")
+        .body_text("{}")
+        .auto_idempotency_key(true);
+    let mut response = client.execute(post);
+    assert_eq!(response.collect_body().await, Bytes::from("sent"));
+
+    // The failed endpoint, the endpoint failed over to and the redirect target
+    let received = server.received_requests();
+    let paths: Vec<_> = received.iter().map(|request| request.path.as_str()).collect();
+    assert_eq!(paths, ["/a/messages", "/b/messages", "/c/messages"]);
+    let key = received[0].header("idempotency-key").expect("the first attempt carries a key");
+    assert!(received.iter().all(|request| request.header("idempotency-key") == Some(key)));
+}
@@ -0,0 +1,114 @@
+use std::convert::Infallible;
+use std::future::poll_fn;
+
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, StatusCode};
+use http_body::Frame;
+use http_body_util::{BodyExt, Empty, Full, StreamBody};
+use quyc_client::middleware::{Middleware, MiddlewareChain};
+use quyc_client::{HttpClient, HttpError, HttpRequest, HttpResponse};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Answers every request with its own method and path
+struct Echo;
+
+impl Middleware for Echo {
+    fn short_circuit(&self, request: &HttpRequest) -> Option<HttpResponse> {
+        let body = format!("{} {}", request.method(), request.url().path());
+        Some(HttpResponse::error(StatusCode::ACCEPTED, body))
+    }
+}
+
+async fn call<S, R>(service: &mut S, request: R) -> Result<S::Response, S::Error>
+where
+    S: Service<R>,
+{
+    poll_fn(|cx| service.poll_ready(cx)).await?;
+    service.call(request).await
+}
+
+#[tokio::test]
+async fn test_client_as_http_service() {
+    let mut client = HttpClient::default().with_middleware(Echo);
+    let request = http::Request::post("https://example.invalid/widgets")
+        .body(Full::new(Bytes::from("payload")))
+        .unwrap();
+
+    let response: http::Response<_> = call(&mut client, request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    assert_eq!(response.headers()["content-type"], "text/plain");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, Bytes::from("POST /widgets"));
+}
+
+#[tokio::test]
+async fn test_middleware_chain_as_layer() {
+    let chain = MiddlewareChain::new().add(Echo);
+    let mut service = chain.layer(HttpClient::default());
+
+    let response: HttpResponse = call(&mut service, HttpRequest::get("https://example.invalid/a/b"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 202);
+    let mut response = response;
+    assert_eq!(response.collect_body().await, Bytes::from("GET /a/b"));
+
+    let request = http::Request::get("https://example.invalid/").body(Empty::<Bytes>::new()).unwrap();
+    let error: Result<HttpRequest, HttpError> = HttpRequest::from_http(request).await;
+    assert!(error.unwrap().body().is_none());
+}
+
+#[tokio::test]
+async fn test_request_conversion_round_trip() {
+    let request = HttpRequest::put("https://example.com/items/7?x=1")
+        .header("x-trace", "abc")
+        .bearer_auth("tok")
+        .body_text("hello");
+
+    let http_request = request.into_http().unwrap();
+    assert_eq!(http_request.method(), http::Method::PUT);
+    assert_eq!(http_request.uri(), "https://example.com/items/7?x=1");
+    assert_eq!(http_request.headers()["authorization"], "Bearer tok");
+    assert_eq!(http_request.headers()["x-trace"], "abc");
+
+    let back = HttpRequest::from_http(http_request).await.unwrap();
+    assert_eq!(back.method(), http::Method::PUT);
+    assert_eq!(back.url().as_str(), "https://example.com/items/7?x=1");
+    assert_eq!(back.headers()["authorization"], "Bearer tok");
+    assert_eq!(back.body().and_then(|b| b.to_bytes()), Some(Bytes::from("hello")));
+}
+
+#[test]
+fn test_request_conversion_rejects_invalid_credentials() {
+    let request = HttpRequest::get("https://example.com/").bearer_auth("tok\nen");
+    assert!(request.auth_headers().is_empty());
+    assert!(request.into_http().is_err());
+}
+
+#[tokio::test]
+async fn test_response_conversion_streams_body_and_trailers() {
+    let mut trailers = HeaderMap::new();
+    trailers.insert("grpc-status", HeaderValue::from_static("0"));
+    let frames = futures::stream::iter(vec![
+        Ok::<_, Infallible>(Frame::data(Bytes::from("hel"))),
+        Ok(Frame::data(Bytes::from("lo"))),
+        Ok(Frame::trailers(trailers)),
+    ]);
+    let upstream = http::Response::builder()
+        .status(StatusCode::CREATED)
+        .header("x-id", "7")
+        .body(StreamBody::new(frames))
+        .unwrap();
+
+    let response = HttpResponse::from_http(upstream);
+    assert_eq!(response.status(), 201);
+
+    // Back to http::Response: head first, then data frames and trailers
+    let converted = response.into_http().await;
+    assert_eq!(converted.status(), StatusCode::CREATED);
+    assert_eq!(converted.headers()["x-id"], "7");
+    let collected = converted.into_body().collect().await.unwrap();
+    assert_eq!(collected.trailers().unwrap()["grpc-status"], "0");
+    assert_eq!(collected.to_bytes(), Bytes::from("hello"));
+}
@@ -0,0 +1,92 @@
+//! Cassette files built from code instead of hand-written JSON
+
+use std::path::PathBuf;
+
+use quyc_client::cassette::interaction::CASSETTE_FORMAT_VERSION;
+use quyc_client::cassette::{
+    CassetteBody, CassetteFile, Interaction, RecordedChunk, RecordedRequest, RecordedResponse,
+};
+
+/// One recorded request and its response, built fluently
+#[derive(Debug, Clone)]
+pub struct Exchange(Interaction);
+
+/// `method url` answered with an empty `200 OK` over HTTP/2
+pub fn exchange(method: &str, url: &str) -> Exchange {
+    Exchange(Interaction {
+        request: RecordedRequest {
+            method: method.to_string(),
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: None,
+        },
+        response: RecordedResponse {
+            status: 200,
+            version: "HTTP/2.0".to_string(),
+            headers: Vec::new(),
+            chunks: Vec::new(),
+            trailers: Vec::new(),
+        },
+        recorded_at: Default::default(),
+    })
+}
+
+impl Exchange {
+    /// Add a request header
+    pub fn request_header(mut self, name: &str, value: &str) -> Self {
+        self.0.request.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Set the request body
+    pub fn request_body(mut self, body: &str) -> Self {
+        self.0.request.body = Some(CassetteBody::Utf8(body.to_string()));
+        self
+    }
+
+    /// Set the response status
+    pub fn status(mut self, status: u16) -> Self {
+        self.0.response.status = status;
+        self
+    }
+
+    /// Set the protocol version the response arrived over, e.g. `HTTP/1.1`
+    pub fn version(mut self, version: &str) -> Self {
+        self.0.response.version = version.to_string();
+        self
+    }
+
+    /// Add a response header
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.0.response.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Append a response body chunk arriving `delay_ms` after the previous one
+    pub fn chunk(mut self, delay_ms: u64, data: impl AsRef<[u8]>) -> Self {
+        self.0.response.chunks.push(RecordedChunk {
+            delay_ms,
+            data: CassetteBody::from_bytes(data.as_ref()),
+        });
+        self
+    }
+
+    /// Append the whole response body as one chunk
+    pub fn body(self, data: impl AsRef<[u8]>) -> Self {
+        self.chunk(0, data)
+    }
+}
+
+/// Write `exchanges` to a cassette file in the temp directory
+///
+/// `name` must be unique across the test binary; the process id keeps
+/// concurrent runs apart.
+pub fn write(name: &str, exchanges: impl IntoIterator<Item = Exchange>) -> PathBuf {
+    let file = CassetteFile {
+        version: CASSETTE_FORMAT_VERSION,
+        interactions: exchanges.into_iter().map(|exchange| exchange.0).collect(),
+    };
+    let path = std::env::temp_dir().join(format!("quyc-{}-{name}.json", std::process::id()));
+    std::fs::write(&path, serde_json::to_vec_pretty(&file).unwrap()).unwrap();
+    path
+}
@@ -0,0 +1,8 @@
+//! Fixtures shared by the integration test modules
+//!
+//! Included with `#[path = "../support/mod.rs"] mod support;`, so each test
+//! file only uses part of it.
+
+#![allow(dead_code)]
+
+pub mod cassette;
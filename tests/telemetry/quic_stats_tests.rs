@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use quyc_client::telemetry::{QuicConnectionStats, QuicStatsRegistry, quic_origin};
+
+fn connection_stats(connection_id: &str, rtt_ms: u64, sent: u64, lost: u64) -> QuicConnectionStats {
+    QuicConnectionStats {
+        origin: quic_origin("https", "example.com", 443),
+        connection_id: connection_id.to_string(),
+        rtt: Duration::from_millis(rtt_ms),
+        min_rtt: Some(Duration::from_millis(rtt_ms / 2)),
+        rttvar: Duration::from_millis(1),
+        cwnd: 12_000,
+        bytes_in_flight: 0,
+        pmtu: 1_350,
+        delivery_rate: 0,
+        packets_sent: sent,
+        packets_received: sent,
+        packets_lost: lost,
+        packets_retransmitted: lost,
+        bytes_sent: sent * 1_000,
+        bytes_received: sent * 1_000,
+        bytes_lost: lost * 1_000,
+        paths_count: 1,
+    }
+}
+
+#[test]
+fn test_registry_aggregates_per_origin() {
+    let registry = QuicStatsRegistry::new();
+    registry.record(connection_stats("aa", 20, 100, 2));
+    registry.record(connection_stats("bb", 40, 100, 0));
+
+    let origin = registry
+        .origin("https://example.com:443")
+        .expect("origin should be tracked");
+    assert_eq!(origin.connections, 2);
+    assert_eq!(origin.avg_rtt(), Duration::from_millis(30));
+    assert_eq!(origin.min_rtt, Some(Duration::from_millis(10)));
+    assert_eq!(origin.packets_lost, 2);
+    assert!((origin.loss_rate() - 0.01).abs() < f64::EPSILON);
+    assert_eq!(origin.last.as_ref().map(|s| s.connection_id.as_str()), Some("bb"));
+}
+
+#[test]
+fn test_registry_keeps_latest_connection_snapshot() {
+    let registry = QuicStatsRegistry::new();
+    registry.record(connection_stats("aa", 20, 10, 0));
+    registry.record(connection_stats("aa", 25, 50, 1));
+
+    let latest = registry.connection("aa").expect("connection should be tracked");
+    assert_eq!(latest.packets_sent, 50);
+    assert_eq!(registry.connections().len(), 1);
+
+    // The origin totals hold the latest snapshot once, next to other connections
+    registry.record(connection_stats("bb", 35, 100, 2));
+    let origin = registry.origin("https://example.com:443").unwrap();
+    assert_eq!(origin.connections, 2);
+    assert_eq!(origin.packets_sent, 150);
+    assert_eq!(origin.packets_lost, 3);
+    assert_eq!(origin.bytes_sent, 150_000);
+    assert_eq!(origin.avg_rtt(), Duration::from_millis(30));
+    assert_eq!(origin.min_rtt, Some(Duration::from_millis(10)));
+
+    registry.clear();
+    assert!(registry.origins().is_empty());
+    assert!(registry.connection("aa").is_none());
+}
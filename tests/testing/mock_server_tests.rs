@@ -0,0 +1,232 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::{HeaderMap, Method, StatusCode};
+use http_body_util::{BodyExt, Full};
+use hyper_util::rt::TokioIo;
+use rustls::pki_types::ServerName;
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+
+use quyc_client::config::HttpConfig;
+use quyc_client::protocols::strategy::{H2Config, H3Config, HttpProtocolStrategy};
+use quyc_client::testing::{BodyPart, Mock, MockResponse, MockServer, ResponseEnd};
+use quyc_client::{HttpClient, HttpRequest};
+
+#[test]
+fn test_mock_matches_method_path_and_headers() {
+    let mock = Mock::post("/items?page=2").with_header("x-api-key", "secret");
+
+    let mut headers = HeaderMap::new();
+    headers.insert("x-api-key", "secret".parse().unwrap());
+
+    assert!(mock.matches(&Method::POST, "/items?page=2", &headers));
+    assert!(!mock.matches(&Method::GET, "/items?page=2", &headers));
+    assert!(!mock.matches(&Method::POST, "/items", &headers));
+    assert!(!mock.matches(&Method::POST, "/items?page=2", &HeaderMap::new()));
+    assert!(Mock::any("/x").matches(&Method::PATCH, "/x", &HeaderMap::new()));
+}
+
+#[test]
+fn test_mock_response_script() {
+    let response = MockResponse::status(201)
+        .header("x-request-id", "abc")
+        .chunks(["one", "two", "three"], Duration::from_millis(10))
+        .trailer("grpc-status", "0");
+
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(response.headers["x-request-id"], "abc");
+    assert_eq!(response.body.len(), 5);
+    assert!(matches!(response.body[1], BodyPart::Delay(d) if d == Duration::from_millis(10)));
+    assert_eq!(response.body_len(), 11);
+    assert_eq!(response.trailers.as_ref().unwrap()["grpc-status"], "0");
+    assert_eq!(response.end, ResponseEnd::Complete);
+    assert_eq!(MockResponse::ok().reset().end, ResponseEnd::Reset);
+}
+
+#[test]
+fn test_mock_response_sse_events() {
+    let response = MockResponse::ok()
+        .sse_event(Some("update"), "line1\nline2")
+        .sse_event(None, "done");
+
+    assert_eq!(response.headers["content-type"], "text/event-stream");
+    let frames: Vec<_> = response
+        .body
+        .iter()
+        .filter_map(|part| match part {
+            BodyPart::Data(data) => Some(String::from_utf8_lossy(data).into_owned()),
+            BodyPart::Delay(_) => None,
+        })
+        .collect();
+    assert_eq!(frames, ["event: update\ndata: line1\ndata: line2\n\n", "data: done\n\n"]);
+}
+
+async fn send_http1(server: &MockServer, request: http::Request<Full<Bytes>>) -> http::Response<Bytes> {
+    let mut roots = rustls::RootCertStore::empty();
+    let mut pem = server.ca_pem().as_bytes();
+    for cert in rustls_pemfile::certs(&mut pem) {
+        roots.add(cert.unwrap()).unwrap();
+    }
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    let tcp = TcpStream::connect(server.addr()).await.unwrap();
+    let tls = TlsConnector::from(Arc::new(config))
+        .connect(ServerName::try_from("localhost").unwrap(), tcp)
+        .await
+        .unwrap();
+
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(tls)).await.unwrap();
+    tokio::spawn(connection);
+
+    let response = sender.send_request(request).await.unwrap();
+    let (parts, body) = response.into_parts();
+    http::Response::from_parts(parts, body.collect().await.unwrap().to_bytes())
+}
+
+#[tokio::test]
+async fn test_mock_server_serves_scripted_responses_in_order() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(
+        Mock::get("/flaky")
+            .respond(MockResponse::status(503))
+            .respond(MockResponse::ok().json(&serde_json::json!({"ok": true}))),
+    );
+
+    let request = || {
+        http::Request::get(server.url("/flaky"))
+            .header("host", "localhost")
+            .body(Full::new(Bytes::new()))
+            .unwrap()
+    };
+
+    assert_eq!(send_http1(&server, request()).await.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let second = send_http1(&server, request()).await;
+    assert_eq!(second.status(), StatusCode::OK);
+    assert_eq!(second.body().as_ref(), br#"{"ok":true}"#);
+    // The last response repeats once the script is exhausted
+    assert_eq!(send_http1(&server, request()).await.status(), StatusCode::OK);
+
+    server.assert_received("GET", "/flaky", 3);
+}
+
+#[tokio::test]
+async fn test_mock_server_records_request_body_and_headers() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(Mock::post("/submit").respond(MockResponse::status(202)));
+
+    let request = http::Request::post(server.url("/submit"))
+        .header("host", "localhost")
+        .header("content-type", "application/json")
+        .body(Full::new(Bytes::from_static(br#"{"name":"quyc"}"#)))
+        .unwrap();
+    let response = send_http1(&server, request).await;
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+    let received = server.received("POST", "/submit");
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].version, http::Version::HTTP_11);
+    assert_eq!(received[0].header("content-type"), Some("application/json"));
+    let body: serde_json::Value = received[0].body_json().unwrap();
+    assert_eq!(body["name"], "quyc");
+}
+
+#[tokio::test]
+async fn test_mock_server_unmatched_request_is_404_and_recorded() {
+    let server = MockServer::start().await.unwrap();
+
+    let request = http::Request::get(server.url("/missing"))
+        .header("host", "localhost")
+        .body(Full::new(Bytes::new()))
+        .unwrap();
+    let response = send_http1(&server, request).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.body().as_ref(), b"no mock for GET /missing");
+    assert_eq!(server.received_requests().len(), 1);
+
+    server.reset();
+    assert!(server.received_requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_mock_server_client_trusts_generated_ca() {
+    let server = MockServer::start().await.unwrap();
+
+    assert!(server.ca_pem().contains("BEGIN CERTIFICATE"));
+    assert_eq!(server.http_config().tls_root_certificates, vec![server.ca_pem().to_string()]);
+    assert!(server.url("/a").starts_with("https://localhost:"));
+    assert_eq!(server.h3_url("/a"), format!("https://{}/a", server.h3_addr()));
+}
+
+fn h2_client(config: HttpConfig) -> HttpClient {
+    HttpClient::with_config_and_strategy(config, HttpProtocolStrategy::Http2(H2Config::default()))
+}
+
+#[tokio::test]
+async fn test_mock_server_answers_quyc_client_over_http2() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(
+        Mock::post("/items")
+            .with_header("x-api-key", "secret")
+            .respond(
+                MockResponse::status(201)
+                    .header("x-request-id", "abc")
+                    .chunks(["{\"id\":", "7}"], Duration::from_millis(10)),
+            ),
+    );
+
+    let request = HttpRequest::post(server.url("/items").as_str())
+        .header("x-api-key", "secret")
+        .body_text(r#"{"name":"widget"}"#);
+    let mut response = h2_client(server.http_config()).execute(request);
+    assert_eq!(response.status(), 201);
+    assert_eq!(response.version, http::Version::HTTP_2);
+    assert_eq!(response.collect_headers().await["x-request-id"], "abc");
+    assert_eq!(response.collect_body().await, Bytes::from(r#"{"id":7}"#));
+
+    let received = server.received("POST", "/items");
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].version, http::Version::HTTP_2);
+    assert_eq!(received[0].body_json::<serde_json::Value>().unwrap()["name"], "widget");
+}
+
+#[tokio::test]
+async fn test_mock_server_answers_quyc_client_over_http3() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(Mock::get("/status").respond(MockResponse::ok().text("up")));
+
+    let client =
+        HttpClient::with_config_and_strategy(server.http_config(), HttpProtocolStrategy::Http3(H3Config::default()));
+    let mut response = client.execute(HttpRequest::get(server.h3_url("/status").as_str()));
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.version, http::Version::HTTP_3);
+    assert_eq!(response.collect_body().await, Bytes::from("up"));
+
+    let received = server.received("GET", "/status");
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].version, http::Version::HTTP_3);
+}
+
+#[tokio::test]
+async fn test_mock_server_handshake_needs_its_own_ca() {
+    let server = MockServer::start().await.unwrap();
+    let other = MockServer::start().await.unwrap();
+    server.mock(Mock::get("/").respond(MockResponse::ok().text("trusted")));
+
+    let mut trusted = h2_client(server.http_config()).execute(HttpRequest::get(server.url("/").as_str()));
+    assert_eq!(trusted.collect_body().await, Bytes::from("trusted"));
+    assert_eq!(server.received_requests().len(), 1);
+
+    // Neither the system roots nor another mock server's CA vouch for it
+    for config in [HttpConfig::default(), other.http_config()] {
+        let mut rejected = h2_client(config).execute(HttpRequest::get(server.url("/").as_str()));
+        rejected.collect_body().await;
+        assert_ne!(rejected.status(), 200);
+    }
+    assert_eq!(server.received_requests().len(), 1);
+}
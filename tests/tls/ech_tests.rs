@@ -0,0 +1,249 @@
+use std::net::{SocketAddr, UdpSocket};
+use std::thread;
+use std::time::Duration;
+
+use bytes::Bytes;
+
+use quyc_client::client::SrvResolver;
+use quyc_client::config::HttpConfig;
+use quyc_client::protocols::strategy::{H2Config, H3Config, HttpProtocolStrategy};
+use quyc_client::testing::{Mock, MockResponse, MockServer};
+use quyc_client::tls::{decode_ech_config_list, lookup_ech_config_list, EchPolicy, TlsConfig, TlsError, TlsManager};
+use quyc_client::{HttpClient, HttpRequest};
+
+/// `ECHConfigList` with one DHKEM(X25519)/HKDF-SHA256/AES-128-GCM config for `public_name`
+fn ech_config_list(public_name: &str) -> Vec<u8> {
+    let mut contents = vec![7];
+    contents.extend_from_slice(&[0x00, 0x20, 0x00, 0x20]);
+    contents.extend((1..=32).collect::<Vec<u8>>());
+    contents.extend_from_slice(&[0x00, 0x04, 0x00, 0x01, 0x00, 0x01]);
+    contents.push(0);
+    contents.push(public_name.len() as u8);
+    contents.extend_from_slice(public_name.as_bytes());
+    contents.extend_from_slice(&[0x00, 0x00]);
+
+    let mut config = vec![0xfe, 0x0d];
+    config.extend_from_slice(&(contents.len() as u16).to_be_bytes());
+    config.extend(contents);
+    let mut list = (config.len() as u16).to_be_bytes().to_vec();
+    list.extend(config);
+    list
+}
+
+/// Local DNS stand-in answering HTTPS queries with `records` of (priority, ech)
+fn dns_stand_in(records: Vec<(u16, Option<Vec<u8>>)>) -> SocketAddr {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let address = socket.local_addr().unwrap();
+    thread::spawn(move || {
+        let mut query = [0u8; 512];
+        while let Ok((len, peer)) = socket.recv_from(&mut query) {
+            let mut end = 12;
+            while end < len && query[end] != 0 {
+                end += usize::from(query[end]) + 1;
+            }
+            end += 5;
+            if end > len {
+                continue;
+            }
+            let is_https = query[end - 4..end - 2] == [0, 65];
+
+            let answers = if is_https { records.len() as u16 } else { 0 };
+            let mut reply = Vec::new();
+            reply.extend_from_slice(&query[..2]);
+            reply.extend_from_slice(&[0x81, 0x80, 0, 1]);
+            reply.extend_from_slice(&answers.to_be_bytes());
+            reply.extend_from_slice(&[0, 0, 0, 0]);
+            reply.extend_from_slice(&query[12..end]);
+            for (priority, ech) in records.iter().take(usize::from(answers)) {
+                // Priority, target "." and the ech parameter (key 5)
+                let mut rdata = priority.to_be_bytes().to_vec();
+                rdata.push(0);
+                if let Some(ech) = ech {
+                    rdata.extend_from_slice(&5u16.to_be_bytes());
+                    rdata.extend_from_slice(&(ech.len() as u16).to_be_bytes());
+                    rdata.extend_from_slice(ech);
+                }
+                reply.extend_from_slice(&[0xC0, 0x0C, 0, 65, 0, 1, 0, 0, 1, 44]);
+                reply.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+                reply.extend_from_slice(&rdata);
+            }
+            drop(socket.send_to(&reply, peer));
+        }
+    });
+    address
+}
+
+/// TLS configuration trusting the mock CA, without revocation checks
+fn mock_tls_config(server: &MockServer, policy: EchPolicy) -> TlsConfig {
+    let mut config = TlsConfig::default();
+    config.enable_ocsp = false;
+    config.enable_crl = false;
+    config.custom_root_certs = vec![server.ca_pem().to_string()];
+    config.ech_policy = policy;
+    config
+}
+
+#[test]
+fn test_ech_disabled_by_default() {
+    let config = TlsConfig::default();
+    assert_eq!(config.ech_policy, EchPolicy::Disable);
+    assert!(config.ech_config_list.is_none());
+    assert!(!EchPolicy::Disable.is_enabled());
+    assert!(EchPolicy::Prefer.is_enabled());
+}
+
+#[test]
+fn test_decode_ech_config_list() {
+    assert_eq!(decode_ech_config_list(" AAEC ").ok(), Some(vec![0x00, 0x01, 0x02]));
+    assert!(matches!(
+        decode_ech_config_list("not base64!"),
+        Err(TlsError::EncryptedClientHello(_))
+    ));
+}
+
+#[test]
+fn test_http_config_ech_settings_reach_tls_config() {
+    let list = ech_config_list("localhost");
+    let config = HttpConfig::default()
+        .with_tls_ech(EchPolicy::Require)
+        .with_tls_ech_config_list(list.clone());
+
+    let tls = TlsConfig::from_http_config(&config);
+    assert_eq!(tls.ech_policy, EchPolicy::Require);
+    assert_eq!(tls.ech_config_list, Some(list));
+    assert_eq!(TlsConfig::from_http_config(&HttpConfig::default()).ech_policy, EchPolicy::Disable);
+}
+
+#[tokio::test]
+async fn test_invalid_explicit_ech_config_fails_before_connecting() {
+    let mut config = TlsConfig::default();
+    config.ech_policy = EchPolicy::Require;
+    config.ech_config_list = Some(vec![0x00, 0x01, 0x02]);
+
+    let manager = TlsManager::with_config(config);
+    let result = manager.create_connection("localhost", 1).await;
+    assert!(matches!(result, Err(TlsError::EncryptedClientHello(_))));
+}
+
+#[tokio::test]
+async fn test_lookup_reads_ech_from_https_records() {
+    let list = ech_config_list("public.example");
+    let resolver = SrvResolver::with_nameservers(vec![dns_stand_in(vec![
+        (0, None),
+        (2, Some(vec![0xAA])),
+        (1, Some(list.clone())),
+    ])]);
+    let found = lookup_ech_config_list(&resolver, "ech.example.").await.unwrap();
+    assert_eq!(found, Some(list));
+
+    // Records without an ech parameter leave ECH off
+    let resolver = SrvResolver::with_nameservers(vec![dns_stand_in(vec![(1, None)])]);
+    assert_eq!(lookup_ech_config_list(&resolver, "plain.example.").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_ech_config_from_dns_is_offered_in_handshake() {
+    let server = MockServer::start().await.unwrap();
+    let resolver = SrvResolver::with_nameservers(vec![dns_stand_in(vec![(1, Some(ech_config_list("localhost")))])]);
+
+    // The mock server does not speak ECH, so the offer is rejected without
+    // retry configs: required ECH fails, preferred ECH falls back
+    let required = TlsManager::with_config(mock_tls_config(&server, EchPolicy::Require)).with_resolver(resolver.clone());
+    match required.create_connection("localhost", server.addr().port()).await {
+        Err(TlsError::EncryptedClientHello(message)) => assert!(message.contains("without retry configs"), "{message}"),
+        other => panic!("expected ECH rejection, got {:?}", other.map(drop)),
+    }
+
+    let preferred = TlsManager::with_config(mock_tls_config(&server, EchPolicy::Prefer)).with_resolver(resolver);
+    let stream = preferred.create_connection("localhost", server.addr().port()).await.unwrap();
+    let (_, connection) = stream.get_ref();
+    assert_eq!(connection.protocol_version(), Some(rustls::ProtocolVersion::TLSv1_3));
+}
+
+#[tokio::test]
+async fn test_without_ech_config_require_fails_and_prefer_connects() {
+    let server = MockServer::start().await.unwrap();
+    let resolver = SrvResolver::with_nameservers(vec![dns_stand_in(Vec::new())]);
+
+    let required = TlsManager::with_config(mock_tls_config(&server, EchPolicy::Require)).with_resolver(resolver.clone());
+    assert!(matches!(
+        required.create_connection("localhost", server.addr().port()).await,
+        Err(TlsError::EncryptedClientHello(_))
+    ));
+
+    let preferred = TlsManager::with_config(mock_tls_config(&server, EchPolicy::Prefer)).with_resolver(resolver);
+    assert!(preferred.create_connection("localhost", server.addr().port()).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_client_ech_policy_applies_to_http2() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(Mock::get("/hello").respond(MockResponse::ok().text("hello")));
+    let client = |policy| {
+        let config = server
+            .http_config()
+            .with_tls_ech(policy)
+            .with_tls_ech_config_list(ech_config_list("localhost"));
+        HttpClient::with_config_and_strategy(config, HttpProtocolStrategy::Http2(H2Config::default()))
+    };
+
+    let mut response = client(EchPolicy::Prefer).execute(HttpRequest::get(server.url("/hello").as_str()));
+    assert_eq!(response.collect_body().await, Bytes::from("hello"));
+
+    let mut response = client(EchPolicy::Require).execute(HttpRequest::get(server.url("/hello").as_str()));
+    response.collect_body().await;
+    assert!(!response.is_success());
+    server.assert_received("GET", "/hello", 1);
+}
+
+/// UDP socket on a port with nothing listening on TCP, and an HTTPS URL for it
+fn quic_trap() -> (UdpSocket, String) {
+    let tcp = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = tcp.local_addr().unwrap();
+    let udp = UdpSocket::bind(addr).unwrap();
+    // TCP connections are refused, so HTTP/2 attempts fail fast
+    drop(tcp);
+    udp.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    (udp, format!("https://{addr}/"))
+}
+
+fn ech_config(policy: EchPolicy) -> HttpConfig {
+    HttpConfig::default()
+        .with_tls_ech(policy)
+        .with_tls_ech_config_list(ech_config_list("localhost"))
+}
+
+#[tokio::test]
+async fn test_required_ech_keeps_auto_clients_off_quic() {
+    let (udp, url) = quic_trap();
+    let mut datagram = [0u8; 1500];
+
+    let mut response = HttpClient::with_config(ech_config(EchPolicy::Require)).execute(HttpRequest::get(url.as_str()));
+    response.collect_body().await;
+    assert!(!response.is_success());
+    udp.set_read_timeout(Some(Duration::from_millis(500))).unwrap();
+    assert!(udp.recv_from(&mut datagram).is_err(), "a QUIC Initial carries the hostname in plaintext SNI");
+
+    // Without a required ECH the same client tries HTTP/3 first
+    udp.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    let client = HttpClient::with_config(ech_config(EchPolicy::Prefer));
+    let request = HttpRequest::get(url.as_str());
+    thread::spawn(move || drop(client.execute(request)));
+    assert!(udp.recv_from(&mut datagram).is_ok());
+}
+
+#[tokio::test]
+async fn test_required_ech_refuses_forced_http3() {
+    let (udp, url) = quic_trap();
+    udp.set_read_timeout(Some(Duration::from_millis(500))).unwrap();
+
+    let client = HttpClient::with_config_and_strategy(
+        ech_config(EchPolicy::Require),
+        HttpProtocolStrategy::Http3(H3Config::default()),
+    );
+    let mut response = client.execute(HttpRequest::get(url.as_str()));
+    let body = String::from_utf8(response.collect_body().await.to_vec()).unwrap();
+    assert_eq!(response.status(), 500);
+    assert!(body.contains("Encrypted Client Hello is required"));
+    assert!(udp.recv_from(&mut [0u8; 1500]).is_err());
+}
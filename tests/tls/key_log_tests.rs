@@ -0,0 +1,71 @@
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+
+use quyc_client::config::HttpConfig;
+use quyc_client::protocols::strategy::{H2Config, HttpProtocolStrategy};
+use quyc_client::testing::{Mock, MockResponse, MockServer};
+use quyc_client::tls::{key_log_path, TlsConfig};
+use quyc_client::{HttpClient, HttpRequest};
+
+#[test]
+fn test_key_logging_off_by_default() {
+    assert!(TlsConfig::default().key_log_file.is_none());
+    assert!(HttpConfig::default().tls_key_log_file.is_none());
+}
+
+#[test]
+fn test_explicit_key_log_file_wins() {
+    let explicit = Path::new("/tmp/explicit-keys.log");
+    assert_eq!(key_log_path(Some(explicit)), Some(PathBuf::from(explicit)));
+}
+
+#[test]
+fn test_http_config_key_log_file_reaches_tls_config() {
+    let config = HttpConfig::default().with_tls_key_log_file("/tmp/sslkeys.log");
+    let tls = TlsConfig::from_http_config(&config);
+    assert_eq!(tls.key_log_file, Some(PathBuf::from("/tmp/sslkeys.log")));
+}
+
+#[test]
+fn test_strategy_key_log_file_reaches_http2() {
+    let path = PathBuf::from("/tmp/sslkeys.log");
+    let HttpProtocolStrategy::Http2(config) =
+        HttpProtocolStrategy::Http2(H2Config::default()).with_key_log_file(Some(path.clone()))
+    else {
+        unreachable!()
+    };
+    assert_eq!(config.key_log_file, Some(path.clone()));
+
+    let HttpProtocolStrategy::Auto { configs, .. } = HttpProtocolStrategy::default().with_key_log_file(Some(path.clone()))
+    else {
+        unreachable!()
+    };
+    assert_eq!(configs.h2.key_log_file, Some(path));
+}
+
+#[tokio::test]
+async fn test_http2_connections_write_key_log() {
+    let server = MockServer::start().await.unwrap();
+    server.mock(Mock::get("/hello").respond(MockResponse::ok().text("hello")));
+    let path = std::env::temp_dir().join(format!("quyc-h2-keys-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let config = server.http_config().with_tls_key_log_file(path.clone());
+    let client = HttpClient::with_config_and_strategy(config, HttpProtocolStrategy::Http2(H2Config::default()));
+    let mut response = client.execute(HttpRequest::get(server.url("/hello").as_str()));
+    assert_eq!(response.collect_body().await, Bytes::from("hello"));
+
+    let log = std::fs::read_to_string(&path).unwrap();
+    #[cfg(unix)]
+    let mode = {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::metadata(&path).unwrap().permissions().mode()
+    };
+    let _ = std::fs::remove_file(&path);
+    // Session secrets stay private to the user
+    #[cfg(unix)]
+    assert_eq!(mode & 0o777, 0o600);
+    assert!(log.contains("CLIENT_HANDSHAKE_TRAFFIC_SECRET "), "{log}");
+    assert!(log.contains("CLIENT_TRAFFIC_SECRET_0 "), "{log}");
+}